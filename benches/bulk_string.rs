@@ -0,0 +1,57 @@
+use bytes::BytesMut;
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use simple_redis::{
+    cmd::{CommandExecutor, Get},
+    Backend, RespArray, RespDecoder, RespEncoder, RespFrame,
+};
+
+const PAYLOAD_SIZE: usize = 1024 * 1024;
+
+fn set_command() -> BytesMut {
+    let frame: RespFrame = RespArray::new(vec![
+        b"set".into(),
+        b"key".into(),
+        vec![b'x'; PAYLOAD_SIZE].as_slice().into(),
+    ])
+    .into();
+    BytesMut::from(&frame.encode()[..])
+}
+
+// 解析 1MB 的 SET，保存到 backend 后通过 GET 命令读取并编码回复
+fn bench_set_get(c: &mut Criterion) {
+    let command = set_command();
+    let backend = Backend::new();
+    let get = Get::try_from(RespArray::new(vec![b"get".into(), b"key".into()])).unwrap();
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let mut group = c.benchmark_group("bulk_string_1mb");
+    group.bench_function("decode", |b| {
+        b.iter_batched(
+            || command.clone(),
+            |mut buf| RespFrame::decode(&mut buf).unwrap(),
+            BatchSize::SmallInput,
+        )
+    });
+    group.bench_function("set_get", |b| {
+        b.iter_batched(
+            || command.clone(),
+            |mut buf| {
+                let RespFrame::Array(args) = RespFrame::decode(&mut buf).unwrap() else {
+                    unreachable!();
+                };
+                backend.set("key".to_string(), args[2].clone());
+                let mut reply = BytesMut::new();
+                runtime
+                    .block_on(get.execute(&backend))
+                    .encode_into(&mut reply);
+                reply
+            },
+            BatchSize::SmallInput,
+        )
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_set_get);
+criterion_main!(benches);
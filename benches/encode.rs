@@ -0,0 +1,46 @@
+use bytes::BytesMut;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use simple_redis::{RespFrame, RespMap, RespVersion};
+
+// 模拟 HGETALL 返回 10k 个字段的回复
+fn hgetall_reply(fields: usize) -> RespFrame {
+    let mut map = RespMap::new();
+    for i in 0..fields {
+        map.insert(
+            format!("field:{}", i).as_bytes().into(),
+            format!("value:{}", i).as_bytes().into(),
+        );
+    }
+    map.into()
+}
+
+fn bench_encode(c: &mut Criterion) {
+    let frame = hgetall_reply(10_000);
+    let mut group = c.benchmark_group("hgetall_10k");
+    for protocol in [RespVersion::Resp2, RespVersion::Resp3] {
+        let name = format!("{:?}", protocol);
+        // 之前编码器的做法：先编码为 Vec 再复制到 codec 的缓冲区
+        group.bench_with_input(BenchmarkId::new("encode_for", &name), &frame, |b, frame| {
+            b.iter(|| {
+                let mut dst = BytesMut::new();
+                dst.extend_from_slice(&frame.encode_for(protocol));
+                dst
+            })
+        });
+        group.bench_with_input(
+            BenchmarkId::new("encode_for_into", &name),
+            &frame,
+            |b, frame| {
+                b.iter(|| {
+                    let mut dst = BytesMut::new();
+                    frame.encode_for_into(protocol, &mut dst);
+                    dst
+                })
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_encode);
+criterion_main!(benches);
@@ -0,0 +1,104 @@
+use std::sync::{Arc, PoisonError, RwLock};
+
+use super::Backend;
+
+// 与 redis 默认配置一致的逻辑数据库数量
+pub const DEFAULT_DATABASES: usize = 16;
+
+// 服务端持有的所有逻辑数据库，每个连接通过下标选择其中一个。
+// 每个下标对应的 Backend 可以被 SWAPDB 替换，因此访问时返回的是 clone（只增加引用计数）
+#[derive(Debug, Clone)]
+pub struct Databases(Arc<Vec<RwLock<Backend>>>);
+
+impl Databases {
+    pub fn new(count: usize) -> Self {
+        Self(Arc::new(
+            (0..count).map(|_| RwLock::new(Backend::new())).collect(),
+        ))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn get(&self, index: usize) -> Option<Backend> {
+        let db = self.0.get(index)?;
+        let backend = db.read().unwrap_or_else(PoisonError::into_inner);
+        Some(backend.clone())
+    }
+
+    // 调用时刻每个下标对应的数据库
+    pub fn iter(&self) -> impl Iterator<Item = Backend> + '_ {
+        (0..self.len()).filter_map(|index| self.get(index))
+    }
+
+    // 交换两个下标对应的数据库，下标越界时返回 false。
+    // 按下标顺序加锁，避免两个方向相反的 SWAPDB 互相等待
+    pub fn swap(&self, a: usize, b: usize) -> bool {
+        if a >= self.len() || b >= self.len() {
+            return false;
+        }
+        if a == b {
+            return true;
+        }
+        let (first, second) = (a.min(b), a.max(b));
+        let mut first = self.0[first]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut second = self.0[second]
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        std::mem::swap(&mut *first, &mut *second);
+        true
+    }
+}
+
+impl Default for Databases {
+    fn default() -> Self {
+        Self::new(DEFAULT_DATABASES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespFrame;
+
+    use super::*;
+
+    #[test]
+    fn test_databases_are_independent() {
+        let databases = Databases::default();
+        assert_eq!(databases.len(), DEFAULT_DATABASES);
+
+        let db0 = databases.get(0).unwrap();
+        db0.set("hello".to_string(), RespFrame::BulkString(b"world".into()));
+        assert!(databases.get(0).unwrap().exists("hello"));
+        assert!(!databases.get(1).unwrap().exists("hello"));
+        assert!(databases.get(DEFAULT_DATABASES).is_none());
+
+        // clone 共享同一组数据库
+        let cloned = databases.clone();
+        assert!(cloned.get(0).unwrap().exists("hello"));
+    }
+
+    #[test]
+    fn test_databases_swap() {
+        let databases = Databases::new(3);
+        databases
+            .get(0)
+            .unwrap()
+            .set("hello".to_string(), b"world".into());
+
+        assert!(databases.swap(2, 0));
+        assert!(!databases.get(0).unwrap().exists("hello"));
+        assert!(databases.get(2).unwrap().exists("hello"));
+
+        assert!(databases.swap(1, 1));
+        assert!(!databases.swap(0, 3));
+        assert!(databases.get(2).unwrap().exists("hello"));
+    }
+}
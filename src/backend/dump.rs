@@ -0,0 +1,265 @@
+use std::collections::VecDeque;
+
+use bytes::BytesMut;
+use dashmap::{DashMap, DashSet};
+use thiserror::Error;
+
+use crate::{RespDecoder, RespEncoder, RespFrame};
+
+use super::{Backend, SortedSet};
+
+// DUMP 的格式（非 redis 兼容）：
+// [version: u8][type: u8][count: u32] 之后是若干个 [len: u32][bytes]，整数均为大端序。
+// string/list/set 的元素是 RESP 编码的值，hash 依次是 field 和 RESP 编码的值，
+// zset 依次是 member 和 8 字节的 f64 分数
+const DUMP_VERSION: u8 = 1;
+
+const TYPE_STRING: u8 = 0;
+const TYPE_HASH: u8 = 1;
+const TYPE_SET: u8 = 2;
+const TYPE_LIST: u8 = 3;
+const TYPE_ZSET: u8 = 4;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RestoreError {
+    #[error("BUSYKEY Target key name already exists.")]
+    BusyKey,
+    #[error("ERR DUMP payload version or checksum are wrong")]
+    InvalidPayload,
+}
+
+// 反序列化后的值，写入对应的 store 之前不持有任何锁
+enum DumpValue {
+    String(RespFrame),
+    Hash(DashMap<String, RespFrame>),
+    Set(DashSet<RespFrame>),
+    List(VecDeque<RespFrame>),
+    ZSet(SortedSet),
+}
+
+impl Backend {
+    pub fn dump(&self, key: &str) -> Option<Vec<u8>> {
+        let mut writer = DumpWriter::default();
+        if let Some(value) = self.map.get(key) {
+            writer.header(TYPE_STRING, 1);
+            writer.item(&value.encode());
+        } else if let Some(hmap) = self.hmap.get(key) {
+            writer.header(TYPE_HASH, hmap.len());
+            for v in hmap.iter() {
+                writer.item(v.key().as_bytes());
+                writer.item(&v.value().encode());
+            }
+        } else if let Some(set) = self.set.get(key) {
+            writer.header(TYPE_SET, set.len());
+            for v in set.iter() {
+                writer.item(&v.encode());
+            }
+        } else if let Some(list) = self.list.get(key) {
+            writer.header(TYPE_LIST, list.len());
+            for v in list.iter() {
+                writer.item(&v.encode());
+            }
+        } else if let Some(zset) = self.zset.get(key) {
+            writer.header(TYPE_ZSET, zset.len());
+            for (member, score) in zset.iter() {
+                writer.item(member.as_bytes());
+                writer.item(&score.to_be_bytes());
+            }
+        } else {
+            return None;
+        }
+        Some(writer.0)
+    }
+
+    // payload 完整校验通过后才会修改 backend
+    pub fn restore(&self, key: &str, payload: &[u8], replace: bool) -> Result<(), RestoreError> {
+        let value = decode_value(payload).ok_or(RestoreError::InvalidPayload)?;
+        if self.exists(key) {
+            if !replace {
+                return Err(RestoreError::BusyKey);
+            }
+            self.del(key);
+        }
+
+        let key = key.to_string();
+        match value {
+            DumpValue::String(value) => {
+                self.map.insert(key, value);
+            }
+            DumpValue::Hash(value) => {
+                self.hmap.insert(key, value);
+            }
+            DumpValue::Set(value) => {
+                self.set.insert(key, value);
+            }
+            DumpValue::List(value) => {
+                self.list.insert(key, value);
+            }
+            DumpValue::ZSet(value) => {
+                self.zset.insert(key, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+struct DumpWriter(Vec<u8>);
+
+impl DumpWriter {
+    fn header(&mut self, kind: u8, count: usize) {
+        self.0.push(DUMP_VERSION);
+        self.0.push(kind);
+        self.0.extend_from_slice(&(count as u32).to_be_bytes());
+    }
+
+    fn item(&mut self, data: &[u8]) {
+        self.0.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        self.0.extend_from_slice(data);
+    }
+}
+
+struct DumpReader<'a>(&'a [u8]);
+
+impl<'a> DumpReader<'a> {
+    fn take(&mut self, n: usize) -> Option<&'a [u8]> {
+        let (data, rest) = self.0.split_at_checked(n)?;
+        self.0 = rest;
+        Some(data)
+    }
+
+    fn u8(&mut self) -> Option<u8> {
+        self.take(1).map(|data| data[0])
+    }
+
+    fn u32(&mut self) -> Option<usize> {
+        let data = self.take(4)?.try_into().ok()?;
+        Some(u32::from_be_bytes(data) as usize)
+    }
+
+    fn item(&mut self) -> Option<&'a [u8]> {
+        let len = self.u32()?;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        String::from_utf8(self.item()?.to_vec()).ok()
+    }
+
+    // 每个元素必须恰好是一个完整的 RESP 帧
+    fn frame(&mut self) -> Option<RespFrame> {
+        let mut buf = BytesMut::from(self.item()?);
+        let frame = RespFrame::decode(&mut buf).ok()?;
+        buf.is_empty().then_some(frame)
+    }
+
+    fn score(&mut self) -> Option<f64> {
+        let data = self.item()?.try_into().ok()?;
+        Some(f64::from_be_bytes(data)).filter(|score| !score.is_nan())
+    }
+}
+
+fn decode_value(payload: &[u8]) -> Option<DumpValue> {
+    let mut reader = DumpReader(payload);
+    if reader.u8()? != DUMP_VERSION {
+        return None;
+    }
+    let kind = reader.u8()?;
+    let count = reader.u32()?;
+    let value = match kind {
+        TYPE_STRING if count == 1 => DumpValue::String(reader.frame()?),
+        TYPE_HASH => {
+            let hmap = DashMap::new();
+            for _ in 0..count {
+                let field = reader.string()?;
+                hmap.insert(field, reader.frame()?);
+            }
+            DumpValue::Hash(hmap)
+        }
+        TYPE_SET => {
+            let set = DashSet::new();
+            for _ in 0..count {
+                set.insert(reader.frame()?);
+            }
+            DumpValue::Set(set)
+        }
+        TYPE_LIST => {
+            let mut list = VecDeque::new();
+            for _ in 0..count {
+                list.push_back(reader.frame()?);
+            }
+            DumpValue::List(list)
+        }
+        TYPE_ZSET => {
+            let mut zset = SortedSet::new();
+            for _ in 0..count {
+                let member = reader.string()?;
+                zset.insert(member, reader.score()?);
+            }
+            DumpValue::ZSet(zset)
+        }
+        _ => return None,
+    };
+    reader.0.is_empty().then_some(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::ListSide;
+
+    use super::*;
+
+    #[test]
+    fn test_dump_restore_round_trip() {
+        let backend = Backend::new();
+        backend.set("str".to_string(), b"hello".into());
+        backend.hset("hash".to_string(), "f".to_string(), b"v".into());
+        backend.sadd("set".to_string(), RespFrame::Integer(1));
+        backend.push(
+            "list".to_string(),
+            vec![b"a".into(), b"b".into()],
+            ListSide::Right,
+            true,
+        );
+        backend
+            .zset
+            .entry("zset".to_string())
+            .or_default()
+            .insert("m".to_string(), 1.5);
+
+        for key in ["str", "hash", "set", "list", "zset"] {
+            let payload = backend.dump(key).unwrap();
+            assert_eq!(
+                backend.restore(key, &payload, false),
+                Err(RestoreError::BusyKey)
+            );
+            backend.restore("copy", &payload, true).unwrap();
+            assert_eq!(backend.dump("copy"), Some(payload), "{}", key);
+        }
+
+        assert_eq!(backend.dump("missing"), None);
+    }
+
+    #[test]
+    fn test_restore_invalid_payload() {
+        let backend = Backend::new();
+        backend.set("str".to_string(), b"hello".into());
+        let payload = backend.dump("str").unwrap();
+
+        let mut invalid = vec![vec![], payload[..payload.len() - 1].to_vec()];
+        let mut trailing = payload.clone();
+        trailing.push(0);
+        invalid.push(trailing);
+        let mut version = payload.clone();
+        version[0] = 0;
+        invalid.push(version);
+
+        for payload in invalid {
+            assert_eq!(
+                backend.restore("other", &payload, false),
+                Err(RestoreError::InvalidPayload)
+            );
+        }
+        assert!(!backend.exists("other"));
+    }
+}
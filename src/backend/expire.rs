@@ -0,0 +1,97 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::Backend;
+
+// 过期时间以 unix 毫秒保存，EXPIRETIME 之类的命令可以直接换算成绝对时间
+pub fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+impl Backend {
+    // key 不存在时返回 false。已经过期的时间也会被记录，由调用方决定是否立即删除
+    pub fn set_expire(&self, key: &str, deadline_ms: u64) -> bool {
+        if !self.exists(key) {
+            return false;
+        }
+        self.expires.insert(key.to_string(), deadline_ms);
+        true
+    }
+
+    pub fn expire_at(&self, key: &str) -> Option<u64> {
+        self.expires.get(key).map(|deadline| *deadline)
+    }
+
+    // 移除过期时间，key 没有设置过期时间时返回 false
+    pub fn persist(&self, key: &str) -> bool {
+        self.expires.remove(key).is_some()
+    }
+
+    // 设置了过期时间的 key 数量
+    pub fn expires_len(&self) -> usize {
+        self.expires.len()
+    }
+
+    // 访问 key 之前调用，key 已经过期时删除并返回 true
+    pub fn expire_if_needed(&self, key: &str) -> bool {
+        let now = now_ms();
+        if self
+            .expires
+            .remove_if(key, |_, deadline| *deadline <= now)
+            .is_none()
+        {
+            return false;
+        }
+        self.del(key);
+        true
+    }
+
+    // 后台定期调用，删除所有已经过期的 key 并返回它们
+    pub fn remove_expired(&self) -> Vec<String> {
+        let now = now_ms();
+        let expired = self
+            .expires
+            .iter()
+            .filter(|entry| *entry.value() <= now)
+            .map(|entry| entry.key().clone())
+            .collect::<Vec<_>>();
+        expired
+            .into_iter()
+            .filter(|key| self.expire_if_needed(key))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::RespFrame;
+
+    #[test]
+    fn test_expire_lifecycle() {
+        let backend = Backend::new();
+        assert!(!backend.set_expire("hello", now_ms() + 10_000));
+
+        backend.set("hello".to_string(), RespFrame::Integer(1));
+        backend.set("other".to_string(), RespFrame::Integer(2));
+        assert!(backend.set_expire("hello", now_ms() + 10_000));
+        assert!(!backend.expire_if_needed("hello"));
+        assert!(backend.persist("hello"));
+        assert!(!backend.persist("hello"));
+        assert_eq!(backend.expire_at("hello"), None);
+
+        backend.set_expire("hello", now_ms() - 1);
+        backend.set_expire("other", now_ms() + 10_000);
+        assert_eq!(backend.expires_len(), 2);
+        assert_eq!(backend.remove_expired(), vec!["hello".to_string()]);
+        assert!(!backend.exists("hello"));
+        assert!(backend.exists("other"));
+        assert_eq!(backend.expires_len(), 1);
+
+        // 覆盖写入会清除过期时间
+        backend.set("other".to_string(), RespFrame::Integer(3));
+        assert_eq!(backend.expire_at("other"), None);
+    }
+}
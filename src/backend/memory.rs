@@ -0,0 +1,152 @@
+use std::mem::{size_of, size_of_val};
+
+use crate::RespFrame;
+
+use super::{Backend, StreamFields};
+
+// 哈希表、集合等容器中每个元素的额外开销（桶指针、哈希值等）的粗略估计
+const ENTRY_OVERHEAD: usize = 16;
+
+// 值占用内存的估计，递归计算嵌套的 RESP 值，只统计字符串内容的长度而不是实际分配的容量
+pub fn deep_size(frame: &RespFrame) -> usize {
+    size_of::<RespFrame>() + heap_size(frame)
+}
+
+fn heap_size(frame: &RespFrame) -> usize {
+    match frame {
+        RespFrame::SimpleString(s) => s.0.len(),
+        RespFrame::Error(e) => e.0.len(),
+        RespFrame::BulkError(e) => e.0.len(),
+        RespFrame::BulkString(s) => s.0.len(),
+        RespFrame::BigNumber(n) => n.0.len(),
+        RespFrame::VerbatimString(s) => s.format().len() + s.data().len(),
+        RespFrame::Array(array) => array.0.iter().map(deep_size).sum(),
+        RespFrame::Push(push) => push.0.iter().map(deep_size).sum(),
+        RespFrame::Attribute(attribute) => {
+            let attributes: usize = attribute
+                .attributes()
+                .iter()
+                .map(|(k, v)| deep_size(k) + deep_size(v) + ENTRY_OVERHEAD)
+                .sum();
+            attributes + deep_size(attribute.value())
+        }
+        RespFrame::Map(map) => map
+            .0
+            .iter()
+            .map(|(k, v)| deep_size(k) + deep_size(v) + ENTRY_OVERHEAD)
+            .sum(),
+        RespFrame::Set(set) => set.0.iter().map(|v| deep_size(v) + ENTRY_OVERHEAD).sum(),
+        RespFrame::Integer(_)
+        | RespFrame::Double(_)
+        | RespFrame::Null(_)
+        | RespFrame::Boolean(_) => 0,
+    }
+}
+
+fn string_size(s: &str) -> usize {
+    size_of::<String>() + s.len()
+}
+
+// 只遍历前 samples 个元素，按平均大小推算整个容器，samples 为 0 时遍历全部元素
+fn sampled_size<I>(len: usize, samples: usize, sizes: I) -> usize
+where
+    I: Iterator<Item = usize>,
+{
+    let limit = if samples == 0 { len } else { samples.min(len) };
+    if limit == 0 {
+        return 0;
+    }
+    let total: usize = sizes.take(limit).sum();
+    total / limit * len
+}
+
+impl Backend {
+    // MEMORY USAGE：key 本身加上值的估计大小，key 不存在时返回 None
+    pub fn memory_usage(&self, key: &str, samples: usize) -> Option<usize> {
+        let value = if let Some(value) = self.map.get(key) {
+            deep_size(&value)
+        } else if let Some(hmap) = self.hmap.get(key) {
+            let entries = hmap
+                .iter()
+                .map(|v| string_size(v.key()) + deep_size(v.value()) + ENTRY_OVERHEAD);
+            sampled_size(hmap.len(), samples, entries)
+        } else if let Some(set) = self.set.get(key) {
+            let entries = set.iter().map(|v| deep_size(&v) + ENTRY_OVERHEAD);
+            sampled_size(set.len(), samples, entries)
+        } else if let Some(list) = self.list.get(key) {
+            sampled_size(list.len(), samples, list.iter().map(deep_size))
+        } else if let Some(zset) = self.zset.get(key) {
+            // member 同时保存在分数表和有序索引中
+            let entries = zset
+                .iter()
+                .map(|(member, _)| 2 * (string_size(member) + ENTRY_OVERHEAD) + size_of::<f64>());
+            sampled_size(zset.len(), samples, entries)
+        } else if let Some(stream) = self.stream.get(key) {
+            let entries = stream.iter().map(|(id, fields)| {
+                let fields: usize = fields
+                    .iter()
+                    .map(|(f, v)| 2 * size_of::<Vec<u8>>() + f.len() + v.len())
+                    .sum();
+                size_of_val(id) + size_of::<StreamFields>() + fields + ENTRY_OVERHEAD
+            });
+            sampled_size(stream.len(), samples, entries)
+        } else {
+            return None;
+        };
+        Some(string_size(key) + ENTRY_OVERHEAD + value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{RespArray, RespMap};
+
+    #[test]
+    fn test_deep_size_grows_with_content() {
+        let small: RespFrame = b"a".into();
+        let large: RespFrame = vec![b'a'; 1000].as_slice().into();
+        assert!(deep_size(&large) > deep_size(&small));
+
+        let array: RespFrame = RespArray::new(vec![small.clone(), large.clone()]).into();
+        assert!(deep_size(&array) > deep_size(&small) + deep_size(&large));
+
+        let mut map = RespMap::new();
+        map.insert(b"key".into(), large.clone());
+        assert!(deep_size(&map.into()) > deep_size(&large));
+    }
+
+    #[test]
+    fn test_memory_usage() {
+        let backend = Backend::new();
+        assert_eq!(backend.memory_usage("missing", 5), None);
+
+        backend.set("small".to_string(), b"a".into());
+        backend.set("large".to_string(), vec![b'a'; 1000].as_slice().into());
+        let small = backend.memory_usage("small", 5).unwrap();
+        let large = backend.memory_usage("large", 5).unwrap();
+        assert!(large > small);
+
+        for i in 0..10 {
+            backend.hset("hash".to_string(), format!("field{}", i), b"value".into());
+        }
+        let hash = backend.memory_usage("hash", 0).unwrap();
+        backend.hset("hash".to_string(), "more".to_string(), b"value".into());
+        assert!(backend.memory_usage("hash", 0).unwrap() > hash);
+    }
+
+    #[test]
+    fn test_memory_usage_samples() {
+        let backend = Backend::new();
+        for i in 0..1000 {
+            backend.sadd(
+                "set".to_string(),
+                format!("member{:04}", i).as_bytes().into(),
+            );
+        }
+        // 元素大小相同时抽样估计与完整遍历的结果一致
+        let full = backend.memory_usage("set", 0).unwrap();
+        let sampled = backend.memory_usage("set", 5).unwrap();
+        assert_eq!(full, sampled);
+    }
+}
@@ -1,7 +1,66 @@
+mod databases;
+mod dump;
+mod expire;
+mod memory;
+mod stream;
+mod zset;
+
 use crate::RespFrame;
-use dashmap::{DashMap, DashSet};
+use dashmap::{mapref::entry::Entry, DashMap, DashSet};
+use rand::seq::{IteratorRandom, SliceRandom};
+use std::collections::{HashSet, VecDeque};
 use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Notify;
+
+pub use self::{
+    databases::{Databases, DEFAULT_DATABASES},
+    dump::RestoreError,
+    expire::now_ms,
+    memory::deep_size,
+    stream::{Stream, StreamError, StreamFields, StreamId, StreamIdSpec},
+    zset::{LexBound, Score, ScoreBound, SortedSet},
+};
+
+// UNLINK 删除元素个数超过这个值的集合时，在后台任务中释放内存，不阻塞当前连接
+const LAZYFREE_THRESHOLD: usize = 64;
+
+// SRANDMEMBER 和 HRANDFIELD 的 count 为负数时允许重复，返回的个数与集合大小无关，
+// 超过这个值时命令直接回复 out of range，避免一次生成过多元素
+pub const MAX_RANDOM_COUNT: u64 = 1_000_000;
+
+// key 所在的 store，同一个 key 只会出现在其中一个 store 中
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyType {
+    String,
+    Hash,
+    Set,
+    List,
+    ZSet,
+    Stream,
+}
+
+impl KeyType {
+    // TYPE 命令返回的名字
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyType::String => "string",
+            KeyType::Hash => "hash",
+            KeyType::Set => "set",
+            KeyType::List => "list",
+            KeyType::ZSet => "zset",
+            KeyType::Stream => "stream",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListSide {
+    Left,
+    Right,
+}
 
 #[derive(Debug, Clone)]
 pub struct Backend(Arc<BackendInner>);
@@ -11,6 +70,37 @@ pub struct BackendInner {
     pub(crate) map: DashMap<String, RespFrame>,
     pub(crate) hmap: DashMap<String, DashMap<String, RespFrame>>,
     pub(crate) set: DashMap<String, DashSet<RespFrame>>,
+    pub(crate) list: DashMap<String, VecDeque<RespFrame>>,
+    pub(crate) zset: DashMap<String, SortedSet>,
+    pub(crate) stream: DashMap<String, Stream>,
+    // BLPOP/BRPOP 等待中的客户端，push 时唤醒对应 key 上的等待者
+    pub(crate) list_waiters: DashMap<String, Arc<Notify>>,
+    // INFO stats 中的 keyspace_hits/keyspace_misses
+    keyspace_hits: AtomicU64,
+    keyspace_misses: AtomicU64,
+    // 每个 key 最后一次被命令访问的时间和访问次数，用于 OBJECT IDLETIME、OBJECT FREQ
+    // 和之后的 LRU/LFU 淘汰。与值分开存放，这样各个 store 的类型和直接访问它们的命令都不需要改动
+    access: DashMap<String, KeyAccess>,
+    // WATCH 使用的 key 版本号，写命令执行后由 frame_handler 更新。只记录正在被 WATCH 的 key，
+    // 最后一个连接取消 WATCH 时删除，所以不会随写入过的 key 一直增长
+    versions: DashMap<String, WatchedVersion>,
+    version_clock: AtomicU64,
+    // 设置了过期时间的 key，值是 unix 毫秒时间戳。访问时惰性删除，后台任务定期清理
+    pub(crate) expires: DashMap<String, u64>,
+}
+
+#[derive(Debug, Default)]
+struct WatchedVersion {
+    version: u64,
+    // WATCH 了这个 key 的连接数
+    watchers: usize,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct KeyAccess {
+    at: Instant,
+    // 简单的访问计数，不像 redis 的 LFU 计数器那样取对数和随时间衰减
+    count: u64,
 }
 
 impl Deref for Backend {
@@ -33,6 +123,16 @@ impl Default for BackendInner {
             map: DashMap::new(),
             hmap: DashMap::new(),
             set: DashMap::new(),
+            list: DashMap::new(),
+            zset: DashMap::new(),
+            stream: DashMap::new(),
+            list_waiters: DashMap::new(),
+            keyspace_hits: AtomicU64::new(0),
+            keyspace_misses: AtomicU64::new(0),
+            access: DashMap::new(),
+            versions: DashMap::new(),
+            version_clock: AtomicU64::new(0),
+            expires: DashMap::new(),
         }
     }
 }
@@ -43,17 +143,58 @@ impl Backend {
     }
 
     pub fn get(&self, key: &str) -> Option<RespFrame> {
-        self.map.get(key).map(|v| v.value().clone())
+        let value = self.map.get(key).map(|v| v.value().clone());
+        self.record_lookup(value.is_some());
+        value
+    }
+
+    pub fn keyspace_hits(&self) -> u64 {
+        self.keyspace_hits.load(Ordering::Relaxed)
     }
 
+    pub fn keyspace_misses(&self) -> u64 {
+        self.keyspace_misses.load(Ordering::Relaxed)
+    }
+
+    fn record_lookup(&self, hit: bool) {
+        let counter = if hit {
+            &self.keyspace_hits
+        } else {
+            &self.keyspace_misses
+        };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    // 所有类型的 key 数量之和，不同类型的同名 key 分别计数
+    pub fn len(&self) -> usize {
+        self.map.len()
+            + self.hmap.len()
+            + self.set.len()
+            + self.list.len()
+            + self.zset.len()
+            + self.stream.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // 与 redis 的 SET 一致，覆盖任意类型的旧值，同时清除过期时间
     pub fn set(&self, key: String, value: RespFrame) {
+        if self.is_wrong_type(&key, KeyType::String) {
+            self.del(&key);
+        }
+        self.expires.remove(&key);
         self.map.insert(key, value);
     }
 
     pub fn hget(&self, key: &str, field: &str) -> Option<RespFrame> {
-        self.hmap
+        let value = self
+            .hmap
             .get(key)
-            .and_then(|v| v.get(field).map(|v| v.value().clone()))
+            .and_then(|v| v.get(field).map(|v| v.value().clone()));
+        self.record_lookup(value.is_some());
+        value
     }
 
     pub fn hset(&self, key: String, field: String, value: RespFrame) {
@@ -61,8 +202,23 @@ impl Backend {
         hmap.insert(field, value);
     }
 
+    // field 不存在时才写入，返回是否写入成功
+    pub fn hsetnx(&self, key: String, field: String, value: RespFrame) -> bool {
+        let hmap = self.hmap.entry(key).or_default();
+        let inserted = match hmap.entry(field) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+                true
+            }
+        };
+        inserted
+    }
+
     pub fn hgetall(&self, key: &str) -> Option<DashMap<String, RespFrame>> {
-        self.hmap.get(key).map(|v| v.clone())
+        let value = self.hmap.get(key).map(|v| v.clone());
+        self.record_lookup(value.is_some());
+        value
     }
 
     pub fn sadd(&self, key: String, value: RespFrame) {
@@ -76,4 +232,552 @@ impl Backend {
             .map(|v| v.contains(value))
             .unwrap_or_default()
     }
+
+    // 两个 key 可能落在同一个 shard 上，同时持有两个 entry 的锁会死锁，
+    // 所以总是先在 source 上删除并释放锁，再写入 destination
+    pub fn smove(&self, source: &str, destination: &str, member: &RespFrame) -> bool {
+        let removed = match self.set.get(source) {
+            Some(set) => set.remove(member).is_some(),
+            None => false,
+        };
+        if !removed {
+            return false;
+        }
+        self.set.remove_if(source, |_, set| set.is_empty());
+        self.sadd(destination.to_string(), member.clone());
+        true
+    }
+
+    // 交集的大小，不生成交集本身。从最小的集合开始逐个检查成员，数到 limit 个就返回
+    pub fn sintercard(&self, keys: &[String], limit: usize) -> usize {
+        let mut sets = Vec::with_capacity(keys.len());
+        for key in keys {
+            match self.set.get(key) {
+                Some(set) => sets.push(set),
+                // 任意一个 key 不存在时交集为空
+                None => return 0,
+            }
+        }
+        sets.sort_by_key(|set| set.len());
+        let Some((smallest, others)) = sets.split_first() else {
+            return 0;
+        };
+        smallest
+            .iter()
+            .filter(|member| others.iter().all(|set| set.contains(member.key())))
+            .take(limit)
+            .count()
+    }
+
+    // count 为正数时返回至多 count 个不重复的 member，为负数时允许重复并返回 |count| 个
+    pub fn srandmember(&self, key: &str, count: i64) -> Vec<RespFrame> {
+        let set = match self.set.get(key) {
+            Some(set) => set,
+            None => return vec![],
+        };
+        let mut rng = rand::thread_rng();
+        if count >= 0 {
+            // choose_multiple 按 count 预先分配，不重复时最多也只有 set.len() 个
+            let count = (count as usize).min(set.len());
+            set.iter()
+                .map(|v| v.key().clone())
+                .choose_multiple(&mut rng, count)
+        } else {
+            let members = set.iter().map(|v| v.key().clone()).collect::<Vec<_>>();
+            (0..count.unsigned_abs().min(MAX_RANDOM_COUNT))
+                .filter_map(|_| members.choose(&mut rng).cloned())
+                .collect()
+        }
+    }
+
+    // 与 srandmember 相同：count 为正数时返回不重复的 field，负数时允许重复
+    pub fn hrandfield(&self, key: &str, count: i64) -> Vec<(String, RespFrame)> {
+        let hmap = match self.hmap.get(key) {
+            Some(hmap) => hmap,
+            None => return vec![],
+        };
+        let mut rng = rand::thread_rng();
+        let entries = hmap.iter().map(|v| (v.key().clone(), v.value().clone()));
+        if count >= 0 {
+            entries.choose_multiple(&mut rng, (count as usize).min(hmap.len()))
+        } else {
+            let entries = entries.collect::<Vec<_>>();
+            (0..count.unsigned_abs().min(MAX_RANDOM_COUNT))
+                .filter_map(|_| entries.choose(&mut rng).cloned())
+                .collect()
+        }
+    }
+
+    // 是否是同一个数据库，而不是内容相同
+    pub fn ptr_eq(&self, other: &Backend) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+
+    // WATCH 之后没有被写命令修改过的 key 版本号不变，没有被 WATCH 的 key 版本号为 0
+    pub fn version(&self, key: &str) -> u64 {
+        self.versions.get(key).map_or(0, |v| v.version)
+    }
+
+    // 开始记录 key 的版本号，返回当前的版本号。每次 watch 都要对应一次 unwatch
+    pub fn watch(&self, key: &str) -> u64 {
+        let mut watched = self.versions.entry(key.to_string()).or_default();
+        watched.watchers += 1;
+        watched.version
+    }
+
+    pub fn unwatch(&self, key: &str) {
+        if let Entry::Occupied(mut entry) = self.versions.entry(key.to_string()) {
+            let watched = entry.get_mut();
+            watched.watchers = watched.watchers.saturating_sub(1);
+            if watched.watchers == 0 {
+                entry.remove();
+            }
+        }
+    }
+
+    // 没有连接 WATCH 这个 key 时不需要记录
+    pub fn bump_version(&self, key: &str) {
+        if let Some(mut watched) = self.versions.get_mut(key) {
+            watched.version = self.version_clock.fetch_add(1, Ordering::Relaxed) + 1;
+        }
+    }
+
+    // 命令执行后由 frame_handler 对参数中的每个 key 调用。key 已存在时只更新时间，
+    // 不会重新分配 key；key 不存在（例如被删除或从未创建）时清理对应的记录。返回 key 是否存在
+    pub fn touch(&self, key: &str) -> bool {
+        if !self.exists(key) {
+            self.access.remove(key);
+            return false;
+        }
+        let now = Instant::now();
+        if let Some(mut accessed) = self.access.get_mut(key) {
+            accessed.at = now;
+            accessed.count = accessed.count.saturating_add(1);
+        } else {
+            self.access
+                .insert(key.to_string(), KeyAccess { at: now, count: 1 });
+        }
+        true
+    }
+
+    // key 不存在时返回 None，存在但还没有访问记录时视为刚刚访问过
+    pub fn idle_time(&self, key: &str) -> Option<Duration> {
+        if !self.exists(key) {
+            return None;
+        }
+        let idle = self
+            .access
+            .get(key)
+            .map(|accessed| accessed.at.elapsed())
+            .unwrap_or_default();
+        Some(idle)
+    }
+
+    pub fn idle_seconds(&self, key: &str) -> Option<u64> {
+        self.idle_time(key).map(|idle| idle.as_secs())
+    }
+
+    // key 被命令访问的次数，key 不存在时返回 None
+    pub fn access_frequency(&self, key: &str) -> Option<u64> {
+        if !self.exists(key) {
+            return None;
+        }
+        Some(self.access.get(key).map_or(0, |accessed| accessed.count))
+    }
+
+    pub fn del(&self, key: &str) -> bool {
+        self.access.remove(key);
+        self.expires.remove(key);
+        let map = self.map.remove(key).is_some();
+        let hmap = self.hmap.remove(key).is_some();
+        let set = self.set.remove(key).is_some();
+        let list = self.list.remove(key).is_some();
+        let zset = self.zset.remove(key).is_some();
+        let stream = self.stream.remove(key).is_some();
+        map || hmap || set || list || zset || stream
+    }
+
+    pub fn exists(&self, key: &str) -> bool {
+        self.key_type(key).is_some()
+    }
+
+    pub fn key_type(&self, key: &str) -> Option<KeyType> {
+        if self.map.contains_key(key) {
+            Some(KeyType::String)
+        } else if self.hmap.contains_key(key) {
+            Some(KeyType::Hash)
+        } else if self.set.contains_key(key) {
+            Some(KeyType::Set)
+        } else if self.list.contains_key(key) {
+            Some(KeyType::List)
+        } else if self.zset.contains_key(key) {
+            Some(KeyType::ZSet)
+        } else if self.stream.contains_key(key) {
+            Some(KeyType::Stream)
+        } else {
+            None
+        }
+    }
+
+    // key 存在但不是 expected 类型，命令应返回 WRONGTYPE
+    pub fn is_wrong_type(&self, key: &str, expected: KeyType) -> bool {
+        self.key_type(key).is_some_and(|t| t != expected)
+    }
+
+    // 以下 *_keys 方法返回调用时刻的 key 快照：遍历过程中只持有单个 shard 的读锁，
+    // 之后的写入不会反映在结果中，调用方可以放心地在遍历快照时修改 backend
+    pub fn map_keys(&self) -> Vec<String> {
+        snapshot_keys(&self.map)
+    }
+
+    pub fn hmap_keys(&self) -> Vec<String> {
+        snapshot_keys(&self.hmap)
+    }
+
+    pub fn set_keys(&self) -> Vec<String> {
+        snapshot_keys(&self.set)
+    }
+
+    pub fn list_keys(&self) -> Vec<String> {
+        snapshot_keys(&self.list)
+    }
+
+    pub fn zset_keys(&self) -> Vec<String> {
+        snapshot_keys(&self.zset)
+    }
+
+    pub fn stream_keys(&self) -> Vec<String> {
+        snapshot_keys(&self.stream)
+    }
+
+    // 所有 store 的 key（去重）
+    pub fn keys(&self) -> HashSet<String> {
+        [
+            self.map_keys(),
+            self.hmap_keys(),
+            self.set_keys(),
+            self.list_keys(),
+            self.zset_keys(),
+            self.stream_keys(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+
+    // DashMap 不支持随机访问，这里收集所有 key 后随机选取一个
+    pub fn random_key(&self) -> Option<String> {
+        self.keys().into_iter().choose(&mut rand::thread_rng())
+    }
+
+    // 将 source 的值复制到 destination，destination 已存在且未指定 replace 时不做任何操作
+    pub fn copy(&self, source: &str, destination: &str, replace: bool) -> bool {
+        if source == destination || !self.exists(source) {
+            return false;
+        }
+        if self.exists(destination) {
+            if !replace {
+                return false;
+            }
+            self.del(destination);
+        }
+
+        if let Some(value) = self.map.get(source).map(|v| v.value().clone()) {
+            self.map.insert(destination.to_string(), value);
+        } else if let Some(value) = self.hmap.get(source).map(|v| v.value().clone()) {
+            self.hmap.insert(destination.to_string(), value);
+        } else if let Some(value) = self.set.get(source).map(|v| v.value().clone()) {
+            self.set.insert(destination.to_string(), value);
+        } else if let Some(value) = self.list.get(source).map(|v| v.value().clone()) {
+            self.list.insert(destination.to_string(), value);
+        } else if let Some(value) = self.zset.get(source).map(|v| v.value().clone()) {
+            self.zset.insert(destination.to_string(), value);
+        } else if let Some(value) = self.stream.get(source).map(|v| v.value().clone()) {
+            self.stream.insert(destination.to_string(), value);
+        } else {
+            return false;
+        }
+        if let Some(deadline) = self.expire_at(source) {
+            self.expires.insert(destination.to_string(), deadline);
+        }
+        true
+    }
+
+    pub fn unlink(&self, key: &str) -> bool {
+        self.access.remove(key);
+        self.expires.remove(key);
+        let map = self.map.remove(key).is_some();
+        let hmap = self
+            .hmap
+            .remove(key)
+            .map(|(_, v)| free_lazily(v.len(), v))
+            .is_some();
+        let set = self
+            .set
+            .remove(key)
+            .map(|(_, v)| free_lazily(v.len(), v))
+            .is_some();
+        let list = self
+            .list
+            .remove(key)
+            .map(|(_, v)| free_lazily(v.len(), v))
+            .is_some();
+        let zset = self
+            .zset
+            .remove(key)
+            .map(|(_, v)| free_lazily(v.len(), v))
+            .is_some();
+        let stream = self
+            .stream
+            .remove(key)
+            .map(|(_, v)| free_lazily(v.len(), v))
+            .is_some();
+        map || hmap || set || list || zset || stream
+    }
+
+    // LPUSH/RPUSH/LPUSHX/RPUSHX 共用，create 为 false 时列表不存在则不创建，返回 0
+    pub fn push(&self, key: String, values: Vec<RespFrame>, side: ListSide, create: bool) -> usize {
+        let push_all = |list: &mut VecDeque<RespFrame>| {
+            for value in values {
+                match side {
+                    ListSide::Left => list.push_front(value),
+                    ListSide::Right => list.push_back(value),
+                }
+            }
+            list.len()
+        };
+        // 通过 entry 持有分片写锁，保证存在性检查与 push 是原子的
+        let len = match self.list.entry(key.clone()) {
+            Entry::Occupied(mut entry) => push_all(entry.get_mut()),
+            Entry::Vacant(entry) if create => {
+                let mut list = VecDeque::new();
+                let len = push_all(&mut list);
+                entry.insert(list);
+                len
+            }
+            Entry::Vacant(_) => 0,
+        };
+        if len > 0 {
+            if let Some(waiter) = self.list_waiters.get(&key) {
+                waiter.notify_waiters();
+            }
+        }
+        len
+    }
+
+    // 弹出一个元素，列表为空后删除 key
+    pub fn pop(&self, key: &str, side: ListSide) -> Option<RespFrame> {
+        let value = {
+            let mut list = self.list.get_mut(key)?;
+            match side {
+                ListSide::Left => list.pop_front(),
+                ListSide::Right => list.pop_back(),
+            }
+        };
+        self.list.remove_if(key, |_, list| list.is_empty());
+        value
+    }
+
+    pub fn list_waiter(&self, key: &str) -> Arc<Notify> {
+        self.list_waiters
+            .entry(key.to_string())
+            .or_default()
+            .clone()
+    }
+
+    // 没有其他等待者时移除 key 对应的 Notify，调用前需要先释放自己持有的 Arc
+    pub fn release_list_waiter(&self, key: &str) {
+        self.list_waiters
+            .remove_if(key, |_, waiter| Arc::strong_count(waiter) == 1);
+    }
+
+    // 返回 element 在列表中的下标，rank 为负数时从尾部开始查找，count 为 0 表示返回全部匹配
+    pub fn lpos(&self, key: &str, element: &RespFrame, rank: i64, count: usize) -> Vec<usize> {
+        let list = match self.list.get(key) {
+            Some(list) => list,
+            None => return vec![],
+        };
+        let skip = (rank.unsigned_abs() - 1) as usize;
+        let take = if count == 0 { usize::MAX } else { count };
+        let matches = list
+            .iter()
+            .enumerate()
+            .filter(|(_, v)| *v == element)
+            .map(|(i, _)| i);
+        if rank > 0 {
+            matches.skip(skip).take(take).collect()
+        } else {
+            matches.rev().skip(skip).take(take).collect()
+        }
+    }
+
+    // 删除与 element 相等的元素，count 为负数时从尾部开始，为 0 时删除全部，返回删除的个数
+    pub fn lrem(&self, key: &str, count: i64, element: &RespFrame) -> usize {
+        let removed = {
+            let mut list = match self.list.get_mut(key) {
+                Some(list) => list,
+                None => return 0,
+            };
+            let limit = if count == 0 {
+                usize::MAX
+            } else {
+                count.unsigned_abs() as usize
+            };
+            let mut removed = 0;
+            if count >= 0 {
+                list.retain(|v| {
+                    let remove = removed < limit && v == element;
+                    removed += remove as usize;
+                    !remove
+                });
+            } else {
+                let mut kept = VecDeque::with_capacity(list.len());
+                for v in list.drain(..).rev() {
+                    if removed < limit && v == *element {
+                        removed += 1;
+                    } else {
+                        kept.push_front(v);
+                    }
+                }
+                *list = kept;
+            }
+            removed
+        };
+        self.list.remove_if(key, |_, list| list.is_empty());
+        removed
+    }
+}
+
+// 大集合的释放放到 blocking 线程池中进行，没有 runtime 时直接释放
+fn free_lazily<T: Send + 'static>(len: usize, value: T) {
+    match tokio::runtime::Handle::try_current() {
+        Ok(handle) if len > LAZYFREE_THRESHOLD => {
+            handle.spawn_blocking(move || drop(value));
+        }
+        _ => drop(value),
+    }
+}
+
+fn snapshot_keys<V>(store: &DashMap<String, V>) -> Vec<String> {
+    store.iter().map(|v| v.key().clone()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_idle_time() {
+        let backend = Backend::new();
+        assert_eq!(backend.idle_time("hello"), None);
+
+        backend.set("hello".to_string(), b"world".into());
+        backend.touch("hello");
+        std::thread::sleep(Duration::from_millis(20));
+        let idle = backend.idle_time("hello").unwrap();
+        assert!(idle >= Duration::from_millis(20));
+        assert_eq!(backend.idle_seconds("hello"), Some(0));
+
+        backend.touch("hello");
+        assert!(backend.idle_time("hello").unwrap() < idle);
+
+        backend.del("hello");
+        assert_eq!(backend.idle_time("hello"), None);
+        backend.touch("missing");
+        assert!(backend.access.is_empty());
+    }
+
+    #[test]
+    fn test_access_frequency() {
+        let backend = Backend::new();
+        assert_eq!(backend.access_frequency("hello"), None);
+
+        backend.set("hello".to_string(), b"world".into());
+        assert_eq!(backend.access_frequency("hello"), Some(0));
+        for expected in 1..=3 {
+            backend.touch("hello");
+            assert_eq!(backend.access_frequency("hello"), Some(expected));
+        }
+
+        // 删除后重新创建的 key 从 0 开始计数
+        backend.del("hello");
+        assert_eq!(backend.access_frequency("hello"), None);
+        backend.set("hello".to_string(), b"world".into());
+        assert_eq!(backend.access_frequency("hello"), Some(0));
+    }
+
+    #[test]
+    fn test_keys_snapshot() {
+        let backend = Backend::new();
+        backend.set("a".to_string(), b"1".into());
+        backend.set("b".to_string(), b"2".into());
+        backend.hset("h".to_string(), "f".to_string(), b"v".into());
+        backend.sadd("s".to_string(), b"m".into());
+        backend.sadd("a".to_string(), b"m".into());
+
+        let mut keys = backend.map_keys();
+        keys.sort();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(backend.hmap_keys(), ["h"]);
+        let mut keys = backend.set_keys();
+        keys.sort();
+        assert_eq!(keys, ["a", "s"]);
+        assert!(backend.list_keys().is_empty());
+
+        let snapshot = backend.keys();
+        assert_eq!(snapshot.len(), 4);
+        for key in snapshot.iter() {
+            backend.del(key);
+        }
+        assert!(backend.keys().is_empty());
+        assert_eq!(snapshot.len(), 4);
+    }
+
+    #[test]
+    fn test_key_type() {
+        let backend = Backend::new();
+        backend.set("str".to_string(), RespFrame::Integer(1));
+        backend.hset("hash".to_string(), "f".to_string(), RespFrame::Integer(1));
+        backend.sadd("set".to_string(), RespFrame::Integer(1));
+        backend.push(
+            "list".to_string(),
+            vec![RespFrame::Integer(1)],
+            ListSide::Left,
+            true,
+        );
+        backend
+            .zset
+            .entry("zset".to_string())
+            .or_default()
+            .insert("m".to_string(), 1.0);
+
+        assert_eq!(backend.key_type("str"), Some(KeyType::String));
+        assert_eq!(backend.key_type("hash"), Some(KeyType::Hash));
+        assert_eq!(backend.key_type("set"), Some(KeyType::Set));
+        assert_eq!(backend.key_type("list"), Some(KeyType::List));
+        assert_eq!(backend.key_type("zset"), Some(KeyType::ZSet));
+        assert_eq!(backend.key_type("missing"), None);
+
+        assert!(backend.is_wrong_type("hash", KeyType::String));
+        assert!(!backend.is_wrong_type("hash", KeyType::Hash));
+        assert!(!backend.is_wrong_type("missing", KeyType::Hash));
+    }
+
+    #[test]
+    fn test_versions_only_for_watched_keys() {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), b"world".into());
+        backend.bump_version("hello");
+        assert!(backend.versions.is_empty());
+
+        // 两个连接 WATCH 同一个 key，都取消之后才删除记录
+        let version = backend.watch("hello");
+        assert_eq!(backend.watch("hello"), version);
+        backend.bump_version("hello");
+        assert_ne!(backend.version("hello"), version);
+        backend.unwatch("hello");
+        assert_eq!(backend.versions.len(), 1);
+        backend.unwatch("hello");
+        assert!(backend.versions.is_empty());
+    }
 }
@@ -0,0 +1,342 @@
+use std::{collections::BTreeMap, fmt, ops::Bound, str::FromStr};
+
+use thiserror::Error;
+
+use super::now_ms;
+
+// <ms>-<seq>，先比较毫秒再比较序号
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StreamId {
+    pub ms: u64,
+    pub seq: u64,
+}
+
+// XADD 指定的 ID
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamIdSpec {
+    // *
+    Auto,
+    // <ms>-*，序号自动生成
+    AutoSeq(u64),
+    Explicit(StreamId),
+}
+
+// 一条消息的字段按写入顺序保存
+pub type StreamFields = Vec<(Vec<u8>, Vec<u8>)>;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stream {
+    entries: BTreeMap<StreamId, StreamFields>,
+    // 删除消息之后新 ID 也不能小于它，所以单独保存
+    last_id: StreamId,
+}
+
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum StreamError {
+    #[error("ERR The ID specified in XADD is equal or smaller than the target stream top item")]
+    IdTooSmall,
+    #[error("ERR The ID specified in XADD must be greater than 0-0")]
+    IdZero,
+    #[error("ERR Invalid stream ID specified as stream command argument")]
+    InvalidId,
+    #[error("ERR The stream has exhausted the last possible ID, unable to add more items")]
+    Exhausted,
+}
+
+impl StreamId {
+    pub const MIN: StreamId = StreamId { ms: 0, seq: 0 };
+    pub const MAX: StreamId = StreamId {
+        ms: u64::MAX,
+        seq: u64::MAX,
+    };
+
+    pub fn new(ms: u64, seq: u64) -> Self {
+        Self { ms, seq }
+    }
+
+    // 范围查询中的 ID 可以省略序号，作为起点时取 0，作为终点时取最大值
+    pub fn parse_bound(s: &str, start: bool) -> Result<Self, StreamError> {
+        match s {
+            "-" => Ok(Self::MIN),
+            "+" => Ok(Self::MAX),
+            _ if s.contains('-') => s.parse(),
+            _ => {
+                let ms = s.parse().map_err(|_| StreamError::InvalidId)?;
+                let seq = if start { 0 } else { u64::MAX };
+                Ok(Self::new(ms, seq))
+            }
+        }
+    }
+}
+
+impl FromStr for StreamId {
+    type Err = StreamError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (ms, seq) = s.split_once('-').ok_or(StreamError::InvalidId)?;
+        match (ms.parse(), seq.parse()) {
+            (Ok(ms), Ok(seq)) => Ok(Self::new(ms, seq)),
+            _ => Err(StreamError::InvalidId),
+        }
+    }
+}
+
+impl fmt::Display for StreamId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.ms, self.seq)
+    }
+}
+
+impl FromStr for StreamIdSpec {
+    type Err = StreamError;
+
+    // 与 redis 一致，只有毫秒部分时序号为 0
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "*" {
+            return Ok(Self::Auto);
+        }
+        match s.split_once('-') {
+            Some((ms, "*")) => ms
+                .parse()
+                .map(Self::AutoSeq)
+                .map_err(|_| StreamError::InvalidId),
+            Some(_) => s.parse().map(Self::Explicit),
+            None => s
+                .parse()
+                .map(|ms| Self::Explicit(StreamId::new(ms, 0)))
+                .map_err(|_| StreamError::InvalidId),
+        }
+    }
+}
+
+impl Stream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn last_id(&self) -> StreamId {
+        self.last_id
+    }
+
+    // 新消息的 ID 必须大于之前所有的 ID，自动生成时在同一毫秒内递增序号
+    pub fn add(
+        &mut self,
+        spec: StreamIdSpec,
+        fields: StreamFields,
+    ) -> Result<StreamId, StreamError> {
+        // 最后一个 ID 已经是最大值时，任何 ID 都无法再加入
+        if self.last_id == StreamId::MAX {
+            return Err(StreamError::Exhausted);
+        }
+        let id = self.next_id(spec)?;
+        if id == StreamId::MIN {
+            return Err(StreamError::IdZero);
+        }
+        if id <= self.last_id {
+            return Err(StreamError::IdTooSmall);
+        }
+        self.entries.insert(id, fields);
+        self.last_id = id;
+        Ok(id)
+    }
+
+    fn next_id(&self, spec: StreamIdSpec) -> Result<StreamId, StreamError> {
+        let last = self.last_id;
+        let id = match spec {
+            // 时钟回拨时沿用上一个 ID 的毫秒数
+            StreamIdSpec::Auto => match now_ms() {
+                ms if ms > last.ms => StreamId::new(ms, 0),
+                _ => next_seq(last)?,
+            },
+            StreamIdSpec::AutoSeq(ms) if ms == last.ms => next_seq(last)?,
+            StreamIdSpec::AutoSeq(ms) if ms < last.ms => return Err(StreamError::IdTooSmall),
+            // 0-* 的第一个序号是 1，因为 0-0 不是合法的 ID
+            StreamIdSpec::AutoSeq(0) => StreamId::new(0, 1),
+            StreamIdSpec::AutoSeq(ms) => StreamId::new(ms, 0),
+            StreamIdSpec::Explicit(id) => id,
+        };
+        Ok(id)
+    }
+
+    // count 为 None 时返回全部
+    pub fn range(
+        &self,
+        start: Bound<StreamId>,
+        end: Bound<StreamId>,
+        count: Option<usize>,
+    ) -> Vec<(StreamId, &StreamFields)> {
+        if let (Bound::Included(s) | Bound::Excluded(s), Bound::Included(e) | Bound::Excluded(e)) =
+            (start, end)
+        {
+            // BTreeMap::range 在起点大于终点时会 panic
+            if s > e {
+                return vec![];
+            }
+            if s == e && !matches!((start, end), (Bound::Included(_), Bound::Included(_))) {
+                return vec![];
+            }
+        }
+        self.entries
+            .range((start, end))
+            .take(count.unwrap_or(usize::MAX))
+            .map(|(id, fields)| (*id, fields))
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&StreamId, &StreamFields)> {
+        self.entries.iter()
+    }
+}
+
+fn next_seq(last: StreamId) -> Result<StreamId, StreamError> {
+    match last.seq.checked_add(1) {
+        Some(seq) => Ok(StreamId::new(last.ms, seq)),
+        None => last
+            .ms
+            .checked_add(1)
+            .map(|ms| StreamId::new(ms, 0))
+            .ok_or(StreamError::Exhausted),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(pairs: &[(&str, &str)]) -> StreamFields {
+        pairs
+            .iter()
+            .map(|(f, v)| (f.as_bytes().to_vec(), v.as_bytes().to_vec()))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_ids() {
+        assert_eq!("5-3".parse::<StreamId>(), Ok(StreamId::new(5, 3)));
+        assert_eq!("5".parse::<StreamId>(), Err(StreamError::InvalidId));
+        assert_eq!("a-1".parse::<StreamId>(), Err(StreamError::InvalidId));
+        assert_eq!("*".parse::<StreamIdSpec>(), Ok(StreamIdSpec::Auto));
+        assert_eq!("5-*".parse::<StreamIdSpec>(), Ok(StreamIdSpec::AutoSeq(5)));
+        assert_eq!(
+            "5".parse::<StreamIdSpec>(),
+            Ok(StreamIdSpec::Explicit(StreamId::new(5, 0)))
+        );
+        assert_eq!(StreamId::parse_bound("5", true), Ok(StreamId::new(5, 0)));
+        assert_eq!(
+            StreamId::parse_bound("5", false),
+            Ok(StreamId::new(5, u64::MAX))
+        );
+        assert_eq!(StreamId::parse_bound("+", false), Ok(StreamId::MAX));
+        assert_eq!(StreamId::new(5, 10).to_string(), "5-10");
+        assert!(StreamId::new(5, 10) < StreamId::new(6, 0));
+    }
+
+    #[test]
+    fn test_add_enforces_monotonic_ids() {
+        let mut stream = Stream::new();
+        let a = fields(&[("a", "1")]);
+        assert_eq!(
+            stream.add(StreamIdSpec::Explicit(StreamId::MIN), a.clone()),
+            Err(StreamError::IdZero)
+        );
+        assert_eq!(
+            stream.add(StreamIdSpec::AutoSeq(0), a.clone()),
+            Ok(StreamId::new(0, 1))
+        );
+        assert_eq!(
+            stream.add(StreamIdSpec::Explicit(StreamId::new(5, 1)), a.clone()),
+            Ok(StreamId::new(5, 1))
+        );
+        assert_eq!(
+            stream.add(StreamIdSpec::AutoSeq(5), a.clone()),
+            Ok(StreamId::new(5, 2))
+        );
+        assert_eq!(
+            stream.add(StreamIdSpec::Explicit(StreamId::new(5, 2)), a.clone()),
+            Err(StreamError::IdTooSmall)
+        );
+        assert_eq!(
+            stream.add(StreamIdSpec::AutoSeq(4), a.clone()),
+            Err(StreamError::IdTooSmall)
+        );
+        assert_eq!(stream.len(), 3);
+
+        // 自动生成的 ID 总是递增，同一毫秒内序号加一
+        let first = stream.add(StreamIdSpec::Auto, a.clone()).unwrap();
+        let second = stream.add(StreamIdSpec::Auto, a).unwrap();
+        assert!(second > first);
+        if second.ms == first.ms {
+            assert_eq!(second.seq, first.seq + 1);
+        }
+    }
+
+    #[test]
+    fn test_add_after_max_id() {
+        let mut stream = Stream::new();
+        let a = fields(&[("a", "1")]);
+        assert_eq!(
+            stream.add(StreamIdSpec::Explicit(StreamId::MAX), a.clone()),
+            Ok(StreamId::MAX)
+        );
+        for spec in [
+            StreamIdSpec::Auto,
+            StreamIdSpec::AutoSeq(u64::MAX),
+            StreamIdSpec::Explicit(StreamId::new(1, 1)),
+        ] {
+            assert_eq!(stream.add(spec, a.clone()), Err(StreamError::Exhausted));
+        }
+        assert_eq!(stream.len(), 1);
+    }
+
+    #[test]
+    fn test_range() {
+        let mut stream = Stream::new();
+        for (ms, seq) in [(1, 0), (1, 1), (2, 0), (3, 5)] {
+            stream
+                .add(
+                    StreamIdSpec::Explicit(StreamId::new(ms, seq)),
+                    fields(&[("ms", &ms.to_string())]),
+                )
+                .unwrap();
+        }
+        let ids = |entries: Vec<(StreamId, &StreamFields)>| {
+            entries
+                .into_iter()
+                .map(|(id, _)| id.to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let all = stream.range(
+            Bound::Included(StreamId::MIN),
+            Bound::Included(StreamId::MAX),
+            None,
+        );
+        assert_eq!(ids(all), ["1-0", "1-1", "2-0", "3-5"]);
+        let first_ms = stream.range(
+            Bound::Included(StreamId::new(1, 0)),
+            Bound::Included(StreamId::new(1, u64::MAX)),
+            None,
+        );
+        assert_eq!(ids(first_ms), ["1-0", "1-1"]);
+        let paged = stream.range(
+            Bound::Excluded(StreamId::new(1, 1)),
+            Bound::Included(StreamId::MAX),
+            Some(1),
+        );
+        assert_eq!(ids(paged), ["2-0"]);
+        let reversed = stream.range(
+            Bound::Included(StreamId::new(3, 0)),
+            Bound::Included(StreamId::new(1, 0)),
+            None,
+        );
+        assert!(reversed.is_empty());
+    }
+}
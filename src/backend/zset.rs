@@ -0,0 +1,306 @@
+use std::{
+    cmp::Ordering,
+    collections::{BTreeMap, BTreeSet, HashMap},
+    ops::Bound,
+};
+
+// f64 本身没有实现 Ord，这里用 total_cmp 给出全序，NaN 在解析阶段就会被拒绝
+#[derive(Debug, Clone, Copy)]
+pub struct Score(pub f64);
+
+impl PartialEq for Score {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Score {}
+
+impl PartialOrd for Score {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Score {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+// 分数区间的一端，exclusive 对应命令参数中的 "(" 前缀
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreBound {
+    pub value: f64,
+    pub exclusive: bool,
+}
+
+impl ScoreBound {
+    pub fn inclusive(value: f64) -> Self {
+        Self {
+            value,
+            exclusive: false,
+        }
+    }
+
+    pub fn exclusive(value: f64) -> Self {
+        Self {
+            value,
+            exclusive: true,
+        }
+    }
+
+    fn to_bound(self) -> Bound<Score> {
+        if self.exclusive {
+            Bound::Excluded(Score(self.value))
+        } else {
+            Bound::Included(Score(self.value))
+        }
+    }
+}
+
+// 字典序区间的一端，对应命令参数中的 "-"、"+"、"[member" 和 "(member"
+#[derive(Debug, Clone, PartialEq)]
+pub enum LexBound {
+    Min,
+    Max,
+    Inclusive(String),
+    Exclusive(String),
+}
+
+impl LexBound {
+    // member 是否不小于作为下界的 self，String 的比较即按字节序比较
+    fn below(&self, member: &str) -> bool {
+        match self {
+            Self::Min => true,
+            Self::Max => false,
+            Self::Inclusive(value) => value.as_str() <= member,
+            Self::Exclusive(value) => value.as_str() < member,
+        }
+    }
+
+    // member 是否不大于作为上界的 self
+    fn above(&self, member: &str) -> bool {
+        match self {
+            Self::Min => false,
+            Self::Max => true,
+            Self::Inclusive(value) => member <= value.as_str(),
+            Self::Exclusive(value) => member < value.as_str(),
+        }
+    }
+}
+
+// 有序集合：member -> score 的映射用于 O(1) 查询分数，score -> members 的有序索引用于范围查询，
+// 同一分数下的 member 按字节序排列
+#[derive(Debug, Default, Clone)]
+pub struct SortedSet {
+    scores: HashMap<String, f64>,
+    index: BTreeMap<Score, BTreeSet<String>>,
+}
+
+impl SortedSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 插入或更新 member 的分数，返回 member 之前的分数
+    pub fn insert(&mut self, member: String, score: f64) -> Option<f64> {
+        // -0.0 与 0.0 视为同一个分数
+        let score = if score == 0.0 { 0.0 } else { score };
+        let old = self.scores.insert(member.clone(), score);
+        if let Some(old) = old {
+            self.remove_from_index(old, &member);
+        }
+        self.index.entry(Score(score)).or_default().insert(member);
+        old
+    }
+
+    pub fn remove(&mut self, member: &str) -> Option<f64> {
+        let score = self.scores.remove(member)?;
+        self.remove_from_index(score, member);
+        Some(score)
+    }
+
+    fn remove_from_index(&mut self, score: f64, member: &str) {
+        if let Some(members) = self.index.get_mut(&Score(score)) {
+            members.remove(member);
+            if members.is_empty() {
+                self.index.remove(&Score(score));
+            }
+        }
+    }
+
+    pub fn score(&self, member: &str) -> Option<f64> {
+        self.scores.get(member).copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.scores.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scores.is_empty()
+    }
+
+    // 按分数升序遍历，分数相同时按 member 的字节序
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        self.index
+            .iter()
+            .flat_map(|(score, members)| members.iter().map(move |m| (m.as_str(), score.0)))
+    }
+
+    // 返回分数落在 [min, max] 区间内的 member，区间为空时返回空迭代器
+    pub fn range_by_score(
+        &self,
+        min: ScoreBound,
+        max: ScoreBound,
+    ) -> impl DoubleEndedIterator<Item = (&str, f64)> {
+        self.score_range(min, max)
+            .flat_map(|(score, members)| members.iter().map(move |m| (m.as_str(), score.0)))
+    }
+
+    // 与 redis 一致，只有所有 member 分数相同时结果才有意义，按 (score, member) 顺序过滤
+    pub fn range_by_lex<'a>(
+        &'a self,
+        min: &'a LexBound,
+        max: &'a LexBound,
+    ) -> impl DoubleEndedIterator<Item = (&'a str, f64)> {
+        self.iter()
+            .filter(move |(member, _)| min.below(member) && max.above(member))
+    }
+
+    // 只遍历分数索引，不需要逐个访问 member
+    pub fn count_by_score(&self, min: ScoreBound, max: ScoreBound) -> usize {
+        self.score_range(min, max)
+            .map(|(_, members)| members.len())
+            .sum()
+    }
+
+    fn score_range(
+        &self,
+        min: ScoreBound,
+        max: ScoreBound,
+    ) -> impl DoubleEndedIterator<Item = (&Score, &BTreeSet<String>)> {
+        // BTreeMap::range 在区间非法时会 panic，需要提前判断
+        let valid =
+            min.value < max.value || (min.value == max.value && !min.exclusive && !max.exclusive);
+        valid
+            .then(|| self.index.range((min.to_bound(), max.to_bound())))
+            .into_iter()
+            .flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sorted_set_order() {
+        let mut zset = SortedSet::new();
+        assert_eq!(zset.insert("b".to_string(), 2.0), None);
+        assert_eq!(zset.insert("a".to_string(), 2.0), None);
+        assert_eq!(zset.insert("c".to_string(), f64::NEG_INFINITY), None);
+        assert_eq!(zset.insert("d".to_string(), 1.0), None);
+        assert_eq!(zset.insert("d".to_string(), 3.0), Some(1.0));
+
+        assert_eq!(zset.len(), 4);
+        assert_eq!(
+            zset.iter().collect::<Vec<_>>(),
+            vec![("c", f64::NEG_INFINITY), ("a", 2.0), ("b", 2.0), ("d", 3.0)]
+        );
+
+        assert_eq!(zset.remove("a"), Some(2.0));
+        assert_eq!(zset.remove("a"), None);
+        assert_eq!(zset.score("b"), Some(2.0));
+        assert_eq!(zset.len(), 3);
+    }
+
+    #[test]
+    fn test_sorted_set_range_by_score() {
+        let mut zset = SortedSet::new();
+        for (member, score) in [("a", 1.0), ("b", 2.0), ("c", 2.0), ("d", 3.0)] {
+            zset.insert(member.to_string(), score);
+        }
+
+        let members = |min, max| {
+            zset.range_by_score(min, max)
+                .map(|(m, _)| m)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            members(ScoreBound::inclusive(2.0), ScoreBound::inclusive(3.0)),
+            vec!["b", "c", "d"]
+        );
+        assert_eq!(
+            members(ScoreBound::exclusive(2.0), ScoreBound::inclusive(3.0)),
+            vec!["d"]
+        );
+        assert_eq!(
+            members(
+                ScoreBound::inclusive(f64::NEG_INFINITY),
+                ScoreBound::exclusive(2.0)
+            ),
+            vec!["a"]
+        );
+        assert!(members(ScoreBound::inclusive(3.0), ScoreBound::inclusive(1.0)).is_empty());
+        assert!(members(ScoreBound::exclusive(2.0), ScoreBound::inclusive(2.0)).is_empty());
+    }
+
+    #[test]
+    fn test_sorted_set_count_by_score() {
+        let mut zset = SortedSet::new();
+        for (member, score) in [("a", 1.0), ("b", 2.0), ("c", 2.0), ("d", 3.0)] {
+            zset.insert(member.to_string(), score);
+        }
+
+        let inf = f64::INFINITY;
+        assert_eq!(
+            zset.count_by_score(ScoreBound::inclusive(-inf), ScoreBound::inclusive(inf)),
+            4
+        );
+        assert_eq!(
+            zset.count_by_score(ScoreBound::inclusive(2.0), ScoreBound::inclusive(2.0)),
+            2
+        );
+        assert_eq!(
+            zset.count_by_score(ScoreBound::exclusive(1.0), ScoreBound::exclusive(3.0)),
+            2
+        );
+        assert_eq!(
+            zset.count_by_score(ScoreBound::inclusive(3.0), ScoreBound::inclusive(1.0)),
+            0
+        );
+    }
+
+    #[test]
+    fn test_sorted_set_range_by_lex() {
+        let mut zset = SortedSet::new();
+        for member in ["a", "b", "c", "d", "e", "f", "g"] {
+            zset.insert(member.to_string(), 0.0);
+        }
+
+        let members = |min: LexBound, max: LexBound| {
+            zset.range_by_lex(&min, &max)
+                .map(|(m, _)| m.to_string())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(members(LexBound::Min, LexBound::Max).len(), 7);
+        assert_eq!(
+            members(LexBound::Min, LexBound::Inclusive("c".to_string())),
+            vec!["a", "b", "c"]
+        );
+        assert_eq!(
+            members(LexBound::Min, LexBound::Exclusive("c".to_string())),
+            vec!["a", "b"]
+        );
+        assert_eq!(
+            members(
+                LexBound::Exclusive("aaa".to_string()),
+                LexBound::Exclusive("g".to_string())
+            ),
+            vec!["b", "c", "d", "e", "f"]
+        );
+        assert!(members(LexBound::Max, LexBound::Min).is_empty());
+    }
+}
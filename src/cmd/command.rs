@@ -0,0 +1,475 @@
+use crate::{Backend, RespArray, RespFrame, RespNull, SimpleError, SimpleString};
+
+use super::{extract_args, parse_integer, CommandError, CommandExecutor};
+
+// 命令表中的一项，arity 与 redis 相同：正数表示参数个数固定（包括命令名），负数表示至少 -arity 个
+#[derive(Debug, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub arity: i64,
+    pub flags: &'static [&'static str],
+    pub first_key: i64,
+    pub last_key: i64,
+    pub step: i64,
+    // numkeys 参数的下标，不为 0 时 key 是紧跟在它后面的 numkeys 个参数
+    pub numkeys: usize,
+}
+
+const fn spec(
+    name: &'static str,
+    arity: i64,
+    flags: &'static [&'static str],
+    keys: (i64, i64, i64),
+) -> CommandSpec {
+    CommandSpec {
+        name,
+        arity,
+        flags,
+        first_key: keys.0,
+        last_key: keys.1,
+        step: keys.2,
+        numkeys: 0,
+    }
+}
+
+// 与 redis 的 movablekeys 命令一致，first key、last key 和 step 都是 0，key 的位置由 numkeys 决定
+const fn movable_spec(
+    name: &'static str,
+    arity: i64,
+    flags: &'static [&'static str],
+    numkeys: usize,
+) -> CommandSpec {
+    CommandSpec {
+        numkeys,
+        ..spec(name, arity, flags, NO_KEYS)
+    }
+}
+
+const NO_KEYS: (i64, i64, i64) = (0, 0, 0);
+const ONE_KEY: (i64, i64, i64) = (1, 1, 1);
+const ALL_KEYS: (i64, i64, i64) = (1, -1, 1);
+
+const WRITE: &[&str] = &["write", "denyoom"];
+const WRITE_FAST: &[&str] = &["write", "denyoom", "fast"];
+const READONLY: &[&str] = &["readonly"];
+const READONLY_FAST: &[&str] = &["readonly", "fast"];
+const READONLY_MOVABLE: &[&str] = &["readonly", "movablekeys"];
+const CONNECTION: &[&str] = &["noscript", "loading", "stale", "fast"];
+const ADMIN: &[&str] = &["admin", "noscript", "loading", "stale"];
+const PUBSUB: &[&str] = &["pubsub", "noscript", "loading", "stale"];
+const PUBSUB_FAST: &[&str] = &["pubsub", "loading", "stale", "fast"];
+const TRANSACTION_FAST: &[&str] = &["noscript", "loading", "stale", "fast"];
+
+// 所有已实现命令的描述，Command::try_from 分发前先在这里查找命令并校验参数个数
+pub static COMMAND_TABLE: &[CommandSpec] = &[
+    spec("get", 2, READONLY_FAST, ONE_KEY),
+    spec("getex", -2, WRITE_FAST, ONE_KEY),
+    spec("set", 3, WRITE, ONE_KEY),
+    spec("incrbyfloat", 3, WRITE_FAST, ONE_KEY),
+    spec("setbit", 4, WRITE, ONE_KEY),
+    spec("getbit", 3, READONLY_FAST, ONE_KEY),
+    spec("bitcount", -2, READONLY, ONE_KEY),
+    spec("hget", 3, READONLY_FAST, ONE_KEY),
+    spec("hset", 4, WRITE_FAST, ONE_KEY),
+    spec("hsetnx", 4, WRITE_FAST, ONE_KEY),
+    spec("hgetall", 2, READONLY, ONE_KEY),
+    spec("hmget", -3, READONLY_FAST, ONE_KEY),
+    spec("hrandfield", -2, &["readonly"], ONE_KEY),
+    spec("sadd", -3, WRITE_FAST, ONE_KEY),
+    spec("sismember", 3, READONLY_FAST, ONE_KEY),
+    spec("smove", 4, WRITE_FAST, (1, 2, 1)),
+    spec("srandmember", -2, READONLY, ONE_KEY),
+    spec("hscan", -3, READONLY, ONE_KEY),
+    spec("sscan", -3, READONLY, ONE_KEY),
+    movable_spec("sintercard", -3, READONLY_MOVABLE, 1),
+    spec("del", -2, &["write"], ALL_KEYS),
+    spec("unlink", -2, &["write", "fast"], ALL_KEYS),
+    spec("copy", -3, WRITE, (1, 2, 1)),
+    spec("touch", -2, READONLY_FAST, ALL_KEYS),
+    spec("expire", 3, WRITE_FAST, ONE_KEY),
+    spec("pexpire", 3, WRITE_FAST, ONE_KEY),
+    spec("ttl", 2, READONLY_FAST, ONE_KEY),
+    spec("pttl", 2, READONLY_FAST, ONE_KEY),
+    spec("persist", 2, WRITE_FAST, ONE_KEY),
+    spec("expireat", 3, WRITE_FAST, ONE_KEY),
+    spec("pexpireat", 3, WRITE_FAST, ONE_KEY),
+    spec("expiretime", 2, READONLY_FAST, ONE_KEY),
+    spec("pexpiretime", 2, READONLY_FAST, ONE_KEY),
+    spec("dump", 2, &["readonly"], ONE_KEY),
+    spec("restore", -4, WRITE, ONE_KEY),
+    spec("sort", -2, WRITE, ONE_KEY),
+    spec("randomkey", 1, &["readonly"], NO_KEYS),
+    spec("type", 2, READONLY_FAST, ONE_KEY),
+    spec("wait", 3, &["noscript"], NO_KEYS),
+    spec("lpush", -3, WRITE_FAST, ONE_KEY),
+    spec("rpush", -3, WRITE_FAST, ONE_KEY),
+    spec("lpushx", -3, WRITE_FAST, ONE_KEY),
+    spec("rpushx", -3, WRITE_FAST, ONE_KEY),
+    spec("lpos", -3, READONLY, ONE_KEY),
+    spec("lrem", 4, &["write"], ONE_KEY),
+    spec("ltrim", 4, &["write"], ONE_KEY),
+    spec("blpop", -3, &["write", "noscript", "blocking"], (1, -2, 1)),
+    spec("brpop", -3, &["write", "noscript", "blocking"], (1, -2, 1)),
+    spec("zadd", -4, WRITE_FAST, ONE_KEY),
+    spec("zincrby", 4, WRITE_FAST, ONE_KEY),
+    spec("zscore", 3, READONLY_FAST, ONE_KEY),
+    spec("zmscore", -3, READONLY_FAST, ONE_KEY),
+    spec("zcard", 2, READONLY_FAST, ONE_KEY),
+    spec("zcount", 4, READONLY_FAST, ONE_KEY),
+    spec("zrange", -4, READONLY, ONE_KEY),
+    spec("zrangebyscore", -4, READONLY, ONE_KEY),
+    spec("zrangebylex", -4, READONLY, ONE_KEY),
+    spec("zlexcount", 4, READONLY_FAST, ONE_KEY),
+    spec("xadd", -5, WRITE_FAST, ONE_KEY),
+    spec("xlen", 2, READONLY_FAST, ONE_KEY),
+    spec("xrange", -4, READONLY, ONE_KEY),
+    spec("object", -2, READONLY, (2, 2, 1)),
+    spec("memory", -2, READONLY, (2, 2, 1)),
+    spec("echo", 2, &["fast"], NO_KEYS),
+    spec("ping", -1, &["fast"], NO_KEYS),
+    spec("select", 2, &["loading", "stale", "fast"], NO_KEYS),
+    spec("swapdb", 3, &["write", "fast"], NO_KEYS),
+    spec("hello", -1, CONNECTION, NO_KEYS),
+    spec("reset", 1, CONNECTION, NO_KEYS),
+    spec("quit", -1, CONNECTION, NO_KEYS),
+    spec("info", -1, &["loading", "stale"], NO_KEYS),
+    spec("debug", -2, ADMIN, NO_KEYS),
+    spec("command", -1, &["loading", "stale"], NO_KEYS),
+    spec("monitor", 1, ADMIN, NO_KEYS),
+    spec("shutdown", -1, ADMIN, NO_KEYS),
+    spec("slowlog", -2, ADMIN, NO_KEYS),
+    spec("config", -2, ADMIN, NO_KEYS),
+    spec("time", 1, &["loading", "stale", "fast"], NO_KEYS),
+    spec("lolwut", -1, &["readonly", "fast"], NO_KEYS),
+    spec("client", -2, ADMIN, NO_KEYS),
+    spec("multi", 1, TRANSACTION_FAST, NO_KEYS),
+    spec("exec", 1, &["noscript", "loading", "stale"], NO_KEYS),
+    spec("discard", 1, TRANSACTION_FAST, NO_KEYS),
+    spec("watch", -2, TRANSACTION_FAST, ALL_KEYS),
+    spec("unwatch", 1, TRANSACTION_FAST, NO_KEYS),
+    spec("subscribe", -2, PUBSUB, NO_KEYS),
+    spec("unsubscribe", -1, PUBSUB, NO_KEYS),
+    spec("psubscribe", -2, PUBSUB, NO_KEYS),
+    spec("punsubscribe", -1, PUBSUB, NO_KEYS),
+    spec("publish", 3, PUBSUB_FAST, NO_KEYS),
+    spec("pubsub", -2, &["pubsub", "loading", "stale"], NO_KEYS),
+];
+
+// 命令名不区分大小写
+pub fn lookup_command(name: &[u8]) -> Option<&'static CommandSpec> {
+    COMMAND_TABLE
+        .iter()
+        .find(|spec| spec.name.as_bytes().eq_ignore_ascii_case(name))
+}
+
+impl CommandSpec {
+    // 参数数组中 key 的下标，last_key 为负数时从末尾开始计算。
+    // numkeys 不是合法的正整数时没有 key，与 redis 一样由命令本身报告参数错误
+    pub fn key_indices(&self, args: &[RespFrame]) -> impl Iterator<Item = usize> {
+        let len = args.len() as i64;
+        let (first, last, step) = match self.numkeys {
+            0 if self.last_key < 0 => (self.first_key, len + self.last_key, self.step.max(1)),
+            0 => (self.first_key, self.last_key.min(len - 1), self.step.max(1)),
+            index => {
+                let count = args
+                    .get(index)
+                    .and_then(|numkeys| parse_integer(numkeys.clone()).ok())
+                    .unwrap_or(0)
+                    .clamp(0, len);
+                let first = index as i64 + 1;
+                (first, (first + count - 1).min(len - 1), 1)
+            }
+        };
+        (first..=last)
+            .step_by(step as usize)
+            .filter(move |_| first > 0)
+            .map(|i| i as usize)
+    }
+
+    pub fn has_flag(&self, flag: &str) -> bool {
+        self.flags.contains(&flag)
+    }
+
+    pub fn check_arity(&self, len: usize) -> Result<(), CommandError> {
+        let len = len as i64;
+        let valid = if self.arity >= 0 {
+            len == self.arity
+        } else {
+            len >= -self.arity
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(CommandError::InvalidArguments(format!(
+                "wrong number of arguments for '{}' command",
+                self.name
+            )))
+        }
+    }
+
+    // COMMAND 输出中的一项：[name, arity, [flags], first key, last key, step]
+    fn to_frame(&self) -> RespFrame {
+        let flags = self
+            .flags
+            .iter()
+            .map(|flag| SimpleString::new(*flag).into())
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(vec![
+            self.name.as_bytes().into(),
+            RespFrame::Integer(self.arity),
+            RespArray::new(flags).into(),
+            RespFrame::Integer(self.first_key),
+            RespFrame::Integer(self.last_key),
+            RespFrame::Integer(self.step),
+        ])
+        .into()
+    }
+}
+
+// command
+// command count
+// command info [name ...]
+// command getkeys name [arg ...]
+// "*3\r\n$7\r\ncommand\r\n$4\r\ninfo\r\n$3\r\nget\r\n"
+#[derive(Debug)]
+pub struct CommandIntrospect {
+    subcommand: CommandSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+enum CommandSubcommand {
+    List,
+    Count,
+    Info(Vec<Vec<u8>>),
+    // 要解析的完整命令，第一项是命令名
+    GetKeys(Vec<RespFrame>),
+}
+
+impl CommandExecutor for CommandIntrospect {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        match &self.subcommand {
+            CommandSubcommand::List => RespArray::new(
+                COMMAND_TABLE
+                    .iter()
+                    .map(CommandSpec::to_frame)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            CommandSubcommand::Count => RespFrame::Integer(COMMAND_TABLE.len() as i64),
+            CommandSubcommand::Info(names) => RespArray::new(
+                names
+                    .iter()
+                    .map(|name| match lookup_command(name) {
+                        Some(spec) => spec.to_frame(),
+                        None => RespFrame::Null(RespNull),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            CommandSubcommand::GetKeys(args) => get_keys(args),
+        }
+    }
+}
+
+// 按命令表中的 key 位置取出参数，与 redis 一致，不执行命令本身
+fn get_keys(args: &[RespFrame]) -> RespFrame {
+    let spec = match args.first() {
+        Some(RespFrame::BulkString(name)) => lookup_command(name),
+        _ => None,
+    };
+    let Some(spec) = spec else {
+        return SimpleError::new("ERR Invalid command specified").into();
+    };
+    if spec.check_arity(args.len()).is_err() {
+        return SimpleError::new("ERR Invalid number of arguments specified for command").into();
+    }
+    let keys = spec
+        .key_indices(args)
+        .map(|i| args[i].clone())
+        .collect::<Vec<_>>();
+    if keys.is_empty() {
+        return SimpleError::new("ERR The command has no key arguments").into();
+    }
+    RespArray::new(keys).into()
+}
+
+impl TryFrom<RespArray> for CommandIntrospect {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let subcommand = match args.next() {
+            None => CommandSubcommand::List,
+            Some(RespFrame::BulkString(sub)) => match sub.to_ascii_lowercase().as_slice() {
+                b"count" if args.len() == 0 => CommandSubcommand::Count,
+                b"info" => {
+                    let mut names = Vec::with_capacity(args.len());
+                    for arg in args {
+                        match arg {
+                            RespFrame::BulkString(name) => names.push(name.0.into()),
+                            _ => {
+                                return Err(CommandError::InvalidArguments(
+                                    "Invalid command name".to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    CommandSubcommand::Info(names)
+                }
+                b"getkeys" if args.len() > 0 => CommandSubcommand::GetKeys(args.collect()),
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "unknown subcommand or wrong number of arguments for '{}'",
+                        String::from_utf8_lossy(&sub)
+                    )))
+                }
+            },
+            Some(_) => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid subcommand".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self { subcommand })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecoder;
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    async fn run(input: &str) -> Result<RespFrame> {
+        let mut buf = BytesMut::from(input);
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = CommandIntrospect::try_from(frame)?;
+        Ok(cmd.execute(&Backend::new()).await)
+    }
+
+    #[test]
+    fn test_check_arity() {
+        let get = lookup_command(b"GET").unwrap();
+        assert!(get.check_arity(2).is_ok());
+        assert!(get.check_arity(3).is_err());
+
+        let del = lookup_command(b"del").unwrap();
+        assert!(del.check_arity(1).is_err());
+        assert!(del.check_arity(5).is_ok());
+
+        assert!(lookup_command(b"nosuchcommand").is_none());
+    }
+
+    #[test]
+    fn test_key_indices() {
+        let indices = |args: &[&str]| {
+            let frames = args
+                .iter()
+                .map(|arg| arg.as_bytes().into())
+                .collect::<Vec<RespFrame>>();
+            lookup_command(args[0].as_bytes())
+                .unwrap()
+                .key_indices(&frames)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(indices(&["get", "k"]), [1]);
+        assert_eq!(indices(&["del", "a", "b", "c"]), [1, 2, 3]);
+        assert_eq!(indices(&["blpop", "a", "b", "0"]), [1, 2]);
+        assert_eq!(indices(&["smove", "a", "b", "m"]), [1, 2]);
+        assert_eq!(indices(&["object", "encoding", "k"]), [2]);
+        assert!(indices(&["echo", "x"]).is_empty());
+
+        // SINTERCARD 的 key 由 numkeys 决定，LIMIT 和它的参数不是 key
+        assert_eq!(indices(&["sintercard", "2", "a", "b"]), [2, 3]);
+        assert_eq!(indices(&["sintercard", "1", "a", "LIMIT", "5"]), [2]);
+        assert_eq!(indices(&["sintercard", "5", "a"]), [2]);
+        assert!(indices(&["sintercard", "x", "a"]).is_empty());
+        assert!(indices(&["sintercard", "-1", "a"]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_command_count_and_list() -> Result<()> {
+        let ret = run("*2\r\n$7\r\ncommand\r\n$5\r\ncount\r\n").await?;
+        assert_eq!(ret, RespFrame::Integer(COMMAND_TABLE.len() as i64));
+
+        let RespFrame::Array(table) = run("*1\r\n$7\r\ncommand\r\n").await? else {
+            panic!("expected an array");
+        };
+        assert_eq!(table.len(), COMMAND_TABLE.len());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_command_info() -> Result<()> {
+        let ret = run("*4\r\n$7\r\ncommand\r\n$4\r\ninfo\r\n$3\r\nGET\r\n$3\r\nfoo\r\n").await?;
+        let expected = RespArray::new(vec![
+            RespArray::new(vec![
+                b"get".into(),
+                RespFrame::Integer(2),
+                RespArray::new(vec![
+                    SimpleString::new("readonly").into(),
+                    SimpleString::new("fast").into(),
+                ])
+                .into(),
+                RespFrame::Integer(1),
+                RespFrame::Integer(1),
+                RespFrame::Integer(1),
+            ])
+            .into(),
+            RespFrame::Null(RespNull),
+        ]);
+        assert_eq!(ret, expected.into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_command_getkeys() -> Result<()> {
+        let ret =
+            run("*5\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$3\r\nSET\r\n$1\r\nk\r\n$1\r\nv\r\n")
+                .await?;
+        assert_eq!(ret, RespArray::new(vec![b"k".into()]).into());
+
+        let ret = run("*4\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$3\r\nget\r\n$1\r\nk\r\n").await?;
+        assert_eq!(ret, RespArray::new(vec![b"k".into()]).into());
+
+        let ret = run(
+            "*6\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$5\r\nblpop\r\n$1\r\na\r\n$1\r\nb\r\n$1\r\n0\r\n",
+        )
+        .await?;
+        assert_eq!(ret, RespArray::new(vec![b"a".into(), b"b".into()]).into());
+
+        let ret = run(
+            "*7\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$10\r\nsintercard\r\n$1\r\n1\r\n$1\r\na\r\n$5\r\nLIMIT\r\n$1\r\n5\r\n",
+        )
+        .await?;
+        assert_eq!(ret, RespArray::new(vec![b"a".into()]).into());
+
+        let ret = run("*4\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$4\r\necho\r\n$1\r\nx\r\n").await?;
+        assert_eq!(
+            ret,
+            SimpleError::new("ERR The command has no key arguments").into()
+        );
+
+        let ret = run("*3\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$3\r\nget\r\n").await?;
+        assert_eq!(
+            ret,
+            SimpleError::new("ERR Invalid number of arguments specified for command").into()
+        );
+
+        let ret = run("*3\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n$3\r\nfoo\r\n").await?;
+        assert_eq!(
+            ret,
+            SimpleError::new("ERR Invalid command specified").into()
+        );
+
+        let mut buf = BytesMut::from("*2\r\n$7\r\ncommand\r\n$7\r\ngetkeys\r\n");
+        assert!(CommandIntrospect::try_from(RespArray::decode(&mut buf)?).is_err());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,805 @@
+use std::{fmt::Write as _, net::SocketAddr};
+
+use crate::{
+    Backend, BulkString, ClientInfo, RespArray, RespFrame, RespMap, RespPush, RespVersion, Session,
+    SimpleError, SimpleString,
+};
+
+use super::{
+    extract_args, parse_integer, validate_command, CommandError, CommandExecutor, RESP_OK,
+};
+
+// client setname name
+// client getname
+// client id
+// client list
+// client kill [id client-id] [addr ip:port]
+// client tracking on|off
+// "*3\r\n$6\r\nclient\r\n$7\r\nsetname\r\n$8\r\nmyworker\r\n"
+#[derive(Debug)]
+pub struct Client {
+    subcommand: ClientSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+enum ClientSubcommand {
+    SetName(String),
+    GetName,
+    Id,
+    List,
+    Kill(KillFilter),
+    Tracking(bool),
+}
+
+// 多个条件同时满足时才关闭对应的连接
+#[derive(Debug, Default, PartialEq)]
+struct KillFilter {
+    id: Option<u64>,
+    addr: Option<SocketAddr>,
+}
+
+// ping [message]
+// "*1\r\n$4\r\nping\r\n"
+#[derive(Debug)]
+pub struct Ping {
+    message: Option<Vec<u8>>,
+}
+
+// quit
+// "*1\r\n$4\r\nquit\r\n"
+#[derive(Debug)]
+pub struct Quit;
+
+// reset
+// "*1\r\n$5\r\nreset\r\n"
+#[derive(Debug)]
+pub struct Reset;
+
+// select index
+// "*2\r\n$6\r\nselect\r\n$1\r\n1\r\n"
+#[derive(Debug)]
+pub struct Select {
+    index: i64,
+}
+
+// swapdb index1 index2
+// "*3\r\n$6\r\nswapdb\r\n$1\r\n0\r\n$1\r\n1\r\n"
+#[derive(Debug)]
+pub struct SwapDb {
+    first: i64,
+    second: i64,
+}
+
+// hello [protover]
+// "*2\r\n$5\r\nhello\r\n$1\r\n3\r\n"
+#[derive(Debug)]
+pub struct Hello {
+    protover: Option<i64>,
+}
+
+impl CommandExecutor for Client {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR CLIENT is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        match &self.subcommand {
+            // 与 redis 一致，设置为空字符串表示清除名称
+            ClientSubcommand::SetName(name) => {
+                session.set_name(Some(name.clone()).filter(|name| !name.is_empty()));
+                RESP_OK.clone()
+            }
+            ClientSubcommand::GetName => BulkString::new(session.name().unwrap_or_default()).into(),
+            ClientSubcommand::Id => RespFrame::Integer(session.id() as i64),
+            ClientSubcommand::List => {
+                let list = session
+                    .server()
+                    .clients()
+                    .iter()
+                    .map(client_line)
+                    .collect::<String>();
+                BulkString::new(list).into()
+            }
+            ClientSubcommand::Kill(filter) => {
+                let killed = session.server().kill_clients(|client| {
+                    filter.id.is_none_or(|id| client.id == id)
+                        && filter.addr.is_none_or(|addr| client.addr == Some(addr))
+                });
+                RespFrame::Integer(killed as i64)
+            }
+            ClientSubcommand::Tracking(on) => {
+                if *on {
+                    session.enable_tracking();
+                } else {
+                    session.disable_tracking();
+                }
+                RESP_OK.clone()
+            }
+        }
+    }
+}
+
+// RESP3 下是 push，RESP2 下降级为数组
+pub(crate) fn invalidate_frame(keys: Vec<String>) -> RespFrame {
+    let keys = keys
+        .into_iter()
+        .map(|key| BulkString::new(key).into())
+        .collect::<Vec<RespFrame>>();
+    RespPush::new(vec![b"invalidate".into(), RespArray::new(keys).into()]).into()
+}
+
+// CLIENT LIST 中的一行，字段顺序与 redis 一致，未实现的字段省略
+fn client_line(client: &ClientInfo) -> String {
+    let addr = client.addr.map(|addr| addr.to_string()).unwrap_or_default();
+    let flags = if client.monitoring {
+        "O"
+    } else if client.subscriptions > 0 {
+        "P"
+    } else {
+        "N"
+    };
+    let mut line = String::new();
+    let _ = writeln!(
+        line,
+        "id={} addr={} name={} age={} idle={} flags={} db={} sub={} cmd={}",
+        client.id,
+        addr,
+        client.name.as_deref().unwrap_or_default(),
+        client.created_at.elapsed().as_secs(),
+        client.last_interaction.elapsed().as_secs(),
+        flags,
+        client.db,
+        client.subscriptions,
+        client.last_command.unwrap_or("NULL"),
+    );
+    line
+}
+
+impl CommandExecutor for Ping {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        self.reply(false)
+    }
+
+    // 与 redis 一致，RESP2 下订阅模式的连接回复 ["pong", message]，RESP3 下与普通连接相同
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        self.reply(session.subscription_count() > 0 && session.protocol() == RespVersion::Resp2)
+    }
+}
+
+impl Ping {
+    fn reply(&self, subscribed: bool) -> RespFrame {
+        match (&self.message, subscribed) {
+            (message, true) => RespArray::new(vec![
+                BulkString::new("pong").into(),
+                BulkString::new(message.clone().unwrap_or_default()).into(),
+            ])
+            .into(),
+            (Some(message), false) => BulkString::new(message.clone()).into(),
+            (None, false) => SimpleString::new("PONG").into(),
+        }
+    }
+}
+
+impl CommandExecutor for Quit {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        RESP_OK.clone()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        session.close_after_reply();
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for Reset {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR RESET is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        session.reset();
+        SimpleString::new("RESET").into()
+    }
+}
+
+impl CommandExecutor for Select {
+    // SELECT 修改的是连接状态，没有连接时无法执行
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR SELECT is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        match usize::try_from(self.index) {
+            Ok(index) if session.select(index) => RESP_OK.clone(),
+            _ => SimpleError::new("ERR DB index is out of range").into(),
+        }
+    }
+}
+
+impl CommandExecutor for SwapDb {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR SWAPDB is not allowed without a connection").into()
+    }
+
+    // 所有连接看到的数据库同时交换，已经 SELECT 的连接之后访问的是交换后的数据
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        match (usize::try_from(self.first), usize::try_from(self.second)) {
+            (Ok(first), Ok(second)) if session.databases().swap(first, second) => RESP_OK.clone(),
+            _ => SimpleError::new("ERR DB index is out of range").into(),
+        }
+    }
+}
+
+impl CommandExecutor for Hello {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR HELLO is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        match self.protover {
+            Some(2) => session.set_protocol(RespVersion::Resp2),
+            Some(3) => session.set_protocol(RespVersion::Resp3),
+            Some(_) => return SimpleError::new("NOPROTO unsupported protocol version").into(),
+            None => {}
+        }
+
+        let proto = match session.protocol() {
+            RespVersion::Resp2 => 2,
+            RespVersion::Resp3 => 3,
+        };
+        let mut info = RespMap::new();
+        info.insert(b"server".into(), b"redis".into());
+        info.insert(
+            b"version".into(),
+            BulkString::new(env!("CARGO_PKG_VERSION")).into(),
+        );
+        info.insert(b"proto".into(), RespFrame::Integer(proto));
+        info.insert(b"id".into(), RespFrame::Integer(session.id() as i64));
+        info.insert(b"mode".into(), b"standalone".into());
+        info.insert(b"role".into(), b"master".into());
+        info.insert(b"modules".into(), RespArray::new(vec![]).into());
+        info.into()
+    }
+}
+
+impl TryFrom<RespArray> for Client {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let subcommand = match arr.get(1) {
+            Some(RespFrame::BulkString(sub)) => sub.to_ascii_lowercase(),
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "CLIENT command must have a subcommand".to_string(),
+                ))
+            }
+        };
+
+        let subcommand = match subcommand.as_slice() {
+            b"setname" => {
+                validate_command(&arr, &["client", "setname"], 1)?;
+                let mut args = extract_args(arr, 2)?.into_iter();
+                let name = match args.next() {
+                    Some(RespFrame::BulkString(name)) => String::from_utf8(name.0.into())?,
+                    _ => return Err(CommandError::InvalidArguments("Invalid name".to_string())),
+                };
+                // 名称会出现在 CLIENT LIST 和日志中，只允许可见的 ASCII 字符
+                if !name.bytes().all(|b| b.is_ascii_graphic()) {
+                    return Err(CommandError::InvalidArguments(
+                        "Client names cannot contain spaces, newlines or special characters."
+                            .to_string(),
+                    ));
+                }
+                ClientSubcommand::SetName(name)
+            }
+            b"getname" => {
+                validate_command(&arr, &["client", "getname"], 0)?;
+                ClientSubcommand::GetName
+            }
+            b"id" => {
+                validate_command(&arr, &["client", "id"], 0)?;
+                ClientSubcommand::Id
+            }
+            b"list" => {
+                validate_command(&arr, &["client", "list"], 0)?;
+                ClientSubcommand::List
+            }
+            b"kill" => {
+                let len = arr.len();
+                if len < 4 || !len.is_multiple_of(2) {
+                    return Err(CommandError::InvalidArguments("syntax error".to_string()));
+                }
+                validate_command(&arr, &["client", "kill"], len - 2)?;
+                ClientSubcommand::Kill(parse_kill_filter(extract_args(arr, 2)?)?)
+            }
+            b"tracking" => {
+                validate_command(&arr, &["client", "tracking"], 1)?;
+                match extract_args(arr, 2)?.into_iter().next() {
+                    Some(RespFrame::BulkString(arg)) => match arg.to_ascii_lowercase().as_slice() {
+                        b"on" => ClientSubcommand::Tracking(true),
+                        b"off" => ClientSubcommand::Tracking(false),
+                        _ => {
+                            return Err(CommandError::InvalidArguments("syntax error".to_string()))
+                        }
+                    },
+                    _ => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+                }
+            }
+            _ => {
+                return Err(CommandError::InvalidArguments(format!(
+                    "unknown subcommand '{}'",
+                    String::from_utf8_lossy(&subcommand)
+                )))
+            }
+        };
+
+        Ok(Self { subcommand })
+    }
+}
+
+fn parse_kill_filter(args: Vec<RespFrame>) -> Result<KillFilter, CommandError> {
+    let mut filter = KillFilter::default();
+    let mut args = args.into_iter();
+    while let (Some(name), Some(value)) = (args.next(), args.next()) {
+        let (RespFrame::BulkString(name), RespFrame::BulkString(value)) = (name, value) else {
+            return Err(CommandError::InvalidArguments("syntax error".to_string()));
+        };
+        let value = String::from_utf8(value.0.into())?;
+        match name.to_ascii_lowercase().as_slice() {
+            b"id" => {
+                let id = value.parse::<u64>().map_err(|_| {
+                    CommandError::InvalidArguments("client-id should be greater than 0".to_string())
+                })?;
+                filter.id = Some(id);
+            }
+            b"addr" => {
+                let addr = value.parse::<SocketAddr>().map_err(|_| {
+                    CommandError::InvalidArguments("Invalid client address".to_string())
+                })?;
+                filter.addr = Some(addr);
+            }
+            _ => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+        }
+    }
+    Ok(filter)
+}
+
+impl TryFrom<RespArray> for Ping {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len > 2 {
+            return Err(CommandError::InvalidArguments(
+                "PING command must have at most 1 argument".to_string(),
+            ));
+        }
+        validate_command(&arr, &["ping"], len - 1)?;
+
+        let message = match extract_args(arr, 1)?.into_iter().next() {
+            Some(RespFrame::BulkString(message)) => Some(message.0.into()),
+            Some(_) => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid message".to_string(),
+                ))
+            }
+            None => None,
+        };
+        Ok(Self { message })
+    }
+}
+
+impl TryFrom<RespArray> for Quit {
+    type Error = CommandError;
+
+    // 与 redis 一致，忽略多余的参数
+    fn try_from(_arr: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self)
+    }
+}
+
+impl TryFrom<RespArray> for Reset {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["reset"], 0)?;
+        Ok(Self)
+    }
+}
+
+impl TryFrom<RespArray> for Select {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["select"], 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        match args.next() {
+            Some(index) => Ok(Self {
+                index: parse_integer(index)?,
+            }),
+            None => Err(CommandError::InvalidArguments("Invalid Index".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SwapDb {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["swapdb"], 2)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(first), Some(second)) => Ok(Self {
+                first: parse_integer(first)?,
+                second: parse_integer(second)?,
+            }),
+            _ => Err(CommandError::InvalidArguments("Invalid Index".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Hello {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len > 2 {
+            return Err(CommandError::InvalidArguments(
+                "HELLO command only supports the protocol version argument".to_string(),
+            ));
+        }
+        validate_command(&arr, &["hello"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let protover = args.next().map(parse_integer).transpose()?;
+
+        Ok(Self { protover })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespDecoder, Server};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_select_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n$6\r\nselect\r\n$1\r\n1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Select::try_from(frame)?;
+        assert_eq!(cmd.index, 1);
+
+        let mut buf = BytesMut::from("*2\r\n$6\r\nselect\r\n$3\r\none\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Select::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$6\r\nclient\r\n$7\r\nSETNAME\r\n$8\r\nmyworker\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Client::try_from(frame)?;
+        assert_eq!(
+            cmd.subcommand,
+            ClientSubcommand::SetName("myworker".to_string())
+        );
+
+        for name in ["my worker", "my\nworker"] {
+            let frame = RespArray::new(vec![
+                b"client".into(),
+                b"setname".into(),
+                name.as_bytes().into(),
+            ]);
+            assert!(Client::try_from(frame).is_err());
+        }
+
+        let frame = RespArray::new(vec![b"client".into(), b"getname".into()]);
+        assert_eq!(
+            Client::try_from(frame)?.subcommand,
+            ClientSubcommand::GetName
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_setname_getname() -> Result<()> {
+        let mut session = Session::new(Server::default());
+        let getname = Client {
+            subcommand: ClientSubcommand::GetName,
+        };
+        assert_eq!(getname.execute_with(&mut session).await, b"".into());
+
+        let setname = Client {
+            subcommand: ClientSubcommand::SetName("myworker".to_string()),
+        };
+        assert_eq!(setname.execute_with(&mut session).await, RESP_OK.clone());
+        assert_eq!(session.name(), Some("myworker"));
+        assert_eq!(getname.execute_with(&mut session).await, b"myworker".into());
+
+        let clear = Client {
+            subcommand: ClientSubcommand::SetName(String::new()),
+        };
+        clear.execute_with(&mut session).await;
+        assert_eq!(session.name(), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_id_and_list() -> Result<()> {
+        let server = Server::default();
+        let mut first = Session::new(server.clone());
+        let mut second = Session::new(server.clone());
+        second.set_name(Some("worker".to_string()));
+        second.select(2);
+
+        let id = Client {
+            subcommand: ClientSubcommand::Id,
+        };
+        assert_eq!(
+            id.execute_with(&mut first).await,
+            RespFrame::Integer(first.id() as i64)
+        );
+
+        let list = Client {
+            subcommand: ClientSubcommand::List,
+        };
+        let RespFrame::BulkString(body) = list.execute_with(&mut first).await else {
+            panic!("expected a bulk string");
+        };
+        let body = String::from_utf8(body.0.into())?;
+        let lines = body.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].starts_with(&format!("id={} addr= name= age=", first.id())));
+        assert!(lines[0].ends_with("flags=N db=0 sub=0 cmd=NULL"));
+        assert!(lines[1].starts_with(&format!("id={} addr= name=worker ", second.id())));
+        assert!(lines[1].contains(" db=2 "));
+
+        drop(second);
+        assert_eq!(server.clients().len(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_client_kill_try_from() -> Result<()> {
+        let frame = RespArray::new(vec![
+            b"client".into(),
+            b"kill".into(),
+            b"ID".into(),
+            b"7".into(),
+            b"addr".into(),
+            b"127.0.0.1:6380".into(),
+        ]);
+        let filter = KillFilter {
+            id: Some(7),
+            addr: Some("127.0.0.1:6380".parse()?),
+        };
+        assert_eq!(
+            Client::try_from(frame)?.subcommand,
+            ClientSubcommand::Kill(filter)
+        );
+
+        for args in [
+            &["id"][..],
+            &["id", "x"],
+            &["name", "foo"],
+            &["addr", "nowhere"],
+        ] {
+            let mut frames: Vec<RespFrame> = vec![b"client".into(), b"kill".into()];
+            frames.extend(args.iter().map(|arg| arg.as_bytes().into()));
+            assert!(Client::try_from(RespArray::new(frames)).is_err());
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_kill_by_id() -> Result<()> {
+        let server = Server::default();
+        let mut killer = Session::new(server.clone());
+        let victim = Session::new(server.clone());
+
+        let kill = Client {
+            subcommand: ClientSubcommand::Kill(KillFilter {
+                id: Some(victim.id()),
+                addr: None,
+            }),
+        };
+        assert_eq!(kill.execute_with(&mut killer).await, RespFrame::Integer(1));
+        assert!(victim.kill_token().is_cancelled());
+        assert!(!killer.kill_token().is_cancelled());
+        // 已经被关闭的连接不会重复计数
+        assert_eq!(kill.execute_with(&mut killer).await, RespFrame::Integer(0));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_quit_command() -> Result<()> {
+        let mut session = Session::new(Server::default());
+        assert!(!session.is_closing());
+        assert_eq!(Quit.execute_with(&mut session).await, RESP_OK.clone());
+        assert!(session.is_closing());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reset_command() -> Result<()> {
+        let mut buf = BytesMut::from("*1\r\n$5\r\nRESET\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        Reset::try_from(frame)?;
+
+        let server = Server::default();
+        let mut session = Session::new(server.clone());
+        session.select(1);
+        session.set_protocol(RespVersion::Resp3);
+        session.set_name(Some("worker".to_string()));
+        session.start_monitor();
+
+        let ret = Reset.execute_with(&mut session).await;
+        assert_eq!(ret, SimpleString::new("RESET").into());
+        assert_eq!(session.db(), 0);
+        assert_eq!(session.protocol(), RespVersion::Resp2);
+        assert!(!session.is_monitoring());
+        assert_eq!(session.name(), Some("worker"));
+        let client = server.clients().pop().unwrap();
+        assert_eq!(client.db, 0);
+        assert!(!client.monitoring);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_select_command() -> Result<()> {
+        let mut session = Session::new(Server::default());
+
+        let ret = Select { index: 15 }.execute_with(&mut session).await;
+        assert_eq!(ret, RESP_OK.clone());
+        assert_eq!(session.db(), 15);
+
+        for index in [16, -1] {
+            let ret = Select { index }.execute_with(&mut session).await;
+            assert_eq!(ret, SimpleError::new("ERR DB index is out of range").into());
+            assert_eq!(session.db(), 15);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_swapdb_command() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$6\r\nswapdb\r\n$1\r\n0\r\n$1\r\n1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = SwapDb::try_from(frame)?;
+        assert_eq!((cmd.first, cmd.second), (0, 1));
+
+        let server = Server::default();
+        let mut session = Session::new(server.clone());
+        let mut other = Session::new(server);
+        other.select(1);
+        session.backend().set("hello".to_string(), b"world".into());
+
+        assert_eq!(cmd.execute_with(&mut session).await, RESP_OK.clone());
+        assert_eq!(session.backend().get("hello"), None);
+        assert_eq!(other.backend().get("hello"), Some(b"world".into()));
+        session.select(1);
+        assert_eq!(session.backend().get("hello"), Some(b"world".into()));
+
+        for (first, second) in [(0, 16), (-1, 0)] {
+            let ret = SwapDb { first, second }.execute_with(&mut session).await;
+            assert_eq!(ret, SimpleError::new("ERR DB index is out of range").into());
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hello_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n$5\r\nhello\r\n$1\r\n3\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Hello::try_from(frame)?;
+        assert_eq!(cmd.protover, Some(3));
+
+        let mut buf = BytesMut::from("*1\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Hello::try_from(frame)?;
+        assert_eq!(cmd.protover, None);
+
+        let mut buf = BytesMut::from("*2\r\n$5\r\nhello\r\n$5\r\nthree\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Hello::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hello_negotiates_protocol() -> Result<()> {
+        let mut session = Session::new(Server::default());
+        assert_eq!(session.protocol(), RespVersion::Resp2);
+
+        let RespFrame::Map(info) = Hello { protover: Some(3) }.execute_with(&mut session).await
+        else {
+            panic!("expected a map");
+        };
+        assert_eq!(session.protocol(), RespVersion::Resp3);
+        assert_eq!(info.get(&b"proto".into()), Some(&RespFrame::Integer(3)));
+        assert_eq!(info.get(&b"server".into()), Some(&b"redis".into()));
+        assert_eq!(
+            info.get(&b"id".into()),
+            Some(&RespFrame::Integer(session.id() as i64))
+        );
+
+        let RespFrame::Map(info) = Hello { protover: None }.execute_with(&mut session).await else {
+            panic!("expected a map");
+        };
+        assert_eq!(info.get(&b"proto".into()), Some(&RespFrame::Integer(3)));
+
+        let RespFrame::Map(info) = Hello { protover: Some(2) }.execute_with(&mut session).await
+        else {
+            panic!("expected a map");
+        };
+        assert_eq!(session.protocol(), RespVersion::Resp2);
+        assert_eq!(info.get(&b"proto".into()), Some(&RespFrame::Integer(2)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hello_unsupported_version() -> Result<()> {
+        let mut session = Session::new(Server::default());
+        session.set_protocol(RespVersion::Resp3);
+
+        let ret = Hello { protover: Some(4) }.execute_with(&mut session).await;
+        assert_eq!(
+            ret,
+            SimpleError::new("NOPROTO unsupported protocol version").into()
+        );
+        assert_eq!(session.protocol(), RespVersion::Resp3);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ping() -> Result<()> {
+        let mut buf = BytesMut::from("*1\r\n$4\r\nping\r\n*2\r\n$4\r\nPING\r\n$5\r\nhello\r\n");
+        let ping = Ping::try_from(RespArray::decode(&mut buf)?)?;
+        let echo = Ping::try_from(RespArray::decode(&mut buf)?)?;
+        let mut buf = BytesMut::from("*3\r\n$4\r\nping\r\n$1\r\na\r\n$1\r\nb\r\n");
+        assert!(Ping::try_from(RespArray::decode(&mut buf)?).is_err());
+
+        let mut session = Session::new(Server::default());
+        assert_eq!(
+            ping.execute_with(&mut session).await,
+            SimpleString::new("PONG").into()
+        );
+        assert_eq!(echo.execute_with(&mut session).await, b"hello".into());
+
+        // RESP2 订阅模式下回复数组，RESP3 下与普通连接相同
+        session.subscribe("news".to_string());
+        assert_eq!(
+            ping.execute_with(&mut session).await,
+            RespArray::new(vec![b"pong".into(), b"".into()]).into()
+        );
+        assert_eq!(
+            echo.execute_with(&mut session).await,
+            RespArray::new(vec![b"pong".into(), b"hello".into()]).into()
+        );
+        session.set_protocol(RespVersion::Resp3);
+        assert_eq!(
+            ping.execute_with(&mut session).await,
+            SimpleString::new("PONG").into()
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,283 @@
+use std::time::Duration;
+
+use crate::{Backend, RespArray, RespEncoder, RespFrame, Session, SimpleError, SimpleString};
+
+use super::{
+    extract_args, object::encoding, validate_command, CommandError, CommandExecutor, RESP_OK,
+};
+
+// debug sleep seconds
+// debug object key
+// debug set-active-expire 0|1
+// "*3\r\n$5\r\ndebug\r\n$5\r\nsleep\r\n$3\r\n0.5\r\n"
+#[derive(Debug)]
+pub struct Debug {
+    subcommand: DebugSubcommand,
+}
+
+#[derive(Debug, PartialEq)]
+enum DebugSubcommand {
+    Sleep(Duration),
+    Object(String),
+    SetActiveExpire(bool),
+}
+
+impl CommandExecutor for Debug {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            DebugSubcommand::Sleep(duration) => {
+                // 异步等待，不会阻塞 runtime 上的其他连接
+                tokio::time::sleep(duration).await;
+                RESP_OK.clone()
+            }
+            DebugSubcommand::Object(ref key) => match debug_object(backend, key) {
+                Some(info) => SimpleString::new(info).into(),
+                None => SimpleError::new("ERR no such key").into(),
+            },
+            DebugSubcommand::SetActiveExpire(_) => {
+                SimpleError::new("ERR DEBUG SET-ACTIVE-EXPIRE is not allowed without a connection")
+                    .into()
+            }
+        }
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        if !session.server().config().enable_debug_command {
+            return SimpleError::new(
+                "ERR DEBUG command not allowed. Set the enable-debug-command option to enable it",
+            )
+            .into();
+        }
+        if let DebugSubcommand::SetActiveExpire(enabled) = self.subcommand {
+            session.server().set_active_expire(enabled);
+            return RESP_OK.clone();
+        }
+        self.execute(&session.backend()).await
+    }
+}
+
+// 格式与 redis 相同，便于客户端按 key:value 解析。没有稳定的对象地址，Value at 固定为 0x0
+fn debug_object(backend: &Backend, key: &str) -> Option<String> {
+    let encoding = encoding(backend, key)?;
+    Some(format!(
+        "Value at:0x0 refcount:1 encoding:{} serializedlength:{} lru_seconds_idle:{}",
+        encoding,
+        serialized_length(backend, key),
+        backend.idle_seconds(key).unwrap_or_default()
+    ))
+}
+
+// 以 RESP 编码后的长度近似 redis 中 RDB 序列化后的长度
+fn serialized_length(backend: &Backend, key: &str) -> usize {
+    if let Some(value) = backend.map.get(key) {
+        return value.encode().len();
+    }
+    if let Some(hmap) = backend.hmap.get(key) {
+        return hmap
+            .iter()
+            .map(|v| v.key().len() + v.value().encode().len())
+            .sum();
+    }
+    if let Some(set) = backend.set.get(key) {
+        return set.iter().map(|v| v.encode().len()).sum();
+    }
+    if let Some(list) = backend.list.get(key) {
+        return list.iter().map(|v| v.encode().len()).sum();
+    }
+    if let Some(stream) = backend.stream.get(key) {
+        return stream
+            .iter()
+            .map(|(id, fields)| {
+                let fields: usize = fields.iter().map(|(f, v)| f.len() + v.len()).sum();
+                id.to_string().len() + fields
+            })
+            .sum();
+    }
+    backend
+        .zset
+        .get(key)
+        .map(|zset| {
+            zset.iter()
+                .map(|(member, score)| member.len() + score.to_string().len())
+                .sum()
+        })
+        .unwrap_or_default()
+}
+
+impl TryFrom<RespArray> for Debug {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let subcommand = match arr.get(1) {
+            Some(RespFrame::BulkString(sub)) => sub.to_ascii_lowercase(),
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "DEBUG command must have a subcommand".to_string(),
+                ))
+            }
+        };
+
+        match subcommand.as_slice() {
+            b"sleep" => {
+                validate_command(&arr, &["debug", "sleep"], 1)?;
+                let mut args = extract_args(arr, 2)?.into_iter();
+                let seconds = match args.next() {
+                    Some(RespFrame::BulkString(seconds)) => String::from_utf8(seconds.0.into())?
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|s| s.is_finite() && *s >= 0.0),
+                    _ => None,
+                };
+                let seconds = seconds.ok_or_else(|| {
+                    CommandError::InvalidArguments("value is not a valid float".to_string())
+                })?;
+                // 超出 Duration 能表示的范围时 from_secs_f64 会 panic
+                let duration = Duration::try_from_secs_f64(seconds).map_err(|_| {
+                    CommandError::InvalidArguments("value is out of range".to_string())
+                })?;
+                Ok(Self {
+                    subcommand: DebugSubcommand::Sleep(duration),
+                })
+            }
+            b"object" => {
+                validate_command(&arr, &["debug", "object"], 1)?;
+                let mut args = extract_args(arr, 2)?.into_iter();
+                match args.next() {
+                    Some(RespFrame::BulkString(key)) => Ok(Self {
+                        subcommand: DebugSubcommand::Object(String::from_utf8(key.0.into())?),
+                    }),
+                    _ => Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+                }
+            }
+            b"set-active-expire" => {
+                validate_command(&arr, &["debug", "set-active-expire"], 1)?;
+                let enabled = match extract_args(arr, 2)?.into_iter().next() {
+                    Some(RespFrame::BulkString(flag)) if flag.as_ref() == b"0" => false,
+                    Some(RespFrame::BulkString(flag)) if flag.as_ref() == b"1" => true,
+                    _ => {
+                        return Err(CommandError::InvalidArguments(
+                            "value is not an integer or out of range".to_string(),
+                        ))
+                    }
+                };
+                Ok(Self {
+                    subcommand: DebugSubcommand::SetActiveExpire(enabled),
+                })
+            }
+            _ => Err(CommandError::InvalidArguments(format!(
+                "Unknown DEBUG subcommand: {}",
+                String::from_utf8_lossy(&subcommand)
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use crate::{Databases, RespDecoder, Server, ServerConfig, SimpleError};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_debug_sleep_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$5\r\ndebug\r\n$5\r\nSLEEP\r\n$3\r\n0.5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Debug::try_from(frame)?;
+        assert_eq!(
+            cmd.subcommand,
+            DebugSubcommand::Sleep(Duration::from_millis(500))
+        );
+
+        let mut buf = BytesMut::from("*3\r\n$5\r\ndebug\r\n$5\r\nsleep\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Debug::try_from(frame).is_err());
+
+        let mut buf = BytesMut::from("*3\r\n$5\r\ndebug\r\n$5\r\nsleep\r\n$4\r\n1e20\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            RespFrame::from(Debug::try_from(frame).unwrap_err()),
+            SimpleError::new("ERR value is out of range").into()
+        );
+
+        let mut buf = BytesMut::from("*2\r\n$5\r\ndebug\r\n$5\r\nsleep\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Debug::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_debug_sleep_command() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = Debug {
+            subcommand: DebugSubcommand::Sleep(Duration::from_millis(100)),
+        };
+
+        let start = Instant::now();
+        let ret = cmd.execute(&backend).await;
+        assert_eq!(ret, RESP_OK.clone());
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_debug_object_command() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$5\r\ndebug\r\n$6\r\nOBJECT\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Debug::try_from(frame)?;
+        assert_eq!(cmd.subcommand, DebugSubcommand::Object("hello".to_string()));
+
+        let backend = Backend::new();
+        assert_eq!(
+            cmd.execute(&backend).await,
+            SimpleError::new("ERR no such key").into()
+        );
+
+        backend.set("hello".to_string(), b"world".into());
+        assert_eq!(
+            cmd.execute(&backend).await,
+            SimpleString::new(
+                "Value at:0x0 refcount:1 encoding:embstr serializedlength:11 lru_seconds_idle:0"
+            )
+            .into()
+        );
+
+        // 编码随保存的值变化
+        for (value, encoding) in [
+            (RespFrame::from(b"12345"), "encoding:int "),
+            (RespFrame::from(&[b'x'; 64][..]), "encoding:raw "),
+        ] {
+            backend.set("hello".to_string(), value);
+            let RespFrame::SimpleString(reply) = cmd.execute(&backend).await else {
+                panic!("expected a simple string");
+            };
+            assert!(reply.contains(encoding), "{}", reply.as_str());
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_debug_disabled_by_config() -> Result<()> {
+        let config = ServerConfig {
+            enable_debug_command: false,
+            ..Default::default()
+        };
+        let mut session = Session::new(Server::with_config(Databases::default(), config));
+        let cmd = Debug {
+            subcommand: DebugSubcommand::Sleep(Duration::ZERO),
+        };
+        let ret = cmd.execute_with(&mut session).await;
+        assert!(matches!(ret, RespFrame::Error(_)));
+
+        let mut session = Session::new(Server::default());
+        assert_eq!(cmd.execute_with(&mut session).await, RESP_OK.clone());
+
+        Ok(())
+    }
+}
@@ -7,12 +7,12 @@ use super::{extract_args, validate_command, CommandError, CommandExecutor};
 // echo message *2\r\n$4\r\necho\r\n$5\r\nhello\r\n
 #[derive(Debug)]
 pub struct Echo {
-    message: String,
+    message: Vec<u8>,
 }
 
 impl CommandExecutor for Echo {
-    fn execute(&self, _backend: &Backend) -> RespFrame {
-        RespFrame::BulkString(BulkString::new(self.message.to_string()))
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        RespFrame::BulkString(BulkString::new(self.message.clone()))
     }
 }
 
@@ -26,7 +26,7 @@ impl TryFrom<RespArray> for Echo {
 
         match args.next() {
             Some(RespFrame::BulkString(message)) => Ok(Self {
-                message: String::from_utf8(message.0)?,
+                message: message.0.into(),
             }),
             _ => Err(CommandError::InvalidArguments(
                 "Invalid message".to_string(),
@@ -37,7 +37,7 @@ impl TryFrom<RespArray> for Echo {
 
 #[cfg(test)]
 mod tests {
-    use crate::RespDecoder;
+    use crate::{cmd::Command, RespDecoder, RespEncoder};
 
     use super::*;
     use anyhow::Result;
@@ -49,7 +49,34 @@ mod tests {
         let frame = RespArray::decode(&mut buf)?;
         let cmd = Echo::try_from(frame)?;
 
-        assert_eq!(cmd.message, "hello");
+        assert_eq!(cmd.message, b"hello");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_echo_from_raw_bytes() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n$4\r\nECHO\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Command::try_from(frame)?;
+
+        let backend = Backend::new();
+        let ret = cmd.execute(&backend).await;
+        assert_eq!(ret, RespFrame::BulkString(b"hello".into()));
+        assert_eq!(ret.encode(), b"$5\r\nhello\r\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_echo_is_binary_safe() -> Result<()> {
+        let mut buf = BytesMut::from(&b"*2\r\n$4\r\necho\r\n$3\r\n\xff\x00\xfe\r\n"[..]);
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Command::try_from(frame)?;
+
+        let backend = Backend::new();
+        let ret = cmd.execute(&backend).await;
+        assert_eq!(ret.encode(), b"$3\r\n\xff\x00\xfe\r\n");
 
         Ok(())
     }
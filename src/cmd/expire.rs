@@ -0,0 +1,408 @@
+use crate::{now_ms, Backend, RespArray, RespFrame};
+
+use super::{extract_args, parse_integer, validate_command, CommandError, CommandExecutor};
+
+// expire key seconds
+// "*3\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n"
+#[derive(Debug)]
+pub struct Expire {
+    key: String,
+    millis: i64,
+}
+
+// pexpire key milliseconds
+// "*3\r\n$7\r\npexpire\r\n$5\r\nhello\r\n$3\r\n100\r\n"
+#[derive(Debug)]
+pub struct PExpire {
+    key: String,
+    millis: i64,
+}
+
+// expireat key unix-time-seconds
+// "*3\r\n$8\r\nexpireat\r\n$5\r\nhello\r\n$10\r\n1893456000\r\n"
+#[derive(Debug)]
+pub struct ExpireAt {
+    key: String,
+    deadline_ms: i64,
+}
+
+// pexpireat key unix-time-milliseconds
+// "*3\r\n$9\r\npexpireat\r\n$5\r\nhello\r\n$13\r\n1893456000000\r\n"
+#[derive(Debug)]
+pub struct PExpireAt {
+    key: String,
+    deadline_ms: i64,
+}
+
+// ttl key
+// "*2\r\n$3\r\nttl\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct Ttl {
+    key: String,
+}
+
+// pttl key
+// "*2\r\n$4\r\npttl\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct PTtl {
+    key: String,
+}
+
+// expiretime key
+// "*2\r\n$10\r\nexpiretime\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct ExpireTime {
+    key: String,
+}
+
+// pexpiretime key
+// "*2\r\n$11\r\npexpiretime\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct PExpireTime {
+    key: String,
+}
+
+// persist key
+// "*2\r\n$7\r\npersist\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct Persist {
+    key: String,
+}
+
+impl CommandExecutor for Expire {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        expire(backend, &self.key, self.millis)
+    }
+}
+
+impl CommandExecutor for PExpire {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        expire(backend, &self.key, self.millis)
+    }
+}
+
+impl CommandExecutor for ExpireAt {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        expire_at(backend, &self.key, self.deadline_ms)
+    }
+}
+
+impl CommandExecutor for PExpireAt {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        expire_at(backend, &self.key, self.deadline_ms)
+    }
+}
+
+impl CommandExecutor for Ttl {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        // 与 redis 一致，四舍五入到秒
+        let ttl = match ttl_millis(backend, &self.key) {
+            ttl if ttl < 0 => ttl,
+            ttl => (ttl + 500) / 1000,
+        };
+        RespFrame::Integer(ttl)
+    }
+}
+
+impl CommandExecutor for PTtl {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(ttl_millis(backend, &self.key))
+    }
+}
+
+impl CommandExecutor for ExpireTime {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let deadline = match expire_time_millis(backend, &self.key) {
+            deadline if deadline < 0 => deadline,
+            deadline => deadline / 1000,
+        };
+        RespFrame::Integer(deadline)
+    }
+}
+
+impl CommandExecutor for PExpireTime {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(expire_time_millis(backend, &self.key))
+    }
+}
+
+impl CommandExecutor for Persist {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.persist(&self.key) as i64)
+    }
+}
+
+fn expire(backend: &Backend, key: &str, millis: i64) -> RespFrame {
+    expire_at(backend, key, (now_ms() as i64).saturating_add(millis))
+}
+
+// 过期时间已经过去时直接删除 key
+pub(super) fn expire_at(backend: &Backend, key: &str, deadline_ms: i64) -> RespFrame {
+    if !backend.exists(key) {
+        return RespFrame::Integer(0);
+    }
+    if deadline_ms <= now_ms() as i64 {
+        backend.del(key);
+    } else {
+        backend.set_expire(key, deadline_ms as u64);
+    }
+    RespFrame::Integer(1)
+}
+
+// key 不存在返回 -2，没有过期时间返回 -1
+fn ttl_millis(backend: &Backend, key: &str) -> i64 {
+    if !backend.exists(key) {
+        return -2;
+    }
+    match backend.expire_at(key) {
+        Some(deadline) => deadline.saturating_sub(now_ms()) as i64,
+        None => -1,
+    }
+}
+
+// 返回 unix 毫秒表示的绝对过期时间，key 不存在返回 -2，没有过期时间返回 -1
+fn expire_time_millis(backend: &Backend, key: &str) -> i64 {
+    if !backend.exists(key) {
+        return -2;
+    }
+    backend
+        .expire_at(key)
+        .map_or(-1, |deadline| deadline as i64)
+}
+
+impl TryFrom<RespArray> for Expire {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (key, millis) = parse_expire(arr, "expire", 1000)?;
+        Ok(Self { key, millis })
+    }
+}
+
+impl TryFrom<RespArray> for PExpire {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (key, millis) = parse_expire(arr, "pexpire", 1)?;
+        Ok(Self { key, millis })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireAt {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (key, deadline_ms) = parse_expire(arr, "expireat", 1000)?;
+        Ok(Self { key, deadline_ms })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireAt {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (key, deadline_ms) = parse_expire(arr, "pexpireat", 1)?;
+        Ok(Self { key, deadline_ms })
+    }
+}
+
+impl TryFrom<RespArray> for Ttl {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: parse_key(arr, "ttl")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PTtl {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: parse_key(arr, "pttl")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ExpireTime {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: parse_key(arr, "expiretime")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for PExpireTime {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: parse_key(arr, "pexpiretime")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Persist {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            key: parse_key(arr, "persist")?,
+        })
+    }
+}
+
+fn parse_key(arr: RespArray, name: &'static str) -> Result<String, CommandError> {
+    validate_command(&arr, &[name], 1)?;
+    match extract_args(arr, 1)?.into_iter().next() {
+        Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.0.into())?),
+        _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
+    }
+}
+
+// 返回 key 和换算成毫秒的过期时间，unit 是每个单位对应的毫秒数
+fn parse_expire(
+    arr: RespArray,
+    name: &'static str,
+    unit: i64,
+) -> Result<(String, i64), CommandError> {
+    validate_command(&arr, &[name], 2)?;
+
+    let mut args = extract_args(arr, 1)?.into_iter();
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+        _ => return Err(CommandError::InvalidArguments("Invalid key".to_string())),
+    };
+    let millis = match args.next() {
+        Some(value) => parse_integer(value)?,
+        None => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+    }
+    .checked_mul(unit)
+    .ok_or_else(|| {
+        CommandError::InvalidArguments(format!("invalid expire time in '{}' command", name))
+    })?;
+
+    Ok((key, millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecoder;
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[tokio::test]
+    async fn test_expire_and_ttl() -> Result<()> {
+        let backend = Backend::new();
+        let ttl = Ttl {
+            key: "hello".to_string(),
+        };
+        assert_eq!(ttl.execute(&backend).await, RespFrame::Integer(-2));
+
+        let mut buf = BytesMut::from("*3\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n");
+        let expire = Expire::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(expire.execute(&backend).await, RespFrame::Integer(0));
+
+        backend.set("hello".to_string(), b"world".into());
+        assert_eq!(ttl.execute(&backend).await, RespFrame::Integer(-1));
+        assert_eq!(expire.execute(&backend).await, RespFrame::Integer(1));
+        assert_eq!(ttl.execute(&backend).await, RespFrame::Integer(10));
+        let RespFrame::Integer(pttl) = PTtl {
+            key: "hello".to_string(),
+        }
+        .execute(&backend)
+        .await
+        else {
+            panic!("expected an integer");
+        };
+        assert!(pttl > 9_000 && pttl <= 10_000);
+
+        let persist = Persist {
+            key: "hello".to_string(),
+        };
+        assert_eq!(persist.execute(&backend).await, RespFrame::Integer(1));
+        assert_eq!(persist.execute(&backend).await, RespFrame::Integer(0));
+        assert_eq!(ttl.execute(&backend).await, RespFrame::Integer(-1));
+
+        // 非正数的过期时间会直接删除 key
+        let pexpire = PExpire {
+            key: "hello".to_string(),
+            millis: 0,
+        };
+        assert_eq!(pexpire.execute(&backend).await, RespFrame::Integer(1));
+        assert!(!backend.exists("hello"));
+
+        let mut buf =
+            BytesMut::from("*3\r\n$6\r\nexpire\r\n$5\r\nhello\r\n$19\r\n9223372036854775807\r\n");
+        assert!(Expire::try_from(RespArray::decode(&mut buf)?).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_expireat_and_expiretime() -> Result<()> {
+        let backend = Backend::new();
+        let expiretime = ExpireTime {
+            key: "hello".to_string(),
+        };
+        let pexpiretime = PExpireTime {
+            key: "hello".to_string(),
+        };
+        assert_eq!(expiretime.execute(&backend).await, RespFrame::Integer(-2));
+        assert_eq!(pexpiretime.execute(&backend).await, RespFrame::Integer(-2));
+
+        backend.set("hello".to_string(), b"world".into());
+        assert_eq!(expiretime.execute(&backend).await, RespFrame::Integer(-1));
+
+        let deadline = now_ms() as i64 / 1000 + 100;
+        let mut buf = BytesMut::from(
+            format!(
+                "*3\r\n$8\r\nexpireat\r\n$5\r\nhello\r\n$10\r\n{}\r\n",
+                deadline
+            )
+            .as_str(),
+        );
+        let expireat = ExpireAt::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(expireat.execute(&backend).await, RespFrame::Integer(1));
+        assert_eq!(
+            expiretime.execute(&backend).await,
+            RespFrame::Integer(deadline)
+        );
+        assert_eq!(
+            pexpiretime.execute(&backend).await,
+            RespFrame::Integer(deadline * 1000)
+        );
+
+        let pexpireat = PExpireAt {
+            key: "hello".to_string(),
+            deadline_ms: deadline * 1000 + 1234,
+        };
+        assert_eq!(pexpireat.execute(&backend).await, RespFrame::Integer(1));
+        assert_eq!(
+            pexpiretime.execute(&backend).await,
+            RespFrame::Integer(deadline * 1000 + 1234)
+        );
+        // 秒级结果向下取整
+        assert_eq!(
+            expiretime.execute(&backend).await,
+            RespFrame::Integer(deadline + 1)
+        );
+
+        // 过去的时间会直接删除 key
+        let pexpireat = PExpireAt {
+            key: "hello".to_string(),
+            deadline_ms: 1,
+        };
+        assert_eq!(pexpireat.execute(&backend).await, RespFrame::Integer(1));
+        assert!(!backend.exists("hello"));
+        assert_eq!(pexpireat.execute(&backend).await, RespFrame::Integer(0));
+
+        Ok(())
+    }
+}
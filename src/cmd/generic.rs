@@ -0,0 +1,953 @@
+use crate::{
+    now_ms, Backend, BulkString, RespArray, RespFrame, RespNull, SimpleError, SimpleString,
+};
+
+use super::{
+    extract_args, parse_integer, validate_command, CommandError, CommandExecutor, RESP_OK,
+    RESP_WRONGTYPE,
+};
+
+// del key [key ...]
+// "*3\r\n$3\r\ndel\r\n$5\r\nhello\r\n$5\r\nworld\r\n"
+#[derive(Debug)]
+pub struct Del {
+    keys: Vec<String>,
+}
+
+// unlink key [key ...]
+// "*3\r\n$6\r\nunlink\r\n$5\r\nhello\r\n$5\r\nworld\r\n"
+#[derive(Debug)]
+pub struct Unlink {
+    keys: Vec<String>,
+}
+
+// copy source destination [REPLACE]
+// "*3\r\n$4\r\ncopy\r\n$5\r\nhello\r\n$5\r\nworld\r\n"
+#[derive(Debug)]
+pub struct Copy {
+    source: String,
+    destination: String,
+    replace: bool,
+}
+
+// touch key [key ...]
+// "*3\r\n$5\r\ntouch\r\n$5\r\nhello\r\n$5\r\nworld\r\n"
+#[derive(Debug)]
+pub struct Touch {
+    keys: Vec<String>,
+}
+
+// dump key
+// "*2\r\n$4\r\ndump\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct Dump {
+    key: String,
+}
+
+// restore key ttl serialized-value [REPLACE]
+// "*4\r\n$7\r\nrestore\r\n$5\r\nhello\r\n$1\r\n0\r\n$N\r\n...\r\n"
+// ttl 单位是毫秒，0 表示不过期
+#[derive(Debug)]
+pub struct Restore {
+    key: String,
+    ttl: u64,
+    payload: Vec<u8>,
+    replace: bool,
+}
+
+// randomkey
+// "*1\r\n$9\r\nrandomkey\r\n"
+#[derive(Debug)]
+pub struct RandomKey;
+
+// type key
+// "*2\r\n$4\r\ntype\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct Type {
+    key: String,
+}
+
+// wait numreplicas timeout
+// "*3\r\n$4\r\nwait\r\n$1\r\n0\r\n$3\r\n100\r\n"
+// 单节点没有副本，参数只做校验，总是立即返回 0。
+// 等待副本确认需要先实现复制（复制偏移量和副本的 ACK），目前还没有
+#[derive(Debug)]
+pub struct Wait;
+
+// sort key [LIMIT offset count] [ASC|DESC] [ALPHA]
+// "*3\r\n$4\r\nsort\r\n$5\r\nmylist\r\n$5\r\nalpha\r\n"
+// 只支持 list 和 set，不支持 BY/GET/STORE
+#[derive(Debug)]
+pub struct Sort {
+    key: String,
+    alpha: bool,
+    desc: bool,
+    limit: Option<(i64, i64)>,
+}
+
+impl CommandExecutor for Del {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let count = self.keys.iter().filter(|key| backend.del(key)).count();
+        RespFrame::Integer(count as i64)
+    }
+}
+
+impl CommandExecutor for Unlink {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let count = self.keys.iter().filter(|key| backend.unlink(key)).count();
+        RespFrame::Integer(count as i64)
+    }
+}
+
+impl CommandExecutor for Touch {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let count = self.keys.iter().filter(|key| backend.touch(key)).count();
+        RespFrame::Integer(count as i64)
+    }
+}
+
+impl CommandExecutor for Dump {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        match backend.dump(&self.key) {
+            Some(payload) => BulkString::new(payload).into(),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+impl CommandExecutor for Restore {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        match backend.restore(&self.key, &self.payload, self.replace) {
+            Ok(()) => {
+                if self.ttl > 0 {
+                    backend.set_expire(&self.key, now_ms().saturating_add(self.ttl));
+                }
+                RESP_OK.clone()
+            }
+            Err(e) => SimpleError::new(e.to_string()).into(),
+        }
+    }
+}
+
+impl CommandExecutor for Copy {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let copied = backend.copy(&self.source, &self.destination, self.replace);
+        RespFrame::Integer(copied as i64)
+    }
+}
+
+impl CommandExecutor for RandomKey {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        match backend.random_key() {
+            Some(key) => BulkString::new(key).into(),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+impl CommandExecutor for Type {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let name = backend
+            .key_type(&self.key)
+            .map_or("none", |key_type| key_type.as_str());
+        SimpleString::new(name).into()
+    }
+}
+
+impl CommandExecutor for Wait {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        RespFrame::Integer(0)
+    }
+}
+
+impl TryFrom<RespArray> for Touch {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            keys: extract_keys(arr, "touch")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Del {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            keys: extract_keys(arr, "del")?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Unlink {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        Ok(Self {
+            keys: extract_keys(arr, "unlink")?,
+        })
+    }
+}
+
+impl CommandExecutor for Sort {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let mut elements = if let Some(list) = backend.list.get(&self.key) {
+            list.iter().cloned().collect::<Vec<_>>()
+        } else if let Some(set) = backend.set.get(&self.key) {
+            set.iter().map(|v| v.clone()).collect::<Vec<_>>()
+        } else if backend.exists(&self.key) {
+            return RESP_WRONGTYPE.clone();
+        } else {
+            vec![]
+        };
+
+        if self.alpha {
+            elements.sort_by(|a, b| sort_bytes(a).cmp(sort_bytes(b)));
+        } else {
+            let mut scored = Vec::with_capacity(elements.len());
+            for element in elements {
+                match sort_score(&element) {
+                    Some(score) => scored.push((score, element)),
+                    None => {
+                        return SimpleError::new(
+                            "ERR One or more scores can't be converted into double",
+                        )
+                        .into()
+                    }
+                }
+            }
+            scored.sort_by(|a, b| a.0.total_cmp(&b.0));
+            elements = scored.into_iter().map(|(_, element)| element).collect();
+        }
+        if self.desc {
+            elements.reverse();
+        }
+
+        // 与 redis 一致，offset 小于 0 按 0 处理，count 小于 0 表示取到末尾
+        let elements = match self.limit {
+            Some((offset, count)) => {
+                let offset = offset.max(0) as usize;
+                let count = if count < 0 {
+                    usize::MAX
+                } else {
+                    count as usize
+                };
+                elements.into_iter().skip(offset).take(count).collect()
+            }
+            None => elements,
+        };
+        RespArray::new(elements).into()
+    }
+}
+
+fn sort_bytes(frame: &RespFrame) -> &[u8] {
+    match frame {
+        RespFrame::BulkString(value) => value.as_ref(),
+        _ => &[],
+    }
+}
+
+fn sort_score(frame: &RespFrame) -> Option<f64> {
+    let score = std::str::from_utf8(sort_bytes(frame))
+        .ok()?
+        .parse::<f64>()
+        .ok()?;
+    (!score.is_nan()).then_some(score)
+}
+
+impl TryFrom<RespArray> for Sort {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        validate_command(&arr, &["sort"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let mut sort = Self {
+            key,
+            alpha: false,
+            desc: false,
+            limit: None,
+        };
+        while let Some(arg) = args.next() {
+            let RespFrame::BulkString(option) = arg else {
+                return Err(CommandError::InvalidArguments("syntax error".to_string()));
+            };
+            match option.to_ascii_lowercase().as_slice() {
+                b"alpha" => sort.alpha = true,
+                b"asc" => sort.desc = false,
+                b"desc" => sort.desc = true,
+                b"limit" => match (args.next(), args.next()) {
+                    (Some(offset), Some(count)) => {
+                        sort.limit = Some((parse_integer(offset)?, parse_integer(count)?))
+                    }
+                    _ => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+                },
+                _ => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+            }
+        }
+
+        Ok(sort)
+    }
+}
+
+impl TryFrom<RespArray> for Copy {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len != 3 && len != 4 {
+            return Err(CommandError::InvalidArguments(
+                "COPY command must have 2 or 3 arguments".to_string(),
+            ));
+        }
+        validate_command(&arr, &["copy"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let source = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let destination = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let replace = match args.next() {
+            Some(RespFrame::BulkString(option)) if option.eq_ignore_ascii_case(b"replace") => true,
+            None => false,
+            _ => return Err(CommandError::InvalidArguments("Invalid Option".to_string())),
+        };
+
+        Ok(Self {
+            source,
+            destination,
+            replace,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for Dump {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["dump"], 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Self {
+                key: String::from_utf8(key.0.into())?,
+            }),
+            _ => Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Restore {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len != 4 && len != 5 {
+            return Err(CommandError::InvalidArguments("syntax error".to_string()));
+        }
+        validate_command(&arr, &["restore"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let ttl = match args.next().map(parse_integer).transpose()? {
+            Some(ttl) if ttl >= 0 => ttl as u64,
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid TTL value, must be >= 0".to_string(),
+                ))
+            }
+        };
+
+        let payload = match args.next() {
+            Some(RespFrame::BulkString(payload)) => payload.0.into(),
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid payload".to_string(),
+                ))
+            }
+        };
+
+        let replace = match args.next() {
+            Some(RespFrame::BulkString(option)) if option.eq_ignore_ascii_case(b"replace") => true,
+            None => false,
+            _ => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+        };
+
+        Ok(Self {
+            key,
+            ttl,
+            payload,
+            replace,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for RandomKey {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["randomkey"], 0)?;
+        Ok(Self)
+    }
+}
+
+impl TryFrom<RespArray> for Type {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["type"], 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Self {
+                key: String::from_utf8(key.0.into())?,
+            }),
+            _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Wait {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["wait"], 2)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(numreplicas), Some(timeout)) => {
+                parse_integer(numreplicas)?;
+                if parse_integer(timeout)? < 0 {
+                    return Err(CommandError::InvalidArguments(
+                        "timeout is negative".to_string(),
+                    ));
+                }
+            }
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid Arguments".to_string(),
+                ))
+            }
+        }
+
+        Ok(Self)
+    }
+}
+
+fn extract_keys(arr: RespArray, name: &'static str) -> Result<Vec<String>, CommandError> {
+    let len = arr.len();
+    if len < 2 {
+        return Err(CommandError::InvalidArguments(format!(
+            "{} command must have at least 1 key",
+            name.to_uppercase()
+        )));
+    }
+    validate_command(&arr, &[name], len - 1)?;
+
+    let mut keys = Vec::with_capacity(len - 1);
+    for arg in extract_args(arr, 1)? {
+        match arg {
+            RespFrame::BulkString(key) => keys.push(String::from_utf8(key.0.into())?),
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        }
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{ListSide, RespDecoder};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_del_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$3\r\ndel\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Del::try_from(frame)?;
+
+        assert_eq!(cmd.keys, vec!["hello", "world"]);
+
+        let mut buf = BytesMut::from("*1\r\n$3\r\ndel\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Del::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unlink_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$6\r\nunlink\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Unlink::try_from(frame)?;
+
+        assert_eq!(cmd.keys, vec!["hello", "world"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_del_unlink_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("a".to_string(), RespFrame::BulkString(b"1".into()));
+        backend.hset("b".to_string(), "f".to_string(), b"v".into());
+        backend.sadd("c".to_string(), b"m".into());
+        backend.set("d".to_string(), RespFrame::BulkString(b"2".into()));
+
+        let cmd = Unlink {
+            keys: vec!["a".to_string(), "b".to_string(), "missing".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(2));
+        assert_eq!(backend.get("a"), None);
+        assert_eq!(backend.hget("b", "f"), None);
+
+        let cmd = Del {
+            keys: vec!["c".to_string(), "d".to_string(), "missing".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(2));
+        assert!(!backend.sismember("c", &b"m".into()));
+        assert_eq!(backend.get("d"), None);
+
+        let cmd = Unlink {
+            keys: vec!["a".to_string(), "d".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$4\r\ncopy\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Copy::try_from(frame)?;
+        assert_eq!(cmd.source, "hello");
+        assert_eq!(cmd.destination, "world");
+        assert!(!cmd.replace);
+
+        let mut buf =
+            BytesMut::from("*4\r\n$4\r\ncopy\r\n$5\r\nhello\r\n$5\r\nworld\r\n$7\r\nREPLACE\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Copy::try_from(frame)?;
+        assert!(cmd.replace);
+
+        let mut buf =
+            BytesMut::from("*4\r\n$4\r\ncopy\r\n$5\r\nhello\r\n$5\r\nworld\r\n$3\r\nfoo\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Copy::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("src".to_string(), RespFrame::BulkString(b"v1".into()));
+        backend.set("other".to_string(), RespFrame::BulkString(b"v2".into()));
+
+        let cmd = Copy {
+            source: "src".to_string(),
+            destination: "dst".to_string(),
+            replace: false,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+        assert_eq!(
+            backend.get("dst"),
+            Some(RespFrame::BulkString(b"v1".into()))
+        );
+
+        let cmd = Copy {
+            source: "src".to_string(),
+            destination: "other".to_string(),
+            replace: false,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+        assert_eq!(
+            backend.get("other"),
+            Some(RespFrame::BulkString(b"v2".into()))
+        );
+
+        let cmd = Copy {
+            source: "src".to_string(),
+            destination: "other".to_string(),
+            replace: true,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+        assert_eq!(
+            backend.get("other"),
+            Some(RespFrame::BulkString(b"v1".into()))
+        );
+
+        let cmd = Copy {
+            source: "missing".to_string(),
+            destination: "dst2".to_string(),
+            replace: true,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_copy_hash_and_set() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("hash".to_string(), "f".to_string(), b"v".into());
+        backend.sadd("myset".to_string(), b"m".into());
+
+        let cmd = Copy {
+            source: "hash".to_string(),
+            destination: "hash2".to_string(),
+            replace: false,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+        assert_eq!(backend.hget("hash2", "f"), Some(b"v".into()));
+
+        // 复制出的值与源 key 互不影响
+        backend.hset("hash".to_string(), "f".to_string(), b"changed".into());
+        assert_eq!(backend.hget("hash2", "f"), Some(b"v".into()));
+
+        let cmd = Copy {
+            source: "myset".to_string(),
+            destination: "hash2".to_string(),
+            replace: true,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+        assert!(backend.sismember("hash2", &b"m".into()));
+        assert_eq!(backend.hget("hash2", "f"), None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_touch_command() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$5\r\ntouch\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Touch::try_from(frame)?;
+        assert_eq!(cmd.keys, ["a", "b"]);
+
+        let backend = Backend::new();
+        backend.set("a".to_string(), b"1".into());
+        backend.sadd("b".to_string(), b"m".into());
+        backend.touch("a");
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        let idle = backend.idle_time("a").unwrap();
+
+        let cmd = Touch {
+            keys: vec!["a".to_string(), "b".to_string(), "missing".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(2));
+        assert!(backend.idle_time("a").unwrap() < idle);
+
+        let cmd = Touch {
+            keys: vec!["x".to_string(), "y".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+        assert!(!backend.exists("x"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_dump_restore_commands() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("hash".to_string(), "f".to_string(), b"v".into());
+
+        let dump = Dump {
+            key: "hash".to_string(),
+        };
+        let RespFrame::BulkString(payload) = dump.execute(&backend).await else {
+            panic!("expected a bulk string");
+        };
+        let expected = backend.hgetall("hash").unwrap();
+
+        let frame = RespArray::new(vec![
+            b"restore".into(),
+            b"hash".into(),
+            b"0".into(),
+            RespFrame::BulkString(payload.clone()),
+        ]);
+        let restore = Restore::try_from(frame)?;
+        assert_eq!(
+            restore.execute(&backend).await,
+            SimpleError::new("BUSYKEY Target key name already exists.").into()
+        );
+
+        backend.del("hash");
+        assert_eq!(dump.execute(&backend).await, RespFrame::Null(RespNull));
+        assert_eq!(restore.execute(&backend).await, RESP_OK.clone());
+        let restored = backend.hgetall("hash").unwrap();
+        assert_eq!(restored.len(), expected.len());
+        assert_eq!(backend.hget("hash", "f"), Some(b"v".into()));
+
+        let frame = RespArray::new(vec![
+            b"restore".into(),
+            b"hash".into(),
+            b"0".into(),
+            RespFrame::BulkString(payload.clone()),
+            b"REPLACE".into(),
+        ]);
+        assert!(Restore::try_from(frame)?.replace);
+
+        let frame = RespArray::new(vec![
+            b"restore".into(),
+            b"other".into(),
+            b"0".into(),
+            b"garbage".into(),
+        ]);
+        assert_eq!(
+            Restore::try_from(frame)?.execute(&backend).await,
+            SimpleError::new("ERR DUMP payload version or checksum are wrong").into()
+        );
+
+        let frame = RespArray::new(vec![
+            b"restore".into(),
+            b"other".into(),
+            b"-1".into(),
+            b"garbage".into(),
+        ]);
+        assert!(Restore::try_from(frame).is_err());
+
+        // 非零的 ttl 设置过期时间
+        let frame = RespArray::new(vec![
+            b"restore".into(),
+            b"expiring".into(),
+            b"10000".into(),
+            RespFrame::BulkString(payload),
+        ]);
+        let before = now_ms();
+        assert_eq!(
+            Restore::try_from(frame)?.execute(&backend).await,
+            RESP_OK.clone()
+        );
+        let deadline = backend.expire_at("expiring").unwrap();
+        assert!(deadline >= before + 10_000 && deadline <= now_ms() + 10_000);
+        assert!(backend.expire_at("hash").is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_randomkey_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*1\r\n$9\r\nrandomkey\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        RandomKey::try_from(frame)?;
+
+        let mut buf = BytesMut::from("*2\r\n$9\r\nrandomkey\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(RandomKey::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_randomkey_command() -> Result<()> {
+        let backend = Backend::new();
+        assert_eq!(RandomKey.execute(&backend).await, RespFrame::Null(RespNull));
+
+        backend.set("a".to_string(), RespFrame::BulkString(b"1".into()));
+        backend.set("b".to_string(), RespFrame::BulkString(b"2".into()));
+        backend.hset("c".to_string(), "f".to_string(), b"v".into());
+        backend.sadd("d".to_string(), b"m".into());
+        // 同名 key 出现在多个 store 中
+        backend.sadd("a".to_string(), b"m".into());
+
+        let keys = ["a", "b", "c", "d"]
+            .iter()
+            .map(|k| BulkString::new(*k).into())
+            .collect::<Vec<RespFrame>>();
+        for _ in 0..20 {
+            let ret = RandomKey.execute(&backend).await;
+            assert!(keys.contains(&ret), "{:?}", ret);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unlink_large_collection() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..100 {
+            backend.sadd("big".to_string(), RespFrame::Integer(i));
+        }
+
+        let cmd = Unlink {
+            keys: vec!["big".to_string()],
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+        assert!(!backend.sismember("big", &RespFrame::Integer(1)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wait_command() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$4\r\nwait\r\n$1\r\n0\r\n$3\r\n100\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Wait::try_from(frame)?;
+
+        let backend = Backend::new();
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+
+        let cases = [
+            "*2\r\n$4\r\nwait\r\n$1\r\n0\r\n",
+            "*3\r\n$4\r\nwait\r\n$3\r\none\r\n$3\r\n100\r\n",
+            "*3\r\n$4\r\nwait\r\n$1\r\n0\r\n$2\r\n-1\r\n",
+        ];
+        for case in cases {
+            let mut buf = BytesMut::from(case);
+            let frame = RespArray::decode(&mut buf)?;
+            assert!(
+                matches!(
+                    Wait::try_from(frame),
+                    Err(CommandError::InvalidArguments(_))
+                ),
+                "{:?}",
+                case
+            );
+        }
+
+        Ok(())
+    }
+
+    fn sort(input: &str) -> Result<Sort> {
+        let mut buf = BytesMut::from(input);
+        let frame = RespArray::decode(&mut buf)?;
+        Ok(Sort::try_from(frame)?)
+    }
+
+    fn sort_backend() -> Backend {
+        let backend = Backend::new();
+        let values = ["3", "10", "1", "2.5"];
+        backend.push(
+            "nums".to_string(),
+            values.iter().map(|v| v.as_bytes().into()).collect(),
+            ListSide::Right,
+            true,
+        );
+        for v in ["banana", "apple", "cherry"] {
+            backend.sadd("fruits".to_string(), v.as_bytes().into());
+        }
+        backend
+    }
+
+    fn frames(values: &[&str]) -> RespFrame {
+        RespArray::new(
+            values
+                .iter()
+                .map(|v| v.as_bytes().into())
+                .collect::<Vec<RespFrame>>(),
+        )
+        .into()
+    }
+
+    #[tokio::test]
+    async fn test_sort_numeric() -> Result<()> {
+        let backend = sort_backend();
+        let cmd = sort("*2\r\n$4\r\nsort\r\n$4\r\nnums\r\n")?;
+        assert_eq!(
+            cmd.execute(&backend).await,
+            frames(&["1", "2.5", "3", "10"])
+        );
+
+        let cmd = sort("*3\r\n$4\r\nsort\r\n$4\r\nnums\r\n$4\r\nDESC\r\n")?;
+        assert_eq!(
+            cmd.execute(&backend).await,
+            frames(&["10", "3", "2.5", "1"])
+        );
+
+        let cmd = sort("*2\r\n$4\r\nsort\r\n$6\r\nfruits\r\n")?;
+        assert_eq!(
+            cmd.execute(&backend).await,
+            SimpleError::new("ERR One or more scores can't be converted into double").into()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sort_alpha() -> Result<()> {
+        let backend = sort_backend();
+        let cmd = sort("*3\r\n$4\r\nsort\r\n$6\r\nfruits\r\n$5\r\nALPHA\r\n")?;
+        assert_eq!(
+            cmd.execute(&backend).await,
+            frames(&["apple", "banana", "cherry"])
+        );
+
+        // 字典序下 "10" 排在 "2.5" 之前
+        let cmd = sort("*4\r\n$4\r\nsort\r\n$4\r\nnums\r\n$5\r\nalpha\r\n$4\r\ndesc\r\n")?;
+        assert_eq!(
+            cmd.execute(&backend).await,
+            frames(&["3", "2.5", "10", "1"])
+        );
+
+        backend.set("str".to_string(), b"value".into());
+        let cmd = sort("*2\r\n$4\r\nsort\r\n$3\r\nstr\r\n")?;
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sort_limit() -> Result<()> {
+        let backend = sort_backend();
+        let page = |offset: &str, count: &str| {
+            format!(
+                "*5\r\n$4\r\nsort\r\n$4\r\nnums\r\n$5\r\nlimit\r\n${}\r\n{}\r\n${}\r\n{}\r\n",
+                offset.len(),
+                offset,
+                count.len(),
+                count
+            )
+        };
+        let cmd = sort(&page("0", "2"))?;
+        assert_eq!(cmd.execute(&backend).await, frames(&["1", "2.5"]));
+        let cmd = sort(&page("2", "2"))?;
+        assert_eq!(cmd.execute(&backend).await, frames(&["3", "10"]));
+        let cmd = sort(&page("1", "-1"))?;
+        assert_eq!(cmd.execute(&backend).await, frames(&["2.5", "3", "10"]));
+
+        assert!(sort("*4\r\n$4\r\nsort\r\n$4\r\nnums\r\n$5\r\nlimit\r\n$1\r\n0\r\n").is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("str".to_string(), b"v".into());
+        backend.hset("hash".to_string(), "f".to_string(), b"v".into());
+
+        for (key, expected) in [("str", "string"), ("hash", "hash"), ("missing", "none")] {
+            let cmd = Type {
+                key: key.to_string(),
+            };
+            assert_eq!(
+                cmd.execute(&backend).await,
+                SimpleString::new(expected).into()
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -1,6 +1,12 @@
-use crate::{Backend, BulkString, RespArray, RespFrame, RespNull};
+use crate::{
+    Backend, BulkString, KeyType, RespArray, RespFrame, RespMap, RespNull, RespVersion, Session,
+    MAX_RANDOM_COUNT,
+};
 
-use super::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
+use super::{
+    extract_args, parse_integer, validate_command, CommandError, CommandExecutor, ScanArgs,
+    RESP_OK, RESP_WRONGTYPE,
+};
 
 //     - HGET key field
 //         - ("*3\r\n$4\r\nhget\r\n$3\r\nmap\r\n$5\r\nhello\r\n")
@@ -19,6 +25,15 @@ pub struct HSet {
     value: RespFrame,
 }
 
+//     - HSETNX key field val
+//         - ("*4\r\n$6\r\nhsetnx\r\n$3\r\nmap\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+#[derive(Debug)]
+pub struct HSetNx {
+    key: String,
+    field: String,
+    value: RespFrame,
+}
+
 //     - HGETALL key
 //         - ("*2\r\n$7\r\nhgetall\r\n$3\r\nmap\r\n")
 #[derive(Debug)]
@@ -33,8 +48,25 @@ pub struct HMGet {
     fields: Vec<String>,
 }
 
+//     - HRANDFIELD key [count [WITHVALUES]]
+//         - ("*3\r\n$10\r\nhrandfield\r\n$3\r\nmap\r\n$1\r\n2\r\n")
+#[derive(Debug)]
+pub struct HRandField {
+    key: String,
+    count: Option<i64>,
+    with_values: bool,
+}
+
+//     - HSCAN key cursor [MATCH pattern] [COUNT count]
+//         - ("*3\r\n$5\r\nhscan\r\n$3\r\nmap\r\n$1\r\n0\r\n")
+#[derive(Debug)]
+pub struct HScan {
+    key: String,
+    scan: ScanArgs,
+}
+
 impl CommandExecutor for HGet {
-    fn execute(&self, backend: &Backend) -> RespFrame {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
         match backend.hget(&self.key, &self.field) {
             Some(value) => value,
             None => RespFrame::Null(RespNull),
@@ -43,40 +75,115 @@ impl CommandExecutor for HGet {
 }
 
 impl CommandExecutor for HSet {
-    fn execute(&self, backend: &Backend) -> RespFrame {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if backend.is_wrong_type(&self.key, KeyType::Hash) {
+            return RESP_WRONGTYPE.clone();
+        }
         backend.hset(self.key.clone(), self.field.clone(), self.value.clone());
         RESP_OK.clone()
     }
 }
 
-impl CommandExecutor for HGetAll {
-    fn execute(&self, backend: &Backend) -> RespFrame {
-        match backend.hgetall(&self.key) {
-            Some(value) => {
-                let mut data = Vec::with_capacity(value.len());
+impl CommandExecutor for HSetNx {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if backend.is_wrong_type(&self.key, KeyType::Hash) {
+            return RESP_WRONGTYPE.clone();
+        }
+        let set = backend.hsetnx(self.key.clone(), self.field.clone(), self.value.clone());
+        RespFrame::Integer(set as i64)
+    }
+}
 
-                for v in value.iter() {
-                    data.push((v.key().to_owned(), v.value().clone()));
-                }
+impl CommandExecutor for HGetAll {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        self.build_reply(backend, RespVersion::Resp2)
+    }
 
-                if self.sort {
-                    data.sort_by(|a, b| a.0.cmp(&b.0));
-                }
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        self.build_reply(&session.backend(), session.protocol())
+    }
+}
 
-                let frames = data
-                    .into_iter()
-                    .flat_map(|(k, v)| vec![BulkString::new(k).into(), v])
-                    .collect::<Vec<RespFrame>>();
+impl HGetAll {
+    // RESP3 返回 map，RESP2 返回 field/value 交替排列的数组
+    fn build_reply(&self, backend: &Backend, protocol: RespVersion) -> RespFrame {
+        let value = match backend.hgetall(&self.key) {
+            Some(value) => value,
+            None => return RespFrame::Null(RespNull),
+        };
 
-                RespArray::new(frames).into()
+        if protocol == RespVersion::Resp3 {
+            let mut map = RespMap::new();
+            for v in value.iter() {
+                map.insert(BulkString::new(v.key().as_str()).into(), v.value().clone());
             }
-            None => RespFrame::Null(RespNull),
+            return map.into();
+        }
+
+        let mut data = Vec::with_capacity(value.len());
+
+        for v in value.iter() {
+            data.push((v.key().to_owned(), v.value().clone()));
         }
+
+        if self.sort {
+            data.sort_by(|a, b| a.0.cmp(&b.0));
+        }
+
+        let frames = data
+            .into_iter()
+            .flat_map(|(k, v)| vec![BulkString::new(k).into(), v])
+            .collect::<Vec<RespFrame>>();
+
+        RespArray::new(frames).into()
+    }
+}
+
+impl CommandExecutor for HRandField {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        self.build_reply(backend, RespVersion::Resp2)
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        self.build_reply(&session.backend(), session.protocol())
+    }
+}
+
+impl HRandField {
+    // WITHVALUES 在 RESP3 下返回 [field, value] 数组的数组，RESP2 下 field/value 交替排列
+    fn build_reply(&self, backend: &Backend, protocol: RespVersion) -> RespFrame {
+        let Some(count) = self.count else {
+            return match backend.hrandfield(&self.key, 1).pop() {
+                Some((field, _)) => BulkString::new(field).into(),
+                None => RespFrame::Null(RespNull),
+            };
+        };
+
+        let entries = backend.hrandfield(&self.key, count);
+        let frames = if !self.with_values {
+            entries
+                .into_iter()
+                .map(|(field, _)| BulkString::new(field).into())
+                .collect::<Vec<RespFrame>>()
+        } else if protocol == RespVersion::Resp3 {
+            entries
+                .into_iter()
+                .map(|(field, value)| {
+                    RespArray::new(vec![BulkString::new(field).into(), value]).into()
+                })
+                .collect()
+        } else {
+            entries
+                .into_iter()
+                .flat_map(|(field, value)| [BulkString::new(field).into(), value])
+                .collect()
+        };
+        RespArray::new(frames).into()
     }
 }
 
 impl CommandExecutor for HMGet {
-    fn execute(&self, backend: &Backend) -> RespFrame {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
         let mut data = Vec::with_capacity(self.fields.len());
 
         for field in self.fields.iter() {
@@ -89,6 +196,27 @@ impl CommandExecutor for HMGet {
     }
 }
 
+impl CommandExecutor for HScan {
+    // 每次调用按 field 排序得到快照，cursor 是快照中的下标
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let mut fields = match backend.hmap.get(&self.key) {
+            Some(hmap) => hmap
+                .iter()
+                .map(|v| (v.key().clone(), v.value().clone()))
+                .collect::<Vec<_>>(),
+            None if backend.exists(&self.key) => return RESP_WRONGTYPE.clone(),
+            None => vec![],
+        };
+        fields.sort_by(|a, b| a.0.cmp(&b.0));
+        let (next, page) = self.scan.page(fields, |(field, _)| field.as_bytes());
+        let items = page
+            .into_iter()
+            .flat_map(|(field, value)| [BulkString::new(field).into(), value])
+            .collect();
+        ScanArgs::reply(next, items)
+    }
+}
+
 // *3\r\n$4\r\nhget\r\n$3\r\nmap\r\n$5\r\nhello\r\n
 impl TryFrom<RespArray> for HGet {
     type Error = CommandError;
@@ -99,12 +227,12 @@ impl TryFrom<RespArray> for HGet {
         let mut args = extract_args(arr, 1)?.into_iter();
 
         let key = match args.next() {
-            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
             _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
         };
 
         let field = match args.next() {
-            Some(RespFrame::BulkString(field)) => String::from_utf8(field.0)?,
+            Some(RespFrame::BulkString(field)) => String::from_utf8(field.0.into())?,
             _ => return Err(CommandError::InvalidArguments("Invalid Field".to_string())),
         };
 
@@ -122,12 +250,39 @@ impl TryFrom<RespArray> for HSet {
         let mut args = extract_args(arr, 1)?.into_iter();
 
         let key = match args.next() {
-            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
             _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
         };
 
         let field = match args.next() {
-            Some(RespFrame::BulkString(field)) => String::from_utf8(field.0)?,
+            Some(RespFrame::BulkString(field)) => String::from_utf8(field.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Field".to_string())),
+        };
+
+        let value = match args.next() {
+            Some(value) => value,
+            _ => return Err(CommandError::InvalidArguments("Invalid Value".to_string())),
+        };
+
+        Ok(Self { key, field, value })
+    }
+}
+
+impl TryFrom<RespArray> for HSetNx {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["hsetnx"], 3)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let field = match args.next() {
+            Some(RespFrame::BulkString(field)) => String::from_utf8(field.0.into())?,
             _ => return Err(CommandError::InvalidArguments("Invalid Field".to_string())),
         };
 
@@ -150,7 +305,7 @@ impl TryFrom<RespArray> for HGetAll {
         let mut args = extract_args(arr, 1)?.into_iter();
 
         let key = match args.next() {
-            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
             _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
         };
 
@@ -168,7 +323,7 @@ impl TryFrom<RespArray> for HMGet {
         let mut args = extract_args(arr, 1)?.into_iter();
 
         let key = match args.next() {
-            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
             _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
         };
 
@@ -176,7 +331,9 @@ impl TryFrom<RespArray> for HMGet {
         loop {
             let arg = args.next();
             match arg {
-                Some(RespFrame::BulkString(field)) => fields.push(String::from_utf8(field.0)?),
+                Some(RespFrame::BulkString(field)) => {
+                    fields.push(String::from_utf8(field.0.into())?)
+                }
                 None => break,
                 _ => return Err(CommandError::InvalidArguments("Invalid Field".to_string())),
             }
@@ -186,9 +343,67 @@ impl TryFrom<RespArray> for HMGet {
     }
 }
 
+impl TryFrom<RespArray> for HRandField {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len > 4 {
+            return Err(CommandError::InvalidArguments("syntax error".to_string()));
+        }
+        validate_command(&arr, &["hrandfield"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let count = args.next().map(parse_integer).transpose()?;
+        if count.is_some_and(|count| count < 0 && count.unsigned_abs() > MAX_RANDOM_COUNT) {
+            return Err(CommandError::InvalidArguments(
+                "value is out of range".to_string(),
+            ));
+        }
+
+        let with_values = match args.next() {
+            Some(RespFrame::BulkString(option)) if option.eq_ignore_ascii_case(b"withvalues") => {
+                true
+            }
+            None => false,
+            _ => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+        };
+
+        Ok(Self {
+            key,
+            count,
+            with_values,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for HScan {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        validate_command(&arr, &["hscan"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+        let scan = ScanArgs::parse(args)?;
+
+        Ok(Self { key, scan })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::RespDecoder;
+    use crate::{RespDecoder, SimpleError};
 
     use super::*;
     use anyhow::Result;
@@ -234,15 +449,15 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn test_hset_hget_hgetall_commands() -> Result<()> {
+    #[tokio::test]
+    async fn test_hset_hget_hgetall_commands() -> Result<()> {
         let backend = crate::Backend::new();
         let cmd = HSet {
             key: "map".to_string(),
             field: "hello".to_string(),
             value: RespFrame::BulkString(b"world".into()),
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend).await;
         assert_eq!(result, RESP_OK.clone());
 
         let cmd = HSet {
@@ -250,20 +465,20 @@ mod tests {
             field: "hello1".to_string(),
             value: RespFrame::BulkString(b"world1".into()),
         };
-        cmd.execute(&backend);
+        cmd.execute(&backend).await;
 
         let cmd = HGet {
             key: "map".to_string(),
             field: "hello".to_string(),
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend).await;
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
         let cmd = HGetAll {
             key: "map".to_string(),
             sort: true,
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend).await;
         let expected = RespArray::new(vec![
             b"hello".into(),
             b"world".into(),
@@ -274,6 +489,37 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_hgetall_reply_by_protocol() -> Result<()> {
+        let mut session = Session::new(crate::Server::default());
+        let backend = session.backend();
+        backend.hset("map".to_string(), "hello".to_string(), b"world".into());
+        backend.hset("map".to_string(), "foo".to_string(), b"bar".into());
+
+        let cmd = HGetAll {
+            key: "map".to_string(),
+            sort: true,
+        };
+
+        let result = cmd.execute_with(&mut session).await;
+        let expected = RespArray::new(vec![
+            b"foo".into(),
+            b"bar".into(),
+            b"hello".into(),
+            b"world".into(),
+        ]);
+        assert_eq!(result, expected.into());
+
+        session.set_protocol(RespVersion::Resp3);
+        let result = cmd.execute_with(&mut session).await;
+        let mut expected = RespMap::new();
+        expected.insert(b"foo".into(), b"bar".into());
+        expected.insert(b"hello".into(), b"world".into());
+        assert_eq!(result, expected.into());
+
+        Ok(())
+    }
+
     #[test]
     fn test_hmget_try_from() -> Result<()> {
         let mut buf =
@@ -288,22 +534,22 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn test_hmget_command() -> Result<()> {
+    #[tokio::test]
+    async fn test_hmget_command() -> Result<()> {
         let backend = crate::Backend::new();
         let cmd = HSet {
             key: "map".to_string(),
             field: "field".to_string(),
             value: RespFrame::BulkString(b"hello".into()),
         };
-        cmd.execute(&backend);
+        cmd.execute(&backend).await;
 
         let cmd = HSet {
             key: "map".to_string(),
             field: "field2".to_string(),
             value: RespFrame::BulkString(b"world".into()),
         };
-        cmd.execute(&backend);
+        cmd.execute(&backend).await;
 
         let cmd = HMGet {
             key: "map".to_string(),
@@ -314,7 +560,7 @@ mod tests {
             ],
         };
 
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend).await;
         let expected = RespArray::new(vec![
             RespFrame::BulkString(b"hello".into()),
             RespFrame::BulkString(b"world".into()),
@@ -325,4 +571,273 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_hsetnx_try_from() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*4\r\n$6\r\nhsetnx\r\n$3\r\nmap\r\n$5\r\nhello\r\n$5\r\nworld\r\n");
+
+        let frame = RespArray::decode(&mut buf)?;
+
+        let cmd: HSetNx = frame.try_into()?;
+        assert_eq!(cmd.key, "map");
+        assert_eq!(cmd.field, "hello");
+        assert_eq!(cmd.value, RespFrame::BulkString(b"world".into()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hsetnx_command() -> Result<()> {
+        let backend = crate::Backend::new();
+        let cmd = HSetNx {
+            key: "map".to_string(),
+            field: "hello".to_string(),
+            value: RespFrame::BulkString(b"world".into()),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+
+        let cmd = HSetNx {
+            key: "map".to_string(),
+            field: "hello".to_string(),
+            value: RespFrame::BulkString(b"again".into()),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+        assert_eq!(
+            backend.hget("map", "hello"),
+            Some(RespFrame::BulkString(b"world".into()))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_hrandfield_try_from() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*4\r\n$10\r\nhrandfield\r\n$3\r\nmap\r\n$2\r\n-3\r\n$10\r\nWITHVALUES\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = HRandField::try_from(frame)?;
+        assert_eq!(cmd.key, "map");
+        assert_eq!(cmd.count, Some(-3));
+        assert!(cmd.with_values);
+
+        let mut buf = BytesMut::from("*3\r\n$10\r\nhrandfield\r\n$3\r\nmap\r\n$3\r\ntwo\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(HRandField::try_from(frame).is_err());
+
+        let mut buf = BytesMut::from(
+            "*3\r\n$10\r\nhrandfield\r\n$3\r\nmap\r\n$20\r\n-9223372036854775808\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            RespFrame::from(HRandField::try_from(frame).unwrap_err()),
+            SimpleError::new("ERR value is out of range").into()
+        );
+
+        Ok(())
+    }
+
+    fn hrandfield_backend() -> Backend {
+        let backend = Backend::new();
+        for (field, value) in [("one", "1"), ("two", "2"), ("three", "3")] {
+            backend.hset(
+                "map".to_string(),
+                field.to_string(),
+                value.as_bytes().into(),
+            );
+        }
+        backend
+    }
+
+    #[tokio::test]
+    async fn test_hrandfield_single() -> Result<()> {
+        let backend = hrandfield_backend();
+        let cmd = |key: &str| HRandField {
+            key: key.to_string(),
+            count: None,
+            with_values: false,
+        };
+        let ret = cmd("map").execute(&backend).await;
+        let fields: [RespFrame; 3] = [b"one".into(), b"two".into(), b"three".into()];
+        assert!(fields.contains(&ret), "{:?}", ret);
+        assert_eq!(
+            cmd("missing").execute(&backend).await,
+            RespFrame::Null(RespNull)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hrandfield_count() -> Result<()> {
+        let backend = hrandfield_backend();
+
+        // 正数 count 返回不重复的 field，且不会超过 hash 的大小
+        let cmd = HRandField {
+            key: "map".to_string(),
+            count: Some(5),
+            with_values: false,
+        };
+        let RespFrame::Array(ret) = cmd.execute(&backend).await else {
+            panic!("expected an array");
+        };
+        assert_eq!(ret.len(), 3);
+        let distinct = ret.iter().collect::<std::collections::HashSet<_>>();
+        assert_eq!(distinct.len(), 3);
+
+        // 负数 count 允许重复
+        let cmd = HRandField {
+            key: "map".to_string(),
+            count: Some(-10),
+            with_values: false,
+        };
+        let RespFrame::Array(ret) = cmd.execute(&backend).await else {
+            panic!("expected an array");
+        };
+        assert_eq!(ret.len(), 10);
+
+        // 很大的正数 count 不会按 count 预先分配
+        let cmd = HRandField {
+            key: "map".to_string(),
+            count: Some(i64::MAX),
+            with_values: true,
+        };
+        let RespFrame::Array(ret) = cmd.execute(&backend).await else {
+            panic!("expected an array");
+        };
+        assert_eq!(ret.len(), 6);
+        assert_eq!(
+            backend.hrandfield("map", i64::MIN).len(),
+            MAX_RANDOM_COUNT as usize
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hrandfield_with_values() -> Result<()> {
+        let backend = hrandfield_backend();
+        let cmd = HRandField {
+            key: "map".to_string(),
+            count: Some(-6),
+            with_values: true,
+        };
+        let RespFrame::Array(ret) = cmd.execute(&backend).await else {
+            panic!("expected an array");
+        };
+        assert_eq!(ret.len(), 12);
+        for pair in ret.chunks(2) {
+            let RespFrame::BulkString(field) = &pair[0] else {
+                panic!("expected a bulk string");
+            };
+            assert_eq!(
+                backend.hget("map", &String::from_utf8_lossy(field)),
+                Some(pair[1].clone())
+            );
+        }
+
+        let RespFrame::Array(ret) = cmd.build_reply(&backend, RespVersion::Resp3) else {
+            panic!("expected an array");
+        };
+        assert_eq!(ret.len(), 6);
+        assert!(ret
+            .iter()
+            .all(|pair| matches!(pair, RespFrame::Array(pair) if pair.len() == 2)));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hscan_visits_every_field_once() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..25 {
+            backend.hset(
+                "map".to_string(),
+                format!("field{}", i),
+                RespFrame::Integer(i),
+            );
+        }
+
+        let mut cursor = "0".to_string();
+        let mut seen = std::collections::HashMap::new();
+        let mut calls = 0;
+        loop {
+            let input = format!(
+                "*5\r\n$5\r\nhscan\r\n$3\r\nmap\r\n${}\r\n{}\r\n$5\r\nCOUNT\r\n$1\r\n7\r\n",
+                cursor.len(),
+                cursor
+            );
+            let mut buf = BytesMut::from(input.as_str());
+            let cmd = HScan::try_from(RespArray::decode(&mut buf)?)?;
+            let RespFrame::Array(reply) = cmd.execute(&backend).await else {
+                panic!("expected an array");
+            };
+            let (RespFrame::BulkString(next), RespFrame::Array(items)) = (&reply[0], &reply[1])
+            else {
+                panic!("unexpected reply {:?}", reply);
+            };
+            assert!(items.len() <= 14);
+            for pair in items.chunks(2) {
+                let RespFrame::BulkString(field) = &pair[0] else {
+                    panic!("expected a field");
+                };
+                let field = String::from_utf8(field.to_vec())?;
+                assert!(seen.insert(field, pair[1].clone()).is_none());
+            }
+            calls += 1;
+            cursor = String::from_utf8(next.to_vec())?;
+            if cursor == "0" {
+                break;
+            }
+        }
+        assert_eq!(calls, 4);
+        assert_eq!(seen.len(), 25);
+        assert_eq!(seen["field3"], RespFrame::Integer(3));
+
+        // MATCH 在分页之后过滤
+        let mut buf = BytesMut::from(
+            "*7\r\n$5\r\nhscan\r\n$3\r\nmap\r\n$1\r\n0\r\n$5\r\nMATCH\r\n$7\r\nfield2*\r\n$5\r\nCOUNT\r\n$3\r\n100\r\n",
+        );
+        let cmd = HScan::try_from(RespArray::decode(&mut buf)?)?;
+        let RespFrame::Array(reply) = cmd.execute(&backend).await else {
+            panic!("expected an array");
+        };
+        assert_eq!(reply[0], BulkString::new("0").into());
+        let RespFrame::Array(items) = &reply[1] else {
+            panic!("expected an array");
+        };
+        assert_eq!(items.len(), 12);
+
+        let mut buf = BytesMut::from("*3\r\n$5\r\nhscan\r\n$3\r\nmap\r\n$1\r\nx\r\n");
+        assert!(HScan::try_from(RespArray::decode(&mut buf)?).is_err());
+        let mut buf = BytesMut::from(
+            "*5\r\n$5\r\nhscan\r\n$3\r\nmap\r\n$1\r\n0\r\n$5\r\nCOUNT\r\n$1\r\n0\r\n",
+        );
+        assert!(HScan::try_from(RespArray::decode(&mut buf)?).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hset_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), b"value".into());
+
+        let cmd = HSet {
+            key: "string".to_string(),
+            field: "field".to_string(),
+            value: b"value".into(),
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+        let cmd = HSetNx {
+            key: "string".to_string(),
+            field: "field".to_string(),
+            value: b"value".into(),
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+        assert!(!backend.hmap.contains_key("string"));
+
+        Ok(())
+    }
 }
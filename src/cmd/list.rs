@@ -0,0 +1,1056 @@
+use std::time::Duration;
+
+use futures::future::select_all;
+use tokio::time::Instant;
+
+use crate::{Backend, BulkString, KeyType, ListSide, RespArray, RespFrame, RespNull, Session};
+
+use super::{
+    extract_args, normalize_range, parse_integer, validate_command, CommandError, CommandExecutor,
+    RESP_OK, RESP_WRONGTYPE,
+};
+
+// lpos key element [RANK rank] [COUNT num]
+// "*5\r\n$4\r\nlpos\r\n$6\r\nmylist\r\n$1\r\nc\r\n$4\r\nrank\r\n$2\r\n-1\r\n"
+#[derive(Debug)]
+pub struct LPos {
+    key: String,
+    element: RespFrame,
+    rank: i64,
+    count: Option<usize>,
+}
+
+// lrem key count element
+// "*4\r\n$4\r\nlrem\r\n$6\r\nmylist\r\n$2\r\n-2\r\n$1\r\nc\r\n"
+#[derive(Debug)]
+pub struct LRem {
+    key: String,
+    count: i64,
+    element: RespFrame,
+}
+
+// ltrim key start stop
+// "*4\r\n$5\r\nltrim\r\n$6\r\nmylist\r\n$1\r\n1\r\n$2\r\n-1\r\n"
+#[derive(Debug)]
+pub struct LTrim {
+    key: String,
+    start: i64,
+    stop: i64,
+}
+
+// lpush key element [element ...]
+// "*4\r\n$5\r\nlpush\r\n$6\r\nmylist\r\n$1\r\na\r\n$1\r\nb\r\n"
+#[derive(Debug)]
+pub struct LPush {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+// rpush key element [element ...]
+#[derive(Debug)]
+pub struct RPush {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+// lpushx key element [element ...]，仅当列表已存在时 push
+#[derive(Debug)]
+pub struct LPushX {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+// rpushx key element [element ...]，仅当列表已存在时 push
+#[derive(Debug)]
+pub struct RPushX {
+    key: String,
+    values: Vec<RespFrame>,
+}
+
+// blpop key [key ...] timeout
+// "*3\r\n$5\r\nblpop\r\n$6\r\nmylist\r\n$1\r\n0\r\n"
+#[derive(Debug)]
+pub struct BLPop {
+    keys: Vec<String>,
+    timeout: Duration,
+}
+
+// brpop key [key ...] timeout
+#[derive(Debug)]
+pub struct BRPop {
+    keys: Vec<String>,
+    timeout: Duration,
+}
+
+impl CommandExecutor for LPush {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        push(backend, &self.key, &self.values, ListSide::Left, true)
+    }
+}
+
+impl CommandExecutor for RPush {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        push(backend, &self.key, &self.values, ListSide::Right, true)
+    }
+}
+
+impl CommandExecutor for LPushX {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        push(backend, &self.key, &self.values, ListSide::Left, false)
+    }
+}
+
+impl CommandExecutor for RPushX {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        push(backend, &self.key, &self.values, ListSide::Right, false)
+    }
+}
+
+// 四个 push 命令共用，key 存在但不是列表时不能写入，否则同一个 key 会同时出现在两个 store 中
+fn push(
+    backend: &Backend,
+    key: &str,
+    values: &[RespFrame],
+    side: ListSide,
+    create: bool,
+) -> RespFrame {
+    if backend.is_wrong_type(key, KeyType::List) {
+        return RESP_WRONGTYPE.clone();
+    }
+    let len = backend.push(key.to_string(), values.to_vec(), side, create);
+    RespFrame::Integer(len as i64)
+}
+
+impl CommandExecutor for BLPop {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        blocking_pop(backend, None, &self.keys, self.timeout, ListSide::Left).await
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let backend = session.backend();
+        blocking_pop(
+            &backend,
+            Some(session),
+            &self.keys,
+            self.timeout,
+            ListSide::Left,
+        )
+        .await
+    }
+}
+
+impl CommandExecutor for BRPop {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        blocking_pop(backend, None, &self.keys, self.timeout, ListSide::Right).await
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let backend = session.backend();
+        blocking_pop(
+            &backend,
+            Some(session),
+            &self.keys,
+            self.timeout,
+            ListSide::Right,
+        )
+        .await
+    }
+}
+
+// 依次尝试从 keys 中弹出元素，都为空时等待任意一个 key 被 push 或超时，timeout 为 0 表示一直等待。
+// 有 session 时每次尝试弹出都持有 write gate 的读锁，与 EXEC 互斥，只在等待期间释放
+async fn blocking_pop(
+    backend: &Backend,
+    session: Option<&Session>,
+    keys: &[String],
+    timeout: Duration,
+    side: ListSide,
+) -> RespFrame {
+    // 截止时间溢出时与 timeout 为 0 一样一直等待
+    let deadline = (!timeout.is_zero())
+        .then(|| Instant::now().checked_add(timeout))
+        .flatten();
+    let ret = loop {
+        let waiters = keys
+            .iter()
+            .map(|key| backend.list_waiter(key))
+            .collect::<Vec<_>>();
+        let mut notified = waiters
+            .iter()
+            .map(|waiter| Box::pin(waiter.notified()))
+            .collect::<Vec<_>>();
+        // 先注册等待再检查列表，避免检查与等待之间的 push 通知丢失
+        for n in notified.iter_mut() {
+            n.as_mut().enable();
+        }
+
+        let gate = match session {
+            Some(session) => Some(session.server().write_gate().read().await),
+            None => None,
+        };
+        let popped = keys
+            .iter()
+            .find_map(|key| backend.pop(key, side).map(|value| (key, value)));
+        if let Some((key, value)) = popped {
+            let frame = RespArray::new(vec![BulkString::new(key.as_str()).into(), value]).into();
+            // 在释放读锁之前更新版本号，EXEC 检查 WATCH 时不会漏掉这次弹出
+            if let Some(session) = session {
+                let name = match side {
+                    ListSide::Left => "blpop",
+                    ListSide::Right => "brpop",
+                };
+                // 弹出元素之前 key 一定存在
+                session.after_write(
+                    Some(name),
+                    std::slice::from_ref(key),
+                    Some(vec![true]),
+                    &frame,
+                );
+            }
+            break frame;
+        }
+        drop(gate);
+
+        match deadline {
+            Some(deadline) => {
+                if tokio::time::timeout_at(deadline, select_all(notified))
+                    .await
+                    .is_err()
+                {
+                    break RespFrame::Null(RespNull);
+                }
+            }
+            None => {
+                select_all(notified).await;
+            }
+        }
+    };
+
+    for key in keys {
+        backend.release_list_waiter(key);
+    }
+    ret
+}
+
+impl CommandExecutor for LPos {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let positions = backend.lpos(&self.key, &self.element, self.rank, self.count.unwrap_or(1));
+        // 指定了 COUNT 时总是返回数组（可能为空），否则返回第一个匹配的下标或 Null
+        match self.count {
+            Some(_) => RespArray::new(
+                positions
+                    .into_iter()
+                    .map(|i| RespFrame::Integer(i as i64))
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            None => match positions.first() {
+                Some(i) => RespFrame::Integer(*i as i64),
+                None => RespFrame::Null(RespNull),
+            },
+        }
+    }
+}
+
+impl CommandExecutor for LRem {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        RespFrame::Integer(backend.lrem(&self.key, self.count, &self.element) as i64)
+    }
+}
+
+impl CommandExecutor for LTrim {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if let Some(mut list) = backend.list.get_mut(&self.key) {
+            match normalize_range(self.start, self.stop, list.len()) {
+                Some((start, stop)) => {
+                    list.truncate(stop + 1);
+                    list.drain(..start);
+                }
+                None => list.clear(),
+            }
+        }
+        backend.list.remove_if(&self.key, |_, list| list.is_empty());
+        RESP_OK.clone()
+    }
+}
+
+impl TryFrom<RespArray> for LPush {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = extract_push_args(arr, "lpush")?;
+        Ok(Self { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPush {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = extract_push_args(arr, "rpush")?;
+        Ok(Self { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for LPushX {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = extract_push_args(arr, "lpushx")?;
+        Ok(Self { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for RPushX {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (key, values) = extract_push_args(arr, "rpushx")?;
+        Ok(Self { key, values })
+    }
+}
+
+impl TryFrom<RespArray> for BLPop {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = extract_blocking_pop_args(arr, "blpop")?;
+        Ok(Self { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for BRPop {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (keys, timeout) = extract_blocking_pop_args(arr, "brpop")?;
+        Ok(Self { keys, timeout })
+    }
+}
+
+impl TryFrom<RespArray> for LPos {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len < 3 || len.is_multiple_of(2) {
+            return Err(CommandError::InvalidArguments(
+                "LPOS command must have a key, an element and option pairs".to_string(),
+            ));
+        }
+        validate_command(&arr, &["lpos"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let element = match args.next() {
+            Some(RespFrame::BulkString(element)) => RespFrame::BulkString(element),
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid Element".to_string(),
+                ))
+            }
+        };
+
+        let mut rank = 1;
+        let mut count = None;
+        while let (Some(option), Some(value)) = (args.next(), args.next()) {
+            let option = match option {
+                RespFrame::BulkString(option) => option.to_ascii_lowercase(),
+                _ => return Err(CommandError::InvalidArguments("Invalid Option".to_string())),
+            };
+            let value = parse_integer(value)?;
+            match option.as_slice() {
+                b"rank" => {
+                    if value == 0 {
+                        return Err(CommandError::InvalidArguments(
+                            "RANK can't be zero: use 1 to start from the first match, 2 from the second ... or use negative to start from the end of the list".to_string(),
+                        ));
+                    }
+                    rank = value;
+                }
+                b"count" => {
+                    if value < 0 {
+                        return Err(CommandError::InvalidArguments(
+                            "COUNT can't be negative".to_string(),
+                        ));
+                    }
+                    count = Some(value as usize);
+                }
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "Unknown option: {}",
+                        String::from_utf8_lossy(&option)
+                    )))
+                }
+            }
+        }
+
+        Ok(Self {
+            key,
+            element,
+            rank,
+            count,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LRem {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["lrem"], 3)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let count = match args.next() {
+            Some(count) => parse_integer(count)?,
+            None => return Err(CommandError::InvalidArguments("Invalid Count".to_string())),
+        };
+
+        let element = match args.next() {
+            Some(RespFrame::BulkString(element)) => RespFrame::BulkString(element),
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid Element".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self {
+            key,
+            count,
+            element,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for LTrim {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["ltrim"], 3)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        match (args.next(), args.next()) {
+            (Some(start), Some(stop)) => Ok(Self {
+                key,
+                start: parse_integer(start)?,
+                stop: parse_integer(stop)?,
+            }),
+            _ => Err(CommandError::InvalidArguments("Invalid Range".to_string())),
+        }
+    }
+}
+
+fn extract_push_args(
+    arr: RespArray,
+    name: &'static str,
+) -> Result<(String, Vec<RespFrame>), CommandError> {
+    let len = arr.len();
+    if len < 3 {
+        return Err(CommandError::InvalidArguments(format!(
+            "{} command must have a key and at least 1 element",
+            name.to_uppercase()
+        )));
+    }
+    validate_command(&arr, &[name], len - 1)?;
+
+    let mut args = extract_args(arr, 1)?.into_iter();
+
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+        _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+    };
+
+    let mut values = Vec::with_capacity(args.len());
+    for arg in args {
+        match arg {
+            RespFrame::BulkString(value) => values.push(value.into()),
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid Element".to_string(),
+                ))
+            }
+        }
+    }
+
+    Ok((key, values))
+}
+
+fn extract_blocking_pop_args(
+    arr: RespArray,
+    name: &'static str,
+) -> Result<(Vec<String>, Duration), CommandError> {
+    let len = arr.len();
+    if len < 3 {
+        return Err(CommandError::InvalidArguments(format!(
+            "{} command must have at least 1 key and a timeout",
+            name.to_uppercase()
+        )));
+    }
+    validate_command(&arr, &[name], len - 1)?;
+
+    let mut args = extract_args(arr, 1)?;
+    let timeout = match args.pop() {
+        Some(RespFrame::BulkString(timeout)) => String::from_utf8(timeout.0.into())?
+            .parse::<f64>()
+            .ok()
+            .filter(|t| t.is_finite() && *t >= 0.0),
+        _ => None,
+    };
+    let timeout = match timeout {
+        // 超出 Duration 能表示的范围时 from_secs_f64 会 panic
+        Some(timeout) => Duration::try_from_secs_f64(timeout)
+            .map_err(|_| CommandError::InvalidArguments("timeout is out of range".to_string()))?,
+        None => {
+            return Err(CommandError::InvalidArguments(
+                "timeout is not a float or out of range".to_string(),
+            ))
+        }
+    };
+
+    let mut keys = Vec::with_capacity(args.len());
+    for arg in args {
+        match arg {
+            RespFrame::BulkString(key) => keys.push(String::from_utf8(key.0.into())?),
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        }
+    }
+
+    Ok((keys, timeout))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use crate::{RespDecoder, SimpleError};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn setup_list(backend: &Backend) {
+        let list = ["a", "b", "c", "1", "2", "3", "c", "c"]
+            .iter()
+            .map(|v| RespFrame::BulkString(v.as_bytes().into()))
+            .collect::<VecDeque<RespFrame>>();
+        backend.list.insert("mylist".to_string(), list);
+    }
+
+    fn lpos(rank: i64, count: Option<usize>) -> LPos {
+        LPos {
+            key: "mylist".to_string(),
+            element: RespFrame::BulkString(b"c".into()),
+            rank,
+            count,
+        }
+    }
+
+    fn values(items: &[&str]) -> Vec<RespFrame> {
+        items
+            .iter()
+            .map(|v| RespFrame::BulkString(v.as_bytes().into()))
+            .collect()
+    }
+
+    #[test]
+    fn test_push_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*4\r\n$5\r\nlpush\r\n$6\r\nmylist\r\n$1\r\na\r\n$1\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = LPush::try_from(frame)?;
+        assert_eq!(cmd.key, "mylist");
+        assert_eq!(cmd.values, values(&["a", "b"]));
+
+        let mut buf = BytesMut::from("*3\r\n$6\r\nrpushx\r\n$6\r\nmylist\r\n$1\r\na\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = RPushX::try_from(frame)?;
+        assert_eq!(cmd.key, "mylist");
+        assert_eq!(cmd.values, values(&["a"]));
+
+        let mut buf = BytesMut::from("*2\r\n$6\r\nlpushx\r\n$6\r\nmylist\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(LPushX::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_commands() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = RPush {
+            key: "mylist".to_string(),
+            values: values(&["b", "c"]),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(2));
+
+        let cmd = LPush {
+            key: "mylist".to_string(),
+            values: values(&["a", "z"]),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(4));
+
+        let cmd = RPushX {
+            key: "mylist".to_string(),
+            values: values(&["d"]),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(5));
+
+        let cmd = LPushX {
+            key: "mylist".to_string(),
+            values: values(&["y"]),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(6));
+
+        let list = backend.list.get("mylist").unwrap();
+        assert_eq!(
+            list.iter().cloned().collect::<Vec<_>>(),
+            values(&["y", "z", "a", "b", "c", "d"])
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pushx_does_not_create_key() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = LPushX {
+            key: "mylist".to_string(),
+            values: values(&["a"]),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+
+        let cmd = RPushX {
+            key: "mylist".to_string(),
+            values: values(&["a"]),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+
+        assert!(!backend.list.contains_key("mylist"));
+        assert!(!backend.exists("mylist"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), b"value".into());
+
+        let cmd = LPush {
+            key: "string".to_string(),
+            values: values(&["a"]),
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+        let cmd = RPushX {
+            key: "string".to_string(),
+            values: values(&["a"]),
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+
+        // key 仍然只在原来的 store 中
+        assert!(!backend.list.contains_key("string"));
+        assert_eq!(backend.key_type("string"), Some(KeyType::String));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_blocking_pop_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*4\r\n$5\r\nblpop\r\n$1\r\na\r\n$1\r\nb\r\n$3\r\n1.5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = BLPop::try_from(frame)?;
+        assert_eq!(cmd.keys, vec!["a", "b"]);
+        assert_eq!(cmd.timeout, Duration::from_millis(1500));
+
+        let mut buf = BytesMut::from("*3\r\n$5\r\nbrpop\r\n$1\r\na\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = BRPop::try_from(frame)?;
+        assert_eq!(cmd.keys, vec!["a"]);
+        assert!(cmd.timeout.is_zero());
+
+        let mut buf = BytesMut::from("*3\r\n$5\r\nblpop\r\n$1\r\na\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(BLPop::try_from(frame).is_err());
+
+        let mut buf = BytesMut::from("*2\r\n$5\r\nblpop\r\n$1\r\n0\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(BLPop::try_from(frame).is_err());
+
+        let mut buf = BytesMut::from("*3\r\n$5\r\nblpop\r\n$1\r\na\r\n$4\r\n1e20\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            RespFrame::from(BLPop::try_from(frame).unwrap_err()),
+            SimpleError::new("ERR timeout is out of range").into()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blocking_pop_existing_element() -> Result<()> {
+        let backend = Backend::new();
+        backend.push("b".to_string(), values(&["1", "2"]), ListSide::Right, true);
+
+        let cmd = BRPop {
+            keys: vec!["a".to_string(), "b".to_string()],
+            timeout: Duration::from_secs(1),
+        };
+        let expected = RespArray::new(vec![b"b".into(), b"2".into()]);
+        assert_eq!(cmd.execute(&backend).await, expected.into());
+
+        let cmd = BLPop {
+            keys: vec!["a".to_string(), "b".to_string()],
+            timeout: Duration::from_secs(1),
+        };
+        let expected = RespArray::new(vec![b"b".into(), b"1".into()]);
+        assert_eq!(cmd.execute(&backend).await, expected.into());
+        assert!(!backend.list.contains_key("b"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blpop_unblocks_on_push() -> Result<()> {
+        let backend = Backend::new();
+
+        let cloned = backend.clone();
+        let handle = tokio::spawn(async move {
+            let cmd = BLPop {
+                keys: vec!["mylist".to_string()],
+                timeout: Duration::ZERO,
+            };
+            cmd.execute(&cloned).await
+        });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!handle.is_finished());
+
+        let cmd = LPush {
+            key: "mylist".to_string(),
+            values: values(&["hello"]),
+        };
+        cmd.execute(&backend).await;
+
+        let ret = tokio::time::timeout(Duration::from_secs(1), handle).await??;
+        let expected = RespArray::new(vec![b"mylist".into(), b"hello".into()]);
+        assert_eq!(ret, expected.into());
+        assert!(!backend.list.contains_key("mylist"));
+        assert!(backend.list_waiters.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blpop_huge_timeout() -> Result<()> {
+        let backend = Backend::new();
+        backend.push("mylist".to_string(), values(&["a"]), ListSide::Right, true);
+
+        // 1e19 秒在 Duration 的范围内，但加上当前时间会溢出
+        let mut buf = BytesMut::from("*3\r\n$5\r\nblpop\r\n$6\r\nmylist\r\n$4\r\n1e19\r\n");
+        let cmd = BLPop::try_from(RespArray::decode(&mut buf)?)?;
+        let expected = RespArray::new(vec![b"mylist".into(), b"a".into()]);
+        assert_eq!(cmd.execute(&backend).await, expected.into());
+
+        // 没有元素时一直等待
+        let cloned = backend.clone();
+        let handle = tokio::spawn(async move { cmd.execute(&cloned).await });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!handle.is_finished());
+        backend.push("mylist".to_string(), values(&["b"]), ListSide::Right, true);
+        let ret = tokio::time::timeout(Duration::from_secs(1), handle).await??;
+        let expected = RespArray::new(vec![b"mylist".into(), b"b".into()]);
+        assert_eq!(ret, expected.into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_blpop_timeout() -> Result<()> {
+        let backend = Backend::new();
+        let cmd = BLPop {
+            keys: vec!["mylist".to_string()],
+            timeout: Duration::from_millis(50),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Null(RespNull));
+        assert!(backend.list_waiters.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpos_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$4\r\nlpos\r\n$6\r\nmylist\r\n$1\r\nc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = LPos::try_from(frame)?;
+        assert_eq!(cmd.key, "mylist");
+        assert_eq!(cmd.element, RespFrame::BulkString(b"c".into()));
+        assert_eq!(cmd.rank, 1);
+        assert_eq!(cmd.count, None);
+
+        // options can come in any order
+        let mut buf = BytesMut::from(
+            "*7\r\n$4\r\nlpos\r\n$6\r\nmylist\r\n$1\r\nc\r\n$5\r\nCOUNT\r\n$1\r\n2\r\n$4\r\nrank\r\n$2\r\n-1\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = LPos::try_from(frame)?;
+        assert_eq!(cmd.rank, -1);
+        assert_eq!(cmd.count, Some(2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_lpos_invalid_options() -> Result<()> {
+        let cases = [
+            "*5\r\n$4\r\nlpos\r\n$6\r\nmylist\r\n$1\r\nc\r\n$4\r\nrank\r\n$1\r\n0\r\n",
+            "*5\r\n$4\r\nlpos\r\n$6\r\nmylist\r\n$1\r\nc\r\n$5\r\ncount\r\n$2\r\n-1\r\n",
+            "*5\r\n$4\r\nlpos\r\n$6\r\nmylist\r\n$1\r\nc\r\n$4\r\nrank\r\n$1\r\nx\r\n",
+            "*5\r\n$4\r\nlpos\r\n$6\r\nmylist\r\n$1\r\nc\r\n$3\r\nfoo\r\n$1\r\n1\r\n",
+            "*4\r\n$4\r\nlpos\r\n$6\r\nmylist\r\n$1\r\nc\r\n$4\r\nrank\r\n",
+        ];
+        for case in cases {
+            let mut buf = BytesMut::from(case);
+            let frame = RespArray::decode(&mut buf)?;
+            assert!(LPos::try_from(frame).is_err(), "{:?}", case);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lpos_command() -> Result<()> {
+        let backend = Backend::new();
+        setup_list(&backend);
+
+        assert_eq!(lpos(1, None).execute(&backend).await, RespFrame::Integer(2));
+        assert_eq!(lpos(2, None).execute(&backend).await, RespFrame::Integer(6));
+        assert_eq!(
+            lpos(-1, None).execute(&backend).await,
+            RespFrame::Integer(7)
+        );
+        assert_eq!(
+            lpos(-3, None).execute(&backend).await,
+            RespFrame::Integer(2)
+        );
+        assert_eq!(
+            lpos(4, None).execute(&backend).await,
+            RespFrame::Null(RespNull)
+        );
+
+        let expected = RespArray::new(vec![RespFrame::Integer(2), RespFrame::Integer(6)]);
+        assert_eq!(lpos(1, Some(2)).execute(&backend).await, expected.into());
+
+        let expected = RespArray::new(vec![
+            RespFrame::Integer(2),
+            RespFrame::Integer(6),
+            RespFrame::Integer(7),
+        ]);
+        assert_eq!(lpos(1, Some(0)).execute(&backend).await, expected.into());
+
+        let expected = RespArray::new(vec![RespFrame::Integer(6), RespFrame::Integer(2)]);
+        assert_eq!(lpos(-2, Some(0)).execute(&backend).await, expected.into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lpos_reply_shape_without_match() -> Result<()> {
+        let backend = Backend::new();
+        setup_list(&backend);
+
+        let cmd = LPos {
+            key: "mylist".to_string(),
+            element: RespFrame::BulkString(b"x".into()),
+            rank: 1,
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Null(RespNull));
+
+        let cmd = LPos {
+            key: "mylist".to_string(),
+            element: RespFrame::BulkString(b"x".into()),
+            rank: 1,
+            count: Some(1),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespArray::new(vec![]).into());
+
+        let cmd = LPos {
+            key: "missing".to_string(),
+            element: RespFrame::BulkString(b"x".into()),
+            rank: 1,
+            count: Some(0),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespArray::new(vec![]).into());
+
+        Ok(())
+    }
+
+    fn lrem(count: i64, element: &str) -> LRem {
+        LRem {
+            key: "mylist".to_string(),
+            count,
+            element: RespFrame::BulkString(element.as_bytes().into()),
+        }
+    }
+
+    fn list_values(backend: &Backend) -> Vec<RespFrame> {
+        backend
+            .list
+            .get("mylist")
+            .map(|list| list.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_lrem_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*4\r\n$4\r\nlrem\r\n$6\r\nmylist\r\n$2\r\n-2\r\n$1\r\nc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = LRem::try_from(frame)?;
+        assert_eq!(cmd.key, "mylist");
+        assert_eq!(cmd.count, -2);
+        assert_eq!(cmd.element, RespFrame::BulkString(b"c".into()));
+
+        let mut buf =
+            BytesMut::from("*4\r\n$4\r\nlrem\r\n$6\r\nmylist\r\n$3\r\none\r\n$1\r\nc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(LRem::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lrem_command() -> Result<()> {
+        let backend = Backend::new();
+
+        // count > 0 从头部开始删除
+        setup_list(&backend);
+        assert_eq!(lrem(2, "c").execute(&backend).await, RespFrame::Integer(2));
+        assert_eq!(
+            list_values(&backend),
+            values(&["a", "b", "1", "2", "3", "c"])
+        );
+
+        // count < 0 从尾部开始删除
+        setup_list(&backend);
+        assert_eq!(lrem(-2, "c").execute(&backend).await, RespFrame::Integer(2));
+        assert_eq!(
+            list_values(&backend),
+            values(&["a", "b", "c", "1", "2", "3"])
+        );
+
+        // count == 0 删除全部
+        setup_list(&backend);
+        assert_eq!(lrem(0, "c").execute(&backend).await, RespFrame::Integer(3));
+        assert_eq!(list_values(&backend), values(&["a", "b", "1", "2", "3"]));
+
+        assert_eq!(lrem(0, "x").execute(&backend).await, RespFrame::Integer(0));
+        assert_eq!(
+            LRem {
+                key: "missing".to_string(),
+                count: 0,
+                element: RespFrame::BulkString(b"c".into()),
+            }
+            .execute(&backend)
+            .await,
+            RespFrame::Integer(0)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_lrem_deletes_empty_list() -> Result<()> {
+        let backend = Backend::new();
+        backend.push(
+            "mylist".to_string(),
+            values(&["c", "c"]),
+            ListSide::Right,
+            true,
+        );
+
+        assert_eq!(lrem(5, "c").execute(&backend).await, RespFrame::Integer(2));
+        assert!(!backend.exists("mylist"));
+
+        Ok(())
+    }
+
+    fn ltrim(start: i64, stop: i64) -> LTrim {
+        LTrim {
+            key: "mylist".to_string(),
+            start,
+            stop,
+        }
+    }
+
+    #[test]
+    fn test_ltrim_try_from() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*4\r\n$5\r\nltrim\r\n$6\r\nmylist\r\n$1\r\n1\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = LTrim::try_from(frame)?;
+        assert_eq!(cmd.key, "mylist");
+        assert_eq!((cmd.start, cmd.stop), (1, -1));
+
+        let mut buf = BytesMut::from("*3\r\n$5\r\nltrim\r\n$6\r\nmylist\r\n$1\r\n1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(LTrim::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ltrim_command() -> Result<()> {
+        let backend = Backend::new();
+
+        setup_list(&backend);
+        assert_eq!(ltrim(2, 4).execute(&backend).await, RESP_OK.clone());
+        assert_eq!(list_values(&backend), values(&["c", "1", "2"]));
+
+        setup_list(&backend);
+        assert_eq!(ltrim(-3, -1).execute(&backend).await, RESP_OK.clone());
+        assert_eq!(list_values(&backend), values(&["3", "c", "c"]));
+
+        setup_list(&backend);
+        assert_eq!(ltrim(-100, 1).execute(&backend).await, RESP_OK.clone());
+        assert_eq!(list_values(&backend), values(&["a", "b"]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_ltrim_to_empty_deletes_key() -> Result<()> {
+        let backend = Backend::new();
+
+        setup_list(&backend);
+        assert_eq!(ltrim(5, 2).execute(&backend).await, RESP_OK.clone());
+        assert!(!backend.exists("mylist"));
+
+        setup_list(&backend);
+        assert_eq!(ltrim(100, 200).execute(&backend).await, RESP_OK.clone());
+        assert!(!backend.exists("mylist"));
+
+        assert_eq!(ltrim(0, -1).execute(&backend).await, RESP_OK.clone());
+        assert!(!backend.exists("mylist"));
+
+        Ok(())
+    }
+}
@@ -1,12 +1,37 @@
-use crate::{Backend, RespArray, RespFrame, RespNull};
+use bytes::Bytes;
+use dashmap::mapref::entry::Entry;
 
-use super::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
+use crate::{now_ms, Backend, BulkString, KeyType, RespArray, RespFrame, RespNull, SimpleError};
+
+use super::{
+    expire::expire_at, extract_args, normalize_range, parse_integer, validate_command,
+    CommandError, CommandExecutor, RESP_OK, RESP_WRONGTYPE,
+};
+
+// 与 redis 的 proto-max-bulk-len 默认值一致，字符串最大 512MB
+const MAX_BIT_OFFSET: u64 = 512 * 1024 * 1024 * 8 - 1;
 //     - GET key ("*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
 #[derive(Debug)]
 pub struct Get {
     key: String,
 }
 
+//     - GETEX key [EX seconds | PX milliseconds | EXAT unix-time-seconds | PXAT unix-time-milliseconds | PERSIST]
+//       ("*4\r\n$5\r\ngetex\r\n$5\r\nhello\r\n$2\r\nEX\r\n$2\r\n10\r\n")
+#[derive(Debug)]
+pub struct GetEx {
+    key: String,
+    expiry: Option<GetExExpiry>,
+}
+
+// 相对时间在执行时才换算成绝对时间
+#[derive(Debug, PartialEq, Eq)]
+enum GetExExpiry {
+    After(i64),
+    At(i64),
+    Persist,
+}
+
 //     - SET key val ("*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
 #[derive(Debug)]
 pub struct Set {
@@ -14,22 +39,211 @@ pub struct Set {
     value: RespFrame,
 }
 
+//     - INCRBYFLOAT key increment ("*3\r\n$11\r\nincrbyfloat\r\n$5\r\nhello\r\n$3\r\n0.1\r\n")
+#[derive(Debug)]
+pub struct IncrByFloat {
+    key: String,
+    increment: f64,
+}
+
+//     - SETBIT key offset value ("*4\r\n$6\r\nsetbit\r\n$5\r\nhello\r\n$1\r\n7\r\n$1\r\n1\r\n")
+#[derive(Debug)]
+pub struct SetBit {
+    key: String,
+    offset: u64,
+    value: bool,
+}
+
+//     - GETBIT key offset ("*3\r\n$6\r\ngetbit\r\n$5\r\nhello\r\n$1\r\n7\r\n")
+#[derive(Debug)]
+pub struct GetBit {
+    key: String,
+    offset: u64,
+}
+
+//     - BITCOUNT key [start end [BYTE|BIT]] ("*2\r\n$8\r\nbitcount\r\n$5\r\nhello\r\n")
+#[derive(Debug)]
+pub struct BitCount {
+    key: String,
+    range: Option<BitRange>,
+}
+
+// start 和 end 都是闭区间，支持负数下标
+#[derive(Debug, PartialEq, Eq)]
+struct BitRange {
+    start: i64,
+    end: i64,
+    bit: bool,
+}
+
+// 字符串类型的值总是以 BulkString 返回，其他类型的值或其他 store 中的 key 返回 WRONGTYPE
 impl CommandExecutor for Get {
-    fn execute(&self, backend: &Backend) -> RespFrame {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
         match backend.get(&self.key) {
-            Some(value) => value,
+            // BulkString 只增加引用计数，不复制数据
+            Some(RespFrame::BulkString(s)) => s.into(),
+            Some(value) => match string_bytes(&value) {
+                Some(bytes) => BulkString::from(bytes).into(),
+                None => RESP_WRONGTYPE.clone(),
+            },
+            None if backend.exists(&self.key) => RESP_WRONGTYPE.clone(),
             None => RespFrame::Null(RespNull),
         }
     }
 }
 
+// 回复与 GET 相同，只有 key 是字符串时才修改过期时间
+impl CommandExecutor for GetEx {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let reply = Get {
+            key: self.key.clone(),
+        }
+        .execute(backend)
+        .await;
+        if !matches!(reply, RespFrame::BulkString(_)) {
+            return reply;
+        }
+        match self.expiry {
+            Some(GetExExpiry::After(millis)) => {
+                expire_at(backend, &self.key, (now_ms() as i64).saturating_add(millis));
+            }
+            Some(GetExExpiry::At(deadline_ms)) => {
+                expire_at(backend, &self.key, deadline_ms);
+            }
+            Some(GetExExpiry::Persist) => {
+                backend.persist(&self.key);
+            }
+            None => {}
+        }
+        reply
+    }
+}
+
 impl CommandExecutor for Set {
-    fn execute(&self, backend: &Backend) -> RespFrame {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
         backend.set(self.key.clone(), self.value.clone());
         RESP_OK.clone()
     }
 }
 
+impl CommandExecutor for IncrByFloat {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if backend.is_wrong_type(&self.key, KeyType::String) {
+            return RESP_WRONGTYPE.clone();
+        }
+        // 通过 entry 持有 key 的写锁，保证读取和写回之间不会被其他连接修改
+        let entry = backend.map.entry(self.key.clone());
+        let current = match &entry {
+            Entry::Occupied(entry) => match parse_float(entry.get()) {
+                Some(value) => value,
+                None => return SimpleError::new("ERR value is not a valid float").into(),
+            },
+            Entry::Vacant(_) => 0.0,
+        };
+
+        let value = current + self.increment;
+        if !value.is_finite() {
+            return SimpleError::new("ERR increment would produce NaN or Infinity").into();
+        }
+
+        let value: RespFrame = BulkString::new(format_float(value)).into();
+        entry.insert(value.clone());
+        value
+    }
+}
+
+// 与 redis 一致，offset 0 是第一个字节的最高位
+impl CommandExecutor for SetBit {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if backend.is_wrong_type(&self.key, KeyType::String) {
+            return RESP_WRONGTYPE.clone();
+        }
+        let entry = backend.map.entry(self.key.clone());
+        let mut bytes = match &entry {
+            Entry::Occupied(entry) => match string_bytes(entry.get()) {
+                Some(bytes) => Vec::from(bytes),
+                None => return RESP_WRONGTYPE.clone(),
+            },
+            Entry::Vacant(_) => Vec::new(),
+        };
+
+        let (byte, mask) = bit_position(self.offset);
+        if bytes.len() <= byte {
+            bytes.resize(byte + 1, 0);
+        }
+        let previous = bytes[byte] & mask != 0;
+        if self.value {
+            bytes[byte] |= mask;
+        } else {
+            bytes[byte] &= !mask;
+        }
+        entry.insert(BulkString::new(bytes).into());
+        RespFrame::Integer(previous as i64)
+    }
+}
+
+// 超出字符串长度的位都是 0
+impl CommandExecutor for GetBit {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let bytes = match backend.get(&self.key) {
+            Some(value) => match string_bytes(&value) {
+                Some(bytes) => bytes,
+                None => return RESP_WRONGTYPE.clone(),
+            },
+            None if backend.exists(&self.key) => return RESP_WRONGTYPE.clone(),
+            None => return RespFrame::Integer(0),
+        };
+        let (byte, mask) = bit_position(self.offset);
+        let bit = bytes.get(byte).is_some_and(|b| b & mask != 0);
+        RespFrame::Integer(bit as i64)
+    }
+}
+
+impl CommandExecutor for BitCount {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let bytes = match backend.get(&self.key) {
+            Some(value) => match string_bytes(&value) {
+                Some(bytes) => bytes,
+                None => return RESP_WRONGTYPE.clone(),
+            },
+            None if backend.exists(&self.key) => return RESP_WRONGTYPE.clone(),
+            None => return RespFrame::Integer(0),
+        };
+
+        let count = match &self.range {
+            None => popcount(&bytes),
+            Some(range) if range.bit => {
+                match normalize_range(range.start, range.end, bytes.len() * 8) {
+                    Some((start, end)) => popcount_bits(&bytes, start, end),
+                    None => 0,
+                }
+            }
+            Some(range) => match normalize_range(range.start, range.end, bytes.len()) {
+                Some((start, end)) => popcount(&bytes[start..=end]),
+                None => 0,
+            },
+        };
+        RespFrame::Integer(count as i64)
+    }
+}
+
+fn popcount(bytes: &[u8]) -> u32 {
+    bytes.iter().map(|b| b.count_ones()).sum()
+}
+
+// [start, end] 是位下标，首尾两个字节只统计区间内的位
+fn popcount_bits(bytes: &[u8], start: usize, end: usize) -> u32 {
+    let (first, last) = (start >> 3, end >> 3);
+    let head = 0xffu8 >> (start & 7);
+    let tail = 0xffu8 << (7 - (end & 7));
+    if first == last {
+        return (bytes[first] & head & tail).count_ones();
+    }
+    (bytes[first] & head).count_ones()
+        + popcount(&bytes[first + 1..last])
+        + (bytes[last] & tail).count_ones()
+}
+
 // 2\r\n$3\r\nget\r\n$5\r\nhello\r\n
 impl TryFrom<RespArray> for Get {
     type Error = CommandError;
@@ -46,13 +260,76 @@ impl TryFrom<RespArray> for Get {
 
         match args.next() {
             Some(RespFrame::BulkString(key)) => Ok(Self {
-                key: String::from_utf8(key.0)?, // 这里抛出std::string::FromUtf8Error，所以 CommandError 也需要有个 Utf8Error，否者转换不了
+                key: String::from_utf8(key.0.into())?, // 这里抛出std::string::FromUtf8Error，所以 CommandError 也需要有个 Utf8Error，否者转换不了
             }),
             _ => Err(CommandError::InvalidArguments("Invalid Key".to_string())),
         }
     }
 }
 
+impl TryFrom<RespArray> for GetEx {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len < 2 {
+            return Err(CommandError::InvalidArguments(
+                "wrong number of arguments for 'getex' command".to_string(),
+            ));
+        }
+        validate_command(&arr, &["getex"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+        let syntax_error = || CommandError::InvalidArguments("syntax error".to_string());
+
+        let option = match args.next() {
+            Some(RespFrame::BulkString(option)) => option,
+            Some(_) => return Err(syntax_error()),
+            None => return Ok(Self { key, expiry: None }),
+        };
+        let option = String::from_utf8_lossy(&option).to_ascii_lowercase();
+        if option == "persist" {
+            return match args.next() {
+                Some(_) => Err(syntax_error()),
+                None => Ok(Self {
+                    key,
+                    expiry: Some(GetExExpiry::Persist),
+                }),
+            };
+        }
+
+        // 每个单位对应的毫秒数
+        let unit = match option.as_str() {
+            "ex" | "exat" => 1000,
+            "px" | "pxat" => 1,
+            _ => return Err(syntax_error()),
+        };
+        let value = match (args.next(), args.next()) {
+            (Some(value), None) => parse_integer(value)?,
+            _ => return Err(syntax_error()),
+        };
+        let millis = value
+            .checked_mul(unit)
+            .filter(|_| value > 0)
+            .ok_or_else(|| {
+                CommandError::InvalidArguments("invalid expire time in 'getex' command".to_string())
+            })?;
+        let expiry = if option.ends_with("at") {
+            GetExExpiry::At(millis)
+        } else {
+            GetExExpiry::After(millis)
+        };
+        Ok(Self {
+            key,
+            expiry: Some(expiry),
+        })
+    }
+}
+
 // "*2\r\n$3\r\nget\r\n$5\r\nhello\r\n"
 impl TryFrom<RespArray> for Set {
     type Error = CommandError;
@@ -63,7 +340,7 @@ impl TryFrom<RespArray> for Set {
         let mut args = extract_args(arr, 1)?.into_iter();
 
         let key = match args.next() {
-            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
             _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
         };
 
@@ -76,9 +353,178 @@ impl TryFrom<RespArray> for Set {
     }
 }
 
+impl TryFrom<RespArray> for IncrByFloat {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["incrbyfloat"], 2)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let increment = args
+            .next()
+            .as_ref()
+            .and_then(parse_float)
+            .filter(|v| v.is_finite())
+            .ok_or_else(|| {
+                CommandError::InvalidArguments("value is not a valid float".to_string())
+            })?;
+
+        Ok(Self { key, increment })
+    }
+}
+
+impl TryFrom<RespArray> for SetBit {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["setbit"], 3)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+        let offset = parse_bit_offset(args.next())?;
+        let value = match args.next().map(parse_integer).transpose() {
+            Ok(Some(0)) => false,
+            Ok(Some(1)) => true,
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "bit is not an integer or out of range".to_string(),
+                ))
+            }
+        };
+
+        Ok(Self { key, offset, value })
+    }
+}
+
+impl TryFrom<RespArray> for GetBit {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["getbit"], 2)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+        let offset = parse_bit_offset(args.next())?;
+
+        Ok(Self { key, offset })
+    }
+}
+
+impl TryFrom<RespArray> for BitCount {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        // 只给出 start 没有 end 时是语法错误
+        if !matches!(len, 2 | 4 | 5) {
+            return Err(CommandError::InvalidArguments("syntax error".to_string()));
+        }
+        validate_command(&arr, &["bitcount"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+        let range = match (args.next(), args.next()) {
+            (Some(start), Some(end)) => {
+                let bit = match args.next() {
+                    None => false,
+                    Some(RespFrame::BulkString(unit)) => {
+                        match unit.to_ascii_lowercase().as_slice() {
+                            b"byte" => false,
+                            b"bit" => true,
+                            _ => {
+                                return Err(CommandError::InvalidArguments(
+                                    "syntax error".to_string(),
+                                ))
+                            }
+                        }
+                    }
+                    Some(_) => {
+                        return Err(CommandError::InvalidArguments("syntax error".to_string()))
+                    }
+                };
+                Some(BitRange {
+                    start: parse_integer(start)?,
+                    end: parse_integer(end)?,
+                    bit,
+                })
+            }
+            _ => None,
+        };
+
+        Ok(Self { key, range })
+    }
+}
+
+// map store 中字符串类型的值可能以多种 frame 保存，统一转换为字节
+fn string_bytes(frame: &RespFrame) -> Option<Bytes> {
+    match frame {
+        RespFrame::BulkString(s) => Some(s.0.clone()),
+        RespFrame::SimpleString(s) => Some(s.0.clone().into()),
+        RespFrame::Integer(n) => Some(n.to_string().into()),
+        RespFrame::Double(d) => Some(d.to_string().into()),
+        RespFrame::BigNumber(n) => Some(n.0.clone().into()),
+        RespFrame::VerbatimString(s) => Some(Bytes::copy_from_slice(s.data())),
+        _ => None,
+    }
+}
+
+// 返回 offset 所在的字节下标和该位的掩码
+fn bit_position(offset: u64) -> (usize, u8) {
+    ((offset >> 3) as usize, 0x80 >> (offset & 7))
+}
+
+fn parse_bit_offset(frame: Option<RespFrame>) -> Result<u64, CommandError> {
+    frame
+        .map(parse_integer)
+        .transpose()
+        .ok()
+        .flatten()
+        .and_then(|offset| u64::try_from(offset).ok())
+        .filter(|offset| *offset <= MAX_BIT_OFFSET)
+        .ok_or_else(|| {
+            CommandError::InvalidArguments(
+                "bit offset is not an integer or out of range".to_string(),
+            )
+        })
+}
+
+fn parse_float(frame: &RespFrame) -> Option<f64> {
+    match frame {
+        RespFrame::BulkString(value) => std::str::from_utf8(value)
+            .ok()?
+            .parse::<f64>()
+            .ok()
+            .filter(|v| !v.is_nan()),
+        RespFrame::Integer(value) => Some(*value as f64),
+        _ => None,
+    }
+}
+
+// redis 以 %.17Lg 格式化 long double，f64 直接输出会带上 0.1 + 0.2 = 0.30000000000000004 这样的误差，
+// 这里先保留 15 位有效数字再输出最短表示，同时去掉多余的 0 且不使用科学计数法
+fn format_float(value: f64) -> String {
+    let rounded = format!("{:.14e}", value).parse::<f64>().unwrap_or(value);
+    format!("{}", rounded)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::RespDecoder;
+    use crate::{RespDecoder, SimpleString};
 
     use super::*;
     use anyhow::Result;
@@ -109,22 +555,388 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn test_set_get_command() -> Result<()> {
+    #[tokio::test]
+    async fn test_set_get_command() -> Result<()> {
         let backend = Backend::new();
         let cmd = Set {
             key: "hello".to_string(),
             value: RespFrame::BulkString(b"world".into()),
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend).await;
         assert_eq!(result, RESP_OK.clone());
 
         let cmd = Get {
             key: "hello".to_string(),
         };
-        let result = cmd.execute(&backend);
+        let result = cmd.execute(&backend).await;
         assert_eq!(result, RespFrame::BulkString(b"world".into()));
 
+        // GET 返回的数据与保存的值共享同一块内存
+        let RespFrame::BulkString(stored) = backend.get("hello").unwrap() else {
+            panic!("expected a bulk string");
+        };
+        let RespFrame::BulkString(reply) = result else {
+            panic!("expected a bulk string");
+        };
+        assert_eq!(reply.as_ptr(), stored.as_ptr());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_returns_bulk_string() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("int".to_string(), RespFrame::Integer(42));
+        backend.set("simple".to_string(), SimpleString::new("OK").into());
+
+        let get = |key: &str| Get {
+            key: key.to_string(),
+        };
+        assert_eq!(get("int").execute(&backend).await, b"42".into());
+        assert_eq!(get("simple").execute(&backend).await, b"OK".into());
+        assert_eq!(
+            get("missing").execute(&backend).await,
+            RespFrame::Null(RespNull)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.set(
+            "array".to_string(),
+            RespArray::new(vec![b"a".into(), b"b".into()]).into(),
+        );
+        backend.hset("hash".to_string(), "field".to_string(), b"value".into());
+
+        let get = |key: &str| Get {
+            key: key.to_string(),
+        };
+        assert_eq!(get("array").execute(&backend).await, RESP_WRONGTYPE.clone());
+        assert_eq!(get("hash").execute(&backend).await, RESP_WRONGTYPE.clone());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_incrbyfloat_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$11\r\nincrbyfloat\r\n$5\r\nhello\r\n$3\r\n0.1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = IncrByFloat::try_from(frame)?;
+        assert_eq!(cmd.key, "hello");
+        assert_eq!(cmd.increment, 0.1);
+
+        let mut buf = BytesMut::from("*3\r\n$11\r\nincrbyfloat\r\n$5\r\nhello\r\n$3\r\nabc\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(IncrByFloat::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_incrbyfloat_command() -> Result<()> {
+        let backend = Backend::new();
+        let incr = |increment| IncrByFloat {
+            key: "hello".to_string(),
+            increment,
+        };
+
+        assert_eq!(
+            incr(10.5).execute(&backend).await,
+            RespFrame::BulkString(b"10.5".into())
+        );
+        assert_eq!(
+            incr(0.1).execute(&backend).await,
+            RespFrame::BulkString(b"10.6".into())
+        );
+        assert_eq!(
+            incr(-5.6).execute(&backend).await,
+            RespFrame::BulkString(b"5".into())
+        );
+        assert_eq!(
+            backend.get("hello"),
+            Some(RespFrame::BulkString(b"5".into()))
+        );
+
+        backend.set("sum".to_string(), RespFrame::BulkString(b"0.1".into()));
+        let cmd = IncrByFloat {
+            key: "sum".to_string(),
+            increment: 0.2,
+        };
+        assert_eq!(
+            cmd.execute(&backend).await,
+            RespFrame::BulkString(b"0.3".into())
+        );
+
+        backend.set("big".to_string(), RespFrame::BulkString(b"5.0e3".into()));
+        let cmd = IncrByFloat {
+            key: "big".to_string(),
+            increment: 1.5,
+        };
+        assert_eq!(
+            cmd.execute(&backend).await,
+            RespFrame::BulkString(b"5001.5".into())
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_incrbyfloat_invalid_value() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), RespFrame::BulkString(b"world".into()));
+
+        let cmd = IncrByFloat {
+            key: "hello".to_string(),
+            increment: 1.0,
+        };
+        assert_eq!(
+            cmd.execute(&backend).await,
+            SimpleError::new("ERR value is not a valid float").into()
+        );
+        assert_eq!(
+            backend.get("hello"),
+            Some(RespFrame::BulkString(b"world".into()))
+        );
+
+        backend.set("max".to_string(), RespFrame::BulkString(b"1.7e308".into()));
+        let cmd = IncrByFloat {
+            key: "max".to_string(),
+            increment: 1.7e308,
+        };
+        assert_eq!(
+            cmd.execute(&backend).await,
+            SimpleError::new("ERR increment would produce NaN or Infinity").into()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_setbit_getbit() -> Result<()> {
+        let backend = Backend::new();
+        let mut buf =
+            BytesMut::from("*4\r\n$6\r\nsetbit\r\n$5\r\nhello\r\n$2\r\n13\r\n$1\r\n1\r\n");
+        let cmd = SetBit::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+        // 第 2 个字节的第 6 位：0b0000_0100
+        assert_eq!(
+            backend.get("hello"),
+            Some(BulkString::new(vec![0, 4]).into())
+        );
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+
+        // 较大的 offset 会用 0 补齐字符串
+        let cmd = SetBit {
+            key: "hello".to_string(),
+            offset: 100,
+            value: true,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+        let Some(RespFrame::BulkString(value)) = backend.get("hello") else {
+            panic!("expected a bulk string");
+        };
+        assert_eq!(value.len(), 13);
+        assert_eq!(value[12], 0b0000_1000);
+
+        for (offset, expected) in [(13, 1), (12, 0), (100, 1), (101, 0), (1_000_000, 0)] {
+            let cmd = GetBit {
+                key: "hello".to_string(),
+                offset,
+            };
+            assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(expected));
+        }
+
+        let cmd = SetBit {
+            key: "hello".to_string(),
+            offset: 13,
+            value: false,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+        assert_eq!(
+            GetBit {
+                key: "hello".to_string(),
+                offset: 13
+            }
+            .execute(&backend)
+            .await,
+            RespFrame::Integer(0)
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_setbit_errors() -> Result<()> {
+        let mut buf = BytesMut::from("*4\r\n$6\r\nsetbit\r\n$5\r\nhello\r\n$1\r\n0\r\n$1\r\n2\r\n");
+        assert!(SetBit::try_from(RespArray::decode(&mut buf)?).is_err());
+        let mut buf =
+            BytesMut::from("*4\r\n$6\r\nsetbit\r\n$5\r\nhello\r\n$2\r\n-1\r\n$1\r\n1\r\n");
+        assert!(SetBit::try_from(RespArray::decode(&mut buf)?).is_err());
+
+        let backend = Backend::new();
+        backend.hset("hash".to_string(), "f".to_string(), b"v".into());
+        let cmd = SetBit {
+            key: "hash".to_string(),
+            offset: 0,
+            value: true,
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_bitcount() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("key".to_string(), b"foobar".into());
+
+        let mut buf = BytesMut::from("*2\r\n$8\r\nbitcount\r\n$3\r\nkey\r\n");
+        let cmd = BitCount::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(26));
+
+        // 与 redis 文档中的例子一致
+        for (start, end, bit, expected) in [
+            (0, 0, false, 4),
+            (1, 1, false, 6),
+            (1, -1, false, 22),
+            (-2, -1, false, 7),
+            (5, 30, true, 17),
+            (-8, -1, true, 4),
+            (3, 1, false, 0),
+            (10, 20, false, 0),
+        ] {
+            let cmd = BitCount {
+                key: "key".to_string(),
+                range: Some(BitRange { start, end, bit }),
+            };
+            assert_eq!(
+                cmd.execute(&backend).await,
+                RespFrame::Integer(expected),
+                "{} {} {}",
+                start,
+                end,
+                bit
+            );
+        }
+
+        let cmd = BitCount {
+            key: "missing".to_string(),
+            range: None,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+
+        let mut buf = BytesMut::from("*3\r\n$8\r\nbitcount\r\n$3\r\nkey\r\n$1\r\n0\r\n");
+        assert!(BitCount::try_from(RespArray::decode(&mut buf)?).is_err());
+        let mut buf = BytesMut::from(
+            "*5\r\n$8\r\nbitcount\r\n$3\r\nkey\r\n$1\r\n0\r\n$1\r\n1\r\n$3\r\nBIT\r\n",
+        );
+        let cmd = BitCount::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+
+        Ok(())
+    }
+
+    fn getex(args: &[&str]) -> Result<GetEx, CommandError> {
+        let frames = ["getex"]
+            .iter()
+            .chain(args)
+            .map(|arg| BulkString::new(*arg).into())
+            .collect::<Vec<RespFrame>>();
+        GetEx::try_from(RespArray::new(frames))
+    }
+
+    #[test]
+    fn test_getex_try_from() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*4\r\n$5\r\ngetex\r\n$5\r\nhello\r\n$2\r\nEX\r\n$2\r\n10\r\n");
+        let cmd = GetEx::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.key, "hello");
+        assert_eq!(cmd.expiry, Some(GetExExpiry::After(10_000)));
+
+        assert_eq!(getex(&["k"])?.expiry, None);
+        assert_eq!(
+            getex(&["k", "pxat", "1893456000000"])?.expiry,
+            Some(GetExExpiry::At(1893456000000))
+        );
+        assert_eq!(getex(&["k", "Persist"])?.expiry, Some(GetExExpiry::Persist));
+
+        assert!(getex(&[]).is_err());
+        assert!(getex(&["k", "ex"]).is_err());
+        assert!(getex(&["k", "ex", "0"]).is_err());
+        assert!(getex(&["k", "ex", "10", "px", "10"]).is_err());
+        assert!(getex(&["k", "persist", "ex"]).is_err());
+        assert!(getex(&["k", "keepttl"]).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_getex_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("hello".to_string(), BulkString::new("world").into());
+        let world: RespFrame = BulkString::new("world").into();
+
+        // 没有选项时与 GET 相同，不改变过期时间
+        assert_eq!(getex(&["hello"])?.execute(&backend).await, world);
+        assert_eq!(backend.expire_at("hello"), None);
+
+        let before = now_ms();
+        assert_eq!(
+            getex(&["hello", "EX", "10"])?.execute(&backend).await,
+            world
+        );
+        let deadline = backend.expire_at("hello").expect("ttl should be set");
+        assert!(deadline >= before + 10_000 && deadline <= now_ms() + 10_000);
+
+        assert_eq!(getex(&["hello", "PERSIST"])?.execute(&backend).await, world);
+        assert_eq!(backend.expire_at("hello"), None);
+
+        // 过去的绝对时间直接删除 key，本次仍然返回原来的值
+        assert_eq!(
+            getex(&["hello", "PXAT", "1"])?.execute(&backend).await,
+            world
+        );
+        assert!(!backend.exists("hello"));
+
+        let reply = getex(&["missing", "EX", "10"])?.execute(&backend).await;
+        assert_eq!(reply, RespFrame::Null(RespNull));
+        assert!(!backend.exists("missing"));
+
+        backend.set("int".to_string(), RespFrame::Integer(1));
+        backend.hset(
+            "hash".to_string(),
+            "f".to_string(),
+            BulkString::new("v").into(),
+        );
+        let reply = getex(&["hash", "EX", "10"])?.execute(&backend).await;
+        assert_eq!(reply, RESP_WRONGTYPE.clone());
+        assert_eq!(backend.expire_at("hash"), None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_string_writes_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.hset("hash".to_string(), "field".to_string(), b"1".into());
+
+        let cmd = IncrByFloat {
+            key: "hash".to_string(),
+            increment: 1.5,
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+        assert!(!backend.map.contains_key("hash"));
+
+        // SET 与 redis 一致，覆盖其他类型的值
+        let cmd = Set {
+            key: "hash".to_string(),
+            value: b"value".into(),
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_OK.clone());
+        assert!(!backend.hmap.contains_key("hash"));
+        assert_eq!(backend.key_type("hash"), Some(KeyType::String));
+
         Ok(())
     }
 }
@@ -0,0 +1,132 @@
+use crate::{Backend, RespArray, RespFrame, RespNull};
+
+use super::{extract_args, parse_integer, validate_command, CommandError, CommandExecutor};
+
+// 与 redis 一致，聚合类型默认只抽样 5 个元素
+const DEFAULT_SAMPLES: usize = 5;
+
+// memory usage key [samples count]
+// "*3\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct Memory {
+    key: String,
+    // 0 表示遍历全部元素
+    samples: usize,
+}
+
+impl CommandExecutor for Memory {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        match backend.memory_usage(&self.key, self.samples) {
+            Some(size) => RespFrame::Integer(size as i64),
+            None => RespFrame::Null(RespNull),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for Memory {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        match arr.get(1) {
+            Some(RespFrame::BulkString(sub)) if sub.eq_ignore_ascii_case(b"usage") => {}
+            Some(RespFrame::BulkString(sub)) => {
+                return Err(CommandError::InvalidArguments(format!(
+                    "unknown subcommand '{}'",
+                    String::from_utf8_lossy(sub)
+                )))
+            }
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "MEMORY command must have a subcommand".to_string(),
+                ))
+            }
+        }
+        let n_args = arr.len().clamp(3, 5) - 2;
+        if n_args == 2 {
+            return Err(CommandError::InvalidArguments("syntax error".to_string()));
+        }
+        validate_command(&arr, &["memory", "usage"], n_args)?;
+
+        let mut args = extract_args(arr, 2)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid key".to_string())),
+        };
+        let samples = match (args.next(), args.next()) {
+            (None, _) => DEFAULT_SAMPLES,
+            (Some(RespFrame::BulkString(option)), Some(count))
+                if option.eq_ignore_ascii_case(b"samples") =>
+            {
+                let count = parse_integer(count)?;
+                if count < 0 {
+                    return Err(CommandError::InvalidArguments(
+                        "value is out of range, must be positive".to_string(),
+                    ));
+                }
+                count as usize
+            }
+            _ => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+        };
+
+        Ok(Self { key, samples })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecoder;
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn parse(input: &str) -> Result<Memory> {
+        let mut buf = BytesMut::from(input);
+        let frame = RespArray::decode(&mut buf)?;
+        Ok(Memory::try_from(frame)?)
+    }
+
+    #[test]
+    fn test_memory_try_from() -> Result<()> {
+        let cmd = parse("*3\r\n$6\r\nmemory\r\n$5\r\nUSAGE\r\n$5\r\nhello\r\n")?;
+        assert_eq!(cmd.key, "hello");
+        assert_eq!(cmd.samples, DEFAULT_SAMPLES);
+
+        let cmd = parse(
+            "*5\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$5\r\nhello\r\n$7\r\nsamples\r\n$1\r\n0\r\n",
+        )?;
+        assert_eq!(cmd.samples, 0);
+
+        assert!(
+            parse("*4\r\n$6\r\nmemory\r\n$5\r\nusage\r\n$5\r\nhello\r\n$7\r\nsamples\r\n").is_err()
+        );
+        assert!(parse("*3\r\n$6\r\nmemory\r\n$6\r\ndoctor\r\n$5\r\nhello\r\n").is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_memory_usage_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("small".to_string(), b"a".into());
+        backend.set("large".to_string(), [b'a'; 1024][..].into());
+
+        let usage = |key: &str| Memory {
+            key: key.to_string(),
+            samples: DEFAULT_SAMPLES,
+        };
+        let RespFrame::Integer(small) = usage("small").execute(&backend).await else {
+            panic!("expected an integer");
+        };
+        let RespFrame::Integer(large) = usage("large").execute(&backend).await else {
+            panic!("expected an integer");
+        };
+        assert!(large > small);
+        assert_eq!(
+            usage("missing").execute(&backend).await,
+            RespFrame::Null(RespNull)
+        );
+
+        Ok(())
+    }
+}
@@ -1,29 +1,77 @@
+mod command;
+mod connection;
+mod debug;
 mod echo;
+mod expire;
+mod generic;
 mod hmap;
+mod list;
 mod map;
+mod memory;
+mod object;
+mod pubsub;
+mod server;
 mod set;
+mod stream;
+mod transaction;
+mod zset;
 
 use enum_dispatch::enum_dispatch;
 use lazy_static::lazy_static;
 use thiserror::Error;
 
-use crate::{Backend, RespArray, RespError, RespFrame, SimpleString};
+use crate::{
+    glob_match, Backend, BulkString, RespArray, RespError, RespFrame, Session, SimpleError,
+    SimpleString,
+};
 
 pub use self::{
+    command::{lookup_command, CommandIntrospect, CommandSpec, COMMAND_TABLE},
+    connection::{Client, Hello, Ping, Quit, Reset, Select, SwapDb},
+    debug::Debug,
     echo::Echo,
-    hmap::{HGet, HGetAll, HMGet, HSet},
-    map::{Get, Set},
-    set::{SAdd, SIsMember},
+    expire::{Expire, ExpireAt, ExpireTime, PExpire, PExpireAt, PExpireTime, PTtl, Persist, Ttl},
+    generic::{Copy, Del, Dump, RandomKey, Restore, Sort, Touch, Type, Unlink, Wait},
+    hmap::{HGet, HGetAll, HMGet, HRandField, HScan, HSet, HSetNx},
+    list::{BLPop, BRPop, LPos, LPush, LPushX, LRem, LTrim, RPush, RPushX},
+    map::{BitCount, Get, GetBit, GetEx, IncrByFloat, Set, SetBit},
+    memory::Memory,
+    object::Object,
+    pubsub::{PSubscribe, PUnsubscribe, PubSubIntrospect, Publish, Subscribe, Unsubscribe},
+    server::{Config, Info, Lolwut, Monitor, Shutdown, Slowlog, Time},
+    set::{SAdd, SInterCard, SIsMember, SMove, SRandMember, SScan},
+    stream::{XAdd, XLen, XRange},
+    transaction::{Discard, Exec, Multi, Unwatch, Watch},
+    zset::{
+        ZAdd, ZCard, ZCount, ZIncrBy, ZLexCount, ZMScore, ZRange, ZRangeByLex, ZRangeByScore,
+        ZScore,
+    },
+};
+pub(crate) use self::{
+    connection::invalidate_frame,
+    pubsub::message_frame,
+    server::monitor_line,
+    transaction::{exec_transaction, queued},
 };
 
 // lazy_static 懒加载
 lazy_static! {
     static ref RESP_OK: RespFrame = SimpleString::new("OK").into();
+    static ref RESP_WRONGTYPE: RespFrame =
+        SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value")
+            .into();
 }
 
+// 命令总是通过具体的 Command 类型执行，future 的 Send 可以由编译器推导出来
+#[allow(async_fn_in_trait)]
 #[enum_dispatch]
 pub trait CommandExecutor {
-    fn execute(&self, backend: &Backend) -> RespFrame;
+    async fn execute(&self, backend: &Backend) -> RespFrame;
+
+    // 需要读取或修改连接状态的命令覆盖此方法，其余命令直接在当前连接的 backend 上执行
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        self.execute(&session.backend()).await
+    }
 }
 
 #[enum_dispatch(CommandExecutor)]
@@ -35,9 +83,91 @@ pub enum Command {
     HSet(HSet),
     HGetAll(HGetAll),
     HMGet(HMGet),
+    HRandField(HRandField),
     Echo(Echo),
     SAdd(SAdd),
     SIsMember(SIsMember),
+    Del(Del),
+    Unlink(Unlink),
+    LPos(LPos),
+    Copy(Copy),
+    LPush(LPush),
+    RPush(RPush),
+    LPushX(LPushX),
+    RPushX(RPushX),
+    RandomKey(RandomKey),
+    Type(Type),
+    Object(Object),
+    ZAdd(ZAdd),
+    ZIncrBy(ZIncrBy),
+    Multi(Multi),
+    Exec(Exec),
+    Discard(Discard),
+    Watch(Watch),
+    Unwatch(Unwatch),
+    Debug(Debug),
+    ZScore(ZScore),
+    ZMScore(ZMScore),
+    BLPop(BLPop),
+    BRPop(BRPop),
+    ZRange(ZRange),
+    Wait(Wait),
+    ZCard(ZCard),
+    LRem(LRem),
+    LTrim(LTrim),
+    ZRangeByScore(ZRangeByScore),
+    ZCount(ZCount),
+    IncrByFloat(IncrByFloat),
+    SetBit(SetBit),
+    GetBit(GetBit),
+    BitCount(BitCount),
+    HSetNx(HSetNx),
+    SMove(SMove),
+    ZRangeByLex(ZRangeByLex),
+    ZLexCount(ZLexCount),
+    SRandMember(SRandMember),
+    Select(Select),
+    Hello(Hello),
+    Info(Info),
+    CommandIntrospect(CommandIntrospect),
+    Client(Client),
+    Monitor(Monitor),
+    Time(Time),
+    Lolwut(Lolwut),
+    Touch(Touch),
+    Expire(Expire),
+    PExpire(PExpire),
+    Ttl(Ttl),
+    PTtl(PTtl),
+    Persist(Persist),
+    Reset(Reset),
+    Shutdown(Shutdown),
+    Slowlog(Slowlog),
+    Config(Config),
+    Memory(Memory),
+    Sort(Sort),
+    Subscribe(Subscribe),
+    Unsubscribe(Unsubscribe),
+    Publish(Publish),
+    PubSubIntrospect(PubSubIntrospect),
+    SwapDb(SwapDb),
+    Quit(Quit),
+    Dump(Dump),
+    Restore(Restore),
+    XAdd(XAdd),
+    XLen(XLen),
+    XRange(XRange),
+    HScan(HScan),
+    SScan(SScan),
+    PSubscribe(PSubscribe),
+    PUnsubscribe(PUnsubscribe),
+    ExpireAt(ExpireAt),
+    PExpireAt(PExpireAt),
+    ExpireTime(ExpireTime),
+    PExpireTime(PExpireTime),
+    GetEx(GetEx),
+    SInterCard(SInterCard),
+    Ping(Ping),
 }
 
 #[derive(Debug, Error)]
@@ -54,26 +184,28 @@ pub enum CommandError {
     Utf8Error(#[from] std::string::FromUtf8Error),
 }
 
+// 命令解析失败时返回给客户端的错误，连接保持打开
+impl From<CommandError> for RespFrame {
+    fn from(e: CommandError) -> Self {
+        let msg = match e {
+            CommandError::InvalidCommand(msg) | CommandError::InvalidArguments(msg) => msg,
+            e => e.to_string(),
+        };
+        SimpleError::new(format!("ERR {}", msg)).into()
+    }
+}
+
 impl TryFrom<RespArray> for Command {
     type Error = CommandError;
 
     fn try_from(array: RespArray) -> Result<Self, Self::Error> {
         match array.first() {
-            Some(RespFrame::BulkString(cmd)) => match cmd.as_ref() {
-                b"get" => Ok(Get::try_from(array)?.into()),
-                b"set" => Ok(Set::try_from(array)?.into()),
-                b"hget" => Ok(HGet::try_from(array)?.into()),
-                b"hset" => Ok(HSet::try_from(array)?.into()),
-                b"hgetall" => Ok(HGetAll::try_from(array)?.into()),
-                b"hmget" => Ok(HMGet::try_from(array)?.into()),
-                b"echo" => Ok(Echo::try_from(array)?.into()),
-                b"sadd" => Ok(SAdd::try_from(array)?.into()),
-                b"sismember" => Ok(SIsMember::try_from(array)?.into()),
-                _ => Err(CommandError::InvalidCommand(format!(
-                    "Invalid command: {}",
-                    String::from_utf8_lossy(cmd)
-                ))),
-            },
+            // 命令名不区分大小写
+            Some(RespFrame::BulkString(cmd)) => {
+                let spec = lookup_command(cmd).ok_or_else(|| unknown_command(cmd))?;
+                spec.check_arity(array.len())?;
+                dispatch(spec.name, array)
+            }
             _ => Err(CommandError::InvalidCommand(
                 "Command must be a BulkString frame".to_string(),
             )),
@@ -81,6 +213,111 @@ impl TryFrom<RespArray> for Command {
     }
 }
 
+fn unknown_command(cmd: &[u8]) -> CommandError {
+    CommandError::InvalidCommand(format!(
+        "unknown command '{}'",
+        String::from_utf8_lossy(cmd)
+    ))
+}
+
+// name 来自 COMMAND_TABLE，每个表项都必须在这里有对应的分支
+fn dispatch(name: &str, array: RespArray) -> Result<Command, CommandError> {
+    match name {
+        "get" => Ok(Get::try_from(array)?.into()),
+        "set" => Ok(Set::try_from(array)?.into()),
+        "hget" => Ok(HGet::try_from(array)?.into()),
+        "hset" => Ok(HSet::try_from(array)?.into()),
+        "hgetall" => Ok(HGetAll::try_from(array)?.into()),
+        "hmget" => Ok(HMGet::try_from(array)?.into()),
+        "hrandfield" => Ok(HRandField::try_from(array)?.into()),
+        "echo" => Ok(Echo::try_from(array)?.into()),
+        "sadd" => Ok(SAdd::try_from(array)?.into()),
+        "sismember" => Ok(SIsMember::try_from(array)?.into()),
+        "del" => Ok(Del::try_from(array)?.into()),
+        "unlink" => Ok(Unlink::try_from(array)?.into()),
+        "lpos" => Ok(LPos::try_from(array)?.into()),
+        "copy" => Ok(Copy::try_from(array)?.into()),
+        "lpush" => Ok(LPush::try_from(array)?.into()),
+        "rpush" => Ok(RPush::try_from(array)?.into()),
+        "lpushx" => Ok(LPushX::try_from(array)?.into()),
+        "rpushx" => Ok(RPushX::try_from(array)?.into()),
+        "randomkey" => Ok(RandomKey::try_from(array)?.into()),
+        "type" => Ok(Type::try_from(array)?.into()),
+        "object" => Ok(Object::try_from(array)?.into()),
+        "zadd" => Ok(ZAdd::try_from(array)?.into()),
+        "multi" => Ok(Multi::try_from(array)?.into()),
+        "exec" => Ok(Exec::try_from(array)?.into()),
+        "discard" => Ok(Discard::try_from(array)?.into()),
+        "watch" => Ok(Watch::try_from(array)?.into()),
+        "unwatch" => Ok(Unwatch::try_from(array)?.into()),
+        "zincrby" => Ok(ZIncrBy::try_from(array)?.into()),
+        "debug" => Ok(Debug::try_from(array)?.into()),
+        "zscore" => Ok(ZScore::try_from(array)?.into()),
+        "zmscore" => Ok(ZMScore::try_from(array)?.into()),
+        "blpop" => Ok(BLPop::try_from(array)?.into()),
+        "brpop" => Ok(BRPop::try_from(array)?.into()),
+        "zrange" => Ok(ZRange::try_from(array)?.into()),
+        "xadd" => Ok(XAdd::try_from(array)?.into()),
+        "xlen" => Ok(XLen::try_from(array)?.into()),
+        "xrange" => Ok(XRange::try_from(array)?.into()),
+        "wait" => Ok(Wait::try_from(array)?.into()),
+        "zcard" => Ok(ZCard::try_from(array)?.into()),
+        "lrem" => Ok(LRem::try_from(array)?.into()),
+        "ltrim" => Ok(LTrim::try_from(array)?.into()),
+        "zrangebyscore" => Ok(ZRangeByScore::try_from(array)?.into()),
+        "zcount" => Ok(ZCount::try_from(array)?.into()),
+        "incrbyfloat" => Ok(IncrByFloat::try_from(array)?.into()),
+        "setbit" => Ok(SetBit::try_from(array)?.into()),
+        "getbit" => Ok(GetBit::try_from(array)?.into()),
+        "bitcount" => Ok(BitCount::try_from(array)?.into()),
+        "hsetnx" => Ok(HSetNx::try_from(array)?.into()),
+        "smove" => Ok(SMove::try_from(array)?.into()),
+        "zrangebylex" => Ok(ZRangeByLex::try_from(array)?.into()),
+        "zlexcount" => Ok(ZLexCount::try_from(array)?.into()),
+        "srandmember" => Ok(SRandMember::try_from(array)?.into()),
+        "hscan" => Ok(HScan::try_from(array)?.into()),
+        "sscan" => Ok(SScan::try_from(array)?.into()),
+        "select" => Ok(Select::try_from(array)?.into()),
+        "hello" => Ok(Hello::try_from(array)?.into()),
+        "info" => Ok(Info::try_from(array)?.into()),
+        "command" => Ok(CommandIntrospect::try_from(array)?.into()),
+        "client" => Ok(Client::try_from(array)?.into()),
+        "monitor" => Ok(Monitor::try_from(array)?.into()),
+        "time" => Ok(Time::try_from(array)?.into()),
+        "lolwut" => Ok(Lolwut::try_from(array)?.into()),
+        "touch" => Ok(Touch::try_from(array)?.into()),
+        "expire" => Ok(Expire::try_from(array)?.into()),
+        "pexpire" => Ok(PExpire::try_from(array)?.into()),
+        "ttl" => Ok(Ttl::try_from(array)?.into()),
+        "pttl" => Ok(PTtl::try_from(array)?.into()),
+        "persist" => Ok(Persist::try_from(array)?.into()),
+        "expireat" => Ok(ExpireAt::try_from(array)?.into()),
+        "pexpireat" => Ok(PExpireAt::try_from(array)?.into()),
+        "expiretime" => Ok(ExpireTime::try_from(array)?.into()),
+        "pexpiretime" => Ok(PExpireTime::try_from(array)?.into()),
+        "getex" => Ok(GetEx::try_from(array)?.into()),
+        "sintercard" => Ok(SInterCard::try_from(array)?.into()),
+        "ping" => Ok(Ping::try_from(array)?.into()),
+        "reset" => Ok(Reset::try_from(array)?.into()),
+        "shutdown" => Ok(Shutdown::try_from(array)?.into()),
+        "slowlog" => Ok(Slowlog::try_from(array)?.into()),
+        "config" => Ok(Config::try_from(array)?.into()),
+        "memory" => Ok(Memory::try_from(array)?.into()),
+        "sort" => Ok(Sort::try_from(array)?.into()),
+        "subscribe" => Ok(Subscribe::try_from(array)?.into()),
+        "unsubscribe" => Ok(Unsubscribe::try_from(array)?.into()),
+        "psubscribe" => Ok(PSubscribe::try_from(array)?.into()),
+        "punsubscribe" => Ok(PUnsubscribe::try_from(array)?.into()),
+        "publish" => Ok(Publish::try_from(array)?.into()),
+        "pubsub" => Ok(PubSubIntrospect::try_from(array)?.into()),
+        "swapdb" => Ok(SwapDb::try_from(array)?.into()),
+        "quit" => Ok(Quit::try_from(array)?.into()),
+        "dump" => Ok(Dump::try_from(array)?.into()),
+        "restore" => Ok(Restore::try_from(array)?.into()),
+        _ => Err(unknown_command(name.as_bytes())),
+    }
+}
+
 impl TryFrom<RespFrame> for Command {
     type Error = CommandError;
 
@@ -101,9 +338,8 @@ fn validate_command(
 ) -> Result<(), CommandError> {
     if frames.len() != keys.len() + n_args {
         return Err(CommandError::InvalidArguments(format!(
-            "Expected {} arguments, got {}",
-            n_args,
-            frames.len() - keys.len()
+            "wrong number of arguments for '{}' command",
+            keys.join("|")
         )));
     }
     // 校验 keys 是否匹配
@@ -128,6 +364,117 @@ fn validate_command(
     Ok(())
 }
 
+fn parse_integer(frame: RespFrame) -> Result<i64, CommandError> {
+    match frame {
+        RespFrame::BulkString(value) => {
+            String::from_utf8(value.0.into())?
+                .parse::<i64>()
+                .map_err(|_| {
+                    CommandError::InvalidArguments(
+                        "value is not an integer or out of range".to_string(),
+                    )
+                })
+        }
+        RespFrame::Integer(value) => Ok(value),
+        _ => Err(CommandError::InvalidArguments(
+            "value is not an integer or out of range".to_string(),
+        )),
+    }
+}
+
+// 将支持负数下标的 [start, stop] 闭区间转换为 [0, len) 内的下标，区间为空时返回 None
+fn normalize_range(start: i64, stop: i64, len: usize) -> Option<(usize, usize)> {
+    let len = len as i64;
+    let start = if start < 0 {
+        (len + start).max(0)
+    } else {
+        start
+    };
+    let stop = if stop < 0 {
+        len + stop
+    } else {
+        stop.min(len - 1)
+    };
+    if start > stop || start >= len {
+        return None;
+    }
+    Some((start as usize, stop as usize))
+}
+
+// HSCAN/SSCAN 共用的参数：cursor [MATCH pattern] [COUNT count]
+#[derive(Debug, PartialEq, Eq)]
+struct ScanArgs {
+    cursor: usize,
+    pattern: Option<Vec<u8>>,
+    count: usize,
+}
+
+impl ScanArgs {
+    // 与 redis 一致，默认每次返回 10 个元素
+    const DEFAULT_COUNT: usize = 10;
+
+    fn parse(args: impl IntoIterator<Item = RespFrame>) -> Result<Self, CommandError> {
+        let mut args = args.into_iter();
+        let cursor = match args.next() {
+            Some(RespFrame::BulkString(cursor)) => std::str::from_utf8(&cursor)
+                .ok()
+                .and_then(|c| c.parse().ok())
+                .ok_or_else(|| CommandError::InvalidArguments("invalid cursor".to_string()))?,
+            _ => return Err(CommandError::InvalidArguments("invalid cursor".to_string())),
+        };
+        let mut scan = Self {
+            cursor,
+            pattern: None,
+            count: Self::DEFAULT_COUNT,
+        };
+        let syntax_error = || CommandError::InvalidArguments("syntax error".to_string());
+        while let Some(option) = args.next() {
+            let RespFrame::BulkString(option) = option else {
+                return Err(syntax_error());
+            };
+            match (option.to_ascii_lowercase().as_slice(), args.next()) {
+                (b"match", Some(RespFrame::BulkString(pattern))) => {
+                    scan.pattern = Some(pattern.0.into())
+                }
+                (b"count", Some(count)) => match parse_integer(count)? {
+                    count if count >= 1 => scan.count = count as usize,
+                    _ => return Err(syntax_error()),
+                },
+                _ => return Err(syntax_error()),
+            }
+        }
+        Ok(scan)
+    }
+
+    // cursor 是排好序的快照中的下标，遍历期间没有被修改的元素恰好返回一次。
+    // 与 redis 一致，MATCH 在取出 count 个元素之后过滤，所以一页可能少于 count 个甚至为空。
+    // 返回下一个 cursor，0 表示遍历结束
+    fn page<T>(&self, items: Vec<T>, name: impl Fn(&T) -> &[u8]) -> (usize, Vec<T>) {
+        let end = self.cursor.saturating_add(self.count).min(items.len());
+        let next = if end >= items.len() { 0 } else { end };
+        let page = items
+            .into_iter()
+            .skip(self.cursor)
+            .take(end.saturating_sub(self.cursor))
+            .filter(|item| {
+                self.pattern
+                    .as_ref()
+                    .is_none_or(|pattern| glob_match(pattern, name(item)))
+            })
+            .collect();
+        (next, page)
+    }
+
+    // [next_cursor, [items...]]
+    fn reply(next: usize, items: Vec<RespFrame>) -> RespFrame {
+        RespArray::new(vec![
+            BulkString::new(next.to_string()).into(),
+            RespArray::new(items).into(),
+        ])
+        .into()
+    }
+}
+
 pub fn extract_args(frames: RespArray, start: usize) -> Result<Vec<RespFrame>, CommandError> {
     Ok(frames.0.into_iter().skip(start).collect::<Vec<RespFrame>>())
 }
@@ -140,7 +487,47 @@ mod tests {
     use bytes::BytesMut;
 
     #[test]
-    fn test_command() -> Result<()> {
+    fn test_normalize_range() {
+        assert_eq!(normalize_range(0, -1, 5), Some((0, 4)));
+        assert_eq!(normalize_range(1, 2, 5), Some((1, 2)));
+        assert_eq!(normalize_range(-3, -2, 5), Some((2, 3)));
+        assert_eq!(normalize_range(-100, 100, 5), Some((0, 4)));
+        assert_eq!(normalize_range(3, 1, 5), None);
+        assert_eq!(normalize_range(5, 10, 5), None);
+        assert_eq!(normalize_range(0, -1, 0), None);
+    }
+
+    #[test]
+    fn test_command_table_is_dispatched() {
+        for spec in COMMAND_TABLE {
+            let array = RespArray::new(vec![spec.name.as_bytes().into()]);
+            if let Err(CommandError::InvalidCommand(msg)) = dispatch(spec.name, array) {
+                assert!(
+                    !msg.starts_with("unknown command"),
+                    "{} is not dispatched",
+                    spec.name
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_command_arity_from_table() {
+        let array = RespArray::new(vec![b"GET".into()]);
+        let Err(CommandError::InvalidArguments(msg)) = Command::try_from(array) else {
+            panic!("expected an arity error");
+        };
+        assert_eq!(msg, "wrong number of arguments for 'get' command");
+
+        let array = RespArray::new(vec![b"nosuchcommand".into()]);
+        assert!(matches!(
+            Command::try_from(array),
+            Err(CommandError::InvalidCommand(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_command() -> Result<()> {
         let mut buf = BytesMut::new();
         buf.extend_from_slice(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n");
 
@@ -150,7 +537,7 @@ mod tests {
 
         let backend = Backend::new();
 
-        let ret = cmd.execute(&backend);
+        let ret = cmd.execute(&backend).await;
         assert_eq!(ret, RespFrame::Null(RespNull));
 
         Ok(())
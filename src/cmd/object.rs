@@ -0,0 +1,258 @@
+use crate::{Backend, BulkString, KeyType, RespArray, RespFrame, SimpleError};
+
+use super::{extract_args, validate_command, CommandError, CommandExecutor};
+
+// 与 redis 的 OBJ_ENCODING_EMBSTR_SIZE_LIMIT 保持一致
+const EMBSTR_SIZE_LIMIT: usize = 44;
+const LISTPACK_MAX_ENTRIES: usize = 128;
+
+// object encoding|refcount|idletime|freq key
+// "*3\r\n$6\r\nobject\r\n$8\r\nencoding\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct Object {
+    subcommand: ObjectSubcommand,
+    key: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ObjectSubcommand {
+    Encoding,
+    RefCount,
+    IdleTime,
+    Freq,
+}
+
+impl CommandExecutor for Object {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        match self.subcommand {
+            ObjectSubcommand::Encoding => match encoding(backend, &self.key) {
+                Some(encoding) => BulkString::new(encoding).into(),
+                None => SimpleError::new("ERR no such key").into(),
+            },
+            // 值不会在 key 之间共享，引用计数总是 1
+            ObjectSubcommand::RefCount if backend.exists(&self.key) => RespFrame::Integer(1),
+            ObjectSubcommand::IdleTime => match backend.idle_seconds(&self.key) {
+                Some(idle) => RespFrame::Integer(idle as i64),
+                None => SimpleError::new("ERR no such key").into(),
+            },
+            ObjectSubcommand::Freq => match backend.access_frequency(&self.key) {
+                Some(freq) => RespFrame::Integer(freq as i64),
+                None => SimpleError::new("ERR no such key").into(),
+            },
+            _ => SimpleError::new("ERR no such key").into(),
+        }
+    }
+}
+
+// 根据值的类型推断 redis 中对应的编码，只是近似值
+pub(super) fn encoding(backend: &Backend, key: &str) -> Option<&'static str> {
+    let encoding = match backend.key_type(key)? {
+        KeyType::String => match backend.map.get(key)?.value() {
+            RespFrame::Integer(_) => "int",
+            RespFrame::BulkString(s) => {
+                if std::str::from_utf8(s)
+                    .ok()
+                    .and_then(|s| s.parse::<i64>().ok())
+                    .is_some()
+                {
+                    "int"
+                } else if s.len() <= EMBSTR_SIZE_LIMIT {
+                    "embstr"
+                } else {
+                    "raw"
+                }
+            }
+            _ => "raw",
+        },
+        KeyType::Hash | KeyType::Set => "hashtable",
+        KeyType::List => {
+            if backend.list.get(key)?.len() <= LISTPACK_MAX_ENTRIES {
+                "listpack"
+            } else {
+                "quicklist"
+            }
+        }
+        KeyType::ZSet => {
+            if backend.zset.get(key)?.len() <= LISTPACK_MAX_ENTRIES {
+                "listpack"
+            } else {
+                "skiplist"
+            }
+        }
+        KeyType::Stream => "stream",
+    };
+    Some(encoding)
+}
+
+impl TryFrom<RespArray> for Object {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (subcommand, name) = match arr.get(1) {
+            Some(RespFrame::BulkString(sub)) => match sub.to_ascii_lowercase().as_slice() {
+                b"encoding" => (ObjectSubcommand::Encoding, "encoding"),
+                b"refcount" => (ObjectSubcommand::RefCount, "refcount"),
+                b"idletime" => (ObjectSubcommand::IdleTime, "idletime"),
+                b"freq" => (ObjectSubcommand::Freq, "freq"),
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "Unknown OBJECT subcommand: {}",
+                        String::from_utf8_lossy(sub)
+                    )))
+                }
+            },
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "OBJECT command must have a subcommand".to_string(),
+                ))
+            }
+        };
+        validate_command(&arr, &["object", name], 1)?;
+
+        let mut args = extract_args(arr, 2)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        Ok(Self { subcommand, key })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecoder;
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn object_encoding(key: &str) -> Object {
+        Object {
+            subcommand: ObjectSubcommand::Encoding,
+            key: key.to_string(),
+        }
+    }
+
+    fn object(subcommand: ObjectSubcommand, key: &str) -> Object {
+        Object {
+            subcommand,
+            key: key.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_object_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$6\r\nobject\r\n$8\r\nENCODING\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Object::try_from(frame)?;
+        assert_eq!(cmd.subcommand, ObjectSubcommand::Encoding);
+        assert_eq!(cmd.key, "hello");
+
+        let mut buf = BytesMut::from("*3\r\n$6\r\nobject\r\n$8\r\nrefcount\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            Object::try_from(frame)?.subcommand,
+            ObjectSubcommand::RefCount
+        );
+
+        let mut buf = BytesMut::from("*3\r\n$6\r\nobject\r\n$3\r\nfoo\r\n$5\r\nhello\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Object::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_encoding_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("int".to_string(), RespFrame::BulkString(b"12345".into()));
+        backend.set("str".to_string(), RespFrame::BulkString(b"hello".into()));
+        backend.set(
+            "raw".to_string(),
+            RespFrame::BulkString([b'a'; 64][..].into()),
+        );
+        backend.hset("hash".to_string(), "f".to_string(), b"v".into());
+
+        let cases = [
+            ("int", "int"),
+            ("str", "embstr"),
+            ("raw", "raw"),
+            ("hash", "hashtable"),
+        ];
+        for (key, expected) in cases {
+            assert_eq!(
+                object_encoding(key).execute(&backend).await,
+                BulkString::new(expected).into(),
+                "{}",
+                key
+            );
+        }
+
+        assert_eq!(
+            object_encoding("missing").execute(&backend).await,
+            SimpleError::new("ERR no such key").into()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_refcount_and_idletime() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("str".to_string(), b"hello".into());
+        backend.sadd("set".to_string(), b"a".into());
+
+        for key in ["str", "set"] {
+            let ret = object(ObjectSubcommand::RefCount, key)
+                .execute(&backend)
+                .await;
+            assert_eq!(ret, RespFrame::Integer(1));
+
+            let ret = object(ObjectSubcommand::IdleTime, key)
+                .execute(&backend)
+                .await;
+            assert!(matches!(ret, RespFrame::Integer(idle) if idle >= 0));
+        }
+
+        for subcommand in [ObjectSubcommand::RefCount, ObjectSubcommand::IdleTime] {
+            assert_eq!(
+                object(subcommand, "missing").execute(&backend).await,
+                SimpleError::new("ERR no such key").into()
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_object_freq() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$6\r\nobject\r\n$4\r\nFREQ\r\n$5\r\nhello\r\n");
+        let cmd = Object::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.subcommand, ObjectSubcommand::Freq);
+
+        let backend = Backend::new();
+        backend.set("hello".to_string(), b"world".into());
+        let mut last = 0;
+        for _ in 0..3 {
+            backend.touch("hello");
+            let RespFrame::Integer(freq) = object(ObjectSubcommand::Freq, "hello")
+                .execute(&backend)
+                .await
+            else {
+                panic!("expected an integer");
+            };
+            assert!(freq > last);
+            last = freq;
+        }
+
+        assert_eq!(
+            object(ObjectSubcommand::Freq, "missing")
+                .execute(&backend)
+                .await,
+            SimpleError::new("ERR no such key").into()
+        );
+        Ok(())
+    }
+}
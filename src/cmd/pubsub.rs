@@ -0,0 +1,547 @@
+use crate::{
+    Backend, PubSubMessage, RespArray, RespFrame, RespNull, RespPush, Session, SimpleError,
+};
+
+use super::{extract_args, validate_command, CommandError, CommandExecutor};
+
+// subscribe channel [channel ...]
+// "*3\r\n$9\r\nsubscribe\r\n$3\r\nch1\r\n$3\r\nch2\r\n"
+#[derive(Debug)]
+pub struct Subscribe {
+    channels: Vec<String>,
+}
+
+// publish channel message
+// "*3\r\n$7\r\npublish\r\n$4\r\nnews\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct Publish {
+    channel: String,
+    message: Vec<u8>,
+}
+
+// unsubscribe [channel ...]
+// "*2\r\n$11\r\nunsubscribe\r\n$3\r\nch1\r\n"
+#[derive(Debug)]
+pub struct Unsubscribe {
+    channels: Vec<String>,
+}
+
+// psubscribe pattern [pattern ...]
+// "*2\r\n$10\r\npsubscribe\r\n$6\r\nnews.*\r\n"
+#[derive(Debug)]
+pub struct PSubscribe {
+    patterns: Vec<String>,
+}
+
+// punsubscribe [pattern ...]
+// "*2\r\n$12\r\npunsubscribe\r\n$6\r\nnews.*\r\n"
+#[derive(Debug)]
+pub struct PUnsubscribe {
+    patterns: Vec<String>,
+}
+
+// pubsub channels [pattern]
+// pubsub numsub [channel ...]
+// pubsub numpat
+// "*3\r\n$6\r\npubsub\r\n$6\r\nnumsub\r\n$4\r\nnews\r\n"
+#[derive(Debug)]
+pub struct PubSubIntrospect {
+    subcommand: PubSubSubcommand,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PubSubSubcommand {
+    Channels(Option<Vec<u8>>),
+    NumSub(Vec<String>),
+    NumPat,
+}
+
+impl CommandExecutor for Subscribe {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR SUBSCRIBE is not allowed without a connection").into()
+    }
+
+    // 每个频道回复一个 [subscribe, channel, count]
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let replies = self
+            .channels
+            .iter()
+            .map(|channel| {
+                let count = session.subscribe(channel.clone());
+                subscription_frame("subscribe", Some(channel), count)
+            })
+            .collect();
+        reply_all(session, replies)
+    }
+}
+
+impl CommandExecutor for Unsubscribe {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR UNSUBSCRIBE is not allowed without a connection").into()
+    }
+
+    // 不带参数时退订所有频道，没有订阅任何频道时回复 [unsubscribe, nil, 0]
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let channels = if self.channels.is_empty() {
+            session.channels()
+        } else {
+            self.channels.clone()
+        };
+        if channels.is_empty() {
+            return subscription_frame("unsubscribe", None, session.subscription_count());
+        }
+        let replies = channels
+            .iter()
+            .map(|channel| {
+                let count = session.unsubscribe(channel);
+                subscription_frame("unsubscribe", Some(channel), count)
+            })
+            .collect();
+        reply_all(session, replies)
+    }
+}
+
+impl CommandExecutor for PSubscribe {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR PSUBSCRIBE is not allowed without a connection").into()
+    }
+
+    // 每个模式回复一个 [psubscribe, pattern, count]
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let replies = self
+            .patterns
+            .iter()
+            .map(|pattern| {
+                let count = session.psubscribe(pattern.clone());
+                subscription_frame("psubscribe", Some(pattern), count)
+            })
+            .collect();
+        reply_all(session, replies)
+    }
+}
+
+impl CommandExecutor for PUnsubscribe {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR PUNSUBSCRIBE is not allowed without a connection").into()
+    }
+
+    // 不带参数时退订所有模式，频道订阅不受影响
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let patterns = if self.patterns.is_empty() {
+            session.patterns()
+        } else {
+            self.patterns.clone()
+        };
+        if patterns.is_empty() {
+            return subscription_frame("punsubscribe", None, session.subscription_count());
+        }
+        let replies = patterns
+            .iter()
+            .map(|pattern| {
+                let count = session.punsubscribe(pattern);
+                subscription_frame("punsubscribe", Some(pattern), count)
+            })
+            .collect();
+        reply_all(session, replies)
+    }
+}
+
+impl CommandExecutor for Publish {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR PUBLISH is not allowed without a connection").into()
+    }
+
+    // 返回收到消息的客户端数量，订阅者消费过慢时不会阻塞发布者
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let count = session
+            .server()
+            .pubsub()
+            .publish(&self.channel, self.message.clone());
+        RespFrame::Integer(count as i64)
+    }
+}
+
+impl CommandExecutor for PubSubIntrospect {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR PUBSUB is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let pubsub = session.server().pubsub();
+        match &self.subcommand {
+            PubSubSubcommand::Channels(pattern) => RespArray::new(
+                pubsub
+                    .active_channels(pattern.as_deref())
+                    .into_iter()
+                    .map(|channel| channel.as_bytes().into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            PubSubSubcommand::NumSub(channels) => RespArray::new(
+                channels
+                    .iter()
+                    .flat_map(|channel| {
+                        let count = pubsub.num_subscribers(channel) as i64;
+                        [channel.as_bytes().into(), RespFrame::Integer(count)]
+                    })
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into(),
+            PubSubSubcommand::NumPat => RespFrame::Integer(pubsub.num_patterns() as i64),
+        }
+    }
+}
+
+fn subscription_frame(kind: &str, channel: Option<&str>, count: usize) -> RespFrame {
+    let channel = match channel {
+        Some(channel) => channel.as_bytes().into(),
+        None => RespFrame::Null(RespNull),
+    };
+    // 与 redis 一致，RESP3 下订阅和退订的确认也是 push
+    RespPush::new(vec![
+        kind.as_bytes().into(),
+        channel,
+        RespFrame::Integer(count as i64),
+    ])
+    .into()
+}
+
+// 除最后一个以外的回复先放入 session，由 process_stream 依次发送
+fn reply_all(session: &mut Session, mut replies: Vec<RespFrame>) -> RespFrame {
+    let last = replies.pop().expect("at least one reply");
+    for reply in replies {
+        session.push_reply(reply);
+    }
+    last
+}
+
+// 推送给订阅者的消息：[message, channel, payload]，
+// 通过模式订阅收到时为 [pmessage, pattern, channel, payload]
+pub(crate) fn message_frame(message: PubSubMessage) -> RespFrame {
+    let mut frames: Vec<RespFrame> = match message.pattern {
+        Some(pattern) => vec![b"pmessage".into(), pattern.as_bytes().into()],
+        None => vec![b"message".into()],
+    };
+    frames.push(message.channel.as_bytes().into());
+    frames.push(message.payload.as_slice().into());
+    RespPush::new(frames).into()
+}
+
+fn extract_channels(arr: RespArray) -> Result<Vec<String>, CommandError> {
+    extract_args(arr, 1)?
+        .into_iter()
+        .map(|arg| match arg {
+            RespFrame::BulkString(channel) => Ok(String::from_utf8(channel.0.into())?),
+            _ => Err(CommandError::InvalidArguments(
+                "Invalid channel".to_string(),
+            )),
+        })
+        .collect()
+}
+
+impl TryFrom<RespArray> for Subscribe {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        validate_command(&arr, &["subscribe"], len.max(2) - 1)?;
+        let channels = extract_channels(arr)?;
+        Ok(Self { channels })
+    }
+}
+
+impl TryFrom<RespArray> for PSubscribe {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        validate_command(&arr, &["psubscribe"], len.max(2) - 1)?;
+        let patterns = extract_channels(arr)?;
+        Ok(Self { patterns })
+    }
+}
+
+impl TryFrom<RespArray> for PUnsubscribe {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        validate_command(&arr, &["punsubscribe"], len - 1)?;
+        let patterns = extract_channels(arr)?;
+        Ok(Self { patterns })
+    }
+}
+
+impl TryFrom<RespArray> for Publish {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["publish"], 2)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        match (args.next(), args.next()) {
+            (Some(RespFrame::BulkString(channel)), Some(RespFrame::BulkString(message))) => {
+                Ok(Self {
+                    channel: String::from_utf8(channel.0.into())?,
+                    message: message.0.into(),
+                })
+            }
+            _ => Err(CommandError::InvalidArguments(
+                "Invalid channel or message".to_string(),
+            )),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for PubSubIntrospect {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        let name = match arr.get(1) {
+            Some(RespFrame::BulkString(sub)) => match sub.to_ascii_lowercase().as_slice() {
+                b"channels" if len <= 3 => "channels",
+                b"numsub" => "numsub",
+                b"numpat" if len == 2 => "numpat",
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "unknown subcommand or wrong number of arguments for '{}'",
+                        String::from_utf8_lossy(sub)
+                    )))
+                }
+            },
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "PUBSUB command must have a subcommand".to_string(),
+                ))
+            }
+        };
+        validate_command(&arr, &["pubsub", name], len - 2)?;
+
+        let mut args = extract_args(arr, 2)?.into_iter();
+        let subcommand = match name {
+            "channels" => match args.next() {
+                Some(RespFrame::BulkString(pattern)) => {
+                    PubSubSubcommand::Channels(Some(pattern.0.into()))
+                }
+                None => PubSubSubcommand::Channels(None),
+                _ => {
+                    return Err(CommandError::InvalidArguments(
+                        "Invalid pattern".to_string(),
+                    ))
+                }
+            },
+            "numsub" => {
+                let channels = args
+                    .map(|arg| match arg {
+                        RespFrame::BulkString(channel) => Ok(String::from_utf8(channel.0.into())?),
+                        _ => Err(CommandError::InvalidArguments(
+                            "Invalid channel".to_string(),
+                        )),
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                PubSubSubcommand::NumSub(channels)
+            }
+            _ => PubSubSubcommand::NumPat,
+        };
+
+        Ok(Self { subcommand })
+    }
+}
+
+impl TryFrom<RespArray> for Unsubscribe {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        validate_command(&arr, &["unsubscribe"], len - 1)?;
+        let channels = extract_channels(arr)?;
+        Ok(Self { channels })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespDecoder, Server};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    #[test]
+    fn test_subscribe_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$9\r\nsubscribe\r\n$3\r\nch1\r\n$3\r\nch2\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Subscribe::try_from(frame)?;
+        assert_eq!(cmd.channels, ["ch1", "ch2"]);
+
+        let mut buf = BytesMut::from("*1\r\n$11\r\nunsubscribe\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Unsubscribe::try_from(frame)?.channels.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_and_unsubscribe() -> Result<()> {
+        let mut session = Session::new(Server::default());
+        let cmd = Subscribe {
+            channels: vec!["ch1".to_string(), "ch2".to_string()],
+        };
+        let ret = cmd.execute_with(&mut session).await;
+        assert_eq!(ret, subscription_frame("subscribe", Some("ch2"), 2));
+        assert_eq!(
+            session.take_replies(),
+            [subscription_frame("subscribe", Some("ch1"), 1)]
+        );
+
+        let cmd = Unsubscribe {
+            channels: vec!["ch1".to_string()],
+        };
+        let ret = cmd.execute_with(&mut session).await;
+        assert_eq!(ret, subscription_frame("unsubscribe", Some("ch1"), 1));
+
+        let cmd = Unsubscribe { channels: vec![] };
+        let ret = cmd.execute_with(&mut session).await;
+        assert_eq!(ret, subscription_frame("unsubscribe", Some("ch2"), 0));
+        assert!(session.take_replies().is_empty());
+
+        let ret = cmd.execute_with(&mut session).await;
+        assert_eq!(ret, subscription_frame("unsubscribe", None, 0));
+        assert_eq!(session.server().pubsub().num_channels(), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_publish_does_not_wait_for_slow_subscribers() -> Result<()> {
+        let server = Server::default();
+        let mut subscriber = Session::new(server.clone());
+        let mut publisher = Session::new(server);
+        subscriber.subscribe("news".to_string());
+
+        for i in 0..2000 {
+            let cmd = Publish {
+                channel: "news".to_string(),
+                message: i.to_string().into_bytes(),
+            };
+            assert_eq!(
+                cmd.execute_with(&mut publisher).await,
+                RespFrame::Integer(1)
+            );
+        }
+        let cmd = Publish {
+            channel: "nobody".to_string(),
+            message: b"hello".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute_with(&mut publisher).await,
+            RespFrame::Integer(0)
+        );
+
+        // 最早的消息已经被丢弃，订阅者从仍然保留的消息继续接收
+        let message = subscriber.next_message().await;
+        assert_eq!(message.channel, "news");
+        assert_ne!(message.payload, b"0");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pubsub_introspection() -> Result<()> {
+        let server = Server::default();
+        let mut first = Session::new(server.clone());
+        let mut second = Session::new(server.clone());
+        first.subscribe("news.sports".to_string());
+        first.subscribe("weather".to_string());
+        second.subscribe("news.sports".to_string());
+
+        let run = |subcommand| PubSubIntrospect { subcommand };
+        let ret = run(PubSubSubcommand::Channels(None))
+            .execute_with(&mut first)
+            .await;
+        assert_eq!(
+            ret,
+            RespArray::new(vec![b"news.sports".into(), b"weather".into()]).into()
+        );
+        let ret = run(PubSubSubcommand::Channels(Some(b"news.*".to_vec())))
+            .execute_with(&mut first)
+            .await;
+        assert_eq!(ret, RespArray::new(vec![b"news.sports".into()]).into());
+
+        let ret = run(PubSubSubcommand::NumSub(vec![
+            "news.sports".to_string(),
+            "missing".to_string(),
+        ]))
+        .execute_with(&mut first)
+        .await;
+        assert_eq!(
+            ret,
+            RespArray::new(vec![
+                b"news.sports".into(),
+                RespFrame::Integer(2),
+                b"missing".into(),
+                RespFrame::Integer(0),
+            ])
+            .into()
+        );
+
+        let ret = run(PubSubSubcommand::NumPat).execute_with(&mut first).await;
+        assert_eq!(ret, RespFrame::Integer(0));
+
+        drop(second);
+        assert_eq!(server.pubsub().num_subscribers("news.sports"), 1);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_psubscribe_receives_matching_channels() -> Result<()> {
+        let server = Server::default();
+        let mut subscriber = Session::new(server.clone());
+        let mut publisher = Session::new(server);
+
+        let cmd = PSubscribe {
+            patterns: vec!["news.*".to_string()],
+        };
+        let ret = cmd.execute_with(&mut subscriber).await;
+        assert_eq!(ret, subscription_frame("psubscribe", Some("news.*"), 1));
+        assert_eq!(subscriber.subscription_count(), 1);
+        assert_eq!(subscriber.server().pubsub().num_patterns(), 1);
+
+        let cmd = Publish {
+            channel: "news.sports".to_string(),
+            message: b"goal".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute_with(&mut publisher).await,
+            RespFrame::Integer(1)
+        );
+        let message = subscriber.next_message().await;
+        assert_eq!(
+            message_frame(message),
+            RespPush::new(vec![
+                b"pmessage".into(),
+                b"news.*".into(),
+                b"news.sports".into(),
+                b"goal".into(),
+            ])
+            .into()
+        );
+
+        let cmd = Publish {
+            channel: "weather".to_string(),
+            message: b"rain".to_vec(),
+        };
+        assert_eq!(
+            cmd.execute_with(&mut publisher).await,
+            RespFrame::Integer(0)
+        );
+
+        let cmd = PUnsubscribe { patterns: vec![] };
+        let ret = cmd.execute_with(&mut subscriber).await;
+        assert_eq!(ret, subscription_frame("punsubscribe", Some("news.*"), 0));
+        assert_eq!(subscriber.server().pubsub().num_patterns(), 0);
+        Ok(())
+    }
+}
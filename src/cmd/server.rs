@@ -0,0 +1,712 @@
+use std::{
+    fmt::Write as _,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use tracing::info;
+
+use crate::{
+    glob_match, Backend, BulkString, NotifyFlags, RespArray, RespFrame, RespMap, RespVersion,
+    Session, SimpleError, SlowLogEntry, VerbatimString,
+};
+
+use super::{
+    extract_args, lookup_command, parse_integer, validate_command, CommandError, CommandExecutor,
+    RESP_OK,
+};
+
+const SECTIONS: [&str; 5] = ["server", "clients", "memory", "stats", "keyspace"];
+// 与 redis 一致，commandstats 不在默认输出中
+const ALL_SECTIONS: [&str; 6] = [
+    "server",
+    "clients",
+    "memory",
+    "stats",
+    "commandstats",
+    "keyspace",
+];
+
+// info [section]
+// "*2\r\n$4\r\ninfo\r\n$8\r\nkeyspace\r\n"
+#[derive(Debug)]
+pub struct Info {
+    section: Option<String>,
+}
+
+// monitor
+// "*1\r\n$7\r\nmonitor\r\n"
+#[derive(Debug)]
+pub struct Monitor;
+
+impl CommandExecutor for Monitor {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR MONITOR is not allowed without a connection").into()
+    }
+
+    // 之后由 process_stream 把其他客户端执行的命令推送给当前连接
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        session.start_monitor();
+        RESP_OK.clone()
+    }
+}
+
+// 按 redis 的格式生成 MONITOR 输出：<timestamp> [db addr] "cmd" "arg" ...
+// admin 命令（DEBUG、CLIENT、MONITOR 等）不输出
+pub(crate) fn monitor_line(session: &Session, array: &RespArray) -> Option<String> {
+    let Some(RespFrame::BulkString(name)) = array.first() else {
+        return None;
+    };
+    let spec = lookup_command(name)?;
+    if spec.flags.contains(&"admin") {
+        return None;
+    }
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let addr = session
+        .addr()
+        .map(|addr| addr.to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+    let mut line = format!(
+        "{}.{:06} [{} {}]",
+        now.as_secs(),
+        now.subsec_micros(),
+        session.db(),
+        addr
+    );
+    for arg in array.iter() {
+        line.push(' ');
+        match arg {
+            RespFrame::BulkString(arg) => quote_arg(&mut line, arg),
+            other => quote_arg(&mut line, format!("{:?}", other).as_bytes()),
+        }
+    }
+    Some(line)
+}
+
+// 与 redis 的 sdscatrepr 相同的转义规则
+fn quote_arg(out: &mut String, arg: &[u8]) {
+    out.push('"');
+    for &b in arg {
+        match b {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\\""),
+            b'\n' => out.push_str("\\n"),
+            b'\r' => out.push_str("\\r"),
+            b'\t' => out.push_str("\\t"),
+            0x07 => out.push_str("\\a"),
+            0x08 => out.push_str("\\b"),
+            b if b.is_ascii_graphic() || b == b' ' => out.push(b as char),
+            b => {
+                let _ = write!(out, "\\x{:02x}", b);
+            }
+        }
+    }
+    out.push('"');
+}
+
+// shutdown [nosave|save]
+// "*2\r\n$8\r\nshutdown\r\n$6\r\nnosave\r\n"
+#[derive(Debug)]
+pub struct Shutdown {
+    save: bool,
+}
+
+impl CommandExecutor for Shutdown {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR SHUTDOWN is not allowed without a connection").into()
+    }
+
+    // 还没有持久化，SAVE 与 NOSAVE 行为相同
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        info!(
+            client = session.id(),
+            save = self.save,
+            "SHUTDOWN requested"
+        );
+        session.server().shutdown();
+        RESP_OK.clone()
+    }
+}
+
+// time
+// "*1\r\n$4\r\ntime\r\n"
+#[derive(Debug)]
+pub struct Time;
+
+// lolwut [VERSION version]
+// "*1\r\n$6\r\nlolwut\r\n"
+// 不画图，只返回版本号，参数都会被忽略。与 redis 一致以 verbatim string 返回，RESP2 下降级为 BulkString
+#[derive(Debug)]
+pub struct Lolwut;
+
+impl CommandExecutor for Lolwut {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        VerbatimString::text(format!("simple-redis ver. {}\n", env!("CARGO_PKG_VERSION"))).into()
+    }
+}
+
+impl CommandExecutor for Time {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        RespArray::new(vec![
+            BulkString::new(now.as_secs().to_string()).into(),
+            BulkString::new(now.subsec_micros().to_string()).into(),
+        ])
+        .into()
+    }
+}
+
+// slowlog get [count]|len|reset
+// "*3\r\n$7\r\nslowlog\r\n$3\r\nget\r\n$1\r\n5\r\n"
+#[derive(Debug)]
+pub struct Slowlog {
+    subcommand: SlowlogSubcommand,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum SlowlogSubcommand {
+    // None 表示返回全部记录
+    Get(Option<usize>),
+    Len,
+    Reset,
+}
+
+impl CommandExecutor for Slowlog {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR SLOWLOG is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let slowlog = &session.server().slowlog;
+        match self.subcommand {
+            SlowlogSubcommand::Get(count) => RespArray::new(
+                slowlog
+                    .get(count.unwrap_or(usize::MAX))
+                    .iter()
+                    .map(slowlog_entry)
+                    .collect::<Vec<_>>(),
+            )
+            .into(),
+            SlowlogSubcommand::Len => RespFrame::Integer(slowlog.len() as i64),
+            SlowlogSubcommand::Reset => {
+                slowlog.reset();
+                RESP_OK.clone()
+            }
+        }
+    }
+}
+
+// config get parameter | config set parameter value
+// "*3\r\n$6\r\nconfig\r\n$3\r\nget\r\n$22\r\nnotify-keyspace-events\r\n"
+// 目前只有 notify-keyspace-events 可以在运行时修改
+#[derive(Debug)]
+pub struct Config {
+    subcommand: ConfigSubcommand,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ConfigSubcommand {
+    // glob 模式
+    Get(Vec<u8>),
+    Set(String, String),
+}
+
+impl CommandExecutor for Config {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR CONFIG is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let server = session.server();
+        match &self.subcommand {
+            ConfigSubcommand::Get(pattern) => {
+                let config = server.config();
+                let params = [
+                    ("notify-keyspace-events", server.notify_flags().to_string()),
+                    (
+                        "slowlog-log-slower-than",
+                        config.slowlog_log_slower_than.to_string(),
+                    ),
+                    ("slowlog-max-len", config.slowlog_max_len.to_string()),
+                ]
+                .into_iter()
+                .filter(|(name, _)| glob_match(pattern, name.as_bytes()));
+
+                if session.protocol() == RespVersion::Resp3 {
+                    let mut map = RespMap::new();
+                    for (name, value) in params {
+                        map.insert(BulkString::new(name).into(), BulkString::new(value).into());
+                    }
+                    return map.into();
+                }
+                let frames = params
+                    .flat_map(|(name, value)| {
+                        [BulkString::new(name).into(), BulkString::new(value).into()]
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(frames).into()
+            }
+            ConfigSubcommand::Set(name, value) => match name.as_str() {
+                "notify-keyspace-events" => match NotifyFlags::parse(value) {
+                    Some(flags) => {
+                        server.set_notify_flags(flags);
+                        RESP_OK.clone()
+                    }
+                    None => SimpleError::new(format!(
+                        "ERR Invalid argument '{}' for CONFIG SET '{}'",
+                        value, name
+                    ))
+                    .into(),
+                },
+                _ => SimpleError::new(format!(
+                    "ERR Unknown option or number of arguments for CONFIG SET - '{}'",
+                    name
+                ))
+                .into(),
+            },
+        }
+    }
+}
+
+// [id, timestamp, duration(us), [arg ...], client addr, client name]
+fn slowlog_entry(entry: &SlowLogEntry) -> RespFrame {
+    let args = entry
+        .args
+        .iter()
+        .map(|arg| BulkString::new(arg.clone()).into())
+        .collect::<Vec<RespFrame>>();
+    RespArray::new(vec![
+        RespFrame::Integer(entry.id as i64),
+        RespFrame::Integer(entry.timestamp as i64),
+        RespFrame::Integer(entry.duration.as_micros() as i64),
+        RespArray::new(args).into(),
+        BulkString::new(entry.client_addr.clone()).into(),
+        BulkString::new(entry.client_name.clone()).into(),
+    ])
+    .into()
+}
+
+impl CommandExecutor for Info {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR INFO is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        let sections: Vec<&str> = match self.section.as_deref() {
+            None | Some("default") => SECTIONS.to_vec(),
+            Some("all" | "everything") => ALL_SECTIONS.to_vec(),
+            Some(section) => ALL_SECTIONS.into_iter().filter(|s| *s == section).collect(),
+        };
+        let body = sections
+            .into_iter()
+            .map(|section| info_section(section, session))
+            .collect::<Vec<_>>()
+            .join("\r\n");
+        BulkString::new(body).into()
+    }
+}
+
+// 每个 section 以 "# Name" 开头，每行一个 key:value，section 之间用空行分隔
+fn info_section(section: &str, session: &Session) -> String {
+    let server = session.server();
+    let stats = &server.stats;
+    let mut out = String::new();
+    match section {
+        "server" => {
+            let uptime = stats.uptime_in_seconds();
+            out.push_str("# Server\r\n");
+            let _ = write!(out, "redis_version:{}\r\n", env!("CARGO_PKG_VERSION"));
+            out.push_str("redis_mode:standalone\r\n");
+            let _ = write!(out, "process_id:{}\r\n", std::process::id());
+            let _ = write!(out, "uptime_in_seconds:{}\r\n", uptime);
+            let _ = write!(out, "uptime_in_days:{}\r\n", uptime / 86400);
+        }
+        "clients" => {
+            out.push_str("# Clients\r\n");
+            let _ = write!(out, "connected_clients:{}\r\n", stats.connected_clients());
+        }
+        "memory" => {
+            out.push_str("# Memory\r\n");
+            out.push_str("maxmemory:0\r\n");
+            out.push_str("maxmemory_policy:noeviction\r\n");
+        }
+        "stats" => {
+            let databases = server.databases();
+            let hits: u64 = databases.iter().map(|db| db.keyspace_hits()).sum();
+            let misses: u64 = databases.iter().map(|db| db.keyspace_misses()).sum();
+            out.push_str("# Stats\r\n");
+            let _ = write!(
+                out,
+                "total_connections_received:{}\r\n",
+                stats.total_connections_received()
+            );
+            let _ = write!(
+                out,
+                "total_commands_processed:{}\r\n",
+                stats.total_commands_processed()
+            );
+            let _ = write!(out, "keyspace_hits:{}\r\n", hits);
+            let _ = write!(out, "keyspace_misses:{}\r\n", misses);
+        }
+        "commandstats" => {
+            out.push_str("# Commandstats\r\n");
+            for (name, stat) in stats.command_stats() {
+                let _ = write!(
+                    out,
+                    "cmdstat_{}:calls={},usec={},usec_per_call={:.2},usec_min={},usec_max={}\r\n",
+                    name,
+                    stat.calls,
+                    stat.usec,
+                    stat.usec_per_call(),
+                    stat.min_usec,
+                    stat.max_usec
+                );
+            }
+        }
+        "keyspace" => {
+            out.push_str("# Keyspace\r\n");
+            for (index, db) in server.databases().iter().enumerate() {
+                if !db.is_empty() {
+                    let _ = write!(
+                        out,
+                        "db{}:keys={},expires={},avg_ttl=0\r\n",
+                        index,
+                        db.len(),
+                        db.expires_len()
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+    out
+}
+
+impl TryFrom<RespArray> for Info {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len > 2 {
+            return Err(CommandError::InvalidArguments(
+                "INFO command only supports a single section".to_string(),
+            ));
+        }
+        validate_command(&arr, &["info"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let section = match args.next() {
+            Some(RespFrame::BulkString(section)) => {
+                Some(String::from_utf8(section.0.into())?.to_ascii_lowercase())
+            }
+            Some(_) => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid section".to_string(),
+                ))
+            }
+            None => None,
+        };
+
+        Ok(Self { section })
+    }
+}
+
+impl TryFrom<RespArray> for Shutdown {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len > 2 {
+            return Err(CommandError::InvalidArguments("syntax error".to_string()));
+        }
+        validate_command(&arr, &["shutdown"], len - 1)?;
+
+        let save = match extract_args(arr, 1)?.into_iter().next() {
+            None => false,
+            Some(RespFrame::BulkString(arg)) => match arg.to_ascii_lowercase().as_slice() {
+                b"save" => true,
+                b"nosave" => false,
+                _ => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+            },
+            Some(_) => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+        };
+
+        Ok(Self { save })
+    }
+}
+
+impl TryFrom<RespArray> for Slowlog {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (name, n_args) = match arr.get(1) {
+            Some(RespFrame::BulkString(sub)) => match sub.to_ascii_lowercase().as_slice() {
+                b"get" => ("get", arr.len().min(3) - 2),
+                b"len" => ("len", 0),
+                b"reset" => ("reset", 0),
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "unknown subcommand '{}'",
+                        String::from_utf8_lossy(sub)
+                    )))
+                }
+            },
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "SLOWLOG command must have a subcommand".to_string(),
+                ))
+            }
+        };
+        validate_command(&arr, &["slowlog", name], n_args)?;
+
+        let subcommand = match name {
+            "len" => SlowlogSubcommand::Len,
+            "reset" => SlowlogSubcommand::Reset,
+            _ => match extract_args(arr, 2)?.into_iter().next() {
+                None => SlowlogSubcommand::Get(Some(10)),
+                Some(count) => match parse_integer(count)? {
+                    -1 => SlowlogSubcommand::Get(None),
+                    count if count >= 0 => SlowlogSubcommand::Get(Some(count as usize)),
+                    _ => {
+                        return Err(CommandError::InvalidArguments(
+                            "count should be greater than or equal to -1".to_string(),
+                        ))
+                    }
+                },
+            },
+        };
+
+        Ok(Self { subcommand })
+    }
+}
+
+impl TryFrom<RespArray> for Config {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let (name, n_args) = match arr.get(1) {
+            Some(RespFrame::BulkString(sub)) => match sub.to_ascii_lowercase().as_slice() {
+                b"get" => ("get", 1),
+                b"set" => ("set", 2),
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "unknown subcommand '{}'",
+                        String::from_utf8_lossy(sub)
+                    )))
+                }
+            },
+            _ => {
+                return Err(CommandError::InvalidArguments(
+                    "CONFIG command must have a subcommand".to_string(),
+                ))
+            }
+        };
+        validate_command(&arr, &["config", name], n_args)?;
+
+        let mut args = extract_args(arr, 2)?.into_iter().map(|arg| match arg {
+            RespFrame::BulkString(arg) => Ok(Vec::from(arg)),
+            _ => Err(CommandError::InvalidArguments(
+                "Invalid argument".to_string(),
+            )),
+        });
+        let param = args
+            .next()
+            .transpose()?
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        let subcommand = match name {
+            "get" => ConfigSubcommand::Get(param),
+            _ => ConfigSubcommand::Set(
+                String::from_utf8(param)?,
+                String::from_utf8(args.next().transpose()?.unwrap_or_default())?,
+            ),
+        };
+
+        Ok(Self { subcommand })
+    }
+}
+
+impl TryFrom<RespArray> for Time {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["time"], 0)?;
+        Ok(Self)
+    }
+}
+
+impl TryFrom<RespArray> for Lolwut {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        validate_command(&arr, &["lolwut"], len - 1)?;
+        Ok(Self)
+    }
+}
+
+impl TryFrom<RespArray> for Monitor {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["monitor"], 0)?;
+        Ok(Self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::{RespDecoder, Server};
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    async fn info(section: Option<&str>, session: &mut Session) -> String {
+        let cmd = Info {
+            section: section.map(|s| s.to_string()),
+        };
+        let ret = cmd.execute_with(session).await;
+        let RespFrame::BulkString(body) = ret else {
+            panic!("expected a bulk string");
+        };
+        String::from_utf8(body.0.into()).unwrap()
+    }
+
+    fn fields(body: &str) -> HashMap<&str, &str> {
+        body.lines()
+            .filter_map(|line| line.split_once(':'))
+            .collect()
+    }
+
+    #[test]
+    fn test_info_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n$4\r\ninfo\r\n$8\r\nKeySpace\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Info::try_from(frame)?;
+        assert_eq!(cmd.section.as_deref(), Some("keyspace"));
+
+        let mut buf = BytesMut::from("*1\r\n$4\r\ninfo\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Info::try_from(frame)?;
+        assert_eq!(cmd.section, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_command() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n$8\r\nshutdown\r\n$4\r\nSAVE\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(Shutdown::try_from(frame)?.save);
+
+        let frame = RespArray::new(vec![b"shutdown".into(), b"later".into()]);
+        assert!(Shutdown::try_from(frame).is_err());
+
+        let server = Server::default();
+        let mut session = Session::new(server.clone());
+        let ret = Shutdown { save: false }.execute_with(&mut session).await;
+        assert_eq!(ret, RESP_OK.clone());
+        assert!(server.shutdown_token().is_cancelled());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_time_command() -> Result<()> {
+        let mut buf = BytesMut::from("*1\r\n$4\r\nTIME\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Time::try_from(frame)?;
+
+        let RespFrame::Array(reply) = cmd.execute(&Backend::new()).await else {
+            panic!("expected an array");
+        };
+        let parts = reply
+            .iter()
+            .map(|part| match part {
+                RespFrame::BulkString(s) => Ok(std::str::from_utf8(s)?.parse::<u64>()?),
+                _ => anyhow::bail!("expected a bulk string"),
+            })
+            .collect::<Result<Vec<_>>>()?;
+        assert_eq!(parts.len(), 2);
+        let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        assert!(now.abs_diff(parts[0]) <= 1);
+        assert!(parts[1] < 1_000_000);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_monitor_line() {
+        let session = Session::new(Server::default());
+        let array = RespArray::new(vec![
+            b"SET".into(),
+            b"key".into(),
+            (&b"a \"b\"\r\n\xff"[..]).into(),
+        ]);
+        let line = monitor_line(&session, &array).unwrap();
+        let (timestamp, rest) = line.split_once(' ').unwrap();
+        assert!(timestamp.parse::<f64>().is_ok());
+        assert_eq!(rest, r#"[0 unknown] "SET" "key" "a \"b\"\r\n\xff""#);
+
+        let array = RespArray::new(vec![b"debug".into(), b"sleep".into(), b"0".into()]);
+        assert_eq!(monitor_line(&session, &array), None);
+    }
+
+    #[tokio::test]
+    async fn test_info_all_sections() {
+        let mut session = Session::new(Server::default());
+        let body = info(None, &mut session).await;
+        for header in ["# Server", "# Clients", "# Memory", "# Stats", "# Keyspace"] {
+            assert!(body.contains(header), "missing {}", header);
+        }
+
+        let fields = fields(&body);
+        assert_eq!(fields["connected_clients"], "1");
+        assert_eq!(fields["total_connections_received"], "1");
+        assert_eq!(fields["redis_mode"], "standalone");
+    }
+
+    #[tokio::test]
+    async fn test_info_keyspace_section() {
+        let mut session = Session::new(Server::default());
+        assert_eq!(info(Some("keyspace"), &mut session).await, "# Keyspace\r\n");
+
+        session.backend().set("hello".to_string(), b"world".into());
+        session.select(3);
+        session.backend().sadd("set".to_string(), b"a".into());
+        session
+            .backend()
+            .hset("map".to_string(), "f".to_string(), b"v".into());
+
+        let body = info(Some("keyspace"), &mut session).await;
+        assert_eq!(
+            body,
+            "# Keyspace\r\ndb0:keys=1,expires=0,avg_ttl=0\r\ndb3:keys=2,expires=0,avg_ttl=0\r\n"
+        );
+        assert_eq!(info(Some("nosuchsection"), &mut session).await, "");
+    }
+
+    #[tokio::test]
+    async fn test_lolwut() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$6\r\nlolwut\r\n$7\r\nVERSION\r\n$1\r\n5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = Lolwut::try_from(frame)?;
+
+        let reply = cmd.execute(&Backend::new()).await;
+        let RespFrame::VerbatimString(ref text) = reply else {
+            panic!("expected a verbatim string");
+        };
+        assert_eq!(text.format(), b"txt");
+        assert!(std::str::from_utf8(text.data())?.contains(env!("CARGO_PKG_VERSION")));
+        assert!(reply.encode_for(RespVersion::Resp2).starts_with(b"$"));
+
+        Ok(())
+    }
+}
@@ -1,6 +1,9 @@
-use crate::{Backend, RespArray, RespFrame};
+use crate::{Backend, KeyType, RespArray, RespFrame, RespNull, MAX_RANDOM_COUNT};
 
-use super::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
+use super::{
+    extract_args, parse_integer, validate_command, CommandError, CommandExecutor, ScanArgs,
+    RESP_OK, RESP_WRONGTYPE,
+};
 
 // sadd key member
 // "*3\r\n$4\r\nsadd\r\n$5\r\nmyset\r\n$3\r\none\r\n"
@@ -11,7 +14,10 @@ pub struct SAdd {
 }
 
 impl CommandExecutor for SAdd {
-    fn execute(&self, backend: &Backend) -> RespFrame {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if backend.is_wrong_type(&self.key, KeyType::Set) {
+            return RESP_WRONGTYPE.clone();
+        }
         let set = backend.set.entry(self.key.clone()).or_default();
         for member in self.members.iter() {
             set.insert(member.clone());
@@ -30,7 +36,7 @@ impl TryFrom<RespArray> for SAdd {
         let mut args = extract_args(arr, 1)?.into_iter();
 
         let key = match args.next() {
-            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
             _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
         };
 
@@ -59,7 +65,7 @@ pub struct SIsMember {
 }
 
 impl CommandExecutor for SIsMember {
-    fn execute(&self, backend: &Backend) -> RespFrame {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
         let set = backend.set.get(&self.key);
         match set {
             Some(set) => {
@@ -83,7 +89,7 @@ impl TryFrom<RespArray> for SIsMember {
         let mut args = extract_args(arr, 1)?.into_iter();
 
         let key = match args.next() {
-            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0)?,
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
             _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
         };
 
@@ -96,9 +102,237 @@ impl TryFrom<RespArray> for SIsMember {
     }
 }
 
+// smove source destination member
+// "*4\r\n$5\r\nsmove\r\n$5\r\nmyset\r\n$7\r\nanother\r\n$3\r\none\r\n"
+#[derive(Debug)]
+pub struct SMove {
+    source: String,
+    destination: String,
+    member: RespFrame,
+}
+
+impl CommandExecutor for SMove {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        // 与 redis 一致，源和目标任意一个不是集合都返回 WRONGTYPE
+        if backend.is_wrong_type(&self.source, KeyType::Set)
+            || backend.is_wrong_type(&self.destination, KeyType::Set)
+        {
+            return RESP_WRONGTYPE.clone();
+        }
+        let moved = backend.smove(&self.source, &self.destination, &self.member);
+        RespFrame::Integer(moved as i64)
+    }
+}
+
+impl TryFrom<RespArray> for SMove {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["smove"], 3)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let source = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let destination = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let member = match args.next() {
+            Some(RespFrame::BulkString(member)) => RespFrame::BulkString(member),
+            _ => return Err(CommandError::InvalidArguments("Invalid Member".to_string())),
+        };
+
+        Ok(Self {
+            source,
+            destination,
+            member,
+        })
+    }
+}
+
+// srandmember key [count]
+// "*3\r\n$11\r\nsrandmember\r\n$5\r\nmyset\r\n$2\r\n-5\r\n"
+#[derive(Debug)]
+pub struct SRandMember {
+    key: String,
+    count: Option<i64>,
+}
+
+impl CommandExecutor for SRandMember {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        match self.count {
+            Some(count) => RespArray::new(backend.srandmember(&self.key, count)).into(),
+            None => match backend.srandmember(&self.key, 1).pop() {
+                Some(member) => member,
+                None => RespFrame::Null(RespNull),
+            },
+        }
+    }
+}
+
+impl TryFrom<RespArray> for SRandMember {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len != 2 && len != 3 {
+            return Err(CommandError::InvalidArguments(
+                "SRANDMEMBER command must have 1 or 2 arguments".to_string(),
+            ));
+        }
+        validate_command(&arr, &["srandmember"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let count = args.next().map(parse_integer).transpose()?;
+        if count.is_some_and(|count| count < 0 && count.unsigned_abs() > MAX_RANDOM_COUNT) {
+            return Err(CommandError::InvalidArguments(
+                "value is out of range".to_string(),
+            ));
+        }
+
+        Ok(Self { key, count })
+    }
+}
+
+// sscan key cursor [MATCH pattern] [COUNT count]
+// "*3\r\n$5\r\nsscan\r\n$5\r\nmyset\r\n$1\r\n0\r\n"
+#[derive(Debug)]
+pub struct SScan {
+    key: String,
+    scan: ScanArgs,
+}
+
+impl CommandExecutor for SScan {
+    // 每次调用对成员排序得到快照，cursor 是快照中的下标
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let mut members = match backend.set.get(&self.key) {
+            Some(set) => set.iter().map(|v| v.key().clone()).collect::<Vec<_>>(),
+            None if backend.exists(&self.key) => return RESP_WRONGTYPE.clone(),
+            None => vec![],
+        };
+        members.sort();
+        let (next, page) = self.scan.page(members, |member| match member {
+            RespFrame::BulkString(member) => member.as_ref(),
+            _ => &[],
+        });
+        ScanArgs::reply(next, page)
+    }
+}
+
+impl TryFrom<RespArray> for SScan {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        validate_command(&arr, &["sscan"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+        let scan = ScanArgs::parse(args)?;
+
+        Ok(Self { key, scan })
+    }
+}
+
+// sintercard numkeys key [key ...] [LIMIT limit]
+// "*4\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$5\r\nmyset\r\n$7\r\nanother\r\n"
+#[derive(Debug)]
+pub struct SInterCard {
+    keys: Vec<String>,
+    // 与 redis 一致，LIMIT 0 表示不限制
+    limit: usize,
+}
+
+impl CommandExecutor for SInterCard {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if self
+            .keys
+            .iter()
+            .any(|key| backend.is_wrong_type(key, KeyType::Set))
+        {
+            return RESP_WRONGTYPE.clone();
+        }
+        let limit = match self.limit {
+            0 => usize::MAX,
+            limit => limit,
+        };
+        RespFrame::Integer(backend.sintercard(&self.keys, limit) as i64)
+    }
+}
+
+impl TryFrom<RespArray> for SInterCard {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len < 3 {
+            return Err(CommandError::InvalidArguments(
+                "wrong number of arguments for 'sintercard' command".to_string(),
+            ));
+        }
+        validate_command(&arr, &["sintercard"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let numkeys = match args.next() {
+            Some(numkeys) => parse_integer(numkeys)?,
+            None => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+        };
+        if numkeys <= 0 {
+            return Err(CommandError::InvalidArguments(
+                "numkeys should be greater than 0".to_string(),
+            ));
+        }
+        if numkeys as usize > args.len() {
+            return Err(CommandError::InvalidArguments(
+                "Number of keys can't be greater than number of args".to_string(),
+            ));
+        }
+
+        let mut keys = Vec::with_capacity(numkeys as usize);
+        for _ in 0..numkeys {
+            match args.next() {
+                Some(RespFrame::BulkString(key)) => keys.push(String::from_utf8(key.0.into())?),
+                _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+            }
+        }
+
+        let limit = match (args.next(), args.next(), args.next()) {
+            (None, _, _) => 0,
+            (Some(RespFrame::BulkString(option)), Some(limit), None)
+                if option.eq_ignore_ascii_case(b"limit") =>
+            {
+                let limit = parse_integer(limit)?;
+                if limit < 0 {
+                    return Err(CommandError::InvalidArguments(
+                        "LIMIT can't be negative".to_string(),
+                    ));
+                }
+                limit as usize
+            }
+            _ => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+        };
+
+        Ok(Self { keys, limit })
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::RespDecoder;
+    use crate::{BulkString, RespDecoder, SimpleError};
 
     use super::*;
     use anyhow::Result;
@@ -127,29 +361,29 @@ mod tests {
         Ok(())
     }
 
-    #[test]
-    fn test_sadd_command() -> Result<()> {
+    #[tokio::test]
+    async fn test_sadd_command() -> Result<()> {
         let backend = Backend::new();
         let cmd = SAdd {
             key: "myset".to_string(),
             members: vec![RespFrame::BulkString(b"one".into())],
         };
 
-        let ret = cmd.execute(&backend);
+        let ret = cmd.execute(&backend).await;
         assert_eq!(ret, RESP_OK.clone());
 
         Ok(())
     }
 
-    #[test]
-    fn test_sismember_command() -> Result<()> {
+    #[tokio::test]
+    async fn test_sismember_command() -> Result<()> {
         let backend = Backend::new();
         let cmd = SAdd {
             key: "myset".to_string(),
             members: vec![RespFrame::BulkString(b"one".into())],
         };
 
-        let ret = cmd.execute(&backend);
+        let ret = cmd.execute(&backend).await;
         assert_eq!(ret, RESP_OK.clone());
 
         let cmd = SIsMember {
@@ -157,7 +391,7 @@ mod tests {
             member: RespFrame::BulkString(b"one".into()),
         };
 
-        let ret = cmd.execute(&backend);
+        let ret = cmd.execute(&backend).await;
         assert_eq!(ret, RespFrame::Integer(1));
 
         let cmd = SIsMember {
@@ -165,9 +399,330 @@ mod tests {
             member: RespFrame::BulkString(b"two".into()),
         };
 
-        let ret = cmd.execute(&backend);
+        let ret = cmd.execute(&backend).await;
+        assert_eq!(ret, RespFrame::Integer(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_smove_try_from() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*4\r\n$5\r\nsmove\r\n$5\r\nmyset\r\n$7\r\nanother\r\n$3\r\none\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = SMove::try_from(frame)?;
+
+        assert_eq!(cmd.source, "myset");
+        assert_eq!(cmd.destination, "another");
+        assert_eq!(cmd.member, RespFrame::BulkString(b"one".into()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_smove_command() -> Result<()> {
+        let backend = Backend::new();
+        backend.sadd("myset".to_string(), RespFrame::BulkString(b"one".into()));
+        backend.sadd("myset".to_string(), RespFrame::BulkString(b"two".into()));
+
+        let smove = |member: &str| SMove {
+            source: "myset".to_string(),
+            destination: "another".to_string(),
+            member: RespFrame::BulkString(member.as_bytes().into()),
+        };
+
+        let ret = smove("one").execute(&backend).await;
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert!(!backend.sismember("myset", &RespFrame::BulkString(b"one".into())));
+        assert!(backend.sismember("another", &RespFrame::BulkString(b"one".into())));
+
+        let ret = smove("three").execute(&backend).await;
+        assert_eq!(ret, RespFrame::Integer(0));
+        assert!(!backend.sismember("another", &RespFrame::BulkString(b"three".into())));
+
+        // source 的最后一个 member 被移走后删除 source
+        let ret = smove("two").execute(&backend).await;
+        assert_eq!(ret, RespFrame::Integer(1));
+        assert!(!backend.exists("myset"));
+        assert_eq!(backend.set.get("another").map(|set| set.len()), Some(2));
+
+        let ret = smove("two").execute(&backend).await;
         assert_eq!(ret, RespFrame::Integer(0));
 
         Ok(())
     }
+
+    #[test]
+    fn test_srandmember_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n$11\r\nsrandmember\r\n$5\r\nmyset\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = SRandMember::try_from(frame)?;
+        assert_eq!(cmd.key, "myset");
+        assert_eq!(cmd.count, None);
+
+        let mut buf = BytesMut::from("*3\r\n$11\r\nsrandmember\r\n$5\r\nmyset\r\n$2\r\n-5\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = SRandMember::try_from(frame)?;
+        assert_eq!(cmd.count, Some(-5));
+
+        let mut buf = BytesMut::from("*3\r\n$11\r\nsrandmember\r\n$5\r\nmyset\r\n$3\r\ntwo\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(SRandMember::try_from(frame).is_err());
+
+        // 负数 count 会生成同样多的元素，太大时直接拒绝
+        let mut buf = BytesMut::from(
+            "*3\r\n$11\r\nsrandmember\r\n$5\r\nmyset\r\n$20\r\n-9223372036854775808\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            RespFrame::from(SRandMember::try_from(frame).unwrap_err()),
+            SimpleError::new("ERR value is out of range").into()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_srandmember_command() -> Result<()> {
+        let backend = Backend::new();
+        let members = ["one", "two", "three"]
+            .iter()
+            .map(|m| RespFrame::BulkString(m.as_bytes().into()))
+            .collect::<Vec<_>>();
+        for member in members.iter() {
+            backend.sadd("myset".to_string(), member.clone());
+        }
+
+        let cmd = SRandMember {
+            key: "myset".to_string(),
+            count: None,
+        };
+        let ret = cmd.execute(&backend).await;
+        assert!(members.contains(&ret), "{:?}", ret);
+
+        // 正数 count 返回不重复的 member，且不会超过集合大小
+        let cmd = SRandMember {
+            key: "myset".to_string(),
+            count: Some(5),
+        };
+        let RespFrame::Array(ret) = cmd.execute(&backend).await else {
+            panic!("expected an array");
+        };
+        assert_eq!(ret.len(), 3);
+        for member in members.iter() {
+            assert!(ret.contains(member));
+        }
+
+        // 负数 count 允许重复
+        let cmd = SRandMember {
+            key: "myset".to_string(),
+            count: Some(-10),
+        };
+        let RespFrame::Array(ret) = cmd.execute(&backend).await else {
+            panic!("expected an array");
+        };
+        assert_eq!(ret.len(), 10);
+        assert!(ret.iter().all(|m| members.contains(m)));
+
+        // 很大的正数 count 不会按 count 预先分配
+        let cmd = SRandMember {
+            key: "myset".to_string(),
+            count: Some(i64::MAX),
+        };
+        let RespFrame::Array(ret) = cmd.execute(&backend).await else {
+            panic!("expected an array");
+        };
+        assert_eq!(ret.len(), 3);
+        assert_eq!(
+            backend.srandmember("myset", i64::MIN).len(),
+            MAX_RANDOM_COUNT as usize
+        );
+
+        // member 没有被移除
+        assert_eq!(backend.set.get("myset").map(|set| set.len()), Some(3));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_srandmember_missing_key() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = SRandMember {
+            key: "missing".to_string(),
+            count: None,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Null(RespNull));
+
+        let cmd = SRandMember {
+            key: "missing".to_string(),
+            count: Some(-3),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespArray::new(vec![]).into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sscan_visits_every_member_once() -> Result<()> {
+        let backend = Backend::new();
+        for i in 0..23 {
+            backend.sadd(
+                "myset".to_string(),
+                BulkString::new(format!("m{}", i)).into(),
+            );
+        }
+
+        let scan = |cursor: &str| -> Result<SScan> {
+            let input = format!(
+                "*5\r\n$5\r\nsscan\r\n$5\r\nmyset\r\n${}\r\n{}\r\n$5\r\ncount\r\n$1\r\n5\r\n",
+                cursor.len(),
+                cursor
+            );
+            let mut buf = BytesMut::from(input.as_str());
+            Ok(SScan::try_from(RespArray::decode(&mut buf)?)?)
+        };
+
+        let mut cursor = "0".to_string();
+        let mut seen = std::collections::HashSet::new();
+        loop {
+            let RespFrame::Array(reply) = scan(&cursor)?.execute(&backend).await else {
+                panic!("expected an array");
+            };
+            let (RespFrame::BulkString(next), RespFrame::Array(items)) = (&reply[0], &reply[1])
+            else {
+                panic!("unexpected reply {:?}", reply);
+            };
+            assert!(items.len() <= 5);
+            for member in items.iter() {
+                assert!(seen.insert(member.clone()));
+            }
+            cursor = String::from_utf8(next.to_vec())?;
+            if cursor == "0" {
+                break;
+            }
+        }
+        assert_eq!(seen.len(), 23);
+
+        // key 不存在时直接结束
+        let RespFrame::Array(reply) = SScan {
+            key: "missing".to_string(),
+            scan: ScanArgs::parse([BulkString::new("0").into()])?,
+        }
+        .execute(&backend)
+        .await
+        else {
+            panic!("expected an array");
+        };
+        assert_eq!(reply[0], BulkString::new("0").into());
+
+        backend.set("str".to_string(), RespFrame::Integer(1));
+        let cmd = SScan {
+            key: "str".to_string(),
+            scan: ScanArgs::parse([BulkString::new("0").into()])?,
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+
+        Ok(())
+    }
+
+    fn sintercard(args: &[&str]) -> Result<SInterCard, CommandError> {
+        let frames = ["sintercard"]
+            .iter()
+            .chain(args)
+            .map(|arg| BulkString::new(*arg).into())
+            .collect::<Vec<RespFrame>>();
+        SInterCard::try_from(RespArray::new(frames))
+    }
+
+    #[test]
+    fn test_sintercard_try_from() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*4\r\n$10\r\nsintercard\r\n$1\r\n2\r\n$5\r\nmyset\r\n$7\r\nanother\r\n",
+        );
+        let cmd = SInterCard::try_from(RespArray::decode(&mut buf)?)?;
+        assert_eq!(cmd.keys, ["myset", "another"]);
+        assert_eq!(cmd.limit, 0);
+
+        let cmd = sintercard(&["1", "myset", "limit", "3"])?;
+        assert_eq!(cmd.keys, ["myset"]);
+        assert_eq!(cmd.limit, 3);
+
+        assert!(sintercard(&["0", "myset"]).is_err());
+        assert!(sintercard(&["3", "a", "b"]).is_err());
+        assert!(sintercard(&["1", "a", "LIMIT", "-1"]).is_err());
+        assert!(sintercard(&["1", "a", "LIMIT"]).is_err());
+        assert!(sintercard(&["1", "a", "b"]).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_sintercard_command() -> Result<()> {
+        let backend = Backend::new();
+        for member in ["a", "b", "c", "d", "e"] {
+            backend.sadd("s1".to_string(), BulkString::new(member).into());
+        }
+        for member in ["b", "c", "d", "e", "f"] {
+            backend.sadd("s2".to_string(), BulkString::new(member).into());
+        }
+        for member in ["c", "d", "e", "x"] {
+            backend.sadd("s3".to_string(), BulkString::new(member).into());
+        }
+
+        let reply = sintercard(&["2", "s1", "s2"])?.execute(&backend).await;
+        assert_eq!(reply, RespFrame::Integer(4));
+        let reply = sintercard(&["3", "s1", "s2", "s3"])?
+            .execute(&backend)
+            .await;
+        assert_eq!(reply, RespFrame::Integer(3));
+
+        // LIMIT 小于交集大小时返回 LIMIT，0 表示不限制
+        let reply = sintercard(&["2", "s1", "s2", "LIMIT", "2"])?
+            .execute(&backend)
+            .await;
+        assert_eq!(reply, RespFrame::Integer(2));
+        let reply = sintercard(&["2", "s1", "s2", "LIMIT", "10"])?
+            .execute(&backend)
+            .await;
+        assert_eq!(reply, RespFrame::Integer(4));
+        let reply = sintercard(&["2", "s1", "s2", "LIMIT", "0"])?
+            .execute(&backend)
+            .await;
+        assert_eq!(reply, RespFrame::Integer(4));
+
+        let reply = sintercard(&["2", "s1", "missing"])?.execute(&backend).await;
+        assert_eq!(reply, RespFrame::Integer(0));
+        let reply = sintercard(&["1", "s1"])?.execute(&backend).await;
+        assert_eq!(reply, RespFrame::Integer(5));
+
+        backend.set("str".to_string(), BulkString::new("v").into());
+        let reply = sintercard(&["2", "s1", "str"])?.execute(&backend).await;
+        assert_eq!(reply, RESP_WRONGTYPE.clone());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_set_writes_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), b"value".into());
+        backend.sadd("myset".to_string(), b"one".into());
+
+        let cmd = SAdd {
+            key: "string".to_string(),
+            members: vec![b"one".into()],
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+
+        // 目标不是集合时成员不会被移出源集合
+        let cmd = SMove {
+            source: "myset".to_string(),
+            destination: "string".to_string(),
+            member: b"one".into(),
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+        assert!(backend.sismember("myset", &b"one".into()));
+        assert!(!backend.set.contains_key("string"));
+
+        Ok(())
+    }
 }
@@ -0,0 +1,382 @@
+use std::ops::Bound;
+
+use dashmap::mapref::entry::Entry;
+
+use crate::{
+    Backend, BulkString, KeyType, RespArray, RespFrame, SimpleError, Stream, StreamError,
+    StreamFields, StreamId, StreamIdSpec,
+};
+
+use super::{
+    extract_args, parse_integer, validate_command, CommandError, CommandExecutor, RESP_WRONGTYPE,
+};
+
+// xadd key *|id field value [field value ...]
+// "*5\r\n$4\r\nxadd\r\n$8\r\nmystream\r\n$1\r\n*\r\n$4\r\nname\r\n$5\r\nalice\r\n"
+#[derive(Debug)]
+pub struct XAdd {
+    key: String,
+    id: StreamIdSpec,
+    fields: StreamFields,
+}
+
+// xlen key
+// "*2\r\n$4\r\nxlen\r\n$8\r\nmystream\r\n"
+#[derive(Debug)]
+pub struct XLen {
+    key: String,
+}
+
+// xrange key start end [COUNT count]
+// "*4\r\n$6\r\nxrange\r\n$8\r\nmystream\r\n$1\r\n-\r\n$1\r\n+\r\n"
+#[derive(Debug)]
+pub struct XRange {
+    key: String,
+    start: Bound<StreamId>,
+    end: Bound<StreamId>,
+    count: Option<usize>,
+}
+
+impl CommandExecutor for XAdd {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if backend.is_wrong_type(&self.key, KeyType::Stream) {
+            return RESP_WRONGTYPE.clone();
+        }
+        // ID 不合法时不能留下一个空的 stream
+        // 通过 entry 持有分片锁，并发的 XADD 不会互相覆盖新建的 stream
+        let added = match backend.stream.entry(self.key.clone()) {
+            Entry::Occupied(mut entry) => entry.get_mut().add(self.id, self.fields.clone()),
+            Entry::Vacant(entry) => {
+                let mut stream = Stream::new();
+                let added = stream.add(self.id, self.fields.clone());
+                if added.is_ok() {
+                    entry.insert(stream);
+                }
+                added
+            }
+        };
+        match added {
+            Ok(id) => BulkString::new(id.to_string()).into(),
+            Err(e) => SimpleError::new(e.to_string()).into(),
+        }
+    }
+}
+
+impl CommandExecutor for XLen {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        match backend.stream.get(&self.key) {
+            Some(stream) => RespFrame::Integer(stream.len() as i64),
+            None if backend.exists(&self.key) => RESP_WRONGTYPE.clone(),
+            None => RespFrame::Integer(0),
+        }
+    }
+}
+
+impl CommandExecutor for XRange {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let stream = match backend.stream.get(&self.key) {
+            Some(stream) => stream,
+            None if backend.exists(&self.key) => return RESP_WRONGTYPE.clone(),
+            None => return RespArray::new(vec![]).into(),
+        };
+        // 每条消息是 [id, [field, value, ...]]
+        let entries = stream
+            .range(self.start, self.end, self.count)
+            .into_iter()
+            .map(|(id, fields)| {
+                let fields = fields
+                    .iter()
+                    .flat_map(|(field, value)| {
+                        [
+                            BulkString::new(field.clone()).into(),
+                            BulkString::new(value.clone()).into(),
+                        ]
+                    })
+                    .collect::<Vec<RespFrame>>();
+                RespArray::new(vec![
+                    BulkString::new(id.to_string()).into(),
+                    RespArray::new(fields).into(),
+                ])
+                .into()
+            })
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(entries).into()
+    }
+}
+
+impl TryFrom<RespArray> for XAdd {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len < 5 || len.is_multiple_of(2) {
+            return Err(CommandError::InvalidArguments(
+                "wrong number of arguments for 'xadd' command".to_string(),
+            ));
+        }
+        validate_command(&arr, &["xadd"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let id = parse_string(args.next())?
+            .parse::<StreamIdSpec>()
+            .map_err(invalid_id)?;
+
+        let mut fields = Vec::with_capacity((len - 3) / 2);
+        while let (Some(field), Some(value)) = (args.next(), args.next()) {
+            match (field, value) {
+                (RespFrame::BulkString(field), RespFrame::BulkString(value)) => {
+                    fields.push((field.0.into(), value.0.into()))
+                }
+                _ => {
+                    return Err(CommandError::InvalidArguments(
+                        "Invalid field or value".to_string(),
+                    ))
+                }
+            }
+        }
+        Ok(Self { key, id, fields })
+    }
+}
+
+impl TryFrom<RespArray> for XLen {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["xlen"], 1)?;
+        let mut args = extract_args(arr, 1)?.into_iter();
+        Ok(Self {
+            key: parse_key(args.next())?,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for XRange {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len != 4 && len != 6 {
+            return Err(CommandError::InvalidArguments("syntax error".to_string()));
+        }
+        validate_command(&arr, &["xrange"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let key = parse_key(args.next())?;
+        let start = parse_range_bound(&parse_string(args.next())?, true)?;
+        let end = parse_range_bound(&parse_string(args.next())?, false)?;
+
+        let count = match args.next() {
+            Some(RespFrame::BulkString(option)) if option.eq_ignore_ascii_case(b"count") => {
+                let count = match args.next() {
+                    Some(count) => parse_integer(count)?,
+                    None => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+                };
+                Some(count.max(0) as usize)
+            }
+            Some(_) => return Err(CommandError::InvalidArguments("syntax error".to_string())),
+            None => None,
+        };
+
+        Ok(Self {
+            key,
+            start,
+            end,
+            count,
+        })
+    }
+}
+
+// "-" 和 "+" 表示最小和最大的 ID，"(" 前缀表示不包含该 ID
+fn parse_range_bound(s: &str, start: bool) -> Result<Bound<StreamId>, CommandError> {
+    match s.strip_prefix('(') {
+        Some(id) if id != "-" && id != "+" => StreamId::parse_bound(id, start)
+            .map(Bound::Excluded)
+            .map_err(invalid_id),
+        Some(_) => Err(invalid_id(StreamError::InvalidId)),
+        None => StreamId::parse_bound(s, start)
+            .map(Bound::Included)
+            .map_err(invalid_id),
+    }
+}
+
+// StreamError 的消息已经带有 ERR 前缀，CommandError 转换为回复时会再加一次
+fn invalid_id(e: StreamError) -> CommandError {
+    let msg = e.to_string();
+    CommandError::InvalidArguments(msg.trim_start_matches("ERR ").to_string())
+}
+
+fn parse_key(frame: Option<RespFrame>) -> Result<String, CommandError> {
+    match frame {
+        Some(RespFrame::BulkString(key)) => Ok(String::from_utf8(key.0.into())?),
+        _ => Err(CommandError::InvalidArguments("Invalid key".to_string())),
+    }
+}
+
+fn parse_string(frame: Option<RespFrame>) -> Result<String, CommandError> {
+    match frame {
+        Some(RespFrame::BulkString(s)) => Ok(String::from_utf8(s.0.into())?),
+        _ => Err(CommandError::InvalidArguments("syntax error".to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecoder;
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn xadd(key: &str, id: &str, fields: &[&str]) -> Result<XAdd> {
+        let mut frames: Vec<RespFrame> = vec![
+            BulkString::new("xadd").into(),
+            BulkString::new(key).into(),
+            BulkString::new(id).into(),
+        ];
+        frames.extend(fields.iter().map(|f| BulkString::new(*f).into()));
+        Ok(XAdd::try_from(RespArray::new(frames))?)
+    }
+
+    fn ids(frame: RespFrame) -> Vec<String> {
+        let RespFrame::Array(entries) = frame else {
+            panic!("expected an array, got {:?}", frame);
+        };
+        entries
+            .iter()
+            .map(|entry| match entry {
+                RespFrame::Array(entry) => match &entry[0] {
+                    RespFrame::BulkString(id) => String::from_utf8_lossy(id).to_string(),
+                    other => panic!("unexpected id {:?}", other),
+                },
+                other => panic!("unexpected entry {:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_xadd_explicit_ids() -> Result<()> {
+        let backend = Backend::new();
+        let reply = xadd("s", "1-1", &["a", "1"])?.execute(&backend).await;
+        assert_eq!(reply, BulkString::new("1-1").into());
+        let reply = xadd("s", "1-*", &["a", "2"])?.execute(&backend).await;
+        assert_eq!(reply, BulkString::new("1-2").into());
+
+        let reply = xadd("s", "1-2", &["a", "3"])?.execute(&backend).await;
+        assert_eq!(
+            reply,
+            SimpleError::new(
+                "ERR The ID specified in XADD is equal or smaller than the target stream top item"
+            )
+            .into()
+        );
+        // 不合法的 ID 不会创建 key
+        let reply = xadd("other", "0-0", &["a", "1"])?.execute(&backend).await;
+        assert_eq!(
+            reply,
+            SimpleError::new("ERR The ID specified in XADD must be greater than 0-0").into()
+        );
+        assert!(!backend.exists("other"));
+
+        let max = format!("{}-{}", u64::MAX, u64::MAX);
+        xadd("full", &max, &["a", "1"])?.execute(&backend).await;
+        let reply = xadd("full", "*", &["a", "2"])?.execute(&backend).await;
+        assert_eq!(
+            reply,
+            SimpleError::new(
+                "ERR The stream has exhausted the last possible ID, unable to add more items"
+            )
+            .into()
+        );
+
+        assert!(xadd("s", "abc", &["a", "1"]).is_err());
+        assert!(xadd("s", "*", &["a"]).is_err());
+
+        let len = XLen {
+            key: "s".to_string(),
+        };
+        assert_eq!(len.execute(&backend).await, RespFrame::Integer(2));
+
+        backend.set("str".to_string(), RespFrame::Integer(1));
+        let reply = xadd("str", "*", &["a", "1"])?.execute(&backend).await;
+        assert_eq!(reply, RESP_WRONGTYPE.clone());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_xadd_auto_ids() -> Result<()> {
+        let backend = Backend::new();
+        let mut last = StreamId::MIN;
+        for _ in 0..10 {
+            let RespFrame::BulkString(id) = xadd("s", "*", &["a", "1"])?.execute(&backend).await
+            else {
+                panic!("expected an id");
+            };
+            let id: StreamId = String::from_utf8(id.0.into())?.parse()?;
+            // 同一毫秒内序号递增
+            assert!(id > last);
+            if id.ms == last.ms {
+                assert_eq!(id.seq, last.seq + 1);
+            }
+            last = id;
+        }
+        assert_eq!(backend.stream.get("s").unwrap().last_id(), last);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_xrange() -> Result<()> {
+        let backend = Backend::new();
+        for id in ["1-0", "1-1", "2-0", "3-0"] {
+            xadd("s", id, &["id", id])?.execute(&backend).await;
+        }
+
+        let mut buf = BytesMut::from("*4\r\n$6\r\nxrange\r\n$1\r\ns\r\n$1\r\n-\r\n$1\r\n+\r\n");
+        let cmd = XRange::try_from(RespArray::decode(&mut buf)?)?;
+        let reply = cmd.execute(&backend).await;
+        assert_eq!(ids(reply.clone()), ["1-0", "1-1", "2-0", "3-0"]);
+        let RespFrame::Array(entries) = reply else {
+            unreachable!()
+        };
+        assert_eq!(
+            entries[0],
+            RespArray::new(vec![
+                BulkString::new("1-0").into(),
+                RespArray::new(vec![
+                    BulkString::new("id").into(),
+                    BulkString::new("1-0").into()
+                ])
+                .into(),
+            ])
+            .into()
+        );
+
+        // 省略序号的 ID 覆盖整个毫秒
+        let range = |start: &str, end: &str, count: Option<&str>| {
+            let mut frames: Vec<RespFrame> = vec![
+                BulkString::new("xrange").into(),
+                BulkString::new("s").into(),
+                BulkString::new(start).into(),
+                BulkString::new(end).into(),
+            ];
+            if let Some(count) = count {
+                frames.push(BulkString::new("COUNT").into());
+                frames.push(BulkString::new(count).into());
+            }
+            XRange::try_from(RespArray::new(frames))
+        };
+        let reply = range("1", "1", None)?.execute(&backend).await;
+        assert_eq!(ids(reply), ["1-0", "1-1"]);
+        let reply = range("(1-0", "+", Some("2"))?.execute(&backend).await;
+        assert_eq!(ids(reply), ["1-1", "2-0"]);
+        let reply = range("2", "(3-0", None)?.execute(&backend).await;
+        assert_eq!(ids(reply), ["2-0"]);
+        let reply = range("3", "1", None)?.execute(&backend).await;
+        assert_eq!(ids(reply), Vec::<String>::new());
+
+        assert!(range("x", "+", None).is_err());
+        assert!(range("(-", "+", None).is_err());
+        assert!(range("-", "+", Some("x")).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{Backend, RespArray, RespFrame, RespNull, Session, SimpleError, SimpleString};
+
+use super::{extract_args, validate_command, CommandError, CommandExecutor, RESP_OK};
+
+// multi
+// "*1\r\n$5\r\nmulti\r\n"
+#[derive(Debug)]
+pub struct Multi;
+
+// exec
+// "*1\r\n$4\r\nexec\r\n"
+// 由 frame_handler 调用 exec_transaction 执行，这里只负责解析
+#[derive(Debug)]
+pub struct Exec;
+
+// discard
+// "*1\r\n$7\r\ndiscard\r\n"
+#[derive(Debug)]
+pub struct Discard;
+
+impl CommandExecutor for Multi {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR MULTI is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        if session.multi() {
+            RESP_OK.clone()
+        } else {
+            SimpleError::new("ERR MULTI calls can not be nested").into()
+        }
+    }
+}
+
+// 事务中的命令也通过 Command 执行，EXEC 不能再经由 Command 分发，否则 future 的类型会递归
+impl CommandExecutor for Exec {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR EXEC is not allowed without a connection").into()
+    }
+}
+
+impl CommandExecutor for Discard {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR DISCARD is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        match session.take_transaction() {
+            Some(_) => {
+                session.unwatch();
+                RESP_OK.clone()
+            }
+            None => SimpleError::new("ERR DISCARD without MULTI").into(),
+        }
+    }
+}
+
+// watch key [key ...]
+// "*2\r\n$5\r\nwatch\r\n$5\r\nhello\r\n"
+#[derive(Debug)]
+pub struct Watch {
+    keys: Vec<String>,
+}
+
+// unwatch
+// "*1\r\n$7\r\nunwatch\r\n"
+#[derive(Debug)]
+pub struct Unwatch;
+
+impl CommandExecutor for Watch {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR WATCH is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        if session.in_multi() {
+            return SimpleError::new("ERR WATCH inside MULTI is not allowed").into();
+        }
+        for key in &self.keys {
+            session.watch(key.clone());
+        }
+        RESP_OK.clone()
+    }
+}
+
+impl CommandExecutor for Unwatch {
+    async fn execute(&self, _backend: &Backend) -> RespFrame {
+        SimpleError::new("ERR UNWATCH is not allowed without a connection").into()
+    }
+
+    async fn execute_with(&self, session: &mut Session) -> RespFrame {
+        session.unwatch();
+        RESP_OK.clone()
+    }
+}
+
+// 依次执行排队的命令，执行期间持有写锁，其他连接的写命令要等待事务结束
+pub(crate) async fn exec_transaction(session: &mut Session) -> RespFrame {
+    let transaction = match session.take_transaction() {
+        Some(transaction) => transaction,
+        None => return SimpleError::new("ERR EXEC without MULTI").into(),
+    };
+    if transaction.aborted {
+        return SimpleError::new("EXECABORT Transaction discarded because of previous errors.")
+            .into();
+    }
+
+    let server = session.server().clone();
+    let _gate = server.write_gate().write().await;
+    // 持有写锁之后其他连接无法再修改 key，检查和执行之间不会有其他写入
+    let changed = session.watched_keys_changed();
+    session.unwatch();
+    if changed {
+        return RespFrame::Null(RespNull);
+    }
+
+    let mut replies = Vec::with_capacity(transaction.commands.len());
+    for queued in transaction.commands {
+        session.expire_keys(&queued.keys);
+        let existed = queued
+            .write
+            .then(|| session.keys_existed(&queued.keys))
+            .flatten();
+        let reply = queued.cmd.execute_with(session).await;
+        if let (Some(name), Some(existed)) = (queued.name, existed) {
+            session.notify_write(name, &queued.keys, &existed, &reply);
+        }
+        replies.push(reply);
+        let tracking = session.server().tracking();
+        if queued.write {
+            tracking.invalidate(session.id(), &queued.keys);
+        } else if session.is_tracking() {
+            tracking.track(session.id(), &queued.keys);
+        }
+        let backend = session.backend();
+        for key in queued.keys {
+            if queued.write {
+                backend.bump_version(&key);
+            }
+            backend.touch(&key);
+        }
+    }
+    RespArray::new(replies).into()
+}
+
+pub(crate) fn queued() -> RespFrame {
+    SimpleString::new("QUEUED").into()
+}
+
+impl TryFrom<RespArray> for Multi {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["multi"], 0)?;
+        Ok(Self)
+    }
+}
+
+impl TryFrom<RespArray> for Exec {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["exec"], 0)?;
+        Ok(Self)
+    }
+}
+
+impl TryFrom<RespArray> for Watch {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        validate_command(&arr, &["watch"], len.max(2) - 1)?;
+
+        let keys = extract_args(arr, 1)?
+            .into_iter()
+            .map(|arg| match arg {
+                RespFrame::BulkString(key) => Ok(String::from_utf8(key.0.into())?),
+                _ => Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self { keys })
+    }
+}
+
+impl TryFrom<RespArray> for Unwatch {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["unwatch"], 0)?;
+        Ok(Self)
+    }
+}
+
+impl TryFrom<RespArray> for Discard {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["discard"], 0)?;
+        Ok(Self)
+    }
+}
@@ -0,0 +1,1585 @@
+use crate::{
+    Backend, BulkString, KeyType, LexBound, RespArray, RespDouble, RespFrame, RespNull, ScoreBound,
+    SimpleError,
+};
+
+use super::{
+    extract_args, normalize_range, parse_integer, validate_command, CommandError, CommandExecutor,
+    RESP_WRONGTYPE,
+};
+
+// zadd key [NX|XX] [GT|LT] [CH] score member [score member ...]
+// "*4\r\n$4\r\nzadd\r\n$5\r\nmyset\r\n$1\r\n1\r\n$3\r\none\r\n"
+#[derive(Debug)]
+pub struct ZAdd {
+    key: String,
+    options: ZAddOptions,
+    members: Vec<(f64, String)>,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct ZAddOptions {
+    nx: bool,
+    xx: bool,
+    gt: bool,
+    lt: bool,
+    ch: bool,
+}
+
+// zscore key member
+// "*3\r\n$6\r\nzscore\r\n$6\r\nmyzset\r\n$3\r\none\r\n"
+#[derive(Debug)]
+pub struct ZScore {
+    key: String,
+    member: String,
+}
+
+// zincrby key increment member
+// "*4\r\n$7\r\nzincrby\r\n$6\r\nmyzset\r\n$1\r\n2\r\n$3\r\none\r\n"
+#[derive(Debug)]
+pub struct ZIncrBy {
+    key: String,
+    increment: f64,
+    member: String,
+}
+
+// zcard key
+// "*2\r\n$5\r\nzcard\r\n$6\r\nmyzset\r\n"
+#[derive(Debug)]
+pub struct ZCard {
+    key: String,
+}
+
+// zmscore key member [member ...]
+// "*4\r\n$7\r\nzmscore\r\n$6\r\nmyzset\r\n$3\r\none\r\n$3\r\ntwo\r\n"
+#[derive(Debug)]
+pub struct ZMScore {
+    key: String,
+    members: Vec<String>,
+}
+
+// zrange key start stop [BYSCORE] [REV] [LIMIT offset count] [WITHSCORES]
+// "*4\r\n$6\r\nzrange\r\n$6\r\nmyzset\r\n$1\r\n0\r\n$2\r\n-1\r\n"
+#[derive(Debug)]
+pub struct ZRange {
+    key: String,
+    by: ZRangeBy,
+    rev: bool,
+    limit: Option<(i64, i64)>,
+    with_scores: bool,
+}
+
+// zrangebyscore key min max [WITHSCORES] [LIMIT offset count]
+// "*4\r\n$13\r\nzrangebyscore\r\n$6\r\nmyzset\r\n$4\r\n-inf\r\n$2\r\n(3\r\n"
+#[derive(Debug)]
+pub struct ZRangeByScore {
+    // 等价于 ZRANGE key min max BYSCORE
+    range: ZRange,
+}
+
+// zcount key min max
+// "*4\r\n$6\r\nzcount\r\n$6\r\nmyzset\r\n$1\r\n1\r\n$2\r\n(3\r\n"
+#[derive(Debug)]
+pub struct ZCount {
+    key: String,
+    min: ScoreBound,
+    max: ScoreBound,
+}
+
+// zrangebylex key min max [LIMIT offset count]
+// "*4\r\n$11\r\nzrangebylex\r\n$6\r\nmyzset\r\n$1\r\n-\r\n$2\r\n[c\r\n"
+#[derive(Debug)]
+pub struct ZRangeByLex {
+    key: String,
+    min: LexBound,
+    max: LexBound,
+    limit: Option<(i64, i64)>,
+}
+
+// zlexcount key min max
+// "*4\r\n$9\r\nzlexcount\r\n$6\r\nmyzset\r\n$1\r\n-\r\n$1\r\n+\r\n"
+#[derive(Debug)]
+pub struct ZLexCount {
+    key: String,
+    min: LexBound,
+    max: LexBound,
+}
+
+#[derive(Debug, PartialEq)]
+enum ZRangeBy {
+    Rank(i64, i64),
+    // 总是以 (min, max) 保存，REV 时参数顺序为 max min，解析时已交换
+    Score(ScoreBound, ScoreBound),
+}
+
+impl CommandExecutor for ZAdd {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if backend.is_wrong_type(&self.key, KeyType::ZSet) {
+            return RESP_WRONGTYPE.clone();
+        }
+        // XX 只更新已存在的 member，key 不存在时不应该创建
+        if self.options.xx && !backend.zset.contains_key(&self.key) {
+            return RespFrame::Integer(0);
+        }
+
+        let mut zset = backend.zset.entry(self.key.clone()).or_default();
+        let mut added = 0;
+        let mut changed = 0;
+        for (score, member) in self.members.iter() {
+            match zset.score(member) {
+                Some(old) => {
+                    let skip = self.options.nx
+                        || (self.options.gt && *score <= old)
+                        || (self.options.lt && *score >= old);
+                    if !skip && *score != old {
+                        zset.insert(member.clone(), *score);
+                        changed += 1;
+                    }
+                }
+                None if !self.options.xx => {
+                    zset.insert(member.clone(), *score);
+                    added += 1;
+                }
+                None => {}
+            }
+        }
+
+        let ret = if self.options.ch {
+            added + changed
+        } else {
+            added
+        };
+        RespFrame::Integer(ret)
+    }
+}
+
+impl CommandExecutor for ZIncrBy {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        if backend.is_wrong_type(&self.key, KeyType::ZSet) {
+            return RESP_WRONGTYPE.clone();
+        }
+
+        // insert 会同时更新分数表和有序索引
+        let mut zset = backend.zset.entry(self.key.clone()).or_default();
+        let score = zset.score(&self.member).unwrap_or(0.0) + self.increment;
+        if score.is_nan() {
+            return SimpleError::new("ERR resulting score is not a number (NaN)").into();
+        }
+        zset.insert(self.member.clone(), score);
+        RespDouble::new(score).into()
+    }
+}
+
+impl CommandExecutor for ZScore {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let score = backend
+            .zset
+            .get(&self.key)
+            .and_then(|zset| zset.score(&self.member));
+        score_frame(score)
+    }
+}
+
+impl CommandExecutor for ZCard {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        match backend.zset.get(&self.key) {
+            Some(zset) => RespFrame::Integer(zset.len() as i64),
+            None if backend.exists(&self.key) => RESP_WRONGTYPE.clone(),
+            None => RespFrame::Integer(0),
+        }
+    }
+}
+
+impl CommandExecutor for ZMScore {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let zset = backend.zset.get(&self.key);
+        let frames = self
+            .members
+            .iter()
+            .map(|member| score_frame(zset.as_ref().and_then(|zset| zset.score(member))))
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(frames).into()
+    }
+}
+
+impl CommandExecutor for ZRange {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let zset = match backend.zset.get(&self.key) {
+            Some(zset) => zset,
+            None => return RespArray::new(vec![]).into(),
+        };
+
+        let members: Vec<(&str, f64)> = match self.by {
+            ZRangeBy::Rank(start, stop) => match normalize_range(start, stop, zset.len()) {
+                Some((start, stop)) if self.rev => zset
+                    .iter()
+                    .rev()
+                    .skip(start)
+                    .take(stop - start + 1)
+                    .collect(),
+                Some((start, stop)) => zset.iter().skip(start).take(stop - start + 1).collect(),
+                None => vec![],
+            },
+            ZRangeBy::Score(min, max) => {
+                let (offset, count) = self.limit.unwrap_or((0, -1));
+                if offset < 0 {
+                    vec![]
+                } else {
+                    let count = if count < 0 {
+                        usize::MAX
+                    } else {
+                        count as usize
+                    };
+                    let range = zset.range_by_score(min, max);
+                    if self.rev {
+                        range.rev().skip(offset as usize).take(count).collect()
+                    } else {
+                        range.skip(offset as usize).take(count).collect()
+                    }
+                }
+            }
+        };
+
+        members_reply(members, self.with_scores)
+    }
+}
+
+impl TryFrom<RespArray> for ZAdd {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len < 4 {
+            return Err(CommandError::InvalidArguments(
+                "ZADD command must have a key and at least 1 score/member pair".to_string(),
+            ));
+        }
+        validate_command(&arr, &["zadd"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter().peekable();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let mut options = ZAddOptions::default();
+        while let Some(RespFrame::BulkString(option)) = args.peek() {
+            match option.to_ascii_lowercase().as_slice() {
+                b"nx" => options.nx = true,
+                b"xx" => options.xx = true,
+                b"gt" => options.gt = true,
+                b"lt" => options.lt = true,
+                b"ch" => options.ch = true,
+                _ => break,
+            }
+            args.next();
+        }
+
+        if options.nx && options.xx {
+            return Err(CommandError::InvalidArguments(
+                "XX and NX options at the same time are not compatible".to_string(),
+            ));
+        }
+        if (options.gt && options.lt) || (options.nx && (options.gt || options.lt)) {
+            return Err(CommandError::InvalidArguments(
+                "GT, LT, and/or NX options at the same time are not compatible".to_string(),
+            ));
+        }
+
+        let args = args.collect::<Vec<RespFrame>>();
+        if args.is_empty() || !args.len().is_multiple_of(2) {
+            return Err(CommandError::InvalidArguments(
+                "ZADD command must have score/member pairs".to_string(),
+            ));
+        }
+
+        let mut members = Vec::with_capacity(args.len() / 2);
+        let mut args = args.into_iter();
+        while let (Some(score), Some(member)) = (args.next(), args.next()) {
+            let score = parse_score(score)?;
+            let member = match member {
+                RespFrame::BulkString(member) => String::from_utf8(member.0.into())?,
+                _ => return Err(CommandError::InvalidArguments("Invalid Member".to_string())),
+            };
+            members.push((score, member));
+        }
+
+        Ok(Self {
+            key,
+            options,
+            members,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZScore {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["zscore"], 2)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let member = match args.next() {
+            Some(RespFrame::BulkString(member)) => String::from_utf8(member.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Member".to_string())),
+        };
+
+        Ok(Self { key, member })
+    }
+}
+
+impl TryFrom<RespArray> for ZIncrBy {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["zincrby"], 3)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let increment = match args.next() {
+            Some(increment) => parse_score(increment)?,
+            None => {
+                return Err(CommandError::InvalidArguments(
+                    "Invalid Increment".to_string(),
+                ))
+            }
+        };
+
+        let member = match args.next() {
+            Some(RespFrame::BulkString(member)) => String::from_utf8(member.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Member".to_string())),
+        };
+
+        Ok(Self {
+            key,
+            increment,
+            member,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZCard {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["zcard"], 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        match args.next() {
+            Some(RespFrame::BulkString(key)) => Ok(Self {
+                key: String::from_utf8(key.0.into())?,
+            }),
+            _ => Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        }
+    }
+}
+
+impl TryFrom<RespArray> for ZMScore {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len < 3 {
+            return Err(CommandError::InvalidArguments(
+                "ZMSCORE command must have a key and at least 1 member".to_string(),
+            ));
+        }
+        validate_command(&arr, &["zmscore"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+
+        let mut members = Vec::with_capacity(len - 2);
+        for arg in args {
+            match arg {
+                RespFrame::BulkString(member) => members.push(String::from_utf8(member.0.into())?),
+                _ => return Err(CommandError::InvalidArguments("Invalid Member".to_string())),
+            }
+        }
+
+        Ok(Self { key, members })
+    }
+}
+
+impl TryFrom<RespArray> for ZRange {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len < 4 {
+            return Err(CommandError::InvalidArguments(
+                "ZRANGE command must have a key, a start and a stop".to_string(),
+            ));
+        }
+        validate_command(&arr, &["zrange"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+
+        let key = match args.next() {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+            _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+        };
+        let (start, stop) = match (args.next(), args.next()) {
+            (Some(start), Some(stop)) => (start, stop),
+            _ => return Err(CommandError::InvalidArguments("Invalid Range".to_string())),
+        };
+
+        let mut by_score = false;
+        let mut rev = false;
+        let mut limit = None;
+        let mut with_scores = false;
+        while let Some(arg) = args.next() {
+            let option = match arg {
+                RespFrame::BulkString(option) => option.to_ascii_lowercase(),
+                _ => return Err(CommandError::InvalidArguments("Invalid Option".to_string())),
+            };
+            match option.as_slice() {
+                b"byscore" => by_score = true,
+                b"rev" => rev = true,
+                b"withscores" => with_scores = true,
+                b"limit" => limit = Some(parse_limit(&mut args)?),
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "Unknown option: {}",
+                        String::from_utf8_lossy(&option)
+                    )))
+                }
+            }
+        }
+
+        let by = if by_score {
+            let (start, stop) = (parse_score_bound(start)?, parse_score_bound(stop)?);
+            if rev {
+                ZRangeBy::Score(stop, start)
+            } else {
+                ZRangeBy::Score(start, stop)
+            }
+        } else {
+            if limit.is_some() {
+                return Err(CommandError::InvalidArguments(
+                    "syntax error, LIMIT is only supported in combination with either BYSCORE or BYLEX"
+                        .to_string(),
+                ));
+            }
+            ZRangeBy::Rank(parse_integer(start)?, parse_integer(stop)?)
+        };
+
+        Ok(Self {
+            key,
+            by,
+            rev,
+            limit,
+            with_scores,
+        })
+    }
+}
+
+impl CommandExecutor for ZRangeByScore {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        self.range.execute(backend).await
+    }
+}
+
+impl CommandExecutor for ZCount {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let count = backend
+            .zset
+            .get(&self.key)
+            .map(|zset| zset.count_by_score(self.min, self.max))
+            .unwrap_or(0);
+        RespFrame::Integer(count as i64)
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeByScore {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len < 4 {
+            return Err(CommandError::InvalidArguments(
+                "ZRANGEBYSCORE command must have a key, a min and a max".to_string(),
+            ));
+        }
+        validate_command(&arr, &["zrangebyscore"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let (key, min, max) = extract_score_range(&mut args)?;
+
+        let mut limit = None;
+        let mut with_scores = false;
+        while let Some(arg) = args.next() {
+            let option = match arg {
+                RespFrame::BulkString(option) => option.to_ascii_lowercase(),
+                _ => return Err(CommandError::InvalidArguments("Invalid Option".to_string())),
+            };
+            match option.as_slice() {
+                b"withscores" => with_scores = true,
+                b"limit" => limit = Some(parse_limit(&mut args)?),
+                _ => {
+                    return Err(CommandError::InvalidArguments(format!(
+                        "Unknown option: {}",
+                        String::from_utf8_lossy(&option)
+                    )))
+                }
+            }
+        }
+
+        Ok(Self {
+            range: ZRange {
+                key,
+                by: ZRangeBy::Score(min, max),
+                rev: false,
+                limit,
+                with_scores,
+            },
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZCount {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["zcount"], 3)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let (key, min, max) = extract_score_range(&mut args)?;
+
+        Ok(Self { key, min, max })
+    }
+}
+
+impl CommandExecutor for ZRangeByLex {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let zset = match backend.zset.get(&self.key) {
+            Some(zset) => zset,
+            None => return RespArray::new(vec![]).into(),
+        };
+
+        let (offset, count) = self.limit.unwrap_or((0, -1));
+        if offset < 0 {
+            return RespArray::new(vec![]).into();
+        }
+        let count = if count < 0 {
+            usize::MAX
+        } else {
+            count as usize
+        };
+        let members = zset
+            .range_by_lex(&self.min, &self.max)
+            .skip(offset as usize)
+            .take(count)
+            .collect();
+        members_reply(members, false)
+    }
+}
+
+impl CommandExecutor for ZLexCount {
+    async fn execute(&self, backend: &Backend) -> RespFrame {
+        let count = backend
+            .zset
+            .get(&self.key)
+            .map(|zset| zset.range_by_lex(&self.min, &self.max).count())
+            .unwrap_or(0);
+        RespFrame::Integer(count as i64)
+    }
+}
+
+impl TryFrom<RespArray> for ZRangeByLex {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        let len = arr.len();
+        if len != 4 && len != 7 {
+            return Err(CommandError::InvalidArguments(
+                "ZRANGEBYLEX command must have a key, a min, a max and an optional LIMIT"
+                    .to_string(),
+            ));
+        }
+        validate_command(&arr, &["zrangebylex"], len - 1)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let (key, min, max) = extract_lex_range(&mut args)?;
+
+        let limit = match args.next() {
+            Some(RespFrame::BulkString(option)) if option.eq_ignore_ascii_case(b"limit") => {
+                Some(parse_limit(&mut args)?)
+            }
+            None => None,
+            _ => return Err(CommandError::InvalidArguments("Invalid Option".to_string())),
+        };
+
+        Ok(Self {
+            key,
+            min,
+            max,
+            limit,
+        })
+    }
+}
+
+impl TryFrom<RespArray> for ZLexCount {
+    type Error = CommandError;
+
+    fn try_from(arr: RespArray) -> Result<Self, Self::Error> {
+        validate_command(&arr, &["zlexcount"], 3)?;
+
+        let mut args = extract_args(arr, 1)?.into_iter();
+        let (key, min, max) = extract_lex_range(&mut args)?;
+
+        Ok(Self { key, min, max })
+    }
+}
+
+fn extract_lex_range(
+    args: &mut impl Iterator<Item = RespFrame>,
+) -> Result<(String, LexBound, LexBound), CommandError> {
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+        _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+    };
+    match (args.next(), args.next()) {
+        (Some(min), Some(max)) => Ok((key, parse_lex_bound(min)?, parse_lex_bound(max)?)),
+        _ => Err(CommandError::InvalidArguments("Invalid Range".to_string())),
+    }
+}
+
+// 解析字典序区间的一端："-" 和 "+" 表示无穷小和无穷大，其余必须以 "[" 或 "(" 开头
+fn parse_lex_bound(frame: RespFrame) -> Result<LexBound, CommandError> {
+    let err =
+        || CommandError::InvalidArguments("min or max not valid string range item".to_string());
+    let bound = match frame {
+        RespFrame::BulkString(bound) => String::from_utf8(bound.0.into())?,
+        _ => return Err(err()),
+    };
+    match bound.as_str() {
+        "-" => Ok(LexBound::Min),
+        "+" => Ok(LexBound::Max),
+        _ => match bound.split_at_checked(1) {
+            Some(("[", member)) => Ok(LexBound::Inclusive(member.to_string())),
+            Some(("(", member)) => Ok(LexBound::Exclusive(member.to_string())),
+            _ => Err(err()),
+        },
+    }
+}
+
+fn extract_score_range(
+    args: &mut impl Iterator<Item = RespFrame>,
+) -> Result<(String, ScoreBound, ScoreBound), CommandError> {
+    let key = match args.next() {
+        Some(RespFrame::BulkString(key)) => String::from_utf8(key.0.into())?,
+        _ => return Err(CommandError::InvalidArguments("Invalid Key".to_string())),
+    };
+    match (args.next(), args.next()) {
+        (Some(min), Some(max)) => Ok((key, parse_score_bound(min)?, parse_score_bound(max)?)),
+        _ => Err(CommandError::InvalidArguments("Invalid Range".to_string())),
+    }
+}
+
+fn parse_limit(args: &mut impl Iterator<Item = RespFrame>) -> Result<(i64, i64), CommandError> {
+    match (args.next(), args.next()) {
+        (Some(offset), Some(count)) => Ok((parse_integer(offset)?, parse_integer(count)?)),
+        _ => Err(CommandError::InvalidArguments(
+            "LIMIT requires an offset and a count".to_string(),
+        )),
+    }
+}
+
+// 将 member 列表转换为回复，WITHSCORES 时 member 与分数交替排列
+fn members_reply(members: Vec<(&str, f64)>, with_scores: bool) -> RespFrame {
+    let mut frames = Vec::with_capacity(members.len() * if with_scores { 2 } else { 1 });
+    for (member, score) in members {
+        frames.push(BulkString::new(member).into());
+        if with_scores {
+            frames.push(BulkString::new(format_score(score)).into());
+        }
+    }
+    RespArray::new(frames).into()
+}
+
+// 解析分数区间的一端：支持 "(" 前缀表示开区间以及 -inf/+inf
+fn parse_score_bound(frame: RespFrame) -> Result<ScoreBound, CommandError> {
+    let err = || CommandError::InvalidArguments("min or max is not a float".to_string());
+    let bound = match frame {
+        RespFrame::BulkString(bound) => String::from_utf8(bound.0.into())?,
+        _ => return Err(err()),
+    };
+    let (value, exclusive) = match bound.strip_prefix('(') {
+        Some(value) => (value, true),
+        None => (bound.as_str(), false),
+    };
+    let value = value
+        .parse::<f64>()
+        .ok()
+        .filter(|v| !v.is_nan())
+        .ok_or_else(err)?;
+    Ok(ScoreBound { value, exclusive })
+}
+
+// redis 以文本形式返回分数，整数分数不带小数点，f64 的 Display 输出可以无损地解析回原值
+fn format_score(score: f64) -> String {
+    format!("{}", score)
+}
+
+fn score_frame(score: Option<f64>) -> RespFrame {
+    match score {
+        Some(score) => BulkString::new(format_score(score)).into(),
+        None => RespFrame::Null(RespNull),
+    }
+}
+
+// 与 RespDouble 接受相同的文本格式，包括 inf/-inf，但不接受 NaN
+fn parse_score(frame: RespFrame) -> Result<f64, CommandError> {
+    let score = match frame {
+        RespFrame::BulkString(score) => String::from_utf8(score.0.into())?.parse::<f64>().ok(),
+        RespFrame::Double(score) => Some(score.value()),
+        RespFrame::Integer(score) => Some(score as f64),
+        _ => None,
+    };
+    match score {
+        Some(score) if !score.is_nan() => Ok(score),
+        _ => Err(CommandError::InvalidArguments(
+            "value is not a valid float".to_string(),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::RespDecoder;
+
+    use super::*;
+    use anyhow::Result;
+    use bytes::BytesMut;
+
+    fn zadd(options: ZAddOptions, members: &[(f64, &str)]) -> ZAdd {
+        ZAdd {
+            key: "myzset".to_string(),
+            options,
+            members: members.iter().map(|(s, m)| (*s, m.to_string())).collect(),
+        }
+    }
+
+    #[test]
+    fn test_zadd_try_from() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*8\r\n$4\r\nzadd\r\n$6\r\nmyzset\r\n$2\r\nNX\r\n$2\r\nch\r\n$1\r\n1\r\n$3\r\none\r\n$4\r\n-inf\r\n$3\r\ntwo\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZAdd::try_from(frame)?;
+        assert_eq!(cmd.key, "myzset");
+        assert_eq!(
+            cmd.options,
+            ZAddOptions {
+                nx: true,
+                ch: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            cmd.members,
+            vec![
+                (1.0, "one".to_string()),
+                (f64::NEG_INFINITY, "two".to_string())
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zadd_invalid_arguments() -> Result<()> {
+        let cases = [
+            "*4\r\n$4\r\nzadd\r\n$6\r\nmyzset\r\n$3\r\nabc\r\n$3\r\none\r\n",
+            "*4\r\n$4\r\nzadd\r\n$6\r\nmyzset\r\n$3\r\nnan\r\n$3\r\none\r\n",
+            "*5\r\n$4\r\nzadd\r\n$6\r\nmyzset\r\n$1\r\n1\r\n$3\r\none\r\n$1\r\n2\r\n",
+            "*6\r\n$4\r\nzadd\r\n$6\r\nmyzset\r\n$2\r\nnx\r\n$2\r\nxx\r\n$1\r\n1\r\n$3\r\none\r\n",
+            "*6\r\n$4\r\nzadd\r\n$6\r\nmyzset\r\n$2\r\ngt\r\n$2\r\nlt\r\n$1\r\n1\r\n$3\r\none\r\n",
+            "*4\r\n$4\r\nzadd\r\n$6\r\nmyzset\r\n$2\r\nnx\r\n$1\r\n1\r\n",
+        ];
+        for case in cases {
+            let mut buf = BytesMut::from(case);
+            let frame = RespArray::decode(&mut buf)?;
+            assert!(ZAdd::try_from(frame).is_err(), "{:?}", case);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zadd_command() -> Result<()> {
+        let backend = Backend::new();
+
+        let cmd = zadd(ZAddOptions::default(), &[(1.0, "one"), (2.0, "two")]);
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(2));
+
+        let cmd = zadd(
+            ZAddOptions::default(),
+            &[(3.0, "two"), (f64::INFINITY, "inf")],
+        );
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+
+        let zset = backend.zset.get("myzset").unwrap();
+        assert_eq!(
+            zset.iter().collect::<Vec<_>>(),
+            vec![("one", 1.0), ("two", 3.0), ("inf", f64::INFINITY)]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zadd_nx_xx() -> Result<()> {
+        let backend = Backend::new();
+
+        let xx = ZAddOptions {
+            xx: true,
+            ..Default::default()
+        };
+        let cmd = zadd(xx, &[(1.0, "one")]);
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+        assert!(!backend.zset.contains_key("myzset"));
+
+        let cmd = zadd(ZAddOptions::default(), &[(1.0, "one")]);
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+
+        let nx = ZAddOptions {
+            nx: true,
+            ..Default::default()
+        };
+        let cmd = zadd(nx, &[(5.0, "one"), (2.0, "two")]);
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+        assert_eq!(backend.zset.get("myzset").unwrap().score("one"), Some(1.0));
+
+        let xx_ch = ZAddOptions {
+            xx: true,
+            ch: true,
+            ..Default::default()
+        };
+        let cmd = zadd(xx_ch, &[(5.0, "one"), (3.0, "three")]);
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+        let zset = backend.zset.get("myzset").unwrap();
+        assert_eq!(zset.score("one"), Some(5.0));
+        assert_eq!(zset.score("three"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zscore_zmscore_try_from() -> Result<()> {
+        let mut buf = BytesMut::from("*3\r\n$6\r\nzscore\r\n$6\r\nmyzset\r\n$3\r\none\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZScore::try_from(frame)?;
+        assert_eq!(cmd.key, "myzset");
+        assert_eq!(cmd.member, "one");
+
+        let mut buf =
+            BytesMut::from("*4\r\n$7\r\nzmscore\r\n$6\r\nmyzset\r\n$3\r\none\r\n$3\r\ntwo\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZMScore::try_from(frame)?;
+        assert_eq!(cmd.key, "myzset");
+        assert_eq!(cmd.members, vec!["one", "two"]);
+
+        let mut buf = BytesMut::from("*2\r\n$7\r\nzmscore\r\n$6\r\nmyzset\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZMScore::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zscore_command() -> Result<()> {
+        let backend = Backend::new();
+        zadd(
+            ZAddOptions::default(),
+            &[
+                (1.0, "one"),
+                (2.5, "two"),
+                (f64::INFINITY, "inf"),
+                (f64::NEG_INFINITY, "ninf"),
+                (0.1, "tenth"),
+            ],
+        )
+        .execute(&backend)
+        .await;
+
+        let cases = [
+            ("one", b"1".as_slice()),
+            ("two", b"2.5"),
+            ("inf", b"inf"),
+            ("ninf", b"-inf"),
+            ("tenth", b"0.1"),
+        ];
+        for (member, expected) in cases {
+            let cmd = ZScore {
+                key: "myzset".to_string(),
+                member: member.to_string(),
+            };
+            assert_eq!(
+                cmd.execute(&backend).await,
+                BulkString::from(expected).into()
+            );
+        }
+
+        let cmd = ZScore {
+            key: "myzset".to_string(),
+            member: "missing".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Null(RespNull));
+
+        let cmd = ZScore {
+            key: "missing".to_string(),
+            member: "one".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Null(RespNull));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zmscore_command() -> Result<()> {
+        let backend = Backend::new();
+        zadd(
+            ZAddOptions::default(),
+            &[(1.0, "one"), (f64::INFINITY, "inf")],
+        )
+        .execute(&backend)
+        .await;
+
+        let cmd = ZMScore {
+            key: "myzset".to_string(),
+            members: vec!["one".to_string(), "missing".to_string(), "inf".to_string()],
+        };
+        let expected = RespArray::new(vec![b"1".into(), RespFrame::Null(RespNull), b"inf".into()]);
+        assert_eq!(cmd.execute(&backend).await, expected.into());
+
+        let cmd = ZMScore {
+            key: "missing".to_string(),
+            members: vec!["one".to_string()],
+        };
+        let expected = RespArray::new(vec![RespFrame::Null(RespNull)]);
+        assert_eq!(cmd.execute(&backend).await, expected.into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zcard_command() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n$5\r\nzcard\r\n$6\r\nmyzset\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZCard::try_from(frame)?;
+        assert_eq!(cmd.key, "myzset");
+
+        let backend = Backend::new();
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+
+        zadd(
+            ZAddOptions::default(),
+            &[(1.0, "a"), (2.0, "b"), (2.0, "c")],
+        )
+        .execute(&backend)
+        .await;
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(3));
+
+        backend.set("str".to_string(), RespFrame::BulkString(b"v".into()));
+        let cmd = ZCard {
+            key: "str".to_string(),
+        };
+        assert_eq!(
+            cmd.execute(&backend).await,
+            SimpleError::new("WRONGTYPE Operation against a key holding the wrong kind of value")
+                .into()
+        );
+
+        let mut buf = BytesMut::from("*1\r\n$5\r\nzcard\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZCard::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrange_try_from() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*4\r\n$6\r\nzrange\r\n$6\r\nmyzset\r\n$1\r\n0\r\n$2\r\n-1\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZRange::try_from(frame)?;
+        assert_eq!(cmd.key, "myzset");
+        assert_eq!(cmd.by, ZRangeBy::Rank(0, -1));
+        assert!(!cmd.rev && !cmd.with_scores);
+        assert_eq!(cmd.limit, None);
+
+        let mut buf = BytesMut::from(
+            "*10\r\n$6\r\nzrange\r\n$6\r\nmyzset\r\n$2\r\n(5\r\n$4\r\n-inf\r\n$3\r\nREV\r\n$7\r\nBYSCORE\r\n$5\r\nLIMIT\r\n$1\r\n1\r\n$1\r\n2\r\n$10\r\nWITHSCORES\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZRange::try_from(frame)?;
+        assert_eq!(
+            cmd.by,
+            ZRangeBy::Score(
+                ScoreBound::inclusive(f64::NEG_INFINITY),
+                ScoreBound::exclusive(5.0)
+            )
+        );
+        assert!(cmd.rev && cmd.with_scores);
+        assert_eq!(cmd.limit, Some((1, 2)));
+
+        let cases = [
+            "*7\r\n$6\r\nzrange\r\n$6\r\nmyzset\r\n$1\r\n0\r\n$2\r\n-1\r\n$5\r\nlimit\r\n$1\r\n0\r\n$1\r\n1\r\n",
+            "*5\r\n$6\r\nzrange\r\n$6\r\nmyzset\r\n$1\r\na\r\n$2\r\n-1\r\n$7\r\nbyscore\r\n",
+            "*4\r\n$6\r\nzrange\r\n$6\r\nmyzset\r\n$2\r\n(1\r\n$1\r\n2\r\n",
+            "*5\r\n$6\r\nzrange\r\n$6\r\nmyzset\r\n$1\r\n0\r\n$1\r\n1\r\n$3\r\nfoo\r\n",
+        ];
+        for case in cases {
+            let mut buf = BytesMut::from(case);
+            let frame = RespArray::decode(&mut buf)?;
+            assert!(ZRange::try_from(frame).is_err(), "{:?}", case);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zrange_command() -> Result<()> {
+        let backend = Backend::new();
+        zadd(
+            ZAddOptions::default(),
+            &[(1.0, "a"), (2.0, "b"), (3.0, "c"), (4.0, "d"), (5.0, "e")],
+        )
+        .execute(&backend)
+        .await;
+
+        let inc = ScoreBound::inclusive;
+        let exc = ScoreBound::exclusive;
+        let inf = f64::INFINITY;
+        // (by, rev, limit, withscores, expected)
+        type Case = (ZRangeBy, bool, Option<(i64, i64)>, bool, Vec<&'static str>);
+        let cases: Vec<Case> = vec![
+            (
+                ZRangeBy::Rank(0, -1),
+                false,
+                None,
+                false,
+                vec!["a", "b", "c", "d", "e"],
+            ),
+            (ZRangeBy::Rank(-2, 10), false, None, false, vec!["d", "e"]),
+            (ZRangeBy::Rank(1, 2), true, None, false, vec!["d", "c"]),
+            (ZRangeBy::Rank(3, 1), false, None, false, vec![]),
+            (
+                ZRangeBy::Rank(0, 1),
+                false,
+                None,
+                true,
+                vec!["a", "1", "b", "2"],
+            ),
+            (
+                ZRangeBy::Score(exc(1.0), inc(3.0)),
+                false,
+                None,
+                false,
+                vec!["b", "c"],
+            ),
+            (
+                ZRangeBy::Score(exc(2.0), inc(4.0)),
+                true,
+                None,
+                false,
+                vec!["d", "c"],
+            ),
+            (
+                ZRangeBy::Score(inc(-inf), inc(inf)),
+                false,
+                Some((1, 2)),
+                false,
+                vec!["b", "c"],
+            ),
+            (
+                ZRangeBy::Score(inc(-inf), inc(inf)),
+                true,
+                Some((0, 2)),
+                true,
+                vec!["e", "5", "d", "4"],
+            ),
+            (
+                ZRangeBy::Score(inc(2.0), inc(4.0)),
+                false,
+                Some((1, -1)),
+                false,
+                vec!["c", "d"],
+            ),
+            (
+                ZRangeBy::Score(exc(3.0), exc(3.0)),
+                false,
+                None,
+                false,
+                vec![],
+            ),
+        ];
+
+        for (by, rev, limit, with_scores, expected) in cases {
+            let desc = format!("{:?} rev={} limit={:?}", by, rev, limit);
+            let cmd = ZRange {
+                key: "myzset".to_string(),
+                by,
+                rev,
+                limit,
+                with_scores,
+            };
+            let expected = expected
+                .into_iter()
+                .map(|v| BulkString::new(v).into())
+                .collect::<Vec<RespFrame>>();
+            assert_eq!(
+                cmd.execute(&backend).await,
+                RespArray::new(expected).into(),
+                "{}",
+                desc
+            );
+        }
+
+        let cmd = ZRange {
+            key: "missing".to_string(),
+            by: ZRangeBy::Rank(0, -1),
+            rev: false,
+            limit: None,
+            with_scores: false,
+        };
+        assert_eq!(cmd.execute(&backend).await, RespArray::new(vec![]).into());
+
+        Ok(())
+    }
+
+    fn score_bound(bound: &str) -> Result<ScoreBound, CommandError> {
+        parse_score_bound(BulkString::new(bound).into())
+    }
+
+    #[test]
+    fn test_parse_score_bound() -> Result<()> {
+        assert_eq!(score_bound("1.5")?, ScoreBound::inclusive(1.5));
+        assert_eq!(score_bound("(1.5")?, ScoreBound::exclusive(1.5));
+        assert_eq!(
+            score_bound("-inf")?,
+            ScoreBound::inclusive(f64::NEG_INFINITY)
+        );
+        assert_eq!(score_bound("+inf")?, ScoreBound::inclusive(f64::INFINITY));
+        assert_eq!(score_bound("(inf")?, ScoreBound::exclusive(f64::INFINITY));
+
+        for bound in ["", "(", "abc", "((1", "nan", "(nan"] {
+            assert!(score_bound(bound).is_err(), "{:?}", bound);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrangebyscore_try_from() -> Result<()> {
+        let mut buf = BytesMut::from(
+            "*4\r\n$13\r\nzrangebyscore\r\n$6\r\nmyzset\r\n$4\r\n-inf\r\n$2\r\n(3\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZRangeByScore::try_from(frame)?;
+        assert_eq!(cmd.range.key, "myzset");
+        assert_eq!(
+            cmd.range.by,
+            ZRangeBy::Score(
+                ScoreBound::inclusive(f64::NEG_INFINITY),
+                ScoreBound::exclusive(3.0)
+            )
+        );
+        assert!(!cmd.range.with_scores);
+        assert_eq!(cmd.range.limit, None);
+
+        let mut buf = BytesMut::from(
+            "*8\r\n$13\r\nzrangebyscore\r\n$6\r\nmyzset\r\n$1\r\n1\r\n$1\r\n5\r\n$10\r\nWITHSCORES\r\n$5\r\nLIMIT\r\n$1\r\n0\r\n$1\r\n2\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZRangeByScore::try_from(frame)?;
+        assert!(cmd.range.with_scores);
+        assert_eq!(cmd.range.limit, Some((0, 2)));
+
+        let mut buf = BytesMut::from(
+            "*5\r\n$13\r\nzrangebyscore\r\n$6\r\nmyzset\r\n$1\r\n1\r\n$1\r\n5\r\n$3\r\nrev\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZRangeByScore::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zrangebyscore_and_zcount_boundaries() -> Result<()> {
+        let backend = Backend::new();
+        zadd(
+            ZAddOptions::default(),
+            &[(1.0, "a"), (2.0, "b"), (2.0, "c"), (3.0, "d")],
+        )
+        .execute(&backend)
+        .await;
+
+        let inc = ScoreBound::inclusive;
+        let exc = ScoreBound::exclusive;
+        let inf = f64::INFINITY;
+        let cases = [
+            (inc(2.0), inc(3.0), vec!["b", "c", "d"]),
+            (exc(2.0), inc(3.0), vec!["d"]),
+            (inc(1.0), exc(2.0), vec!["a"]),
+            (inc(2.0), inc(2.0), vec!["b", "c"]),
+            (exc(2.0), inc(2.0), vec![]),
+            (inc(-inf), inc(inf), vec!["a", "b", "c", "d"]),
+            (exc(3.0), inc(inf), vec![]),
+        ];
+
+        for (min, max, expected) in cases {
+            let cmd = ZRangeByScore {
+                range: ZRange {
+                    key: "myzset".to_string(),
+                    by: ZRangeBy::Score(min, max),
+                    rev: false,
+                    limit: None,
+                    with_scores: false,
+                },
+            };
+            let members = expected
+                .iter()
+                .map(|v| BulkString::new(*v).into())
+                .collect::<Vec<RespFrame>>();
+            assert_eq!(
+                cmd.execute(&backend).await,
+                RespArray::new(members).into(),
+                "{:?} {:?}",
+                min,
+                max
+            );
+
+            let cmd = ZCount {
+                key: "myzset".to_string(),
+                min,
+                max,
+            };
+            assert_eq!(
+                cmd.execute(&backend).await,
+                RespFrame::Integer(expected.len() as i64),
+                "{:?} {:?}",
+                min,
+                max
+            );
+        }
+
+        let cmd = ZCount {
+            key: "missing".to_string(),
+            min: inc(-inf),
+            max: inc(inf),
+        };
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(0));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zcount_try_from() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*4\r\n$6\r\nzcount\r\n$6\r\nmyzset\r\n$1\r\n1\r\n$2\r\n(3\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZCount::try_from(frame)?;
+        assert_eq!(cmd.key, "myzset");
+        assert_eq!(cmd.min, ScoreBound::inclusive(1.0));
+        assert_eq!(cmd.max, ScoreBound::exclusive(3.0));
+
+        let mut buf =
+            BytesMut::from("*4\r\n$6\r\nzcount\r\n$6\r\nmyzset\r\n$1\r\nx\r\n$2\r\n(3\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZCount::try_from(frame).is_err());
+
+        Ok(())
+    }
+
+    fn lex_bound(bound: &str) -> Result<LexBound, CommandError> {
+        parse_lex_bound(BulkString::new(bound).into())
+    }
+
+    #[test]
+    fn test_parse_lex_bound() -> Result<()> {
+        assert_eq!(lex_bound("-")?, LexBound::Min);
+        assert_eq!(lex_bound("+")?, LexBound::Max);
+        assert_eq!(lex_bound("[a")?, LexBound::Inclusive("a".to_string()));
+        assert_eq!(lex_bound("(a")?, LexBound::Exclusive("a".to_string()));
+        assert_eq!(lex_bound("[")?, LexBound::Inclusive("".to_string()));
+        assert_eq!(lex_bound("[-")?, LexBound::Inclusive("-".to_string()));
+
+        for bound in ["", "a", "--", "+a", "{a"] {
+            assert!(lex_bound(bound).is_err(), "{:?}", bound);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_zrangebylex_try_from() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*4\r\n$11\r\nzrangebylex\r\n$6\r\nmyzset\r\n$1\r\n-\r\n$2\r\n[c\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZRangeByLex::try_from(frame)?;
+        assert_eq!(cmd.key, "myzset");
+        assert_eq!(cmd.min, LexBound::Min);
+        assert_eq!(cmd.max, LexBound::Inclusive("c".to_string()));
+        assert_eq!(cmd.limit, None);
+
+        let mut buf = BytesMut::from(
+            "*7\r\n$11\r\nzrangebylex\r\n$6\r\nmyzset\r\n$1\r\n-\r\n$1\r\n+\r\n$5\r\nLIMIT\r\n$1\r\n1\r\n$1\r\n2\r\n",
+        );
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZRangeByLex::try_from(frame)?;
+        assert_eq!(cmd.limit, Some((1, 2)));
+
+        let mut buf =
+            BytesMut::from("*4\r\n$11\r\nzrangebylex\r\n$6\r\nmyzset\r\n$1\r\na\r\n$2\r\n[c\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert!(ZRangeByLex::try_from(frame).is_err());
+
+        let mut buf =
+            BytesMut::from("*4\r\n$9\r\nzlexcount\r\n$6\r\nmyzset\r\n$1\r\n-\r\n$1\r\n+\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZLexCount::try_from(frame)?;
+        assert_eq!((cmd.min, cmd.max), (LexBound::Min, LexBound::Max));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zrangebylex_and_zlexcount() -> Result<()> {
+        let backend = Backend::new();
+        let members = ["a", "b", "c", "d", "e", "f", "g"]
+            .iter()
+            .map(|m| (0.0, *m))
+            .collect::<Vec<_>>();
+        zadd(ZAddOptions::default(), &members)
+            .execute(&backend)
+            .await;
+
+        let cases = [
+            ("-", "[c", None, vec!["a", "b", "c"]),
+            ("-", "(c", None, vec!["a", "b"]),
+            ("[aaa", "(g", None, vec!["b", "c", "d", "e", "f"]),
+            ("(b", "[d", None, vec!["c", "d"]),
+            ("[b", "[b", None, vec!["b"]),
+            ("(b", "(b", None, vec![]),
+            ("+", "-", None, vec![]),
+            ("-", "+", Some((2, 3)), vec!["c", "d", "e"]),
+            ("-", "+", Some((5, -1)), vec!["f", "g"]),
+        ];
+
+        for (min, max, limit, expected) in cases {
+            let cmd = ZRangeByLex {
+                key: "myzset".to_string(),
+                min: lex_bound(min)?,
+                max: lex_bound(max)?,
+                limit,
+            };
+            let frames = expected
+                .iter()
+                .map(|v| BulkString::new(*v).into())
+                .collect::<Vec<RespFrame>>();
+            assert_eq!(
+                cmd.execute(&backend).await,
+                RespArray::new(frames).into(),
+                "{} {} {:?}",
+                min,
+                max,
+                limit
+            );
+
+            if limit.is_none() {
+                let cmd = ZLexCount {
+                    key: "myzset".to_string(),
+                    min: lex_bound(min)?,
+                    max: lex_bound(max)?,
+                };
+                assert_eq!(
+                    cmd.execute(&backend).await,
+                    RespFrame::Integer(expected.len() as i64),
+                    "{} {}",
+                    min,
+                    max
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_format_score_round_trip() {
+        for score in [1.0, -3.0, 0.1, 1.0 / 3.0, 1e20, 1.5e-7, f64::INFINITY] {
+            let text = format_score(score);
+            assert_eq!(text.parse::<f64>().unwrap(), score, "{}", text);
+        }
+        assert_eq!(format_score(42.0), "42");
+    }
+
+    #[tokio::test]
+    async fn test_zadd_gt_lt() -> Result<()> {
+        let backend = Backend::new();
+        zadd(ZAddOptions::default(), &[(5.0, "a"), (5.0, "b")])
+            .execute(&backend)
+            .await;
+
+        let gt = ZAddOptions {
+            gt: true,
+            ch: true,
+            ..Default::default()
+        };
+        let cmd = zadd(gt, &[(3.0, "a"), (8.0, "b")]);
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+
+        let lt = ZAddOptions {
+            lt: true,
+            ch: true,
+            ..Default::default()
+        };
+        let cmd = zadd(lt, &[(3.0, "a"), (9.0, "b")]);
+        assert_eq!(cmd.execute(&backend).await, RespFrame::Integer(1));
+
+        let zset = backend.zset.get("myzset").unwrap();
+        assert_eq!(zset.score("a"), Some(3.0));
+        assert_eq!(zset.score("b"), Some(8.0));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zincrby_command() -> Result<()> {
+        let mut buf =
+            BytesMut::from("*4\r\n$7\r\nzincrby\r\n$6\r\nmyzset\r\n$3\r\n2.5\r\n$3\r\none\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        let cmd = ZIncrBy::try_from(frame)?;
+        assert_eq!(cmd.key, "myzset");
+        assert_eq!(cmd.increment, 2.5);
+        assert_eq!(cmd.member, "one");
+
+        let backend = Backend::new();
+        let incr = |increment, member: &str| ZIncrBy {
+            key: "myzset".to_string(),
+            increment,
+            member: member.to_string(),
+        };
+
+        // member 不存在时以 increment 作为分数加入
+        assert_eq!(
+            incr(2.5, "one").execute(&backend).await,
+            RespDouble::new(2.5).into()
+        );
+        zadd(ZAddOptions::default(), &[(5.0, "two")])
+            .execute(&backend)
+            .await;
+        assert_eq!(
+            incr(4.0, "one").execute(&backend).await,
+            RespDouble::new(6.5).into()
+        );
+
+        // 有序索引同步更新
+        let zset = backend.zset.get("myzset").unwrap();
+        assert_eq!(
+            zset.iter().collect::<Vec<_>>(),
+            [("two", 5.0), ("one", 6.5)]
+        );
+        drop(zset);
+
+        assert_eq!(
+            ZCard {
+                key: "myzset".to_string()
+            }
+            .execute(&backend)
+            .await,
+            RespFrame::Integer(2)
+        );
+
+        backend.set("str".to_string(), b"v".into());
+        let cmd = ZIncrBy {
+            key: "str".to_string(),
+            increment: 1.0,
+            member: "one".to_string(),
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zrangebyscore_limit_paging() -> Result<()> {
+        let backend = Backend::new();
+        zadd(
+            ZAddOptions::default(),
+            &[(1.0, "a"), (2.0, "b"), (3.0, "c"), (4.0, "d"), (5.0, "e")],
+        )
+        .execute(&backend)
+        .await;
+
+        let run = |args: &[&str]| {
+            let frames = ["zrangebyscore", "myzset"]
+                .iter()
+                .chain(args)
+                .map(|arg| arg.as_bytes().into())
+                .collect::<Vec<RespFrame>>();
+            ZRangeByScore::try_from(RespArray::new(frames))
+        };
+        let members = |members: &[&str]| {
+            RespArray::new(
+                members
+                    .iter()
+                    .map(|m| m.as_bytes().into())
+                    .collect::<Vec<RespFrame>>(),
+            )
+            .into()
+        };
+
+        // 开区间的下界排除 a，两页各两个 member
+        let page = run(&["(1", "+inf", "LIMIT", "0", "2"])?;
+        assert_eq!(page.execute(&backend).await, members(&["b", "c"]));
+        let page = run(&["(1", "+inf", "LIMIT", "2", "2"])?;
+        assert_eq!(page.execute(&backend).await, members(&["d", "e"]));
+
+        // count 为负数时返回 offset 之后的全部 member
+        let page = run(&["-inf", "(5", "LIMIT", "1", "-1"])?;
+        assert_eq!(page.execute(&backend).await, members(&["b", "c", "d"]));
+
+        let page = run(&["2", "3", "WITHSCORES", "LIMIT", "1", "1"])?;
+        assert_eq!(page.execute(&backend).await, members(&["c", "3"]));
+
+        assert!(run(&["abc", "3"]).is_err());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_zadd_wrong_type() -> Result<()> {
+        let backend = Backend::new();
+        backend.set("string".to_string(), b"value".into());
+
+        let cmd = ZAdd {
+            key: "string".to_string(),
+            options: ZAddOptions::default(),
+            members: vec![(1.0, "one".to_string())],
+        };
+        assert_eq!(cmd.execute(&backend).await, RESP_WRONGTYPE.clone());
+        assert!(!backend.zset.contains_key("string"));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,137 @@
+// 与 redis 的 stringmatchlen 相同的 glob 匹配：支持 *、?、[abc]、[^abc]、[a-z] 和 \ 转义
+// 迭代匹配，失败时只回溯到最近的一个 *，复杂度为 O(len(pattern) * len(string))
+pub fn glob_match(pattern: &[u8], string: &[u8]) -> bool {
+    let (mut p, mut s) = (0, 0);
+    // 最近一个 * 之后的 pattern 位置，以及这个 * 吞掉的 string 结尾
+    let mut backtrack = None;
+    while s < string.len() {
+        if pattern.get(p) == Some(&b'*') {
+            p += 1;
+            backtrack = Some((p, s));
+            continue;
+        }
+        if let Some(len) = match_one(&pattern[p..], string[s]) {
+            p += len;
+            s += 1;
+            continue;
+        }
+        // 让最近的 * 多吞一个字符再试，更早的 * 不需要回溯
+        match backtrack {
+            Some((star_p, star_s)) => {
+                p = star_p;
+                s = star_s + 1;
+                backtrack = Some((star_p, s));
+            }
+            None => return false,
+        }
+    }
+    pattern[p..].iter().all(|&c| c == b'*')
+}
+
+// 用 pattern 开头的一个元素匹配字符 c，匹配时返回这个元素在 pattern 中的长度
+fn match_one(pattern: &[u8], c: u8) -> Option<usize> {
+    let (matched, len) = match pattern {
+        [] | [b'*', ..] => return None,
+        [b'?', ..] => (true, 1),
+        [b'[', rest @ ..] => {
+            let (matched, rest) = match_class(rest, c);
+            (matched, pattern.len() - rest.len())
+        }
+        [b'\\', escaped, ..] => (*escaped == c, 2),
+        [other, ..] => (*other == c, 1),
+    };
+    matched.then_some(len)
+}
+
+// 匹配 [...] 中的字符集合，返回是否匹配以及 ] 之后剩余的 pattern。
+// 没有闭合的 ] 时与 redis 一样把 pattern 的结尾当作集合的结束
+fn match_class(pattern: &[u8], c: u8) -> (bool, &[u8]) {
+    let (negate, mut pattern) = match pattern.split_first() {
+        Some((b'^', rest)) => (true, rest),
+        _ => (false, pattern),
+    };
+    let mut matched = false;
+    loop {
+        match pattern {
+            [] => break,
+            [b']', rest @ ..] => {
+                pattern = rest;
+                break;
+            }
+            [b'\\', escaped, rest @ ..] => {
+                matched |= *escaped == c;
+                pattern = rest;
+            }
+            [start, b'-', end, rest @ ..] if *end != b']' => {
+                let (low, high) = if start <= end {
+                    (*start, *end)
+                } else {
+                    (*end, *start)
+                };
+                matched |= (low..=high).contains(&c);
+                pattern = rest;
+            }
+            [other, rest @ ..] => {
+                matched |= *other == c;
+                pattern = rest;
+            }
+        }
+    }
+    (matched != negate, pattern)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        let cases: &[(&str, &str, bool)] = &[
+            ("*", "anything", true),
+            ("*", "", true),
+            ("news.*", "news.sports", true),
+            ("news.*", "weather", false),
+            ("h?llo", "hello", true),
+            ("h?llo", "hllo", false),
+            ("h[ae]llo", "hallo", true),
+            ("h[ae]llo", "hillo", false),
+            ("h[^e]llo", "hallo", true),
+            ("h[^e]llo", "hello", false),
+            ("h[a-c]llo", "hbllo", true),
+            ("h[a-c]llo", "hdllo", false),
+            ("h\\*llo", "h*llo", true),
+            ("h\\*llo", "hello", false),
+            ("a*b*c", "axxbyyc", true),
+            ("a*b*c", "axxbyy", false),
+            ("**a", "ba", true),
+            ("a*", "a", true),
+            ("*b", "", false),
+            ("[", "", false),
+            ("[a", "a", true),
+            ("a\\", "a\\", true),
+            ("*[ab]c", "xxbc", true),
+            ("*?", "", false),
+            ("a*a", "a", false),
+        ];
+        for (pattern, string, expected) in cases {
+            assert_eq!(
+                glob_match(pattern.as_bytes(), string.as_bytes()),
+                *expected,
+                "{} {}",
+                pattern,
+                string
+            );
+        }
+    }
+
+    #[test]
+    fn test_glob_match_many_stars_is_linear() {
+        // 递归回溯时这里是指数时间，80 个字节就要十几秒
+        let string = vec![b'a'; 100_000];
+        assert!(!glob_match(b"*a*a*a*a*a*a*b", &string));
+        assert!(glob_match(b"*a*a*a*a*a*a*", &string));
+        let mut string = string;
+        string.push(b'b');
+        assert!(glob_match(b"*a*a*a*a*a*a*b", &string));
+    }
+}
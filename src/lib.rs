@@ -1,8 +1,22 @@
 mod backend;
+mod glob;
+mod notify;
+mod pubsub;
 mod resp;
+mod server;
+mod session;
+mod slowlog;
+mod tracking;
 
 pub mod cmd;
 pub mod network;
 
 pub use backend::*;
+pub use glob::glob_match;
+pub use notify::{EventClass, NotifyFlags};
+pub use pubsub::*;
 pub use resp::*;
+pub use server::*;
+pub use session::*;
+pub use slowlog::*;
+pub use tracking::Tracking;
@@ -1,31 +1,62 @@
-use anyhow::Result;
-use simple_redis::{network, Backend};
-use tokio::net::TcpListener;
+use anyhow::{bail, Context, Result};
+use simple_redis::{network, Databases, Server, ServerConfig};
+use tokio::net::{TcpListener, UnixListener};
 use tracing::info;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     tracing_subscriber::fmt::init();
 
-    let addr = "0.0.0.0:6379";
-    let listener = TcpListener::bind(addr).await?;
-    info!("Listening on: {}", addr);
+    let config = parse_args(std::env::args().skip(1))?;
+    let tcp = match config.port {
+        0 => None,
+        port => {
+            let addr = format!("0.0.0.0:{}", port);
+            let listener = TcpListener::bind(&addr).await?;
+            info!("Listening on: {}", addr);
+            Some(listener)
+        }
+    };
+    let unix = match &config.unixsocket {
+        Some(path) => {
+            // 与 redis 一致，启动前删除上次留下的 socket 文件
+            let _ = std::fs::remove_file(path);
+            let listener = UnixListener::bind(path)?;
+            info!("Listening on: {}", path.display());
+            Some(listener)
+        }
+        None => None,
+    };
 
-    let backend = Backend::new();
+    let unixsocket = config.unixsocket.clone();
+    let server = Server::with_config(Databases::default(), config);
+    let result = match (tcp, unix) {
+        (Some(tcp), Some(unix)) => network::serve_both(tcp, unix, server).await,
+        (Some(tcp), None) => network::serve(tcp, server).await,
+        (None, Some(unix)) => network::serve(unix, server).await,
+        (None, None) => bail!("Nothing to listen on, set --port or --unixsocket"),
+    };
+    if let Some(path) = unixsocket {
+        let _ = std::fs::remove_file(path);
+    }
+    result
+}
 
-    loop {
-        let (stream, remote_addr) = listener.accept().await?;
-        info!("Accepted connection from: {}", remote_addr);
-        let cloned_backend = backend.clone();
-        tokio::spawn(async move {
-            match network::process_stream(stream, cloned_backend).await {
-                Ok(_) => {
-                    info!("Connection closed: {}", remote_addr);
-                }
-                Err(e) => {
-                    info!("Connection closed with error: {:?}: {}", e, remote_addr);
-                }
+// 与 redis-server 一样用 --port 和 --unixsocket 指定监听的地址
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<ServerConfig> {
+    let mut config = ServerConfig::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--port" => {
+                let port = args.next().context("--port requires a value")?;
+                config.port = port.parse().context("Invalid port")?;
+            }
+            "--unixsocket" => {
+                let path = args.next().context("--unixsocket requires a value")?;
+                config.unixsocket = Some(path.into());
             }
-        });
+            _ => bail!("Unknown argument: {}", arg),
+        }
     }
+    Ok(config)
 }
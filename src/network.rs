@@ -1,47 +1,361 @@
+use std::{
+    future::Future,
+    io,
+    net::SocketAddr,
+    time::{Duration, Instant},
+};
+
 use anyhow::Result;
 use futures::SinkExt;
-use tokio::net::TcpStream;
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, TcpStream},
+};
 use tokio_stream::StreamExt;
 use tokio_util::codec::{Decoder, Encoder, Framed};
 use tracing::info;
 
 use crate::{
-    cmd::{Command, CommandExecutor as _},
-    Backend, RespDecoder as _, RespEncoder, RespError, RespFrame,
+    cmd::{
+        exec_transaction, invalidate_frame, lookup_command, message_frame, monitor_line, queued,
+        Command, CommandExecutor as _, CommandSpec,
+    },
+    slowlog_args, QueuedCommand, RespArray, RespDecoder as _, RespError, RespFrame, RespLimits,
+    RespVersion, Server, Session, SessionPush, SimpleError, SimpleString,
 };
 
+// 编码时按照连接协商的协议版本输出，RESP2 下会降级 RESP3 特有的类型
 #[derive(Debug)]
-struct RespFrameCodec;
+struct RespFrameCodec {
+    protocol: RespVersion,
+    // 解析时的嵌套层数、长度和元素个数限制
+    limits: RespLimits,
+}
+
+// 订阅了频道的连接只能执行这些命令
+const SUBSCRIBE_MODE_COMMANDS: &[&str] = &[
+    "subscribe",
+    "unsubscribe",
+    "psubscribe",
+    "punsubscribe",
+    "ping",
+    "quit",
+    "reset",
+];
+
+const ACTIVE_EXPIRE_INTERVAL: Duration = Duration::from_millis(100);
 
-pub async fn process_stream(stream: TcpStream, backend: Backend) -> Result<()> {
-    let mut frames = Framed::new(stream, RespFrameCodec);
+// MULTI 之后直接执行而不是排队的命令
+const TRANSACTION_COMMANDS: &[&str] = &["multi", "exec", "discard", "watch", "quit", "reset"];
+
+// serve 可以接受连接的监听器。TCP 连接带有对端地址，Unix socket 连接没有
+pub trait Listener {
+    type Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static;
+
+    fn accept(&self)
+        -> impl Future<Output = io::Result<(Self::Stream, Option<SocketAddr>)>> + Send;
+}
+
+impl Listener for TcpListener {
+    type Stream = TcpStream;
+
+    async fn accept(&self) -> io::Result<(TcpStream, Option<SocketAddr>)> {
+        let (stream, addr) = TcpListener::accept(self).await?;
+        Ok((stream, Some(addr)))
+    }
+}
+
+#[cfg(unix)]
+impl Listener for tokio::net::UnixListener {
+    type Stream = tokio::net::UnixStream;
+
+    async fn accept(&self) -> io::Result<(tokio::net::UnixStream, Option<SocketAddr>)> {
+        let (stream, _) = tokio::net::UnixListener::accept(self).await?;
+        Ok((stream, None))
+    }
+}
+
+// 接受连接直到 SHUTDOWN，每个连接在独立的任务中处理
+pub async fn serve(listener: impl Listener, server: Server) -> Result<()> {
+    tokio::spawn(active_expire(server.clone()));
+    accept_loop(listener, server).await
+}
+
+// 同时监听 TCP 和 Unix socket，两者共用同一个 Server 和后台过期任务
+#[cfg(unix)]
+pub async fn serve_both(
+    tcp: TcpListener,
+    unix: tokio::net::UnixListener,
+    server: Server,
+) -> Result<()> {
+    tokio::spawn(active_expire(server.clone()));
+    tokio::try_join!(accept_loop(tcp, server.clone()), accept_loop(unix, server))?;
+    Ok(())
+}
+
+async fn accept_loop(listener: impl Listener, server: Server) -> Result<()> {
+    let shutdown = server.shutdown_token().clone();
     loop {
-        match frames.next().await {
+        let (stream, addr) = tokio::select! {
+            _ = shutdown.cancelled() => {
+                info!("Shutting down");
+                return Ok(());
+            }
+            accepted = listener.accept() => accepted?,
+        };
+        let remote_addr = addr.map_or_else(|| "unix socket".to_string(), |addr| addr.to_string());
+        info!("Accepted connection from: {}", remote_addr);
+        let cloned_server = server.clone();
+        tokio::spawn(async move {
+            match process_stream(stream, addr, cloned_server).await {
+                Ok(_) => {
+                    info!("Connection closed: {}", remote_addr);
+                }
+                Err(e) => {
+                    info!("Connection closed with error: {:?}: {}", e, remote_addr);
+                }
+            }
+        });
+    }
+}
+
+// 与 redis 的 hz 默认值 10 一致，每 100ms 清理一次过期的 key
+async fn active_expire(server: Server) {
+    let shutdown = server.shutdown_token().clone();
+    let mut interval = tokio::time::interval(ACTIVE_EXPIRE_INTERVAL);
+    loop {
+        tokio::select! {
+            _ = shutdown.cancelled() => return,
+            _ = interval.tick() => {}
+        }
+        if server.active_expire() {
+            // 与写命令一样和 EXEC 互斥，删除 key 时更新的版本号不会在 EXEC 检查之后才出现
+            let _gate = server.write_gate().read().await;
+            server.active_expire_cycle();
+        }
+    }
+}
+
+// addr 是客户端地址，Unix socket 连接没有地址
+pub async fn process_stream<S>(stream: S, addr: Option<SocketAddr>, server: Server) -> Result<()>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let codec = RespFrameCodec::new(server.config().resp_limits());
+    let mut frames = Framed::new(stream, codec);
+    let mut session = Session::new(server);
+    if let Some(addr) = addr {
+        session.set_addr(addr);
+    }
+    let kill = session.kill_token().clone();
+    let shutdown = session.server().shutdown_token().clone();
+    loop {
+        // 只在两次命令之间检查，正在处理的命令的回复总会完整发送
+        let frame = tokio::select! {
+            _ = shutdown.cancelled() => return Ok(()),
+            _ = kill.cancelled() => {
+                info!(client = session.id(), name = session.name(), "Killed by CLIENT KILL");
+                return Ok(());
+            }
+            frame = frames.next() => frame,
+            push = session.next_push() => {
+                let frame = match push {
+                    SessionPush::Monitor(line) => SimpleString::new(line).into(),
+                    SessionPush::Message(message) => message_frame(message),
+                    SessionPush::Invalidate(keys) => invalidate_frame(keys),
+                };
+                frames.send(frame).await?;
+                continue;
+            }
+        };
+        match frame {
             Some(Ok(frame)) => {
-                info!("Received frame: {:?}", frame);
-                let frame = frame_handler(frame, &backend).await?;
-                info!("Sending frame: {:?}", frame);
+                info!(
+                    client = session.id(),
+                    name = session.name(),
+                    "Received frame: {:?}",
+                    frame
+                );
+                let frame = frame_handler(frame, &mut session).await?;
+                info!(
+                    client = session.id(),
+                    name = session.name(),
+                    "Sending frame: {:?}",
+                    frame
+                );
+                frames.codec_mut().protocol = session.protocol();
+                for reply in session.take_replies() {
+                    frames.feed(reply).await?;
+                }
                 frames.send(frame).await?;
+                if session.is_closing() {
+                    return Ok(());
+                }
+            }
+            Some(Err(e)) => {
+                // 与 redis 一致，协议错误先回复错误再关闭连接，其他错误直接关闭
+                if let Some(error) = e.downcast_ref::<RespError>() {
+                    let reply = SimpleError::new(format!("ERR Protocol error: {}", error));
+                    frames.send(reply.into()).await?;
+                }
+                return Err(e);
             }
-            Some(Err(e)) => return Err(e),
             None => return Ok(()),
         }
     }
 }
 
-async fn frame_handler(frame: RespFrame, backend: &Backend) -> Result<RespFrame> {
-    let cmd = Command::try_from(frame)?;
-    info!("Executing command: {:?}", cmd);
-    let frame = cmd.execute(backend);
+async fn frame_handler(frame: RespFrame, session: &mut Session) -> Result<RespFrame> {
+    // 只有存在 MONITOR 连接时才格式化命令
+    let line = match &frame {
+        RespFrame::Array(array) if session.server().has_monitors() => monitor_line(session, array),
+        _ => None,
+    };
+    let spec = match &frame {
+        RespFrame::Array(array) => match array.first() {
+            Some(RespFrame::BulkString(name)) => lookup_command(name),
+            _ => None,
+        },
+        _ => None,
+    };
+    let keys = match (&frame, spec) {
+        // 与 redis 一致，OBJECT 和 MEMORY 只查看 key 的信息，不更新访问时间
+        (RespFrame::Array(array), Some(spec)) if !matches!(spec.name, "object" | "memory") => {
+            command_keys(array, spec)
+        }
+        _ => vec![],
+    };
+    let name = spec.map(|spec| spec.name);
+    let slowlog_args = match &frame {
+        RespFrame::Array(array) if session.server().config().slowlog_log_slower_than >= 0 => {
+            Some(slowlog_args(array))
+        }
+        _ => None,
+    };
+    session.server().update_client(session.id(), |client| {
+        client.last_interaction = Instant::now();
+        client.last_command = name;
+    });
+    if let Some(name) = name {
+        if session.subscription_count() > 0 && !SUBSCRIBE_MODE_COMMANDS.contains(&name) {
+            return Ok(SimpleError::new(format!(
+                "ERR Can't execute '{}': only (P)SUBSCRIBE / (P)UNSUBSCRIBE / PING / QUIT / RESET are allowed in subscribe mode",
+                name
+            ))
+            .into());
+        }
+    }
+    let queueing =
+        session.in_multi() && !name.is_some_and(|name| TRANSACTION_COMMANDS.contains(&name));
+    let cmd = match Command::try_from(frame) {
+        Ok(cmd) => cmd,
+        Err(e) => {
+            session.abort_transaction();
+            return Ok(e.into());
+        }
+    };
+    if let Some(line) = line {
+        session.server().publish_monitor(line);
+    }
+    session.server().stats.command_processed();
+    if queueing {
+        // 阻塞命令会在持有写锁时一直等待其他连接写入，不允许放入事务
+        if spec.is_some_and(|spec| spec.has_flag("blocking")) {
+            session.abort_transaction();
+            return Ok(
+                SimpleError::new("ERR blocking commands are not allowed inside MULTI").into(),
+            );
+        }
+        session.queue_command(QueuedCommand {
+            cmd,
+            keys,
+            name,
+            write: spec.is_some_and(|spec| spec.has_flag("write")),
+        });
+        return Ok(queued());
+    }
+    info!(
+        client = session.id(),
+        name = session.name(),
+        "Executing command: {:?}",
+        cmd
+    );
+    session.expire_keys(&keys);
+    let start = Instant::now();
+    let frame = match cmd {
+        Command::Exec(_) => exec_transaction(session).await,
+        // 写命令与 EXEC 互斥
+        cmd if spec.is_some_and(|spec| spec.has_flag("write") && !spec.has_flag("blocking")) => {
+            let server = session.server().clone();
+            let _gate = server.write_gate().read().await;
+            let existed = session.keys_existed(&keys);
+            let frame = cmd.execute_with(session).await;
+            // 在释放读锁之前更新版本号，EXEC 检查 WATCH 时不会漏掉这次写入
+            session.after_write(name, &keys, existed, &frame);
+            frame
+        }
+        // 阻塞命令在每次尝试弹出时自己获取读锁，等待期间不持有
+        cmd => cmd.execute_with(session).await,
+    };
+    let duration = start.elapsed();
+    if let Some(name) = name {
+        session.server().stats.record_command(name, duration);
+    }
+    if let Some(args) = slowlog_args {
+        record_slowlog(session, args, duration);
+    }
+    if session.is_tracking() && spec.is_some_and(|spec| spec.has_flag("readonly")) {
+        session.server().tracking().track(session.id(), &keys);
+    }
+    for key in keys {
+        session.backend().touch(&key);
+    }
     Ok(frame)
 }
 
+fn record_slowlog(session: &Session, args: Vec<Vec<u8>>, duration: Duration) {
+    let server = session.server();
+    let threshold = server.config().slowlog_log_slower_than;
+    if threshold < 0 || duration.as_micros() < threshold as u128 {
+        return;
+    }
+    let addr = session
+        .addr()
+        .map(|addr| addr.to_string())
+        .unwrap_or_default();
+    let name = session.name().unwrap_or_default().to_string();
+    server.slowlog.push(args, duration, addr, name);
+}
+
+fn command_keys(array: &RespArray, spec: &CommandSpec) -> Vec<String> {
+    spec.key_indices(array)
+        .filter_map(|i| match array.get(i) {
+            Some(RespFrame::BulkString(key)) => String::from_utf8(key.to_vec()).ok(),
+            _ => None,
+        })
+        .collect()
+}
+
+impl RespFrameCodec {
+    fn new(limits: RespLimits) -> Self {
+        Self {
+            protocol: RespVersion::default(),
+            limits,
+        }
+    }
+}
+
+impl Default for RespFrameCodec {
+    fn default() -> Self {
+        Self::new(RespLimits::default())
+    }
+}
+
 impl Encoder<RespFrame> for RespFrameCodec {
     type Error = anyhow::Error;
 
     fn encode(&mut self, item: RespFrame, dst: &mut bytes::BytesMut) -> Result<()> {
-        let data = item.encode();
-        dst.extend_from_slice(&data);
+        item.encode_for_into(self.protocol, dst);
         Ok(())
     }
 }
@@ -50,11 +364,1347 @@ impl Decoder for RespFrameCodec {
     type Item = RespFrame;
     type Error = anyhow::Error;
 
+    // 先确认缓冲区中有一个完整的帧再解析，不完整的帧原样留在 src 中等待更多数据。
+    // 解析只会从 src 头部切走已消费的字节，剩余数据和已分配的容量留给下一个帧复用
     fn decode(&mut self, src: &mut bytes::BytesMut) -> Result<Option<Self::Item>> {
-        match RespFrame::decode(src) {
-            Ok(frame) => Ok(Some(frame)),
-            Err(RespError::Incomplete) => Ok(None),
-            Err(e) => Err(e.into()),
+        let len = match RespFrame::expect_length_with_limits(src, self.limits) {
+            Ok(len) => len,
+            // 已经缓冲的数据超过上限时不再等待剩余部分
+            Err(RespError::Incomplete) => {
+                self.limits.check_frame_size(src.len())?;
+                return Ok(None);
+            }
+            Err(e) => return Err(e.into()),
+        };
+        self.limits.check_frame_size(len)?;
+        if len > src.len() {
+            return Ok(None);
+        }
+        let remaining = src.len();
+        let frame = RespFrame::decode_with_limits(src, self.limits)?;
+        debug_assert_eq!(remaining - src.len(), len);
+        Ok(Some(frame))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::*;
+    use crate::{
+        BulkString, Databases, NotifyFlags, RespArray, RespEncoder as _, RespNull, ServerConfig,
+        SimpleError, SimpleString,
+    };
+
+    fn command(args: &[&str]) -> RespFrame {
+        let frames = args
+            .iter()
+            .map(|arg| arg.as_bytes().into())
+            .collect::<Vec<RespFrame>>();
+        RespArray::new(frames).into()
+    }
+
+    #[tokio::test]
+    async fn test_frame_handler_awaits_async_command() -> Result<()> {
+        let mut session = Session::new(Server::default());
+
+        let start = Instant::now();
+        let frame = frame_handler(command(&["debug", "sleep", "0.1"]), &mut session).await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_async_command_does_not_block_other_sessions() -> Result<()> {
+        let server = Server::default();
+        let mut sleeper = Session::new(server.clone());
+        let mut session = Session::new(server);
+
+        let handle = tokio::spawn(async move {
+            frame_handler(command(&["debug", "sleep", "0.5"]), &mut sleeper).await
+        });
+
+        let start = Instant::now();
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        let frame = frame_handler(command(&["get", "hello"]), &mut session).await?;
+        assert_eq!(frame, b"world".into());
+        assert!(start.elapsed() < Duration::from_millis(500));
+
+        assert_eq!(handle.await??, SimpleString::new("OK").into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_select_routes_commands_to_database() -> Result<()> {
+        let server = Server::default();
+        let mut session = Session::new(server.clone());
+
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+
+        let frame = frame_handler(command(&["select", "1"]), &mut session).await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+        let frame = frame_handler(command(&["get", "hello"]), &mut session).await?;
+        assert_eq!(frame, RespFrame::Null(RespNull));
+
+        // 其他连接仍然使用 db 0
+        let mut other = Session::new(server);
+        let frame = frame_handler(command(&["get", "hello"]), &mut other).await?;
+        assert_eq!(frame, b"world".into());
+
+        frame_handler(command(&["select", "0"]), &mut session).await?;
+        let frame = frame_handler(command(&["get", "hello"]), &mut session).await?;
+        assert_eq!(frame, b"world".into());
+
+        let frame = frame_handler(command(&["select", "16"]), &mut session).await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("ERR DB index is out of range").into()
+        );
+        let frame = frame_handler(command(&["select", "abc"]), &mut session).await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("ERR value is not an integer or out of range").into()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wrong_arity_replies_with_error() -> Result<()> {
+        let mut session = Session::new(Server::default());
+
+        let frame = frame_handler(command(&["GET"]), &mut session).await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("ERR wrong number of arguments for 'get' command").into()
+        );
+        let frame = frame_handler(command(&["object", "encoding"]), &mut session).await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("ERR wrong number of arguments for 'object|encoding' command").into()
+        );
+        let frame = frame_handler(command(&["nosuchcommand"]), &mut session).await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("ERR unknown command 'nosuchcommand'").into()
+        );
+
+        // 出错后连接仍然可用
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        let frame = frame_handler(command(&["get", "hello"]), &mut session).await?;
+        assert_eq!(frame, b"world".into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_monitor_receives_other_clients_commands() -> Result<()> {
+        let server = Server::default();
+        let mut monitor = Session::new(server.clone());
+        let mut session = Session::new(server);
+
+        let frame = frame_handler(command(&["monitor"]), &mut monitor).await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+        assert!(monitor.is_monitoring());
+
+        frame_handler(command(&["select", "2"]), &mut session).await?;
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+
+        let line = monitor.next_monitor_line().await;
+        assert!(line.ends_with(r#"[0 unknown] "select" "2""#), "{}", line);
+        let line = monitor.next_monitor_line().await;
+        assert!(
+            line.ends_with(r#"[2 unknown] "set" "hello" "world""#),
+            "{}",
+            line
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_kill_closes_victim_stream() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        async fn request(stream: &mut TcpStream, input: &[u8]) -> Result<Vec<u8>> {
+            stream.write_all(input).await?;
+            let mut buf = vec![0; 1024];
+            let n = stream.read(&mut buf).await?;
+            buf.truncate(n);
+            Ok(buf)
+        }
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(async move {
+            while let Ok((stream, addr)) = listener.accept().await {
+                tokio::spawn(process_stream(stream, Some(addr), server.clone()));
+            }
+        });
+
+        let mut victim = TcpStream::connect(addr).await?;
+        let mut killer = TcpStream::connect(addr).await?;
+
+        let reply = request(&mut victim, b"*2\r\n$6\r\nclient\r\n$2\r\nid\r\n").await?;
+        let id = String::from_utf8(reply)?;
+        let id = id.trim_start_matches(':').trim_end();
+        let kill = format!(
+            "*4\r\n$6\r\nclient\r\n$4\r\nkill\r\n$2\r\nid\r\n${}\r\n{}\r\n",
+            id.len(),
+            id
+        );
+        assert_eq!(request(&mut killer, kill.as_bytes()).await?, b":1\r\n");
+
+        let mut buf = [0; 16];
+        let n = tokio::time::timeout(Duration::from_secs(1), victim.read(&mut buf)).await??;
+        assert_eq!(n, 0);
+
+        let reply = request(&mut killer, b"*2\r\n$4\r\necho\r\n$5\r\nhello\r\n").await?;
+        assert_eq!(reply, b"$5\r\nhello\r\n");
+
+        Ok(())
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_serve_unix_socket() -> Result<()> {
+        use tokio::{
+            io::{AsyncReadExt, AsyncWriteExt},
+            net::{UnixListener, UnixStream},
+        };
+
+        let path = std::env::temp_dir().join(format!("simple-redis-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path)?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+
+        let mut stream = UnixStream::connect(&path).await?;
+        let mut buf = vec![0; 64];
+        stream
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        let n = stream.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+        stream
+            .write_all(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
+            .await?;
+        let n = stream.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"$5\r\nworld\r\n");
+
+        // Unix socket 连接没有客户端地址
+        stream
+            .write_all(b"*2\r\n$6\r\nclient\r\n$4\r\nlist\r\n")
+            .await?;
+        let n = stream.read(&mut buf).await?;
+        assert!(String::from_utf8_lossy(&buf[..n]).contains(" addr= name= "));
+
+        server.shutdown();
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_quit_closes_connection() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+
+        let mut stream = TcpStream::connect(addr).await?;
+        stream.write_all(b"*1\r\n$4\r\nQUIT\r\n").await?;
+        let mut buf = vec![0; 64];
+        let n = stream.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+
+        let n = tokio::time::timeout(Duration::from_secs(1), stream.read(&mut buf)).await??;
+        assert_eq!(n, 0);
+
+        // 同一批发送的、位于 QUIT 之后的命令不会执行
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"*1\r\n$4\r\nQUIT\r\n*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        let mut replies = Vec::new();
+        tokio::time::timeout(Duration::from_secs(1), stream.read_to_end(&mut replies)).await??;
+        assert_eq!(replies, b"+OK\r\n");
+        assert_eq!(server.databases().get(0).map(|db| db.len()), Some(0));
+        server.shutdown();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_deeply_nested_frame_is_rejected() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+
+        let mut stream = TcpStream::connect(addr).await?;
+        let mut nested = b"*1\r\n".repeat(1000);
+        nested.extend_from_slice(b":1\r\n");
+        stream.write_all(&nested).await?;
+        let mut reply = Vec::new();
+        tokio::time::timeout(Duration::from_secs(1), stream.read_to_end(&mut reply)).await??;
+        assert_eq!(
+            reply,
+            b"-ERR Protocol error: exceeded nesting depth limit\r\n"
+        );
+
+        // 服务器仍然正常处理其他连接
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"*2\r\n$4\r\necho\r\n$5\r\nhello\r\n")
+            .await?;
+        expect_reply(&mut stream, b"$5\r\nhello\r\n").await?;
+        server.shutdown();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_oversized_bulk_header_is_rejected() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+
+        // 只发送头部，服务器不会一直等待 2GB 的数据
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"*2\r\n$4\r\necho\r\n$2147483647\r\n")
+            .await?;
+        let mut reply = Vec::new();
+        tokio::time::timeout(Duration::from_secs(1), stream.read_to_end(&mut reply)).await??;
+        assert_eq!(reply, b"-ERR Protocol error: invalid bulk length\r\n");
+        server.shutdown();
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_enforces_limits() -> Result<()> {
+        let limits = RespLimits {
+            max_bulk_len: 16,
+            max_elements: 4,
+            max_frame_size: 48,
+            ..Default::default()
+        };
+        let decode = |data: &[u8]| {
+            let mut buf = bytes::BytesMut::from(data);
+            RespFrameCodec::new(limits).decode(&mut buf)
+        };
+        let error = |data: &[u8]| {
+            decode(data)
+                .unwrap_err()
+                .downcast::<RespError>()
+                .expect("expected a protocol error")
+        };
+
+        assert_eq!(
+            error(b"$17\r\n"),
+            RespError::ExceedsLimit("invalid bulk length")
+        );
+        assert_eq!(
+            error(b"*5\r\n"),
+            RespError::ExceedsLimit("invalid multibulk length")
+        );
+        // 每个元素都在限制内，但整个帧超过上限
+        let frame = command(&["set", "key-0123456789", "value-0123456789"]);
+        let mut data = frame.encode();
+        assert!(data.len() > 48);
+        assert_eq!(error(&data), RespError::ExceedsLimit("frame too large"));
+        // 不完整的帧已经缓冲的数据超过上限时同样拒绝
+        data.truncate(data.len() - 2);
+        assert_eq!(error(&data), RespError::ExceedsLimit("frame too large"));
+
+        // 恰好在限制内的帧可以正常解析
+        let frame = command(&["a", "b", "0123456789abcdef"]);
+        assert_eq!(decode(&frame.encode())?, Some(frame));
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_accepts_large_frames_under_default_limits() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let value = "x".repeat(1024 * 1024);
+        let frame = command(&["set", "big", &value]);
+        let mut buf = bytes::BytesMut::new();
+        frame.encode_into(&mut buf);
+        assert_eq!(codec.decode(&mut buf)?, Some(frame));
+
+        let args = vec!["1"; 100_000];
+        let frame = command(&args);
+        let mut buf = bytes::BytesMut::new();
+        frame.encode_into(&mut buf);
+        assert_eq!(codec.decode(&mut buf)?, Some(frame));
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_binary_payload_with_crlf() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = bytes::BytesMut::from(
+            "*3\r\n$3\r\nset\r\n$3\r\nkey\r\n$5\r\na\r\n$*\r\n*1\r\n$4\r\nping\r\n",
+        );
+        assert_eq!(
+            codec.decode(&mut buf)?,
+            Some(command(&["set", "key", "a\r\n$*"]))
+        );
+        assert_eq!(codec.decode(&mut buf)?, Some(command(&["ping"])));
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_empty_array_is_not_null() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let empty: RespFrame = RespArray::new(vec![]).into();
+        let null = RespFrame::Null(RespNull);
+        for (protocol, encoded_null) in [
+            (RespVersion::Resp2, &b"$-1\r\n"[..]),
+            (RespVersion::Resp3, &b"_\r\n"[..]),
+        ] {
+            codec.protocol = protocol;
+            let mut buf = bytes::BytesMut::new();
+            codec.encode(empty.clone(), &mut buf)?;
+            codec.encode(null.clone(), &mut buf)?;
+            assert_eq!(buf, [&b"*0\r\n"[..], encoded_null].concat());
+            assert_eq!(codec.decode(&mut buf)?, Some(empty.clone()));
+            assert_eq!(codec.decode(&mut buf)?, Some(null.clone()));
+        }
+
+        // 空字符串参数不会被当作 null
+        let mut buf = bytes::BytesMut::from("*3\r\n$3\r\nset\r\n$3\r\nkey\r\n$0\r\n\r\n");
+        assert_eq!(codec.decode(&mut buf)?, Some(command(&["set", "key", ""])));
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_uses_configured_depth() -> Result<()> {
+        let mut codec = RespFrameCodec::new(RespLimits {
+            max_depth: 2,
+            ..Default::default()
+        });
+        let mut buf = bytes::BytesMut::from("*1\r\n*1\r\n:1\r\n");
+        assert!(codec.decode(&mut buf)?.is_some());
+
+        let mut buf = bytes::BytesMut::from("*1\r\n*1\r\n*1\r\n:1\r\n");
+        let err = codec.decode(&mut buf).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<RespError>(),
+            Some(&RespError::ExceededDepthLimit)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_stops_server() -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let handle = tokio::spawn(serve(listener, Server::default()));
+
+        let mut idle = TcpStream::connect(addr).await?;
+        let mut stream = TcpStream::connect(addr).await?;
+        stream
+            .write_all(b"*2\r\n$8\r\nshutdown\r\n$6\r\nnosave\r\n")
+            .await?;
+        let mut buf = vec![0; 64];
+        let n = stream.read(&mut buf).await?;
+        assert_eq!(&buf[..n], b"+OK\r\n");
+
+        tokio::time::timeout(Duration::from_secs(1), handle).await???;
+        let n = tokio::time::timeout(Duration::from_secs(1), idle.read(&mut buf)).await??;
+        assert_eq!(n, 0);
+        assert!(TcpStream::connect(addr).await.is_err());
+
+        Ok(())
+    }
+
+    fn info_field(frame: &RespFrame, name: &str) -> u64 {
+        let RespFrame::BulkString(body) = frame else {
+            panic!("expected a bulk string");
+        };
+        String::from_utf8_lossy(&body.0)
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':')?.parse().ok())
+            .unwrap_or_else(|| panic!("missing {}", name))
+    }
+
+    #[tokio::test]
+    async fn test_info_stats_track_commands() -> Result<()> {
+        let server = Server::default();
+        let mut session = Session::new(server.clone());
+        let other = Session::new(server);
+
+        let before = frame_handler(command(&["info", "stats"]), &mut session).await?;
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        frame_handler(command(&["get", "hello"]), &mut session).await?;
+        frame_handler(command(&["get", "missing"]), &mut session).await?;
+        frame_handler(command(&["hget", "missing", "field"]), &mut session).await?;
+        let after = frame_handler(command(&["info", "stats"]), &mut session).await?;
+
+        let processed = info_field(&after, "total_commands_processed");
+        assert_eq!(
+            processed - info_field(&before, "total_commands_processed"),
+            5
+        );
+        assert_eq!(info_field(&after, "keyspace_hits"), 1);
+        assert_eq!(info_field(&after, "keyspace_misses"), 2);
+        assert_eq!(info_field(&after, "total_connections_received"), 2);
+
+        let clients = frame_handler(command(&["info", "clients"]), &mut session).await?;
+        assert_eq!(info_field(&clients, "connected_clients"), 2);
+        drop(other);
+        let clients = frame_handler(command(&["info", "clients"]), &mut session).await?;
+        assert_eq!(info_field(&clients, "connected_clients"), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_keeps_partial_frame() -> Result<()> {
+        let first = b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n";
+        let second = b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n";
+        let (head, tail) = second.split_at(second.len() / 2);
+
+        let mut codec = RespFrameCodec::default();
+        let mut buf = bytes::BytesMut::with_capacity(1024);
+        buf.extend_from_slice(first);
+        buf.extend_from_slice(head);
+        assert_eq!(codec.decode(&mut buf)?, Some(command(&["get", "hello"])));
+        assert_eq!(&buf[..], head);
+        assert_eq!(codec.decode(&mut buf)?, None);
+        assert_eq!(&buf[..], head);
+
+        buf.extend_from_slice(tail);
+        assert_eq!(
+            codec.decode(&mut buf)?,
+            Some(command(&["set", "hello", "world"]))
+        );
+        assert!(buf.is_empty());
+        // 没有重新分配，之后的帧继续使用同一块内存
+        assert!(buf.capacity() >= 1024 - first.len() - second.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_streamed_chunk_split_across_reads() -> Result<()> {
+        let mut codec = RespFrameCodec::default();
+        let mut buf = bytes::BytesMut::from("*?\r\n$4\r\necho\r\n$?\r\n;4\r\nHe");
+        assert_eq!(codec.decode(&mut buf)?, None);
+        buf.extend_from_slice(b"ll\r\n;2\r\no!\r\n;0\r\n");
+        assert_eq!(codec.decode(&mut buf)?, None);
+        buf.extend_from_slice(b".\r\n");
+        assert_eq!(codec.decode(&mut buf)?, Some(command(&["echo", "Hello!"])));
+        assert!(buf.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_codec_never_consumes_incomplete_frame() -> Result<()> {
+        let data = b"*2\r\n$4\r\necho\r\n$2\r\nhi\r\n%1\r\n+k\r\n,1.5\r\n";
+        let mut codec = RespFrameCodec::default();
+        for split in 1..data.len() {
+            let mut buf = bytes::BytesMut::from(&data[..split]);
+            let mut frames = 0;
+            while codec.decode(&mut buf)?.is_some() {
+                frames += 1;
+            }
+            buf.extend_from_slice(&data[split..]);
+            while codec.decode(&mut buf)?.is_some() {
+                frames += 1;
+            }
+            assert_eq!(frames, 2, "split at {}", split);
+            assert!(buf.is_empty());
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_codec_encodes_by_negotiated_protocol() -> Result<()> {
+        let mut session = Session::new(Server::default());
+        let mut codec = RespFrameCodec::default();
+
+        let frame = frame_handler(command(&["hello", "3"]), &mut session).await?;
+        codec.protocol = session.protocol();
+        let mut buf = bytes::BytesMut::new();
+        codec.encode(frame, &mut buf)?;
+        assert!(buf.starts_with(b"%7\r\n"));
+
+        let frame = frame_handler(command(&["hello", "2"]), &mut session).await?;
+        codec.protocol = session.protocol();
+        let mut buf = bytes::BytesMut::new();
+        codec.encode(frame, &mut buf)?;
+        assert!(buf.starts_with(b"*14\r\n"));
+
+        let mut buf = bytes::BytesMut::new();
+        codec.encode(RespFrame::Null(RespNull), &mut buf)?;
+        assert_eq!(&buf[..], b"$-1\r\n");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_distinguishes_missing_and_empty() -> Result<()> {
+        let mut session = Session::new(Server::default());
+        let mut codec = RespFrameCodec::default();
+        frame_handler(command(&["set", "empty", ""]), &mut session).await?;
+
+        let missing = frame_handler(command(&["get", "missing"]), &mut session).await?;
+        let empty = frame_handler(command(&["get", "empty"]), &mut session).await?;
+        assert_eq!(missing, RespFrame::Null(RespNull));
+        assert_eq!(empty, BulkString::new("").into());
+
+        for (protocol, null) in [
+            (RespVersion::Resp2, &b"$-1\r\n"[..]),
+            (RespVersion::Resp3, &b"_\r\n"[..]),
+        ] {
+            codec.protocol = protocol;
+            let mut buf = bytes::BytesMut::new();
+            codec.encode(missing.clone(), &mut buf)?;
+            codec.encode(empty.clone(), &mut buf)?;
+            assert_eq!(buf, [null, b"$0\r\n\r\n"].concat());
+
+            // 解码回来仍然可以区分
+            assert_eq!(codec.decode(&mut buf)?, Some(RespFrame::Null(RespNull)));
+            assert_eq!(codec.decode(&mut buf)?, Some(BulkString::new("").into()));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_slowlog_records_slow_commands() -> Result<()> {
+        let config = ServerConfig {
+            slowlog_log_slower_than: 50_000,
+            ..Default::default()
+        };
+        let server = Server::with_config(Databases::default(), config);
+        let mut session = Session::new(server);
+        session.set_name(Some("sleeper".to_string()));
+
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        frame_handler(command(&["debug", "sleep", "0.1"]), &mut session).await?;
+
+        let frame = frame_handler(command(&["slowlog", "len"]), &mut session).await?;
+        assert_eq!(frame, RespFrame::Integer(1));
+
+        let RespFrame::Array(entries) =
+            frame_handler(command(&["slowlog", "get"]), &mut session).await?
+        else {
+            panic!("expected an array");
+        };
+        assert_eq!(entries.len(), 1);
+        let RespFrame::Array(entry) = &entries[0] else {
+            panic!("expected an array");
+        };
+        assert_eq!(entry[0], RespFrame::Integer(0));
+        assert!(matches!(entry[2], RespFrame::Integer(micros) if micros >= 100_000));
+        assert_eq!(
+            entry[3],
+            RespArray::new(vec![b"debug".into(), b"sleep".into(), b"0.1".into()]).into()
+        );
+        assert_eq!(entry[5], b"sleeper".into());
+
+        let frame = frame_handler(command(&["slowlog", "get", "0"]), &mut session).await?;
+        assert_eq!(frame, RespArray::new(Vec::<RespFrame>::new()).into());
+
+        let frame = frame_handler(command(&["slowlog", "reset"]), &mut session).await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+        let frame = frame_handler(command(&["slowlog", "len"]), &mut session).await?;
+        assert_eq!(frame, RespFrame::Integer(0));
+
+        Ok(())
+    }
+
+    async fn expect_reply(stream: &mut TcpStream, expected: &[u8]) -> Result<()> {
+        use tokio::io::AsyncReadExt;
+
+        let mut buf = vec![0; expected.len()];
+        tokio::time::timeout(Duration::from_secs(1), stream.read_exact(&mut buf)).await??;
+        assert_eq!(
+            String::from_utf8_lossy(&buf),
+            String::from_utf8_lossy(expected)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_receives_messages() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+
+        let mut subscriber = TcpStream::connect(addr).await?;
+        let mut other = TcpStream::connect(addr).await?;
+        subscriber
+            .write_all(b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n$6\r\nsports\r\n")
+            .await?;
+        expect_reply(
+            &mut subscriber,
+            b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n*3\r\n$9\r\nsubscribe\r\n$6\r\nsports\r\n:2\r\n",
+        )
+        .await?;
+        other
+            .write_all(b"*2\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n")
+            .await?;
+        expect_reply(&mut other, b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n").await?;
+
+        assert_eq!(server.pubsub().publish("news", b"hello".to_vec()), 2);
+        let message = b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n";
+        expect_reply(&mut subscriber, message).await?;
+        expect_reply(&mut other, message).await?;
+
+        // 订阅状态下不能执行普通命令，连接保持打开
+        subscriber
+            .write_all(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
+            .await?;
+        expect_reply(
+            &mut subscriber,
+            b"-ERR Can't execute 'get': only (P)SUBSCRIBE / (P)UNSUBSCRIBE / PING / QUIT / RESET are allowed in subscribe mode\r\n",
+        )
+        .await?;
+        subscriber.write_all(b"*1\r\n$4\r\nping\r\n").await?;
+        expect_reply(&mut subscriber, b"*2\r\n$4\r\npong\r\n$0\r\n\r\n").await?;
+
+        subscriber
+            .write_all(b"*1\r\n$11\r\nunsubscribe\r\n")
+            .await?;
+        expect_reply(
+            &mut subscriber,
+            b"*3\r\n$11\r\nunsubscribe\r\n$4\r\nnews\r\n:1\r\n*3\r\n$11\r\nunsubscribe\r\n$6\r\nsports\r\n:0\r\n",
+        )
+        .await?;
+        assert_eq!(server.pubsub().publish("sports", b"goal".to_vec()), 0);
+
+        subscriber
+            .write_all(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
+            .await?;
+        expect_reply(&mut subscriber, b"$-1\r\n").await?;
+        server.shutdown();
+
+        Ok(())
+    }
+
+    // 读取一个完整的帧，用于内容不固定的回复
+    async fn read_frame(stream: &mut TcpStream) -> Result<RespFrame> {
+        use tokio::io::AsyncReadExt;
+
+        let mut buf = bytes::BytesMut::new();
+        loop {
+            match RespFrame::expect_length(&buf) {
+                Ok(_) => return Ok(RespFrame::decode(&mut buf)?),
+                Err(RespError::Incomplete) => {}
+                Err(e) => return Err(e.into()),
+            }
+            let n =
+                tokio::time::timeout(Duration::from_secs(1), stream.read_buf(&mut buf)).await??;
+            anyhow::ensure!(n > 0, "connection closed");
         }
     }
+
+    #[tokio::test]
+    async fn test_resp3_pubsub_uses_push_frames() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+
+        let mut subscriber = TcpStream::connect(addr).await?;
+        subscriber
+            .write_all(b"*2\r\n$5\r\nhello\r\n$1\r\n3\r\n")
+            .await?;
+        assert!(matches!(
+            read_frame(&mut subscriber).await?,
+            RespFrame::Map(_)
+        ));
+
+        subscriber
+            .write_all(b"*2\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n")
+            .await?;
+        expect_reply(
+            &mut subscriber,
+            b">3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n",
+        )
+        .await?;
+
+        assert_eq!(server.pubsub().publish("news", b"hello".to_vec()), 1);
+        expect_reply(
+            &mut subscriber,
+            b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n",
+        )
+        .await?;
+        server.shutdown();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_mode_rejects_other_commands() -> Result<()> {
+        let mut session = Session::new(Server::default());
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        frame_handler(command(&["subscribe", "news"]), &mut session).await?;
+
+        for args in [&["get", "hello"][..], &["set", "hello", "x"], &["multi"]] {
+            let frame = frame_handler(command(args), &mut session).await?;
+            assert_eq!(
+                frame,
+                SimpleError::new(format!(
+                    "ERR Can't execute '{}': only (P)SUBSCRIBE / (P)UNSUBSCRIBE / PING / QUIT / RESET are allowed in subscribe mode",
+                    args[0]
+                ))
+                .into()
+            );
+        }
+        assert!(!session.in_multi());
+
+        // 订阅相关的命令仍然可以执行，退订之后恢复正常
+        frame_handler(command(&["subscribe", "sports"]), &mut session).await?;
+        assert_eq!(session.subscription_count(), 2);
+        frame_handler(command(&["unsubscribe"]), &mut session).await?;
+        session.take_replies();
+        let frame = frame_handler(command(&["get", "hello"]), &mut session).await?;
+        assert_eq!(frame, BulkString::new("world").into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_publish_delivers_to_subscriber() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+
+        let mut subscriber = TcpStream::connect(addr).await?;
+        let mut publisher = TcpStream::connect(addr).await?;
+        subscriber
+            .write_all(b"*2\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n")
+            .await?;
+        expect_reply(
+            &mut subscriber,
+            b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n",
+        )
+        .await?;
+
+        publisher
+            .write_all(b"*3\r\n$7\r\npublish\r\n$4\r\nnews\r\n$5\r\nhello\r\n")
+            .await?;
+        expect_reply(&mut publisher, b":1\r\n").await?;
+        expect_reply(
+            &mut subscriber,
+            b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n",
+        )
+        .await?;
+
+        publisher
+            .write_all(b"*3\r\n$7\r\npublish\r\n$6\r\nsports\r\n$4\r\ngoal\r\n")
+            .await?;
+        expect_reply(&mut publisher, b":0\r\n").await?;
+        server.shutdown();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_pubsub_counts_drop_when_subscriber_disconnects() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+
+        let mut subscriber = TcpStream::connect(addr).await?;
+        let mut client = TcpStream::connect(addr).await?;
+        subscriber
+            .write_all(b"*2\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n")
+            .await?;
+        expect_reply(
+            &mut subscriber,
+            b"*3\r\n$9\r\nsubscribe\r\n$4\r\nnews\r\n:1\r\n",
+        )
+        .await?;
+
+        let numsub = b"*3\r\n$6\r\npubsub\r\n$6\r\nnumsub\r\n$4\r\nnews\r\n";
+        client.write_all(numsub).await?;
+        expect_reply(&mut client, b"*2\r\n$4\r\nnews\r\n:1\r\n").await?;
+
+        // 订阅者直接断开连接，没有 UNSUBSCRIBE
+        drop(subscriber);
+        let start = Instant::now();
+        while server.pubsub().num_subscribers("news") > 0 {
+            assert!(start.elapsed() < Duration::from_secs(1));
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        client.write_all(numsub).await?;
+        expect_reply(&mut client, b"*2\r\n$4\r\nnews\r\n:0\r\n").await?;
+        assert!(server.pubsub().active_channels(None).is_empty());
+        server.shutdown();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multi_exec() -> Result<()> {
+        let mut session = Session::new(Server::default());
+        let queued = SimpleString::new("QUEUED").into();
+
+        let frame = frame_handler(command(&["multi"]), &mut session).await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+        let frame = frame_handler(command(&["multi"]), &mut session).await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("ERR MULTI calls can not be nested").into()
+        );
+        let frame = frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        assert_eq!(frame, queued);
+        let frame = frame_handler(command(&["get", "hello"]), &mut session).await?;
+        assert_eq!(frame, queued);
+        assert_eq!(session.backend().get("hello"), None);
+
+        let frame = frame_handler(command(&["exec"]), &mut session).await?;
+        assert_eq!(
+            frame,
+            RespArray::new(vec![SimpleString::new("OK").into(), b"world".into()]).into()
+        );
+        let frame = frame_handler(command(&["exec"]), &mut session).await?;
+        assert_eq!(frame, SimpleError::new("ERR EXEC without MULTI").into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multi_discard() -> Result<()> {
+        let mut session = Session::new(Server::default());
+
+        frame_handler(command(&["multi"]), &mut session).await?;
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        let frame = frame_handler(command(&["discard"]), &mut session).await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+
+        let frame = frame_handler(command(&["get", "hello"]), &mut session).await?;
+        assert_eq!(frame, RespFrame::Null(RespNull));
+        let frame = frame_handler(command(&["discard"]), &mut session).await?;
+        assert_eq!(frame, SimpleError::new("ERR DISCARD without MULTI").into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_multi_poisoned_by_queueing_error() -> Result<()> {
+        let mut session = Session::new(Server::default());
+
+        frame_handler(command(&["multi"]), &mut session).await?;
+        let frame = frame_handler(command(&["set", "hello"]), &mut session).await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("ERR wrong number of arguments for 'set' command").into()
+        );
+        let frame = frame_handler(command(&["set", "other", "value"]), &mut session).await?;
+        assert_eq!(frame, SimpleString::new("QUEUED").into());
+
+        let frame = frame_handler(command(&["exec"]), &mut session).await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("EXECABORT Transaction discarded because of previous errors.").into()
+        );
+        assert_eq!(session.backend().get("other"), None);
+
+        // 事务结束后连接恢复正常
+        let frame = frame_handler(command(&["set", "other", "value"]), &mut session).await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_exec_is_not_interleaved_with_other_writes() -> Result<()> {
+        let server = Server::default();
+        let mut session = Session::new(server.clone());
+        let mut other = Session::new(server);
+
+        frame_handler(command(&["multi"]), &mut session).await?;
+        frame_handler(command(&["set", "hello", "tx"]), &mut session).await?;
+        frame_handler(command(&["debug", "sleep", "0.2"]), &mut session).await?;
+        frame_handler(command(&["get", "hello"]), &mut session).await?;
+        let handle =
+            tokio::spawn(async move { frame_handler(command(&["exec"]), &mut session).await });
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        frame_handler(command(&["set", "hello", "other"]), &mut other).await?;
+
+        let RespFrame::Array(replies) = handle.await?? else {
+            panic!("expected an array");
+        };
+        assert_eq!(replies[2], b"tx".into());
+        let frame = frame_handler(command(&["get", "hello"]), &mut other).await?;
+        assert_eq!(frame, b"other".into());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watch_loses_race() -> Result<()> {
+        let server = Server::default();
+        let mut watcher = Session::new(server.clone());
+        let mut other = Session::new(server);
+
+        frame_handler(command(&["set", "balance", "10"]), &mut watcher).await?;
+        frame_handler(command(&["watch", "balance"]), &mut watcher).await?;
+        frame_handler(command(&["multi"]), &mut watcher).await?;
+        frame_handler(command(&["set", "balance", "20"]), &mut watcher).await?;
+        frame_handler(command(&["set", "audit", "watcher"]), &mut watcher).await?;
+
+        let frame = frame_handler(command(&["set", "balance", "99"]), &mut other).await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+
+        let frame = frame_handler(command(&["exec"]), &mut watcher).await?;
+        assert_eq!(frame, RespFrame::Null(RespNull));
+        assert_eq!(
+            watcher.backend().get("balance"),
+            Some(BulkString::new("99").into())
+        );
+        assert_eq!(watcher.backend().get("audit"), None);
+
+        // EXEC 之后自动取消 WATCH，下一个事务正常执行
+        frame_handler(command(&["multi"]), &mut watcher).await?;
+        frame_handler(command(&["set", "balance", "20"]), &mut watcher).await?;
+        let frame = frame_handler(command(&["exec"]), &mut watcher).await?;
+        assert_eq!(
+            frame,
+            RespArray::new(vec![SimpleString::new("OK").into()]).into()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_watch_sees_blocking_pop_and_expiry() -> Result<()> {
+        let server = Server::default();
+        let mut watcher = Session::new(server.clone());
+        let mut other = Session::new(server.clone());
+
+        frame_handler(command(&["rpush", "queue", "job"]), &mut other).await?;
+        frame_handler(command(&["watch", "queue"]), &mut watcher).await?;
+        let frame = frame_handler(command(&["blpop", "queue", "0"]), &mut other).await?;
+        assert_eq!(
+            frame,
+            RespArray::new(vec![b"queue".into(), b"job".into()]).into()
+        );
+        frame_handler(command(&["multi"]), &mut watcher).await?;
+        frame_handler(command(&["rpush", "queue", "retry"]), &mut watcher).await?;
+        let frame = frame_handler(command(&["exec"]), &mut watcher).await?;
+        assert_eq!(frame, RespFrame::Null(RespNull));
+
+        // EXEC 等待写锁时被唤醒的 BLPOP 必须排在 EXEC 之后弹出，不能在 EXEC 检查 WATCH 之前修改列表
+        frame_handler(command(&["rpush", "queue", "job"]), &mut other).await?;
+        frame_handler(command(&["watch", "queue"]), &mut watcher).await?;
+        frame_handler(command(&["multi"]), &mut watcher).await?;
+        frame_handler(command(&["rpush", "queue", "retry"]), &mut watcher).await?;
+        let writer = server.write_gate().read().await;
+        let exec = tokio::spawn(async move {
+            let frame = frame_handler(command(&["exec"]), &mut watcher).await;
+            (frame, watcher)
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        let blpop = tokio::spawn(async move {
+            let frame = frame_handler(command(&["blpop", "queue", "0"]), &mut other).await;
+            (frame, other)
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(writer);
+        let (frame, mut watcher) = exec.await?;
+        assert_eq!(frame?, RespArray::new(vec![RespFrame::Integer(2)]).into());
+        let (frame, mut other) = blpop.await?;
+        assert_eq!(
+            frame?,
+            RespArray::new(vec![b"queue".into(), b"job".into()]).into()
+        );
+
+        // 后台清理过期 key 同样会让 EXEC 失败
+        frame_handler(command(&["set", "lease", "1"]), &mut other).await?;
+        frame_handler(command(&["pexpire", "lease", "10"]), &mut other).await?;
+        frame_handler(command(&["watch", "lease"]), &mut watcher).await?;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert_eq!(server.active_expire_cycle(), 1);
+        frame_handler(command(&["multi"]), &mut watcher).await?;
+        frame_handler(command(&["set", "lease", "2"]), &mut watcher).await?;
+        let frame = frame_handler(command(&["exec"]), &mut watcher).await?;
+        assert_eq!(frame, RespFrame::Null(RespNull));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unwatch() -> Result<()> {
+        let server = Server::default();
+        let mut watcher = Session::new(server.clone());
+        let mut other = Session::new(server);
+
+        frame_handler(command(&["watch", "hello"]), &mut watcher).await?;
+        frame_handler(command(&["set", "hello", "world"]), &mut other).await?;
+        let frame = frame_handler(command(&["unwatch"]), &mut watcher).await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+
+        frame_handler(command(&["multi"]), &mut watcher).await?;
+        let frame = frame_handler(command(&["watch", "hello"]), &mut watcher).await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("ERR WATCH inside MULTI is not allowed").into()
+        );
+        frame_handler(command(&["get", "hello"]), &mut watcher).await?;
+        let frame = frame_handler(command(&["exec"]), &mut watcher).await?;
+        assert_eq!(
+            frame,
+            RespArray::new(vec![BulkString::new("world").into()]).into()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_command_stats() -> Result<()> {
+        let mut session = Session::new(Server::default());
+
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        frame_handler(command(&["get", "hello"]), &mut session).await?;
+        frame_handler(command(&["get", "missing"]), &mut session).await?;
+
+        let stats = &session.server().stats;
+        let set = stats.command_stat("set").unwrap();
+        assert_eq!(set.calls, 1);
+        let get = stats.command_stat("get").unwrap();
+        assert_eq!(get.calls, 2);
+        assert!(get.min_usec <= get.max_usec);
+        assert!(get.usec >= get.max_usec);
+        assert_eq!(stats.command_stat("del"), None);
+
+        let frame = frame_handler(command(&["info", "commandstats"]), &mut session).await?;
+        let RespFrame::BulkString(body) = frame else {
+            panic!("unexpected frame: {:?}", frame);
+        };
+        let body = String::from_utf8(body.0.into())?;
+        assert!(body.starts_with("# Commandstats\r\n"));
+        assert!(body.contains("cmdstat_get:calls=2,"));
+        assert!(body.contains("cmdstat_set:calls=1,"));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_keyspace_notifications() -> Result<()> {
+        let server = Server::default();
+        let mut session = Session::new(server.clone());
+        let mut set_events = server.pubsub().subscribe("__keyevent@0__:set");
+        let mut del_events = server.pubsub().subscribe("__keyevent@0__:del");
+        let mut keyspace = server.pubsub().subscribe("__keyspace@0__:mylist");
+
+        // 默认关闭
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        assert!(set_events.try_recv().is_err());
+
+        let frame = frame_handler(
+            command(&["config", "set", "notify-keyspace-events", "KEA"]),
+            &mut session,
+        )
+        .await?;
+        assert_eq!(frame, SimpleString::new("OK").into());
+
+        frame_handler(command(&["set", "hello", "world"]), &mut session).await?;
+        assert_eq!(set_events.try_recv()?.payload, b"hello");
+
+        frame_handler(command(&["lpush", "mylist", "a"]), &mut session).await?;
+        assert_eq!(keyspace.try_recv()?.payload, b"lpush");
+
+        // 删除不存在的 key 没有修改任何数据，不发送通知
+        frame_handler(command(&["del", "missing"]), &mut session).await?;
+        assert!(del_events.try_recv().is_err());
+        frame_handler(command(&["del", "hello"]), &mut session).await?;
+        assert_eq!(del_events.try_recv()?.payload, b"hello");
+
+        // 只开启 list 类型的事件
+        frame_handler(
+            command(&["config", "set", "notify-keyspace-events", "Kl"]),
+            &mut session,
+        )
+        .await?;
+        frame_handler(command(&["set", "hello", "again"]), &mut session).await?;
+        assert!(set_events.try_recv().is_err());
+        frame_handler(command(&["rpush", "mylist", "b"]), &mut session).await?;
+        assert_eq!(keyspace.try_recv()?.payload, b"rpush");
+
+        let frame = frame_handler(
+            command(&["config", "get", "notify-keyspace-*"]),
+            &mut session,
+        )
+        .await?;
+        assert_eq!(
+            frame,
+            RespArray::new(vec![
+                BulkString::new("notify-keyspace-events").into(),
+                BulkString::new("lK").into(),
+            ])
+            .into()
+        );
+        let frame = frame_handler(
+            command(&["config", "set", "notify-keyspace-events", "KQ"]),
+            &mut session,
+        )
+        .await?;
+        assert_eq!(
+            frame,
+            SimpleError::new("ERR Invalid argument 'KQ' for CONFIG SET 'notify-keyspace-events'")
+                .into()
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_tracking_invalidation() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+
+        let mut reader = TcpStream::connect(addr).await?;
+        let mut writer = TcpStream::connect(addr).await?;
+        writer
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        expect_reply(&mut writer, b"+OK\r\n").await?;
+
+        reader
+            .write_all(b"*3\r\n$6\r\nclient\r\n$8\r\ntracking\r\n$2\r\non\r\n")
+            .await?;
+        expect_reply(&mut reader, b"+OK\r\n").await?;
+        reader
+            .write_all(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
+            .await?;
+        expect_reply(&mut reader, b"$5\r\nworld\r\n").await?;
+
+        writer
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nthere\r\n")
+            .await?;
+        expect_reply(&mut writer, b"+OK\r\n").await?;
+        // reader 没有发送任何命令也会收到失效通知
+        expect_reply(
+            &mut reader,
+            b"*2\r\n$10\r\ninvalidate\r\n*1\r\n$5\r\nhello\r\n",
+        )
+        .await?;
+        server.shutdown();
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_client_tracking_cleanup() -> Result<()> {
+        let server = Server::default();
+        let mut session = Session::new(server.clone());
+        let id = session.id();
+
+        frame_handler(command(&["client", "tracking", "on"]), &mut session).await?;
+        frame_handler(command(&["get", "a"]), &mut session).await?;
+        frame_handler(command(&["get", "b"]), &mut session).await?;
+        frame_handler(command(&["hget", "c", "f"]), &mut session).await?;
+        assert_eq!(server.tracking().tracked_keys(id), 3);
+
+        frame_handler(command(&["client", "tracking", "off"]), &mut session).await?;
+        assert_eq!(server.tracking().tracked_keys(id), 0);
+
+        frame_handler(command(&["client", "tracking", "on"]), &mut session).await?;
+        frame_handler(command(&["get", "a"]), &mut session).await?;
+        drop(session);
+        assert_eq!(server.tracking().tracked_keys(id), 0);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_debug_set_active_expire() -> Result<()> {
+        use tokio::io::AsyncWriteExt;
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        let server = Server::default();
+        tokio::spawn(serve(listener, server.clone()));
+        server.set_notify_flags(NotifyFlags::parse("Ex").unwrap());
+        let mut expired = server.pubsub().subscribe("__keyevent@0__:expired");
+        let backend = server.databases().get(0).unwrap();
+
+        let mut client = TcpStream::connect(addr).await?;
+        client
+            .write_all(b"*3\r\n$5\r\ndebug\r\n$17\r\nset-active-expire\r\n$1\r\n0\r\n")
+            .await?;
+        expect_reply(&mut client, b"+OK\r\n").await?;
+        assert!(!server.active_expire());
+
+        client
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nhello\r\n$5\r\nworld\r\n")
+            .await?;
+        expect_reply(&mut client, b"+OK\r\n").await?;
+        client
+            .write_all(b"*3\r\n$7\r\npexpire\r\n$5\r\nhello\r\n$2\r\n10\r\n")
+            .await?;
+        expect_reply(&mut client, b":1\r\n").await?;
+
+        // 后台清理关闭，没有被访问的 key 一直保留
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(backend.map.contains_key("hello"));
+        assert!(expired.try_recv().is_err());
+
+        // 访问时惰性删除
+        client
+            .write_all(b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n")
+            .await?;
+        expect_reply(&mut client, b"$-1\r\n").await?;
+        assert!(!backend.map.contains_key("hello"));
+        assert_eq!(expired.try_recv()?.payload, b"hello");
+
+        // 重新开启后，没有被访问的 key 也会被后台任务删除
+        client
+            .write_all(b"*3\r\n$5\r\ndebug\r\n$17\r\nset-active-expire\r\n$1\r\n1\r\n")
+            .await?;
+        expect_reply(&mut client, b"+OK\r\n").await?;
+        client
+            .write_all(b"*3\r\n$3\r\nset\r\n$5\r\nother\r\n$5\r\nvalue\r\n")
+            .await?;
+        expect_reply(&mut client, b"+OK\r\n").await?;
+        client
+            .write_all(b"*3\r\n$7\r\npexpire\r\n$5\r\nother\r\n$2\r\n10\r\n")
+            .await?;
+        expect_reply(&mut client, b":1\r\n").await?;
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert!(!backend.map.contains_key("other"));
+        assert_eq!(expired.try_recv()?.payload, b"other");
+
+        server.shutdown();
+        Ok(())
+    }
 }
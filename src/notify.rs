@@ -0,0 +1,162 @@
+use std::fmt;
+
+use crate::KeyType;
+
+// notify-keyspace-events 的每个字符对应一个标志位，与 redis 的 NOTIFY_* 一致
+const KEYSPACE: u16 = 1 << 0;
+const KEYEVENT: u16 = 1 << 1;
+const GENERIC: u16 = 1 << 2;
+const STRING: u16 = 1 << 3;
+const LIST: u16 = 1 << 4;
+const SET: u16 = 1 << 5;
+const HASH: u16 = 1 << 6;
+const ZSET: u16 = 1 << 7;
+const EXPIRED: u16 = 1 << 8;
+const EVICTED: u16 = 1 << 9;
+const STREAM: u16 = 1 << 10;
+// "A" 是所有事件类型的别名，不包含 K 和 E
+const ALL: u16 = GENERIC | STRING | LIST | SET | HASH | ZSET | EXPIRED | EVICTED | STREAM;
+
+const CLASS_CHARS: [(char, u16); 9] = [
+    ('g', GENERIC),
+    ('$', STRING),
+    ('l', LIST),
+    ('s', SET),
+    ('h', HASH),
+    ('z', ZSET),
+    ('x', EXPIRED),
+    ('e', EVICTED),
+    ('t', STREAM),
+];
+
+// 事件所属的类型，决定是否被 notify-keyspace-events 过滤掉
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventClass {
+    Generic,
+    String,
+    List,
+    Set,
+    Hash,
+    ZSet,
+    Expired,
+    Evicted,
+    Stream,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct NotifyFlags(u16);
+
+impl From<KeyType> for EventClass {
+    fn from(key_type: KeyType) -> Self {
+        match key_type {
+            KeyType::String => EventClass::String,
+            KeyType::Hash => EventClass::Hash,
+            KeyType::Set => EventClass::Set,
+            KeyType::List => EventClass::List,
+            KeyType::ZSet => EventClass::ZSet,
+            KeyType::Stream => EventClass::Stream,
+        }
+    }
+}
+
+impl EventClass {
+    fn flag(&self) -> u16 {
+        match self {
+            EventClass::Generic => GENERIC,
+            EventClass::String => STRING,
+            EventClass::List => LIST,
+            EventClass::Set => SET,
+            EventClass::Hash => HASH,
+            EventClass::ZSet => ZSET,
+            EventClass::Expired => EXPIRED,
+            EventClass::Evicted => EVICTED,
+            EventClass::Stream => STREAM,
+        }
+    }
+}
+
+impl NotifyFlags {
+    // 遇到不认识的字符返回 None，空字符串表示关闭通知
+    pub fn parse(s: &str) -> Option<Self> {
+        let mut flags = 0;
+        for c in s.chars() {
+            flags |= match c {
+                'K' => KEYSPACE,
+                'E' => KEYEVENT,
+                'A' => ALL,
+                c => CLASS_CHARS.iter().find(|(ch, _)| *ch == c)?.1,
+            };
+        }
+        Some(Self(flags))
+    }
+
+    pub fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub fn keyspace(&self) -> bool {
+        self.0 & KEYSPACE != 0
+    }
+
+    pub fn keyevent(&self) -> bool {
+        self.0 & KEYEVENT != 0
+    }
+
+    // 没有开启 K 或 E 时不会发送任何通知
+    pub fn allows(&self, class: EventClass) -> bool {
+        (self.keyspace() || self.keyevent()) && self.0 & class.flag() != 0
+    }
+}
+
+// CONFIG GET 的输出，与 redis 一致：类型全开时合并为 A，之后是 K 和 E
+impl fmt::Display for NotifyFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0 & ALL == ALL {
+            f.write_str("A")?;
+        } else {
+            for (c, flag) in CLASS_CHARS {
+                if self.0 & flag != 0 {
+                    write!(f, "{}", c)?;
+                }
+            }
+        }
+        if self.keyspace() {
+            f.write_str("K")?;
+        }
+        if self.keyevent() {
+            f.write_str("E")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notify_flags() {
+        let flags = NotifyFlags::parse("KEA").unwrap();
+        assert!(flags.keyspace() && flags.keyevent());
+        assert!(flags.allows(EventClass::Expired));
+        assert_eq!(flags.to_string(), "AKE");
+
+        let flags = NotifyFlags::parse("El$").unwrap();
+        assert!(!flags.keyspace());
+        assert!(flags.allows(EventClass::List));
+        assert!(flags.allows(EventClass::String));
+        assert!(!flags.allows(EventClass::Generic));
+        assert_eq!(flags.to_string(), "$lE");
+
+        // 只有类型没有 K/E 时不发送通知
+        let flags = NotifyFlags::parse("g").unwrap();
+        assert!(!flags.allows(EventClass::Generic));
+
+        assert_eq!(NotifyFlags::parse(""), Some(NotifyFlags::default()));
+        assert_eq!(NotifyFlags::parse("KQ"), None);
+    }
+}
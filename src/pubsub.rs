@@ -0,0 +1,153 @@
+use dashmap::DashMap;
+use tokio::sync::broadcast;
+
+use crate::glob_match;
+
+// 每个频道最多缓存的消息数，订阅者消费过慢时丢弃最旧的消息
+const CHANNEL_CAPACITY: usize = 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PubSubMessage {
+    // 通过 PSUBSCRIBE 收到的消息记录匹配到的模式
+    pub pattern: Option<String>,
+    pub channel: String,
+    pub payload: Vec<u8>,
+}
+
+// 全局的频道注册表，频道在第一个订阅者出现时创建，最后一个订阅者离开时删除
+#[derive(Debug, Default)]
+pub struct PubSub {
+    channels: DashMap<String, broadcast::Sender<PubSubMessage>>,
+    // 模式订阅与频道订阅分开保存，生命周期相同
+    patterns: DashMap<String, broadcast::Sender<PubSubMessage>>,
+}
+
+impl PubSub {
+    pub fn subscribe(&self, channel: &str) -> broadcast::Receiver<PubSubMessage> {
+        self.channels
+            .entry(channel.to_string())
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    // 调用前需要先 drop 对应的 Receiver
+    pub fn unsubscribe(&self, channel: &str) {
+        self.channels
+            .remove_if(channel, |_, sender| sender.receiver_count() == 0);
+    }
+
+    pub fn psubscribe(&self, pattern: &str) -> broadcast::Receiver<PubSubMessage> {
+        self.patterns
+            .entry(pattern.to_string())
+            .or_insert_with(|| broadcast::channel(CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    // 调用前需要先 drop 对应的 Receiver
+    pub fn punsubscribe(&self, pattern: &str) {
+        self.patterns
+            .remove_if(pattern, |_, sender| sender.receiver_count() == 0);
+    }
+
+    // 返回收到消息的订阅者数量，同时订阅了频道和匹配模式的客户端会收到两次
+    pub fn publish(&self, channel: &str, payload: Vec<u8>) -> usize {
+        let mut count = 0;
+        if let Some(sender) = self.channels.get(channel) {
+            let message = PubSubMessage {
+                pattern: None,
+                channel: channel.to_string(),
+                payload: payload.clone(),
+            };
+            count += sender.send(message).unwrap_or(0);
+        }
+        for entry in self.patterns.iter() {
+            if !glob_match(entry.key().as_bytes(), channel.as_bytes()) {
+                continue;
+            }
+            let message = PubSubMessage {
+                pattern: Some(entry.key().clone()),
+                channel: channel.to_string(),
+                payload: payload.clone(),
+            };
+            count += entry.value().send(message).unwrap_or(0);
+        }
+        count
+    }
+
+    pub fn num_channels(&self) -> usize {
+        self.channels.len()
+    }
+
+    // 至少有一个订阅者的频道，pattern 为 None 时返回全部
+    pub fn active_channels(&self, pattern: Option<&[u8]>) -> Vec<String> {
+        let mut channels = self
+            .channels
+            .iter()
+            .filter(|v| v.value().receiver_count() > 0)
+            .filter(|v| pattern.is_none_or(|pattern| glob_match(pattern, v.key().as_bytes())))
+            .map(|v| v.key().clone())
+            .collect::<Vec<_>>();
+        channels.sort();
+        channels
+    }
+
+    pub fn num_subscribers(&self, channel: &str) -> usize {
+        self.channels
+            .get(channel)
+            .map_or(0, |sender| sender.receiver_count())
+    }
+
+    // 至少有一个订阅者的模式数量
+    pub fn num_patterns(&self) -> usize {
+        self.patterns
+            .iter()
+            .filter(|v| v.value().receiver_count() > 0)
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_channel_lifecycle() {
+        let pubsub = PubSub::default();
+        assert_eq!(pubsub.publish("news", b"hello".to_vec()), 0);
+
+        let mut first = pubsub.subscribe("news");
+        let second = pubsub.subscribe("news");
+        assert_eq!(pubsub.num_channels(), 1);
+        assert_eq!(pubsub.publish("news", b"hello".to_vec()), 2);
+        assert_eq!(first.try_recv().unwrap().payload, b"hello");
+
+        drop(first);
+        pubsub.unsubscribe("news");
+        assert_eq!(pubsub.num_channels(), 1);
+        drop(second);
+        pubsub.unsubscribe("news");
+        assert_eq!(pubsub.num_channels(), 0);
+    }
+
+    #[test]
+    fn test_pattern_subscription() {
+        let pubsub = PubSub::default();
+        let mut news = pubsub.psubscribe("news.*");
+        let mut exact = pubsub.subscribe("news.sports");
+        assert_eq!(pubsub.num_patterns(), 1);
+
+        assert_eq!(pubsub.publish("news.sports", b"goal".to_vec()), 2);
+        assert_eq!(pubsub.publish("weather", b"rain".to_vec()), 0);
+        let message = news.try_recv().unwrap();
+        assert_eq!(message.pattern.as_deref(), Some("news.*"));
+        assert_eq!(message.channel, "news.sports");
+        assert_eq!(message.payload, b"goal");
+        assert_eq!(exact.try_recv().unwrap().pattern, None);
+        assert!(news.try_recv().is_err());
+
+        drop(news);
+        pubsub.punsubscribe("news.*");
+        assert_eq!(pubsub.num_patterns(), 0);
+        assert_eq!(pubsub.publish("news.sports", b"goal".to_vec()), 1);
+    }
+}
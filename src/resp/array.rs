@@ -2,62 +2,96 @@ use std::ops::Deref;
 
 use bytes::{Buf as _, BytesMut};
 
-use crate::{RespDecoder, RespEncoder, RespError, RespFrame};
+use crate::{RespDecoder, RespEncoder, RespError, RespFrame, RespLimits};
 
-use super::{extract_len_and_end, extract_length_data, CRLF_LEN};
+use super::{
+    element_length, encode_to_vec, extract_len_and_end, extract_length_data, write_header, CRLF_LEN,
+};
 
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
 pub struct RespArray(pub(crate) Vec<RespFrame>);
 
 // - array: "*<number-of-elements>\r\n<element-1>...<element-n>"
 //         - "*2\r\n$3\r\nget\r\n$5\r\nhello\r\n"
-// - null array: "*-1\r\n"
+// - null array: "*-1\r\n"，由 RespFrame 解析为 RespFrame::Null，空数组编码为 "*0\r\n"
+// - streamed array: "*?\r\n<element-1>...<element-n>.\r\n"，只支持解析
 impl RespEncoder for RespArray {
     fn encode(&self) -> Vec<u8> {
-        if self.is_empty() {
-            return b"*-1\r\n".to_vec();
-        }
-        let mut encoded = format!("*{}\r\n", self.len()).into_bytes();
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        write_header(buf, b'*', self.len());
         for frame in &self.0 {
-            encoded.extend_from_slice(&frame.encode());
+            frame.encode_into(buf);
         }
-        encoded
     }
 }
 
 impl RespDecoder for RespArray {
     const PREFIX: &'static str = "*";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_with_limits(buf, RespLimits::default())
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_with_limits(buf, RespLimits::default())
+    }
+
+    fn decode_with_limits(buf: &mut BytesMut, limits: RespLimits) -> Result<Self, RespError> {
+        if buf.starts_with(Self::STREAMED) {
+            return Self::decode_streamed(buf, limits);
+        }
         let len_data = extract_length_data(buf, Self::PREFIX)?;
-        if len_data == "-1" {
-            buf.advance(3 + CRLF_LEN);
-            return Ok(RespArray::new(vec![]));
+        if len_data.starts_with('-') {
+            return Err(RespError::Invalid(format!(
+                "{}{}\r\n",
+                Self::PREFIX,
+                len_data
+            )));
         }
 
-        let total = Self::expect_length(buf)?;
+        let total = Self::expect_length_with_limits(buf, limits)?;
         if buf.len() < total {
             return Err(RespError::Incomplete);
         }
 
-        buf.advance(Self::PREFIX.len() + len_data.len() + CRLF_LEN);
-
         let nth = len_data
             .parse::<usize>()
             .map_err(|_| RespError::InvalidFrameLength)?;
 
+        let remaining = buf.len();
+        buf.advance(Self::PREFIX.len() + len_data.len() + CRLF_LEN);
+
+        let nested = limits.nested()?;
+
         let mut frames = Vec::with_capacity(nth);
         for _ in 0..nth {
-            let frame = RespFrame::decode(buf)?;
+            let frame = RespFrame::decode_with_limits(buf, nested)?;
             frames.push(frame);
         }
+
+        // 实际消耗的字节数必须与 expect_length 计算的一致，之后的数据属于下一个 frame
+        if remaining - buf.len() != total {
+            return Err(RespError::InvalidFrameLength);
+        }
         Ok(RespArray::new(frames))
     }
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length_with_limits(buf: &[u8], limits: RespLimits) -> Result<usize, RespError> {
+        let nested = limits.nested()?;
+        // null array 没有元素，RespFrame 会把它解析为 RespFrame::Null
+        if buf.starts_with(Self::NULL) {
+            return Ok(Self::NULL.len());
+        }
+        if buf.starts_with(Self::STREAMED) {
+            return Self::streamed_length(buf, nested, limits.max_elements);
+        }
         let (len, end) = extract_len_and_end(buf)?;
+        limits.check_elements(len)?;
         let mut total = end + CRLF_LEN;
         for _ in 0..len {
-            let frame_len = RespFrame::expect_length(&buf[total..])?;
+            let frame_len = element_length(buf, total, nested)?;
             total += frame_len;
         }
         Ok(total)
@@ -65,11 +99,65 @@ impl RespDecoder for RespArray {
 }
 
 impl RespArray {
+    pub(crate) const NULL: &'static [u8] = b"*-1\r\n";
+    const STREAMED: &'static [u8] = b"*?\r\n";
+    const STREAMED_END: &'static [u8] = b".\r\n";
+
     pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
         Self(s.into())
     }
 }
 
+// 流式数组事先不知道元素个数，逐个解析直到遇到结束标记
+impl RespArray {
+    fn decode_streamed(buf: &mut BytesMut, limits: RespLimits) -> Result<Self, RespError> {
+        // 同时检查嵌套层数
+        Self::expect_length_with_limits(buf, limits)?;
+        buf.advance(Self::STREAMED.len());
+        let nested = limits.nested()?;
+        let mut frames = Vec::new();
+        while !buf.starts_with(Self::STREAMED_END) {
+            frames.push(RespFrame::decode_with_limits(buf, nested)?);
+        }
+        buf.advance(Self::STREAMED_END.len());
+        Ok(RespArray::new(frames))
+    }
+
+    // nested 是解析元素时使用的限制，元素个数在解析过程中逐个检查
+    fn streamed_length(
+        buf: &[u8],
+        nested: RespLimits,
+        max_elements: usize,
+    ) -> Result<usize, RespError> {
+        let mut total = Self::STREAMED.len();
+        let mut count = 0;
+        loop {
+            match buf.get(total) {
+                None => return Err(RespError::Incomplete),
+                Some(b'.') => {
+                    let end = total + Self::STREAMED_END.len();
+                    if buf.len() < end {
+                        return Err(RespError::Incomplete);
+                    }
+                    if &buf[total..end] != Self::STREAMED_END {
+                        return Err(RespError::Invalid(
+                            String::from_utf8_lossy(&buf[total..end]).to_string(),
+                        ));
+                    }
+                    return Ok(end);
+                }
+                Some(_) => {
+                    count += 1;
+                    if count > max_elements {
+                        return Err(RespError::ExceedsLimit("invalid multibulk length"));
+                    }
+                    total += element_length(buf, total, nested)?;
+                }
+            }
+        }
+    }
+}
+
 impl Deref for RespArray {
     type Target = Vec<RespFrame>;
 
@@ -80,7 +168,7 @@ impl Deref for RespArray {
 
 #[cfg(test)]
 mod tests {
-    use crate::SimpleString;
+    use crate::{SimpleString, DEFAULT_MAX_DEPTH};
 
     use super::*;
     use anyhow::Result;
@@ -92,9 +180,9 @@ mod tests {
     }
 
     #[test]
-    fn test_null_array_encode() {
+    fn test_empty_array_encode() {
         let frame = RespArray::new(vec![]);
-        assert_eq!(frame.encode(), b"*-1\r\n");
+        assert_eq!(frame.encode(), b"*0\r\n");
     }
 
     #[test]
@@ -115,15 +203,194 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_array_decode_under_count() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n:1\r\n");
+        let ret = RespArray::decode(&mut buf);
+        assert_eq!(ret.unwrap_err(), RespError::Incomplete);
+        // Incomplete 时不应该消耗任何数据
+        assert_eq!(&buf[..], b"*2\r\n:1\r\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_decode_over_count() -> Result<()> {
+        let mut buf = BytesMut::from("*1\r\n:1\r\n:2\r\n");
+        assert_eq!(RespArray::expect_length(&buf)?, 8);
+
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(frame, RespArray::new(vec![RespFrame::Integer(1)]));
+        // 多出来的元素不属于这个数组，原样留给下一次 decode
+        assert_eq!(&buf[..], b":2\r\n");
+        assert_eq!(RespFrame::decode(&mut buf)?, RespFrame::Integer(2));
+        assert!(buf.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_nested_array_decode_consumes_exact_length() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n*1\r\n:1\r\n:2\r\n+OK\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespArray::new(vec![
+                RespArray::new(vec![RespFrame::Integer(1)]).into(),
+                RespFrame::Integer(2)
+            ])
+        );
+        assert_eq!(&buf[..], b"+OK\r\n");
+
+        Ok(())
+    }
+
+    fn nested_arrays(depth: usize) -> BytesMut {
+        let mut buf = BytesMut::new();
+        for _ in 0..depth {
+            buf.extend_from_slice(b"*1\r\n");
+        }
+        buf.extend_from_slice(b":1\r\n");
+        buf
+    }
+
+    #[test]
+    fn test_array_decode_depth_limit() -> Result<()> {
+        let mut buf = nested_arrays(DEFAULT_MAX_DEPTH);
+        RespFrame::decode(&mut buf)?;
+        assert!(buf.is_empty());
+
+        let mut buf = nested_arrays(DEFAULT_MAX_DEPTH + 1);
+        assert_eq!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::ExceededDepthLimit)
+        );
+
+        // 远超限制的嵌套不会导致栈溢出
+        let mut buf = nested_arrays(100_000);
+        assert_eq!(
+            RespArray::decode(&mut buf),
+            Err(RespError::ExceededDepthLimit)
+        );
+
+        // 不完整的深层嵌套同样直接拒绝，而不是等待更多数据
+        let mut buf = nested_arrays(DEFAULT_MAX_DEPTH + 1);
+        buf.truncate(buf.len() - 4);
+        assert_eq!(
+            RespFrame::decode(&mut buf),
+            Err(RespError::ExceededDepthLimit)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_decode_with_custom_depth() -> Result<()> {
+        let limits = RespLimits {
+            max_depth: 2,
+            ..Default::default()
+        };
+        let mut buf = nested_arrays(2);
+        RespFrame::decode_with_limits(&mut buf, limits)?;
+
+        let mut buf = nested_arrays(3);
+        assert_eq!(
+            RespFrame::decode_with_limits(&mut buf, limits),
+            Err(RespError::ExceededDepthLimit)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_array_element_limit() -> Result<()> {
+        // 只有头部也会立即拒绝，不会按声明的长度分配内存
+        let buf = b"*999999999\r\n";
+        assert_eq!(
+            RespFrame::expect_length(buf),
+            Err(RespError::ExceedsLimit("invalid multibulk length"))
+        );
+
+        let limits = RespLimits {
+            max_elements: 2,
+            ..Default::default()
+        };
+        let mut buf = BytesMut::from("*2\r\n:1\r\n:2\r\n");
+        RespFrame::decode_with_limits(&mut buf, limits)?;
+        for data in [
+            "*3\r\n:1\r\n:2\r\n:3\r\n",
+            "*?\r\n:1\r\n:2\r\n:3\r\n.\r\n",
+            "~3\r\n:1\r\n:2\r\n:3\r\n",
+            // map 的每个键值对算两个元素
+            "%2\r\n:1\r\n:2\r\n:3\r\n:4\r\n",
+        ] {
+            assert_eq!(
+                RespFrame::expect_length_with_limits(data.as_bytes(), limits),
+                Err(RespError::ExceedsLimit("invalid multibulk length")),
+                "{data:?}"
+            );
+        }
+
+        // 元素中的 bulk string 同样受长度限制
+        let limits = RespLimits {
+            max_bulk_len: 4,
+            ..Default::default()
+        };
+        let mut buf = BytesMut::from("*1\r\n$5\r\nhello\r\n");
+        assert_eq!(
+            RespFrame::decode_with_limits(&mut buf, limits),
+            Err(RespError::ExceedsLimit("invalid bulk length"))
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_null_array_decode() -> Result<()> {
+        let mut buf = BytesMut::from("*-1\r\n*0\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, RespFrame::Null(crate::RespNull));
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, RespArray::new(vec![]).into());
+        assert!(buf.is_empty());
+
+        // null 不是一个 RespArray
         let mut buf = BytesMut::from("*-1\r\n");
-        let frame = RespArray::decode(&mut buf)?;
-        assert_eq!(frame, RespArray::new(vec![]));
+        let frame = RespArray::decode(&mut buf);
+        assert_eq!(frame, Err(RespError::Invalid("*-1\r\n".to_string())));
 
         let mut buf = BytesMut::from("*-2\r\n");
         let frame = RespArray::decode(&mut buf);
         assert_eq!(frame, Err(RespError::Invalid("*-2\r\n".to_string())));
         Ok(())
     }
+
+    #[test]
+    fn test_streamed_array_decode() -> Result<()> {
+        let data = b"*?\r\n$3\r\nget\r\n$?\r\n;2\r\nhe\r\n;3\r\nllo\r\n;0\r\n.\r\n";
+        assert_eq!(RespFrame::expect_length(data)?, data.len());
+        for end in 0..data.len() {
+            assert_eq!(
+                RespFrame::expect_length(&data[..end]),
+                Err(RespError::Incomplete)
+            );
+        }
+
+        let mut buf = BytesMut::from(&data[..]);
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespArray::new(vec![b"get".into(), b"hello".into()]).into()
+        );
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("*?\r\n.\r\n");
+        assert_eq!(RespArray::decode(&mut buf)?, RespArray::new(vec![]));
+
+        let mut buf = BytesMut::from("*?\r\n:1\r\n.x\r\n");
+        assert!(matches!(
+            RespArray::decode(&mut buf),
+            Err(RespError::Invalid(_))
+        ));
+        Ok(())
+    }
 }
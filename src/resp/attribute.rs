@@ -0,0 +1,134 @@
+use bytes::BytesMut;
+
+use crate::{RespDecoder, RespEncoder, RespError, RespFrame, RespLimits, RespMap};
+
+use super::{
+    element_length, encode_to_vec, extract_len_and_end, extract_nth, write_header, CRLF_LEN,
+};
+
+// 属性本身不是一个独立的回复，总是附加在紧随其后的帧上，解析时两者合并为一个帧
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct RespAttribute {
+    attributes: RespMap,
+    value: Box<RespFrame>,
+}
+
+// - attribute: "|<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n><frame>"
+impl RespEncoder for RespAttribute {
+    fn encode(&self) -> Vec<u8> {
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        write_header(buf, b'|', self.attributes.len());
+        for (key, value) in self.attributes.iter() {
+            key.encode_into(buf);
+            value.encode_into(buf);
+        }
+        self.value.encode_into(buf);
+    }
+}
+
+impl RespDecoder for RespAttribute {
+    const PREFIX: &'static str = "|";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_with_limits(buf, RespLimits::default())
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_with_limits(buf, RespLimits::default())
+    }
+
+    fn decode_with_limits(buf: &mut BytesMut, limits: RespLimits) -> Result<Self, RespError> {
+        let total = Self::expect_length_with_limits(buf, limits)?;
+        if buf.len() < total {
+            return Err(RespError::Incomplete);
+        }
+
+        let nth = extract_nth(buf, Self::PREFIX)?;
+        let nested = limits.nested()?;
+        let mut attributes = RespMap::new();
+        for _ in 0..nth {
+            let key = RespFrame::decode_with_limits(buf, nested)?;
+            let value = RespFrame::decode_with_limits(buf, nested)?;
+            attributes.insert(key, value);
+        }
+        let value = RespFrame::decode_with_limits(buf, nested)?;
+        Ok(Self::new(attributes, value))
+    }
+
+    fn expect_length_with_limits(buf: &[u8], limits: RespLimits) -> Result<usize, RespError> {
+        let nested = limits.nested()?;
+        let (len, end) = extract_len_and_end(buf)?;
+        limits.check_elements(len.saturating_mul(2))?;
+        let mut total = end + CRLF_LEN;
+        // 键值对之后还有被描述的帧
+        for _ in 0..len * 2 + 1 {
+            total += element_length(buf, total, nested)?;
+        }
+        Ok(total)
+    }
+}
+
+impl RespAttribute {
+    pub fn new(attributes: RespMap, value: impl Into<RespFrame>) -> Self {
+        Self {
+            attributes,
+            value: Box::new(value.into()),
+        }
+    }
+
+    pub fn attributes(&self) -> &RespMap {
+        &self.attributes
+    }
+
+    pub fn value(&self) -> &RespFrame {
+        &self.value
+    }
+
+    pub fn into_value(self) -> RespFrame {
+        *self.value
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespVersion, SimpleString};
+
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_attribute_decode() -> Result<()> {
+        let data = b"|1\r\n+ttl\r\n:+3600\r\n$5\r\nhello\r\n";
+        assert_eq!(RespFrame::expect_length(data)?, data.len());
+
+        let mut buf = BytesMut::from(&data[..]);
+        let RespFrame::Attribute(frame) = RespFrame::decode(&mut buf)? else {
+            panic!("expected an attribute frame");
+        };
+        assert!(buf.is_empty());
+        assert_eq!(frame.value(), &RespFrame::from(b"hello"));
+        assert_eq!(
+            frame.attributes().get(&SimpleString::new("ttl").into()),
+            Some(&RespFrame::Integer(3600))
+        );
+
+        let mut buf = BytesMut::from("|1\r\n+ttl\r\n:3600\r\n");
+        assert_eq!(RespFrame::decode(&mut buf), Err(RespError::Incomplete));
+        Ok(())
+    }
+
+    #[test]
+    fn test_attribute_encode() {
+        let mut attributes = RespMap::new();
+        attributes.insert(SimpleString::new("ttl").into(), RespFrame::Integer(3600));
+        let frame: RespFrame = RespAttribute::new(attributes, RespFrame::from(b"hello")).into();
+        assert_eq!(
+            frame.encode_for(RespVersion::Resp3),
+            b"|1\r\n+ttl\r\n:3600\r\n$5\r\nhello\r\n"
+        );
+        // RESP2 客户端不认识属性，只发送被描述的帧
+        assert_eq!(frame.encode_for(RespVersion::Resp2), b"$5\r\nhello\r\n");
+    }
+}
@@ -0,0 +1,113 @@
+use std::ops::Deref;
+
+use bytes::{BufMut as _, BytesMut};
+
+use crate::{RespDecoder, RespEncoder, RespError};
+
+use super::{encode_to_vec, extract_data, CRLF};
+
+// 超出 i64 范围的整数，保留原始的十进制文本
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct RespBigNumber(pub(crate) String);
+
+// - big number: "([+|-]<number>\r\n"
+impl RespEncoder for RespBigNumber {
+    fn encode(&self) -> Vec<u8> {
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u8(b'(');
+        buf.extend_from_slice(self.0.as_bytes());
+        buf.extend_from_slice(CRLF.as_bytes());
+    }
+}
+
+impl RespDecoder for RespBigNumber {
+    const PREFIX: &'static str = "(";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let data = extract_data(buf, Self::PREFIX)?;
+        Self::parse(&data)
+    }
+}
+
+impl RespBigNumber {
+    // 只接受可选的正负号加十进制数字，正号不保留，重新编码时不会输出
+    pub fn parse(s: &str) -> Result<Self, RespError> {
+        let digits = s.strip_prefix(['+', '-']).unwrap_or(s);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(RespError::Invalid(format!("Parse failed: {:?}", s)));
+        }
+        Ok(Self(s.strip_prefix('+').unwrap_or(s).to_string()))
+    }
+}
+
+impl From<i128> for RespBigNumber {
+    fn from(n: i128) -> Self {
+        Self(n.to_string())
+    }
+}
+
+impl From<u128> for RespBigNumber {
+    fn from(n: u128) -> Self {
+        Self(n.to_string())
+    }
+}
+
+impl Deref for RespBigNumber {
+    type Target = String;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_big_number_encode() {
+        let frame = RespBigNumber::parse("-99999999999999999999").unwrap();
+        assert_eq!(frame.encode(), b"(-99999999999999999999\r\n");
+    }
+
+    #[test]
+    fn test_big_number_parse() {
+        assert_eq!(
+            RespBigNumber::parse("+123").map(|n| n.0),
+            Ok("123".to_string())
+        );
+        assert!(RespBigNumber::parse("12a3").is_err());
+        assert!(RespBigNumber::parse("-").is_err());
+        assert!(RespBigNumber::parse("").is_err());
+    }
+
+    #[test]
+    fn test_big_number_decode() -> Result<(), RespError> {
+        let digits = "123456789012345678901234567890123456789012345678901234567890";
+        let frame = RespBigNumber::parse(digits)?;
+        let mut buf = BytesMut::from(frame.encode().as_slice());
+        assert_eq!(RespBigNumber::decode(&mut buf)?, frame);
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("(12a3\r\n");
+        assert!(RespBigNumber::decode(&mut buf).is_err());
+
+        let mut buf = BytesMut::from("(123");
+        assert_eq!(RespBigNumber::decode(&mut buf), Err(RespError::Incomplete));
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_number_from_int() {
+        assert_eq!(
+            RespBigNumber::from(i128::MIN).as_str(),
+            i128::MIN.to_string()
+        );
+        assert_eq!(
+            RespBigNumber::from(u128::MAX).encode(),
+            format!("({}\r\n", u128::MAX).into_bytes()
+        );
+    }
+}
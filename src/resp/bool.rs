@@ -2,12 +2,16 @@ use bytes::BytesMut;
 
 use crate::{RespDecoder, RespEncoder, RespError};
 
-use super::extract_data;
+use super::{encode_to_vec, extract_data};
 
 // - boolean: "#<t|f>\r\n"
 impl RespEncoder for bool {
     fn encode(&self) -> Vec<u8> {
-        format!("#{}\r\n", if *self { "t" } else { "f" }).into_bytes()
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.extend_from_slice(if *self { b"#t\r\n" } else { b"#f\r\n" });
     }
 }
 
@@ -1,10 +1,12 @@
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 
-use bytes::BytesMut;
+use bytes::{Buf as _, BytesMut};
 
-use crate::{RespDecoder, RespEncoder, RespError};
+use crate::{RespDecoder, RespEncoder, RespError, RespLimits};
 
-use super::{extract_data, extract_len_and_end, CRLF_LEN};
+use super::{
+    encode_to_vec, extract_len_and_end, length_prefixed_data, write_header, CRLF, CRLF_LEN,
+};
 
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
 pub struct BulkError(pub(crate) Vec<u8>);
@@ -12,30 +14,49 @@ pub struct BulkError(pub(crate) Vec<u8>);
 // - bulk error: "!<length>\r\n<error>\r\n"
 impl RespEncoder for BulkError {
     fn encode(&self) -> Vec<u8> {
-        format!(
-            "!{}\r\n{}\r\n",
-            self.0.len(),
-            String::from_utf8_lossy(&self.0)
-        )
-        .into_bytes()
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        write_header(buf, b'!', self.0.len());
+        buf.extend_from_slice(&self.0);
+        buf.extend_from_slice(CRLF.as_bytes());
     }
 }
 
 impl RespDecoder for BulkError {
     const PREFIX: &'static str = "!";
-    const N_CRLF: usize = 2;
+    // 与 BulkString 相同，按长度读取，错误信息中可以包含 CRLF
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let (len, end) = extract_len_and_end(buf)?;
-        let prefix = String::from_utf8_lossy(&buf[0..end + CRLF_LEN]).to_string();
-        let data = extract_data(buf, &prefix)?;
-        if data.len() != len {
-            return Err(RespError::InvalidFrameLength);
-        }
-        Ok(BulkError::new(data))
+        let data = Self::data_range(buf)?;
+        let error = BulkError::new(&buf[data.clone()]);
+        buf.advance(data.end + CRLF_LEN);
+        Ok(error)
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::data_range(buf).map(|data| data.end + CRLF_LEN)
+    }
+
+    fn expect_length_with_limits(buf: &[u8], limits: RespLimits) -> Result<usize, RespError> {
+        let (len, _) = extract_len_and_end(buf)?;
+        limits.check_bulk_len(len)?;
+        Self::expect_length(buf)
     }
 }
 
 impl BulkError {
+    fn data_range(buf: &[u8]) -> Result<Range<usize>, RespError> {
+        if !buf.starts_with(Self::PREFIX.as_bytes()) {
+            return Err(RespError::InvalidFrameType(format!(
+                "Invalid frame: {:?}",
+                buf
+            )));
+        }
+        let (len, end) = extract_len_and_end(buf)?;
+        length_prefixed_data(buf, end, len)
+    }
+
     pub fn new(s: impl Into<Vec<u8>>) -> Self {
         Self(s.into())
     }
@@ -51,6 +72,8 @@ impl Deref for BulkError {
 
 #[cfg(test)]
 mod tests {
+    use crate::RespFrame;
+
     use super::*;
     use anyhow::Result;
 
@@ -70,6 +93,26 @@ mod tests {
         let frame = BulkError::decode(&mut buf);
         assert_eq!(frame, Err(RespError::InvalidFrameLength));
 
+        let mut buf = BytesMut::from("!13\r\nError message");
+        let frame = BulkError::decode(&mut buf);
+        assert_eq!(frame, Err(RespError::Incomplete));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_error_payload_with_crlf() -> Result<()> {
+        let payloads: [&[u8]; 3] = [b"ERR a\r\nb", b"$3\r\nfoo", b"*1\r\n:1\r\n"];
+        for payload in payloads {
+            let frame = BulkError::new(payload);
+            let mut data = frame.encode();
+            data.extend_from_slice(b":1\r\n");
+            assert_eq!(RespFrame::expect_length(&data), Ok(data.len() - 4));
+
+            let mut buf = BytesMut::from(&data[..]);
+            assert_eq!(RespFrame::decode(&mut buf)?, frame.into());
+            assert_eq!(RespFrame::decode(&mut buf)?, RespFrame::Integer(1));
+        }
         Ok(())
     }
 }
@@ -1,65 +1,155 @@
-use std::ops::Deref;
+use std::ops::{Deref, Range};
 
-use bytes::{Buf, BytesMut};
+use bytes::{Buf as _, Bytes, BytesMut};
 
-use crate::{RespDecoder, RespEncoder, RespError};
+use crate::{RespDecoder, RespEncoder, RespError, RespLimits};
 
-use super::{extract_data, extract_length_data, find_crlf, CRLF, CRLF_LEN};
+use super::{
+    encode_to_vec, extract_length_data, find_crlf, length_prefixed_data, write_header, CRLF,
+    CRLF_LEN,
+};
 
+// 解析时与读缓冲区共享内存，clone 只增加引用计数
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
-pub struct BulkString(pub(crate) Vec<u8>);
+pub struct BulkString(pub(crate) Bytes);
 
 // - bulk string: "$<length>\r\n<data>\r\n"
-// - null bulk string: "$-1\r\n"
+// - null bulk string: "$-1\r\n"，由 RespFrame 解析为 RespFrame::Null，空字符串编码为 "$0\r\n\r\n"
+// - streamed string: "$?\r\n;<length>\r\n<data>\r\n...;0\r\n"，只支持解析，合并为普通的 BulkString
 impl RespEncoder for BulkString {
     fn encode(&self) -> Vec<u8> {
-        if self.is_empty() {
-            return "$-1\r\n".to_string().into_bytes();
-        }
-        format!("${}\r\n{}\r\n", self.len(), String::from_utf8_lossy(self)).into_bytes()
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        write_header(buf, b'$', self.len());
+        buf.extend_from_slice(self);
+        buf.extend_from_slice(CRLF.as_bytes());
     }
 }
 
 impl RespDecoder for BulkString {
     const PREFIX: &'static str = "$";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let len_data = extract_length_data(buf, Self::PREFIX)?;
-        if len_data == "-1" {
-            buf.advance(3 + CRLF_LEN);
-            return Ok(BulkString::new(""));
+        Self::decode_with_limits(buf, RespLimits::default())
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_with_limits(buf, RespLimits::default())
+    }
+
+    fn decode_with_limits(buf: &mut BytesMut, limits: RespLimits) -> Result<Self, RespError> {
+        if buf.starts_with(Self::STREAMED) {
+            let (chunks, total) = streamed_chunks(buf, limits.max_bulk_len)?;
+            let mut data = Vec::with_capacity(chunks.iter().map(|chunk| chunk.len()).sum());
+            for chunk in chunks {
+                data.extend_from_slice(&buf[chunk]);
+            }
+            buf.advance(total);
+            return Ok(BulkString::new(data));
         }
+        let len_data = extract_length_data(buf, Self::PREFIX)?;
         let len = len_data
-            .parse::<usize>()
+            .parse::<i64>()
             .map_err(|_| RespError::InvalidFrameLength)?;
-        let data = extract_data(
-            buf,
-            format!("{}{}{}", Self::PREFIX, len_data, CRLF).as_str(),
-        )?;
-        if data.len() != len {
-            return Err(RespError::InvalidFrameLength);
+        if len < 0 {
+            return Err(RespError::Invalid(format!(
+                "{}{}{}",
+                Self::PREFIX,
+                len_data,
+                CRLF
+            )));
         }
-        Ok(BulkString::new(data))
+        let len = len as usize;
+        limits.check_bulk_len(len)?;
+        let data = length_prefixed_data(buf, Self::PREFIX.len() + len_data.len(), len)?;
+        // 直接切出读缓冲区中的数据，不复制
+        buf.advance(data.start);
+        let data = buf.split_to(len).freeze();
+        buf.advance(CRLF_LEN);
+        Ok(BulkString(data))
     }
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    // 长度超过限制时只看头部就拒绝，不等待数据
+    fn expect_length_with_limits(buf: &[u8], limits: RespLimits) -> Result<usize, RespError> {
         let len_end = find_crlf(buf, 1, 1).ok_or(RespError::Incomplete)?;
         let data_start = len_end + CRLF_LEN;
-        if &buf[1..len_end] == b"-1" {
-            return Ok(data_start);
-        }
-        let end = find_crlf(&buf[data_start..], 1, 0).ok_or(RespError::Incomplete)?;
-        Ok(data_start + end + CRLF_LEN)
+        let len = match &buf[1..len_end] {
+            b"-1" => return Ok(data_start),
+            b"?" => return streamed_chunks(buf, limits.max_bulk_len).map(|(_, total)| total),
+            [b'-', ..] => {
+                return Err(RespError::Invalid(
+                    String::from_utf8_lossy(&buf[..data_start]).to_string(),
+                ))
+            }
+            len => String::from_utf8_lossy(len)
+                .parse::<usize>()
+                .map_err(|_| RespError::InvalidFrameLength)?,
+        };
+        limits.check_bulk_len(len)?;
+        let data = length_prefixed_data(buf, len_end, len)?;
+        Ok(data.end + CRLF_LEN)
     }
 }
 
 impl BulkString {
+    pub(crate) const NULL: &'static [u8] = b"$-1\r\n";
+    const STREAMED: &'static [u8] = b"$?\r\n";
+
     pub fn new(s: impl Into<Vec<u8>>) -> Self {
-        Self(s.into())
+        Self(Bytes::from(s.into()))
+    }
+}
+
+// 返回每一块数据在 buf 中的位置和整个帧的长度，数据不完整时返回 Incomplete。
+// 流式字符串事先不知道长度，逐块累加检查是否超过 max_len
+fn streamed_chunks(buf: &[u8], max_len: usize) -> Result<(Vec<Range<usize>>, usize), RespError> {
+    let mut chunks = Vec::new();
+    let mut size: usize = 0;
+    let mut pos = BulkString::STREAMED.len();
+    loop {
+        match buf.get(pos) {
+            None => return Err(RespError::Incomplete),
+            Some(b';') => {}
+            Some(_) => {
+                return Err(RespError::Invalid(format!(
+                    "Invalid streamed string chunk: {:?}",
+                    String::from_utf8_lossy(&buf[pos..])
+                )))
+            }
+        }
+        let header_end = pos + find_crlf(&buf[pos..], 1, 1).ok_or(RespError::Incomplete)?;
+        let len = String::from_utf8_lossy(&buf[pos + 1..header_end])
+            .parse::<usize>()
+            .map_err(|_| RespError::InvalidFrameLength)?;
+        let data_start = header_end + CRLF_LEN;
+        // 长度为 0 的块表示结束，之后没有数据
+        if len == 0 {
+            return Ok((chunks, data_start));
+        }
+        size = size
+            .checked_add(len)
+            .ok_or(RespError::ExceedsLimit("frame too large"))?;
+        if size > max_len {
+            return Err(RespError::ExceedsLimit("invalid bulk length"));
+        }
+        let data_end = data_start
+            .checked_add(len)
+            .filter(|end| end.checked_add(CRLF_LEN).is_some())
+            .ok_or(RespError::ExceedsLimit("frame too large"))?;
+        if buf.len() < data_end + CRLF_LEN {
+            return Err(RespError::Incomplete);
+        }
+        if &buf[data_end..data_end + CRLF_LEN] != CRLF.as_bytes() {
+            return Err(RespError::InvalidFrameLength);
+        }
+        chunks.push(data_start..data_end);
+        pos = data_end + CRLF_LEN;
     }
 }
 
 impl Deref for BulkString {
-    type Target = Vec<u8>;
+    type Target = [u8];
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -68,13 +158,25 @@ impl Deref for BulkString {
 
 impl From<&[u8]> for BulkString {
     fn from(s: &[u8]) -> Self {
-        Self(s.to_vec())
+        Self(Bytes::copy_from_slice(s))
     }
 }
 
 impl<const N: usize> From<&[u8; N]> for BulkString {
     fn from(s: &[u8; N]) -> Self {
-        Self(s.to_vec())
+        Self(Bytes::copy_from_slice(s))
+    }
+}
+
+impl From<Bytes> for BulkString {
+    fn from(s: Bytes) -> Self {
+        Self(s)
+    }
+}
+
+impl From<BulkString> for Vec<u8> {
+    fn from(s: BulkString) -> Self {
+        s.0.into()
     }
 }
 
@@ -86,6 +188,8 @@ impl AsRef<[u8]> for BulkString {
 
 #[cfg(test)]
 mod tests {
+    use crate::{RespArray, RespFrame, RespNull, SimpleString, DEFAULT_MAX_BULK_LEN};
+
     use super::*;
     use anyhow::Result;
 
@@ -96,9 +200,21 @@ mod tests {
     }
 
     #[test]
-    fn test_null_bulk_string_encode() {
+    fn test_binary_bulk_string_round_trip() -> Result<()> {
+        let frame = BulkString::new(vec![0xff, 0x00, b'a', 0xfe]);
+        let encoded = frame.encode();
+        assert_eq!(encoded, b"$4\r\n\xff\x00a\xfe\r\n");
+
+        let mut buf = BytesMut::from(&encoded[..]);
+        assert_eq!(BulkString::decode(&mut buf)?, frame);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_bulk_string_encode() {
         let frame = BulkString::new("");
-        assert_eq!(frame.encode(), b"$-1\r\n");
+        assert_eq!(frame.encode(), b"$0\r\n\r\n");
     }
 
     #[test]
@@ -107,22 +223,186 @@ mod tests {
         let frame = BulkString::decode(&mut buf)?;
         assert_eq!(frame, BulkString::new(b"Hello, world!".to_vec()));
 
+        // 按长度读取，数据比声明的短时无法与不完整的帧区分
         let mut buf = BytesMut::from("$13\r\nHello, world\r\n");
         let frame = BulkString::decode(&mut buf);
+        assert_eq!(frame, Err(RespError::Incomplete));
+
+        let mut buf = BytesMut::from("$12\r\nHello, world!\r\n");
+        let frame = BulkString::decode(&mut buf);
         assert_eq!(frame, Err(RespError::InvalidFrameLength));
 
         Ok(())
     }
 
     #[test]
-    fn test_null_bulk_string_decode() -> Result<()> {
+    fn test_bulk_string_payload_with_crlf() -> Result<()> {
+        let payloads: [&[u8]; 4] = [b"a\r\nb", b"\r\n", b"$3\r\nfoo\r\n", b"*2\r\n:1\r\n:2\r\n"];
+        for payload in payloads {
+            let frame = BulkString::new(payload);
+            let mut data = frame.encode();
+            // 之后紧跟一个帧，数据中的字节不能被当作下一个帧
+            data.extend_from_slice(b"+OK\r\n");
+            assert_eq!(RespFrame::expect_length(&data), Ok(data.len() - 5));
+
+            let mut buf = BytesMut::from(&data[..]);
+            assert_eq!(RespFrame::decode(&mut buf)?, frame.into());
+            assert_eq!(RespFrame::decode(&mut buf)?, SimpleString::new("OK").into());
+            assert!(buf.is_empty());
+        }
+
+        // SET key "a\r\nb"
+        let mut buf = BytesMut::from("*3\r\n$3\r\nset\r\n$3\r\nkey\r\n$4\r\na\r\nb\r\n");
+        let frame = RespArray::decode(&mut buf)?;
+        assert_eq!(frame[2], BulkString::new("a\r\nb").into());
+        assert!(buf.is_empty());
+
+        // 数据不完整时等待，而不是把数据中的 CRLF 当作结尾
+        let data = b"$4\r\na\r\nb\r\n";
+        for end in 0..data.len() {
+            assert_eq!(
+                RespFrame::expect_length(&data[..end]),
+                Err(RespError::Incomplete)
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_bulk_string_decode_by_length() -> Result<()> {
         let mut buf = BytesMut::from("$-1\r\n");
-        let frame = BulkString::decode(&mut buf)?;
-        assert_eq!(frame, BulkString::new(""));
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, RespFrame::Null(RespNull));
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("$0\r\n\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, RespFrame::BulkString(BulkString::new("")));
+        assert!(buf.is_empty());
 
         let mut buf = BytesMut::from("$-2\r\n");
+        let frame = RespFrame::decode(&mut buf);
+        assert_eq!(frame, Err(RespError::Invalid("$-2\r\n".to_string())));
+
+        // null 不是一个 BulkString
+        let mut buf = BytesMut::from("$-1\r\n");
+        let frame = BulkString::decode(&mut buf);
+        assert_eq!(frame, Err(RespError::Invalid("$-1\r\n".to_string())));
+
+        let mut buf = BytesMut::from("$abc\r\n");
         let frame = BulkString::decode(&mut buf);
         assert_eq!(frame, Err(RespError::InvalidFrameLength));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_negative_length_in_array() -> Result<()> {
+        let mut buf = BytesMut::from("*2\r\n$-1\r\n$-2\r\n");
+        let frame = RespFrame::decode(&mut buf);
+        assert_eq!(frame, Err(RespError::Invalid("$-2\r\n".to_string())));
+
+        let mut buf = BytesMut::from("*2\r\n$-1\r\n$0\r\n\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespArray::new(vec![RespFrame::Null(RespNull), BulkString::new("").into()]).into()
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_empty_bulk_string_round_trip() -> Result<()> {
+        let frame = BulkString::new("");
+        let mut buf = BytesMut::from(&frame.encode()[..]);
+        assert_eq!(BulkString::decode(&mut buf)?, frame);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_streamed_bulk_string_decode() -> Result<()> {
+        let data = b"$?\r\n;4\r\nHell\r\n;2\r\no!\r\n;0\r\n";
+        assert_eq!(RespFrame::expect_length(data)?, data.len());
+        let mut buf = BytesMut::from(&data[..]);
+        buf.extend_from_slice(b"+OK\r\n");
+        assert_eq!(RespFrame::decode(&mut buf)?, b"Hello!".into());
+        assert_eq!(buf, "+OK\r\n");
+
+        // 块中的数据按长度读取，可以包含 CRLF
+        let mut buf = BytesMut::from("$?\r\n;4\r\na\r\nb\r\n;0\r\n");
+        assert_eq!(BulkString::decode(&mut buf)?, BulkString::new("a\r\nb"));
+
+        let mut buf = BytesMut::from("$?\r\n;0\r\n");
+        assert_eq!(BulkString::decode(&mut buf)?, BulkString::new(""));
+        Ok(())
+    }
+
+    #[test]
+    fn test_streamed_bulk_string_incomplete() {
+        let data = b"$?\r\n;4\r\nHell\r\n;2\r\no!\r\n;0\r\n";
+        for end in 0..data.len() {
+            assert_eq!(
+                RespFrame::expect_length(&data[..end]),
+                Err(RespError::Incomplete),
+                "{:?}",
+                String::from_utf8_lossy(&data[..end])
+            );
+            let mut buf = BytesMut::from(&data[..end]);
+            assert!(RespFrame::decode(&mut buf).is_err());
+            assert_eq!(buf, &data[..end]);
+        }
+    }
+
+    #[test]
+    fn test_streamed_bulk_string_invalid() {
+        let mut buf = BytesMut::from("$?\r\n;4\r\nHello\r\n;0\r\n");
+        assert_eq!(
+            BulkString::decode(&mut buf),
+            Err(RespError::InvalidFrameLength)
+        );
+
+        let mut buf = BytesMut::from("$?\r\n$4\r\nHell\r\n;0\r\n");
+        assert!(matches!(
+            BulkString::decode(&mut buf),
+            Err(RespError::Invalid(_))
+        ));
+
+        // 累计长度超过上限时不需要等数据到齐
+        let data = format!("$?\r\n;4\r\nHell\r\n;{}\r\n", DEFAULT_MAX_BULK_LEN - 3);
+        assert_eq!(
+            RespFrame::expect_length(data.as_bytes()),
+            Err(RespError::ExceedsLimit("invalid bulk length"))
+        );
+
+        // 上限很大时累加长度也不能溢出
+        let data = format!("$?\r\n;4\r\nHell\r\n;{}\r\n", usize::MAX);
+        let mut buf = BytesMut::from(data.as_str());
+        assert_eq!(
+            streamed_chunks(&buf, usize::MAX),
+            Err(RespError::ExceedsLimit("frame too large"))
+        );
+        assert!(BulkString::decode(&mut buf).is_err());
+    }
+
+    #[test]
+    fn test_decode_shares_read_buffer() -> Result<()> {
+        let payload = vec![b'x'; 1024];
+        let frame = BulkString::new(payload.clone());
+        let mut buf = BytesMut::new();
+        frame.encode_into(&mut buf);
+        let start = buf.as_ptr() as usize;
+        let end = start + buf.len();
+
+        let decoded = BulkString::decode(&mut buf)?;
+        // 数据直接指向读缓冲区，没有复制
+        let data = decoded.as_ref().as_ptr() as usize;
+        assert!(data > start && data + payload.len() < end);
+        assert_eq!(decoded, frame);
+        assert_eq!(decoded.as_ref(), payload.as_slice());
+        assert_eq!(decoded.encode(), frame.encode());
+        assert_eq!(Vec::from(decoded), payload);
         Ok(())
     }
 }
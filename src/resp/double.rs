@@ -1,18 +1,32 @@
-use std::ops::Deref;
+use std::{
+    cmp::Ordering,
+    fmt::{self, Write as _},
+    hash::{Hash, Hasher},
+};
 
-use bytes::BytesMut;
+use bytes::{BufMut as _, BytesMut};
 
 use crate::{RespDecoder, RespEncoder, RespError};
 
-use super::extract_data;
+use super::{encode_to_vec, extract_data, CRLF};
 
-#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
-pub struct RespDouble(pub(crate) String);
+// 保存原始的 f64，编码时再转换为文本，保证解析后得到完全相同的值
+#[derive(Debug, Clone, Copy)]
+pub struct RespDouble(pub(crate) f64);
 
 // - double: ",[<+|->]<integral>[.<fractional>][<E|e>[sign]<exponent>]\r\n"
 impl RespEncoder for RespDouble {
     fn encode(&self) -> Vec<u8> {
-        format!(",{}\r\n", self.0).into_bytes()
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u8(b',');
+        if self.0.is_finite() && self.0.is_sign_positive() {
+            buf.put_u8(b'+');
+        }
+        write!(buf, "{}", self).expect("writing to BytesMut never fails");
+        buf.extend_from_slice(CRLF.as_bytes());
     }
 }
 
@@ -29,20 +43,58 @@ impl RespDecoder for RespDouble {
 
 impl RespDouble {
     pub fn new(s: f64) -> Self {
-        let s = if s.abs() > 1e8 {
-            format!("{:+e}", s)
-        } else {
-            format!("{:+}", s)
-        };
         Self(s)
     }
+
+    pub fn value(&self) -> f64 {
+        self.0
+    }
 }
 
-impl Deref for RespDouble {
-    type Target = String;
+// 与 redis 的 %.17g 一样，只有指数很大或很小时才使用科学计数法。
+// 两种格式输出的都是能精确还原的最短表示
+impl fmt::Display for RespDouble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.0;
+        if value.is_nan() {
+            return f.write_str("nan");
+        }
+        if value.is_infinite() {
+            return f.write_str(if value > 0.0 { "inf" } else { "-inf" });
+        }
+        let abs = value.abs();
+        if abs != 0.0 && !(1e-4..1e17).contains(&abs) {
+            write!(f, "{:e}", value)
+        } else {
+            write!(f, "{}", value)
+        }
+    }
+}
+
+// 按位比较，NaN 与自身相等，0.0 与 -0.0 不相等，这样才能满足 Eq 和 Hash 的要求
+impl PartialEq for RespDouble {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.to_bits() == other.0.to_bits()
+    }
+}
+
+impl Eq for RespDouble {}
 
-    fn deref(&self) -> &Self::Target {
-        &self.0
+impl PartialOrd for RespDouble {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for RespDouble {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl Hash for RespDouble {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.to_bits().hash(state);
     }
 }
 
@@ -66,10 +118,58 @@ mod tests {
         assert_eq!(frame.encode(), b",-123.456\r\n");
 
         let frame = RespDouble::new(1.23456e8);
-        assert_eq!(frame.encode(), b",+1.23456e8\r\n");
+        assert_eq!(frame.encode(), b",+123456000\r\n");
 
         let frame = RespDouble::new(-1.23456e8);
-        assert_eq!(frame.encode(), b",-1.23456e8\r\n");
+        assert_eq!(frame.encode(), b",-123456000\r\n");
+
+        let frame = RespDouble::new(1.5e300);
+        assert_eq!(frame.encode(), b",+1.5e300\r\n");
+
+        let frame = RespDouble::new(f64::NEG_INFINITY);
+        assert_eq!(frame.encode(), b",-inf\r\n");
+
+        let frame = RespDouble::new(f64::NAN);
+        assert_eq!(frame.encode(), b",nan\r\n");
+    }
+
+    #[test]
+    fn test_double_round_trip() -> Result<()> {
+        let values = [
+            1e8,
+            1e8 - 1.0,
+            1e8 + 1.0,
+            1.5e8,
+            99_999_999.999_999_99,
+            100_000_000.000_000_01,
+            0.1 + 0.2,
+            std::f64::consts::PI * 1e8,
+            1e17,
+            1e-5,
+            -123_456_789.123_456_78,
+            f64::MAX,
+            f64::MIN_POSITIVE,
+            -0.0,
+        ];
+        for value in values {
+            let encoded = RespDouble::new(value).encode();
+            let mut buf = BytesMut::from(&encoded[..]);
+            let decoded = RespDouble::decode(&mut buf)?;
+            assert_eq!(
+                decoded.value().to_bits(),
+                value.to_bits(),
+                "{}",
+                String::from_utf8_lossy(&encoded)
+            );
+        }
+        // 1e8 两侧使用相同的格式
+        assert_eq!(RespDouble::new(1e8).encode(), b",+100000000\r\n");
+        assert_eq!(RespDouble::new(1.5e8).encode(), b",+150000000\r\n");
+
+        let mut buf = BytesMut::from(",nan\r\n,-inf\r\n");
+        assert!(RespDouble::decode(&mut buf)?.value().is_nan());
+        assert_eq!(RespDouble::decode(&mut buf)?.value(), f64::NEG_INFINITY);
+        Ok(())
     }
 
     #[test]
@@ -1,11 +1,14 @@
-use bytes::BytesMut;
+use bytes::{Buf as _, BytesMut};
 use enum_dispatch::enum_dispatch;
 
 use crate::{
-    BulkError, BulkString, RespArray, RespDecoder, RespDouble, RespError, RespMap, RespNull,
-    RespSet, SimpleError, SimpleString,
+    BulkError, BulkString, RespArray, RespAttribute, RespBigNumber, RespDecoder, RespDouble,
+    RespEncoder, RespError, RespLimits, RespMap, RespNull, RespPush, RespSet, RespVersion,
+    SimpleError, SimpleString, VerbatimString,
 };
 
+use super::{integer::decode_integer_frame, write_header};
+
 #[enum_dispatch(RespEncoder)]
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
 pub enum RespFrame {
@@ -20,29 +23,54 @@ pub enum RespFrame {
     Double(RespDouble),
     Map(RespMap),
     Set(RespSet),
+    BigNumber(RespBigNumber),
+    VerbatimString(VerbatimString),
+    Push(RespPush),
+    Attribute(RespAttribute),
 }
 
 impl RespDecoder for RespFrame {
     const PREFIX: &'static str = "";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_with_limits(buf, RespLimits::default())
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_with_limits(buf, RespLimits::default())
+    }
+
+    fn decode_with_limits(buf: &mut BytesMut, limits: RespLimits) -> Result<Self, RespError> {
         // 使用迭代器方式可以避免buf长度为0时的panic
         let mut iter = buf.iter().peekable();
         match iter.peek() {
             Some(b'+') => SimpleString::decode(buf).map(RespFrame::SimpleString),
             Some(b'-') => SimpleError::decode(buf).map(RespFrame::Error),
             Some(b'!') => BulkError::decode(buf).map(RespFrame::BulkError),
-            Some(b':') => i64::decode(buf).map(RespFrame::Integer),
-            Some(b'$') => BulkString::decode(buf).map(RespFrame::BulkString),
+            Some(b':') => decode_integer_frame(buf),
+            Some(b'$') if buf.starts_with(BulkString::NULL) => {
+                buf.advance(BulkString::NULL.len());
+                Ok(RespFrame::Null(RespNull))
+            }
+            Some(b'$') => BulkString::decode_with_limits(buf, limits).map(RespFrame::BulkString),
             Some(b'_') => RespNull::decode(buf).map(RespFrame::Null),
             Some(b'#') => bool::decode(buf).map(RespFrame::Boolean),
             Some(b',') => RespDouble::decode(buf).map(RespFrame::Double),
-            Some(b'*') => RespArray::decode(buf).map(RespFrame::Array),
+            Some(b'(') => RespBigNumber::decode(buf).map(RespFrame::BigNumber),
+            Some(b'=') => VerbatimString::decode(buf).map(RespFrame::VerbatimString),
+            Some(b'>') => RespPush::decode_with_limits(buf, limits).map(RespFrame::Push),
+            Some(b'|') => RespAttribute::decode_with_limits(buf, limits).map(RespFrame::Attribute),
+            // 与 RESP3 一致，null bulk string 和 null array 都解析为 Null，与空字符串、空数组区分开
+            Some(b'*') if buf.starts_with(RespArray::NULL) => {
+                buf.advance(RespArray::NULL.len());
+                Ok(RespFrame::Null(RespNull))
+            }
+            Some(b'*') => RespArray::decode_with_limits(buf, limits).map(RespFrame::Array),
             Some(b'%') => {
-                let frame = RespMap::decode(buf)?;
+                let frame = RespMap::decode_with_limits(buf, limits)?;
                 Ok(RespFrame::Map(frame))
             }
             Some(b'~') => {
-                let frame = RespSet::decode(buf)?;
+                let frame = RespSet::decode_with_limits(buf, limits)?;
                 Ok(RespFrame::Set(frame))
             }
             None => Err(RespError::Incomplete),
@@ -53,22 +81,26 @@ impl RespDecoder for RespFrame {
         }
     }
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length_with_limits(buf: &[u8], limits: RespLimits) -> Result<usize, RespError> {
         if buf.len() < 3 {
             return Err(RespError::Incomplete);
         }
         match buf[0] {
             b'+' => SimpleString::expect_length(buf),
             b'-' => SimpleError::expect_length(buf),
-            b'!' => BulkError::expect_length(buf),
+            b'!' => BulkError::expect_length_with_limits(buf, limits),
             b':' => i64::expect_length(buf),
-            b'$' => BulkString::expect_length(buf),
-            b'*' => RespArray::expect_length(buf),
+            b'$' => BulkString::expect_length_with_limits(buf, limits),
+            b'*' => RespArray::expect_length_with_limits(buf, limits),
             b'_' => RespNull::expect_length(buf),
             b'#' => bool::expect_length(buf),
             b',' => RespDouble::expect_length(buf),
-            b'%' => RespMap::expect_length(buf),
-            b'~' => RespSet::expect_length(buf),
+            b'(' => RespBigNumber::expect_length(buf),
+            b'=' => VerbatimString::expect_length_with_limits(buf, limits),
+            b'>' => RespPush::expect_length_with_limits(buf, limits),
+            b'|' => RespAttribute::expect_length_with_limits(buf, limits),
+            b'%' => RespMap::expect_length_with_limits(buf, limits),
+            b'~' => RespSet::expect_length_with_limits(buf, limits),
             _ => Err(RespError::InvalidFrameType(format!(
                 "Invalid frame: {:?}",
                 buf
@@ -77,6 +109,75 @@ impl RespDecoder for RespFrame {
     }
 }
 
+impl RespFrame {
+    // 严格解析：data 必须恰好是一个完整的帧，用于校验测试数据等场景
+    pub fn decode_exact(data: &[u8]) -> Result<Self, RespError> {
+        let mut buf = BytesMut::from(data);
+        let frame = Self::decode(&mut buf)?;
+        if !buf.is_empty() {
+            return Err(RespError::TrailingData(buf.len()));
+        }
+        Ok(frame)
+    }
+
+    // RESP2 客户端无法解析 RESP3 新增的类型，按 redis 的规则降级：
+    // Map/Set/Push 展开为数组，Double 为 BulkString，Boolean 为 Integer，Null 为 null bulk string，
+    // BigNumber 按原始文本编码为 Integer，以便代理时原样转发，VerbatimString 去掉格式前缀后为 BulkString，
+    // Attribute 丢弃属性只发送被描述的帧
+    pub fn encode_for(&self, protocol: RespVersion) -> Vec<u8> {
+        let mut buf = BytesMut::new();
+        self.encode_for_into(protocol, &mut buf);
+        buf.into()
+    }
+
+    pub fn encode_for_into(&self, protocol: RespVersion, buf: &mut BytesMut) {
+        if protocol == RespVersion::Resp3 {
+            return self.encode_into(buf);
+        }
+        match self {
+            RespFrame::Null(_) => buf.extend_from_slice(BulkString::NULL),
+            RespFrame::Boolean(b) => RespFrame::Integer(*b as i64).encode_into(buf),
+            RespFrame::Double(d) => {
+                let d = d.to_string();
+                write_header(buf, b'$', d.len());
+                buf.extend_from_slice(d.as_bytes());
+                buf.extend_from_slice(b"\r\n");
+            }
+            RespFrame::BigNumber(n) => write_header(buf, b':', n.as_str()),
+            RespFrame::VerbatimString(s) => {
+                write_header(buf, b'$', s.data().len());
+                buf.extend_from_slice(s.data());
+                buf.extend_from_slice(b"\r\n");
+            }
+            RespFrame::Array(array) => {
+                encode_aggregate_for(array.len(), array.iter(), protocol, buf)
+            }
+            RespFrame::Push(push) => encode_aggregate_for(push.len(), push.iter(), protocol, buf),
+            RespFrame::Attribute(attribute) => attribute.value().encode_for_into(protocol, buf),
+            RespFrame::Set(set) => encode_aggregate_for(set.len(), set.iter(), protocol, buf),
+            RespFrame::Map(map) => encode_aggregate_for(
+                map.len() * 2,
+                map.iter().flat_map(|(k, v)| [k, v]),
+                protocol,
+                buf,
+            ),
+            _ => self.encode_into(buf),
+        }
+    }
+}
+
+fn encode_aggregate_for<'a>(
+    len: usize,
+    frames: impl Iterator<Item = &'a RespFrame>,
+    protocol: RespVersion,
+    buf: &mut BytesMut,
+) {
+    write_header(buf, b'*', len);
+    for frame in frames {
+        frame.encode_for_into(protocol, buf);
+    }
+}
+
 impl From<&str> for RespFrame {
     fn from(s: &str) -> Self {
         SimpleString::from(s).into()
@@ -97,6 +198,202 @@ impl<const N: usize> From<&[u8; N]> for RespFrame {
 
 impl From<f64> for RespFrame {
     fn from(s: f64) -> Self {
-        s.into()
+        RespDouble::new(s).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_for_resp3_is_native() {
+        let frame: RespFrame = RespDouble::new(1.5).into();
+        assert_eq!(frame.encode_for(RespVersion::Resp3), b",+1.5\r\n");
+        let frame = RespFrame::Null(RespNull);
+        assert_eq!(frame.encode_for(RespVersion::Resp3), b"_\r\n");
+    }
+
+    #[test]
+    fn test_encode_for_resp2_down_converts() {
+        let resp2 = RespVersion::Resp2;
+        assert_eq!(RespFrame::Null(RespNull).encode_for(resp2), b"$-1\r\n");
+        assert_eq!(RespFrame::Boolean(true).encode_for(resp2), b":1\r\n");
+        assert_eq!(RespFrame::Boolean(false).encode_for(resp2), b":0\r\n");
+        let frame: RespFrame = RespDouble::new(1.5).into();
+        assert_eq!(frame.encode_for(resp2), b"$3\r\n1.5\r\n");
+
+        let mut map = RespMap::new();
+        map.insert(b"proto".into(), RespFrame::Integer(2));
+        map.insert(b"set".into(), RespSet::new().into());
+        let mut set = RespSet::new();
+        set.insert(RespFrame::Boolean(true));
+        let frame: RespFrame = RespArray::new(vec![map.into(), set.into()]).into();
+        assert_eq!(
+            frame.encode_for(resp2),
+            b"*2\r\n*4\r\n$5\r\nproto\r\n:2\r\n$3\r\nset\r\n*0\r\n*1\r\n:1\r\n"
+        );
+    }
+
+    #[test]
+    fn test_out_of_range_integer_round_trip() -> Result<(), RespError> {
+        let mut buf = BytesMut::from(":123\r\n:99999999999999999999\r\n");
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(frame, RespFrame::Integer(123));
+
+        let frame = RespFrame::decode(&mut buf)?;
+        assert!(matches!(frame, RespFrame::BigNumber(_)));
+        assert_eq!(
+            frame.encode_for(RespVersion::Resp2),
+            b":99999999999999999999\r\n"
+        );
+        assert_eq!(
+            frame.encode_for(RespVersion::Resp3),
+            b"(99999999999999999999\r\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_big_number_frame_decode() -> Result<(), RespError> {
+        let data = b"(-123456789012345678901234567890\r\n";
+        assert_eq!(RespFrame::expect_length(data)?, data.len());
+
+        let mut buf = BytesMut::from(&data[..]);
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespFrame::BigNumber(RespBigNumber::parse("-123456789012345678901234567890")?)
+        );
+        assert_eq!(frame.encode(), data);
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode_exact() {
+        assert_eq!(
+            RespFrame::decode_exact(b"*1\r\n:1\r\n"),
+            Ok(RespArray::new(vec![RespFrame::Integer(1)]).into())
+        );
+        assert_eq!(
+            RespFrame::decode_exact(b"+OK\r\njunk"),
+            Err(RespError::TrailingData(4))
+        );
+        assert_eq!(
+            RespFrame::decode_exact(b":1\r\n:2\r\n"),
+            Err(RespError::TrailingData(4))
+        );
+        assert_eq!(RespFrame::decode_exact(b"+OK"), Err(RespError::Incomplete));
+    }
+
+    #[test]
+    fn test_encode_into_matches_encode() {
+        let mut map = RespMap::new();
+        map.insert(b"field".into(), RespDouble::new(-1.5).into());
+        map.insert(SimpleString::new("set").into(), RespSet::new().into());
+        let frame: RespFrame = RespArray::new(vec![
+            map.into(),
+            RespFrame::Integer(-42),
+            RespFrame::Null(RespNull),
+            RespFrame::Boolean(true),
+            BulkError::new("SYNTAX invalid").into(),
+            VerbatimString::text("a\r\nb").into(),
+            RespPush::new(vec![b"message".into(), SimpleError::new("ERR x").into()]).into(),
+        ])
+        .into();
+
+        // 追加到已有数据之后，不覆盖缓冲区中原来的内容
+        for protocol in [RespVersion::Resp2, RespVersion::Resp3] {
+            let mut buf = BytesMut::from("+OK\r\n");
+            frame.encode_for_into(protocol, &mut buf);
+            assert_eq!(buf, [&b"+OK\r\n"[..], &frame.encode_for(protocol)].concat());
+        }
+        let mut buf = BytesMut::new();
+        frame.encode_into(&mut buf);
+        assert_eq!(buf, frame.encode());
+    }
+
+    // 解析任意输入都不能 panic，只能返回帧或错误
+    fn decode_all(data: &[u8]) {
+        let _ = RespFrame::expect_length(data);
+        let _ = RespFrame::decode(&mut BytesMut::from(data));
+        let _ = RespFrame::decode_exact(data);
+        let _ = SimpleString::decode(&mut BytesMut::from(data));
+        let _ = SimpleError::decode(&mut BytesMut::from(data));
+        let _ = BulkError::decode(&mut BytesMut::from(data));
+        let _ = i64::decode(&mut BytesMut::from(data));
+        let _ = BulkString::decode(&mut BytesMut::from(data));
+        let _ = RespArray::decode(&mut BytesMut::from(data));
+        let _ = RespNull::decode(&mut BytesMut::from(data));
+        let _ = bool::decode(&mut BytesMut::from(data));
+        let _ = RespDouble::decode(&mut BytesMut::from(data));
+        let _ = RespMap::decode(&mut BytesMut::from(data));
+        let _ = RespSet::decode(&mut BytesMut::from(data));
+        let _ = RespBigNumber::decode(&mut BytesMut::from(data));
+        let _ = VerbatimString::decode(&mut BytesMut::from(data));
+        let _ = RespPush::decode(&mut BytesMut::from(data));
+        let _ = RespAttribute::decode(&mut BytesMut::from(data));
+    }
+
+    #[test]
+    fn test_decode_every_prefix_never_panics() {
+        let frames: &[&[u8]] = &[
+            b"+OK\r\n",
+            b"-ERR unknown\r\n",
+            b"!21\r\nSYNTAX invalid syntax\r\n",
+            b":-42\r\n",
+            b"$5\r\nhello\r\n",
+            b"$0\r\n\r\n",
+            b"$-1\r\n",
+            b"$?\r\n;4\r\nHell\r\n;1\r\no\r\n;0\r\n",
+            b"*2\r\n$3\r\nget\r\n$5\r\nhello\r\n",
+            b"*-1\r\n",
+            b"*?\r\n:1\r\n*1\r\n+a\r\n.\r\n",
+            b"_\r\n",
+            b"#t\r\n",
+            b",-1.5e10\r\n",
+            b"(3492890328409238509324850943850943825024385\r\n",
+            b"=15\r\ntxt:Some string\r\n",
+            b"%2\r\n+a\r\n:1\r\n$1\r\nb\r\n~1\r\n#f\r\n",
+            b"~2\r\n:1\r\n:2\r\n",
+            b">2\r\n$7\r\nmessage\r\n*1\r\n:1\r\n",
+            b"|1\r\n+ttl\r\n:3600\r\n$5\r\nhello\r\n",
+        ];
+        for frame in frames {
+            for end in 0..=frame.len() {
+                let prefix = &frame[..end];
+                decode_all(prefix);
+                // 不完整的合法帧总是等待更多数据，而不是报错
+                if end < frame.len() {
+                    assert_eq!(
+                        RespFrame::expect_length(prefix),
+                        Err(RespError::Incomplete),
+                        "{:?}",
+                        String::from_utf8_lossy(prefix)
+                    );
+                }
+            }
+            assert_eq!(RespFrame::expect_length(frame), Ok(frame.len()));
+        }
+    }
+
+    #[test]
+    fn test_decode_random_garbage_never_panics() {
+        use rand::{rngs::StdRng, Rng as _, SeedableRng as _};
+
+        // 偏向协议中有意义的字节，更容易走到解析的深处
+        const ALPHABET: &[u8] = b"+-:$*_#,(!=%~>|?;.\r\n\r\n0123456789-txt";
+        let mut rng = StdRng::seed_from_u64(0x5eed);
+        for _ in 0..20_000 {
+            let len = rng.gen_range(0..32);
+            let data = (0..len)
+                .map(|_| match rng.gen_range(0..10) {
+                    0 => rng.gen(),
+                    _ => ALPHABET[rng.gen_range(0..ALPHABET.len())],
+                })
+                .collect::<Vec<u8>>();
+            decode_all(&data);
+        }
     }
 }
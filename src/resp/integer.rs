@@ -1,14 +1,17 @@
 use bytes::BytesMut;
 
-use crate::{RespDecoder, RespEncoder, RespError};
+use crate::{RespBigNumber, RespDecoder, RespEncoder, RespError, RespFrame};
 
-use super::extract_data;
+use super::{encode_to_vec, extract_data, extract_length_data, write_header};
 
 // - integer: ":[<+|->]<value>\r\n"
 impl RespEncoder for i64 {
     fn encode(&self) -> Vec<u8> {
-        // let sign = if *self < 0 { "" } else { "" }; // 实际测试中不需要 + 号
-        format!(":{}\r\n", self).into_bytes()
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        write_header(buf, b':', self);
     }
 }
 
@@ -23,6 +26,18 @@ impl RespDecoder for i64 {
     }
 }
 
+// i64::decode 对超出范围的值报错，RespFrame 解析时改为返回 BigNumber，
+// 保留原始文本，RESP2 下重新编码为同样的 ":<number>\r\n"
+pub(crate) fn decode_integer_frame(buf: &mut BytesMut) -> Result<RespFrame, RespError> {
+    let data = extract_length_data(buf, <i64 as RespDecoder>::PREFIX)?;
+    if data.parse::<i64>().is_ok() {
+        return i64::decode(buf).map(RespFrame::Integer);
+    }
+    let number = RespBigNumber::parse(&data)?;
+    extract_data(buf, <i64 as RespDecoder>::PREFIX)?;
+    Ok(RespFrame::BigNumber(number))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -35,6 +50,9 @@ mod tests {
 
         let frame = -123;
         assert_eq!(frame.encode(), b":-123\r\n");
+
+        let frame = 0;
+        assert_eq!(frame.encode(), b":0\r\n");
     }
 
     #[test]
@@ -43,6 +61,12 @@ mod tests {
         let frame = i64::decode(&mut buf)?;
         assert_eq!(frame, 123);
 
+        // 解码时仍然接受可选的正号
+        let mut buf = BytesMut::from(":+123\r\n");
+        let frame = i64::decode(&mut buf)?;
+        assert_eq!(frame, 123);
+        assert_eq!(frame.encode(), b":123\r\n");
+
         let mut buf = BytesMut::from(":xxx\r\n");
         let frame = i64::decode(&mut buf);
         assert_eq!(
@@ -52,4 +76,31 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_integer_frame_decode() -> Result<()> {
+        let mut buf = BytesMut::from(":-9223372036854775808\r\n:99999999999999999999\r\n");
+        let frame = decode_integer_frame(&mut buf)?;
+        assert_eq!(frame, RespFrame::Integer(i64::MIN));
+
+        let frame = decode_integer_frame(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespFrame::BigNumber(RespBigNumber::parse("99999999999999999999")?)
+        );
+        assert!(buf.is_empty());
+
+        // 超出范围的正数重新编码时同样不带正号
+        let mut buf = BytesMut::from(":+99999999999999999999\r\n");
+        let frame = decode_integer_frame(&mut buf)?;
+        assert_eq!(
+            frame.encode_for(crate::RespVersion::Resp2),
+            b":99999999999999999999\r\n"
+        );
+
+        let mut buf = BytesMut::from(":12a3\r\n");
+        assert!(decode_integer_frame(&mut buf).is_err());
+
+        Ok(())
+    }
 }
@@ -5,49 +5,66 @@ use std::{
 
 use bytes::BytesMut;
 
-use crate::{RespDecoder, RespEncoder, RespError, RespFrame, SimpleString};
+use crate::{RespDecoder, RespEncoder, RespError, RespFrame, RespLimits};
 
-use super::{extract_len_and_end, extract_nth, CRLF_LEN};
+use super::{
+    element_length, encode_to_vec, extract_len_and_end, extract_nth, write_header, CRLF_LEN,
+};
 
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
-pub struct RespMap(pub(crate) BTreeMap<String, RespFrame>);
+// RESP3 的 map key 可以是任意类型，这里直接以 RespFrame 保存，编码时原样输出
+pub struct RespMap(pub(crate) BTreeMap<RespFrame, RespFrame>);
 
 // - map: "%<number-of-entries>\r\n<key-1><value-1>...<key-n><value-n>"
 impl RespEncoder for RespMap {
     fn encode(&self) -> Vec<u8> {
-        let mut encoded = format!("%{}\r\n", self.len()).into_bytes();
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        write_header(buf, b'%', self.len());
         for (key, value) in &self.0 {
-            encoded.extend_from_slice(&SimpleString::new(key).encode());
-            encoded.extend_from_slice(&value.encode());
+            key.encode_into(buf);
+            value.encode_into(buf);
         }
-        encoded
     }
 }
 
 impl RespDecoder for RespMap {
     const PREFIX: &'static str = "%";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let total = Self::expect_length(buf)?;
+        Self::decode_with_limits(buf, RespLimits::default())
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_with_limits(buf, RespLimits::default())
+    }
+
+    fn decode_with_limits(buf: &mut BytesMut, limits: RespLimits) -> Result<Self, RespError> {
+        let total = Self::expect_length_with_limits(buf, limits)?;
         if buf.len() < total {
             return Err(RespError::Incomplete);
         }
 
         let nth = extract_nth(buf, Self::PREFIX)?;
+        let nested = limits.nested()?;
         let mut map = Self::new();
         for _ in 0..nth {
-            let key = SimpleString::decode(buf)?;
-            let value = RespFrame::decode(buf)?;
-            map.0.insert(key.0, value);
+            let key = RespFrame::decode_with_limits(buf, nested)?;
+            let value = RespFrame::decode_with_limits(buf, nested)?;
+            map.0.insert(key, value);
         }
         Ok(map)
     }
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length_with_limits(buf: &[u8], limits: RespLimits) -> Result<usize, RespError> {
+        let nested = limits.nested()?;
         let (len, end) = extract_len_and_end(buf)?;
+        limits.check_elements(len.saturating_mul(2))?;
         let mut total = end + CRLF_LEN;
         for _ in 0..len {
-            let key_len = RespFrame::expect_length(&buf[total..])?;
-            let value_len = RespFrame::expect_length(&buf[total + key_len..])?;
+            let key_len = element_length(buf, total, nested)?;
+            let value_len = element_length(buf, total + key_len, nested)?;
             total += key_len + value_len;
         }
         Ok(total)
@@ -67,7 +84,7 @@ impl Default for RespMap {
 }
 
 impl Deref for RespMap {
-    type Target = BTreeMap<String, RespFrame>;
+    type Target = BTreeMap<RespFrame, RespFrame>;
 
     fn deref(&self) -> &Self::Target {
         &self.0
@@ -82,7 +99,7 @@ impl DerefMut for RespMap {
 
 #[cfg(test)]
 mod tests {
-    use crate::{BulkString, RespDouble};
+    use crate::{BulkString, RespDouble, SimpleString};
 
     use super::*;
     use anyhow::Result;
@@ -91,10 +108,13 @@ mod tests {
     fn test_map_encode() {
         let mut frame = RespMap::new();
         frame.insert(
-            "hello".to_string(),
+            SimpleString::new("hello").into(),
             BulkString::new("world".to_string()).into(),
         );
-        frame.insert("foo".to_string(), RespDouble::new(-123456.789).into());
+        frame.insert(
+            SimpleString::new("foo").into(),
+            RespDouble::new(-123456.789).into(),
+        );
         assert_eq!(
             frame.encode(),
             b"%2\r\n+foo\r\n,-123456.789\r\n+hello\r\n$5\r\nworld\r\n"
@@ -106,10 +126,36 @@ mod tests {
         let mut buf = BytesMut::from("%2\r\n+get\r\n$5\r\nhello\r\n+set\r\n$5\r\nworld\r\n");
         let frame = RespMap::decode(&mut buf)?;
         let mut map = RespMap::new();
-        map.0.insert("get".to_string(), b"hello".into());
-        map.0.insert("set".to_string(), b"world".into());
+        map.0
+            .insert(SimpleString::new("get").into(), b"hello".into());
+        map.0
+            .insert(SimpleString::new("set").into(), b"world".into());
         assert_eq!(frame, map);
 
         Ok(())
     }
+
+    #[test]
+    fn test_map_decode_non_simple_string_keys() -> Result<()> {
+        let mut buf = BytesMut::from(
+            &b"%3\r\n$3\r\nget\r\n$5\r\nhello\r\n:1\r\n+one\r\n$2\r\n\xff\x00\r\n_\r\n"[..],
+        );
+        let frame = RespMap::decode(&mut buf)?;
+        assert_eq!(frame.len(), 3);
+        assert_eq!(frame.get(&b"get".into()), Some(&b"hello".into()));
+        assert_eq!(
+            frame.get(&RespFrame::Integer(1)),
+            Some(&SimpleString::new("one").into())
+        );
+        assert_eq!(
+            frame.get(&BulkString::new(vec![0xff, 0x00]).into()),
+            Some(&RespFrame::Null(crate::RespNull))
+        );
+
+        // 编码后再解码得到相同的 map
+        let mut buf = BytesMut::from(&frame.encode()[..]);
+        assert_eq!(RespMap::decode(&mut buf)?, frame);
+
+        Ok(())
+    }
 }
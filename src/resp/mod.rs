@@ -1,4 +1,6 @@
 mod array;
+mod attribute;
+mod big_number;
 mod bool;
 mod bulk_error;
 mod bulk_string;
@@ -7,26 +9,71 @@ mod frame;
 mod integer;
 mod map;
 mod null;
+mod push;
 mod set;
 mod simple_error;
 mod simple_string;
+mod verbatim_string;
 
-use bytes::{Buf as _, BytesMut};
+use std::{
+    fmt::{self, Write as _},
+    ops::Range,
+};
+
+use bytes::{Buf as _, BufMut as _, BytesMut};
 use enum_dispatch::enum_dispatch;
 use thiserror::Error;
 
 pub use self::{
-    array::RespArray, bulk_error::BulkError, bulk_string::BulkString, double::RespDouble,
-    frame::RespFrame, map::RespMap, null::RespNull, set::RespSet, simple_error::SimpleError,
-    simple_string::SimpleString,
+    array::RespArray, attribute::RespAttribute, big_number::RespBigNumber, bulk_error::BulkError,
+    bulk_string::BulkString, double::RespDouble, frame::RespFrame, map::RespMap, null::RespNull,
+    push::RespPush, set::RespSet, simple_error::SimpleError, simple_string::SimpleString,
+    verbatim_string::VerbatimString,
 };
 
 const CRLF: &str = "\r\n";
 const CRLF_LEN: usize = CRLF.len();
 
+// 数组、map、set、push 默认允许的最大嵌套层数，与 redis 一致。
+// 防止恶意构造的深层嵌套帧在递归解析时导致栈溢出
+pub const DEFAULT_MAX_DEPTH: usize = 32;
+// 与 redis 的 proto-max-bulk-len 默认值一致
+pub const DEFAULT_MAX_BULK_LEN: usize = 512 * 1024 * 1024;
+// 与 redis 一致，一个数组最多 1M 个元素
+pub const DEFAULT_MAX_ELEMENTS: usize = 1024 * 1024;
+// 与 redis 的 client-query-buffer-limit 默认值一致
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 1024 * 1024 * 1024;
+
+// 解析客户端发送的帧时使用的限制。长度和元素个数在解析出头部时立即检查，
+// 不会为一个声明了超大长度的帧分配内存或一直等待数据
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RespLimits {
+    // 剩余允许的嵌套层数
+    pub max_depth: usize,
+    // bulk string、bulk error、verbatim string 的最大长度
+    pub max_bulk_len: usize,
+    // 数组、map、set、push 的最大元素个数，map 的每个键值对算两个元素
+    pub max_elements: usize,
+    // 整个帧的最大字节数，由 codec 检查
+    pub max_frame_size: usize,
+}
+
+// 连接协商的协议版本，RESP3 下可以直接返回 Map/Set 等类型
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RespVersion {
+    #[default]
+    Resp2,
+    Resp3,
+}
+
 #[enum_dispatch]
 pub trait RespEncoder {
     fn encode(&self) -> Vec<u8>;
+
+    // 直接写入目标缓冲区，嵌套类型不需要为每个元素单独分配
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.extend_from_slice(&self.encode());
+    }
 }
 
 pub trait RespDecoder: Sized {
@@ -40,6 +87,15 @@ pub trait RespDecoder: Sized {
         let end = find_crlf(buf, Self::N_CRLF, Self::PREFIX.len()).ok_or(RespError::Incomplete)?;
         Ok(end + CRLF_LEN)
     }
+
+    // 只有聚合类型和带长度的字符串类型需要覆盖这两个方法
+    fn decode_with_limits(buf: &mut BytesMut, _limits: RespLimits) -> Result<Self, RespError> {
+        Self::decode(buf)
+    }
+
+    fn expect_length_with_limits(buf: &[u8], _limits: RespLimits) -> Result<usize, RespError> {
+        Self::expect_length(buf)
+    }
 }
 
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -52,19 +108,104 @@ pub enum RespError {
     InvalidFrameLength,
     #[error("Invalid frame type: {0}")]
     InvalidFrameType(String),
+    #[error("exceeded nesting depth limit")]
+    ExceededDepthLimit,
+    // 消息与 redis 的协议错误一致，例如 "invalid bulk length"
+    #[error("{0}")]
+    ExceedsLimit(&'static str),
+    #[error("Unexpected {0} trailing bytes after frame")]
+    TrailingData(usize),
 }
 
-fn find_crlf(buf: &[u8], nth: usize, start: usize) -> Option<usize> {
-    let mut count = 0;
-    for i in start..buf.len() - 1 {
-        if buf[i] == b'\r' && buf[i + 1] == b'\n' {
-            count += 1;
-            if count == nth {
-                return Some(i);
-            }
+impl Default for RespLimits {
+    fn default() -> Self {
+        Self {
+            max_depth: DEFAULT_MAX_DEPTH,
+            max_bulk_len: DEFAULT_MAX_BULK_LEN,
+            max_elements: DEFAULT_MAX_ELEMENTS,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+        }
+    }
+}
+
+impl RespLimits {
+    // 进入一层聚合类型，返回解析其中元素时使用的限制
+    fn nested(self) -> Result<Self, RespError> {
+        match self.max_depth.checked_sub(1) {
+            Some(max_depth) => Ok(Self { max_depth, ..self }),
+            None => Err(RespError::ExceededDepthLimit),
+        }
+    }
+
+    fn check_bulk_len(&self, len: usize) -> Result<(), RespError> {
+        if len > self.max_bulk_len {
+            return Err(RespError::ExceedsLimit("invalid bulk length"));
+        }
+        Ok(())
+    }
+
+    fn check_elements(&self, len: usize) -> Result<(), RespError> {
+        if len > self.max_elements {
+            return Err(RespError::ExceedsLimit("invalid multibulk length"));
+        }
+        Ok(())
+    }
+
+    pub fn check_frame_size(&self, len: usize) -> Result<(), RespError> {
+        if len > self.max_frame_size {
+            return Err(RespError::ExceedsLimit("frame too large"));
         }
+        Ok(())
+    }
+}
+
+// 实现了 encode_into 的类型通过它实现 encode
+fn encode_to_vec(frame: &impl RespEncoder) -> Vec<u8> {
+    let mut buf = BytesMut::new();
+    frame.encode_into(&mut buf);
+    buf.into()
+}
+
+// 写入 "<prefix><value>\r\n"，数字直接格式化到 buf 中，不产生临时字符串
+fn write_header(buf: &mut BytesMut, prefix: u8, value: impl fmt::Display) {
+    buf.put_u8(prefix);
+    write!(buf, "{}\r\n", value).expect("writing to BytesMut never fails");
+}
+
+// 从 start 开始查找第 nth 个 CRLF，返回 '\r' 的下标。
+// buf 为空或比 start 短时直接返回 None，由调用方当作数据不完整处理
+fn find_crlf(buf: &[u8], nth: usize, start: usize) -> Option<usize> {
+    buf.get(start..)?
+        .windows(CRLF_LEN)
+        .enumerate()
+        .filter(|(_, window)| *window == CRLF.as_bytes())
+        .nth(nth.checked_sub(1)?)
+        .map(|(i, _)| start + i)
+}
+
+// "<header>\r\n<data>\r\n" 中 data 的位置，len_end 是头部 CRLF 的下标。
+// 按声明的长度读取数据，不查找 CRLF，所以数据中可以包含任意字节
+fn length_prefixed_data(buf: &[u8], len_end: usize, len: usize) -> Result<Range<usize>, RespError> {
+    let start = len_end + CRLF_LEN;
+    let end = start
+        .checked_add(len)
+        .ok_or(RespError::InvalidFrameLength)?;
+    let frame_end = end
+        .checked_add(CRLF_LEN)
+        .ok_or(RespError::InvalidFrameLength)?;
+    if buf.len() < frame_end {
+        return Err(RespError::Incomplete);
+    }
+    if &buf[end..frame_end] != CRLF.as_bytes() {
+        return Err(RespError::InvalidFrameLength);
     }
-    None
+    Ok(start..end)
+}
+
+// 聚合类型中从 offset 开始的下一个元素的长度，offset 越界说明数据还不完整
+fn element_length(buf: &[u8], offset: usize, limits: RespLimits) -> Result<usize, RespError> {
+    let rest = buf.get(offset..).ok_or(RespError::Incomplete)?;
+    RespFrame::expect_length_with_limits(rest, limits)
 }
 
 fn validate_frame_data(buf: &mut BytesMut, prefix: &str) -> Result<(), RespError> {
@@ -89,11 +230,25 @@ fn extract_len_and_end(buf: &[u8]) -> Result<(usize, usize), RespError> {
 }
 
 fn extract_data(buf: &mut BytesMut, prefix: &str) -> Result<String, RespError> {
+    let data = extract_raw_data(buf, prefix)?;
+    Ok(String::from_utf8_lossy(&data).to_string())
+}
+
+// 与 extract_data 相同，但保留原始字节，用于二进制安全的 BulkString
+fn extract_raw_data(buf: &mut BytesMut, prefix: &str) -> Result<Vec<u8>, RespError> {
     validate_frame_data(buf, prefix)?;
     let end = find_crlf(buf, 1, prefix.len()).ok_or(RespError::Incomplete)?;
     let data = buf.split_to(end + CRLF_LEN);
-    let s = String::from_utf8_lossy(&data[prefix.len()..end]).to_string();
-    Ok(s)
+    Ok(data[prefix.len()..end].to_vec())
+}
+
+// redis 不允许 simple string 中出现换行，编码时将 CR/LF 替换为空格
+fn sanitize_line(s: &str) -> std::borrow::Cow<'_, str> {
+    if s.contains(['\r', '\n']) {
+        s.replace(['\r', '\n'], " ").into()
+    } else {
+        s.into()
+    }
 }
 
 fn extract_length_data(buf: &[u8], prefix: &str) -> Result<String, RespError> {
@@ -129,3 +284,34 @@ fn extract_fixed_data(
     buf.advance(end + CRLF_LEN);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_crlf_bounds() {
+        assert_eq!(find_crlf(b"", 1, 0), None);
+        assert_eq!(find_crlf(b"", 1, 1), None);
+        assert_eq!(find_crlf(b"+", 1, 1), None);
+        assert_eq!(find_crlf(b"\r", 1, 0), None);
+        assert_eq!(find_crlf(b"+OK\r\n", 1, 10), None);
+        assert_eq!(find_crlf(b"+OK\r\n", 0, 0), None);
+        assert_eq!(find_crlf(b"+OK\r\n", 1, 1), Some(3));
+        assert_eq!(find_crlf(b"\r\n\r\n", 2, 0), Some(2));
+        assert_eq!(find_crlf(b"\r\n\r\n", 1, 1), Some(2));
+        assert_eq!(find_crlf(b"\r\n\r\n", 3, 0), None);
+    }
+
+    #[test]
+    fn test_element_length_out_of_bounds() {
+        assert_eq!(
+            element_length(b"*1\r\n", 10, RespLimits::default()),
+            Err(RespError::Incomplete)
+        );
+        assert_eq!(
+            element_length(b":1\r\n:2\r\n", 4, RespLimits::default()),
+            Ok(4)
+        );
+    }
+}
@@ -2,7 +2,7 @@ use bytes::BytesMut;
 
 use crate::{RespDecoder, RespEncoder, RespError};
 
-use super::extract_fixed_data;
+use super::{encode_to_vec, extract_fixed_data};
 
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
 pub struct RespNull;
@@ -10,7 +10,11 @@ pub struct RespNull;
 // - null: "_\r\n"
 impl RespEncoder for RespNull {
     fn encode(&self) -> Vec<u8> {
-        b"_\r\n".to_vec()
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.extend_from_slice(b"_\r\n");
     }
 }
 
@@ -0,0 +1,126 @@
+use std::ops::Deref;
+
+use bytes::BytesMut;
+
+use crate::{RespDecoder, RespEncoder, RespError, RespFrame, RespLimits};
+
+use super::{
+    element_length, encode_to_vec, extract_len_and_end, extract_nth, write_header, CRLF_LEN,
+};
+
+// 服务器主动推送的数据，例如订阅消息和 CLIENT TRACKING 的失效通知。
+// 结构与数组相同，RESP2 连接下由 RespFrame::encode_for 降级为数组
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct RespPush(pub(crate) Vec<RespFrame>);
+
+// - push: "><number-of-elements>\r\n<element-1>...<element-n>"
+impl RespEncoder for RespPush {
+    fn encode(&self) -> Vec<u8> {
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        write_header(buf, b'>', self.len());
+        for frame in &self.0 {
+            frame.encode_into(buf);
+        }
+    }
+}
+
+impl RespDecoder for RespPush {
+    const PREFIX: &'static str = ">";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        Self::decode_with_limits(buf, RespLimits::default())
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_with_limits(buf, RespLimits::default())
+    }
+
+    fn decode_with_limits(buf: &mut BytesMut, limits: RespLimits) -> Result<Self, RespError> {
+        let total = Self::expect_length_with_limits(buf, limits)?;
+        if buf.len() < total {
+            return Err(RespError::Incomplete);
+        }
+        let nth = extract_nth(buf, Self::PREFIX)?;
+        let nested = limits.nested()?;
+        let mut frames = Vec::with_capacity(nth);
+        for _ in 0..nth {
+            frames.push(RespFrame::decode_with_limits(buf, nested)?);
+        }
+        Ok(RespPush::new(frames))
+    }
+
+    fn expect_length_with_limits(buf: &[u8], limits: RespLimits) -> Result<usize, RespError> {
+        let nested = limits.nested()?;
+        let (len, end) = extract_len_and_end(buf)?;
+        limits.check_elements(len)?;
+        let mut total = end + CRLF_LEN;
+        for _ in 0..len {
+            total += element_length(buf, total, nested)?;
+        }
+        Ok(total)
+    }
+}
+
+impl RespPush {
+    pub fn new(s: impl Into<Vec<RespFrame>>) -> Self {
+        Self(s.into())
+    }
+}
+
+impl Deref for RespPush {
+    type Target = Vec<RespFrame>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{RespArray, RespVersion};
+
+    use super::*;
+    use anyhow::Result;
+
+    fn message() -> RespPush {
+        RespPush::new(vec![b"message".into(), b"news".into(), b"hello".into()])
+    }
+
+    #[test]
+    fn test_push_encode() {
+        let push: RespFrame = message().into();
+        assert_eq!(
+            push.encode_for(RespVersion::Resp3),
+            b">3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n"
+        );
+        // RESP2 客户端收到的是同样内容的数组
+        let array: RespFrame = RespArray::new(message().0).into();
+        assert_eq!(push.encode_for(RespVersion::Resp2), array.encode());
+        assert_eq!(
+            push.encode_for(RespVersion::Resp2),
+            b"*3\r\n$7\r\nmessage\r\n$4\r\nnews\r\n$5\r\nhello\r\n"
+        );
+    }
+
+    #[test]
+    fn test_push_decode() -> Result<()> {
+        let mut buf = BytesMut::from(">2\r\n$10\r\ninvalidate\r\n*1\r\n$3\r\nkey\r\n+OK\r\n");
+        assert_eq!(RespFrame::expect_length(&buf)?, buf.len() - 5);
+        let frame = RespFrame::decode(&mut buf)?;
+        assert_eq!(
+            frame,
+            RespPush::new(vec![
+                b"invalidate".into(),
+                RespArray::new(vec![b"key".into()]).into(),
+            ])
+            .into()
+        );
+        assert_eq!(buf, "+OK\r\n");
+
+        let mut buf = BytesMut::from(">2\r\n$10\r\ninvalidate\r\n");
+        assert_eq!(RespPush::decode(&mut buf), Err(RespError::Incomplete));
+        Ok(())
+    }
+}
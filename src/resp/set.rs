@@ -5,9 +5,11 @@ use std::{
 
 use bytes::BytesMut;
 
-use crate::{RespDecoder, RespEncoder, RespError, RespFrame};
+use crate::{RespDecoder, RespEncoder, RespError, RespFrame, RespLimits};
 
-use super::{extract_len_and_end, extract_nth, CRLF_LEN};
+use super::{
+    element_length, encode_to_vec, extract_len_and_end, extract_nth, write_header, CRLF_LEN,
+};
 
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
 pub struct RespSet(pub(crate) BTreeSet<RespFrame>);
@@ -15,35 +17,49 @@ pub struct RespSet(pub(crate) BTreeSet<RespFrame>);
 // - set: "~<number-of-elements>\r\n<element-1>...<element-n>"
 impl RespEncoder for RespSet {
     fn encode(&self) -> Vec<u8> {
-        let mut encoded = format!("~{}\r\n", self.len()).into_bytes();
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        write_header(buf, b'~', self.len());
         for frame in &self.0 {
-            encoded.extend_from_slice(&frame.encode());
+            frame.encode_into(buf);
         }
-        encoded
     }
 }
 
 impl RespDecoder for RespSet {
     const PREFIX: &'static str = "~";
     fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
-        let total = Self::expect_length(buf)?;
+        Self::decode_with_limits(buf, RespLimits::default())
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        Self::expect_length_with_limits(buf, RespLimits::default())
+    }
+
+    fn decode_with_limits(buf: &mut BytesMut, limits: RespLimits) -> Result<Self, RespError> {
+        let total = Self::expect_length_with_limits(buf, limits)?;
         if buf.len() < total {
             return Err(RespError::Incomplete);
         }
         let nth = extract_nth(buf, Self::PREFIX)?;
+        let nested = limits.nested()?;
         let mut frames = RespSet::new();
         for _ in 0..nth {
-            let frame = RespFrame::decode(buf)?;
+            let frame = RespFrame::decode_with_limits(buf, nested)?;
             frames.insert(frame);
         }
         Ok(frames)
     }
 
-    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+    fn expect_length_with_limits(buf: &[u8], limits: RespLimits) -> Result<usize, RespError> {
+        let nested = limits.nested()?;
         let (len, end) = extract_len_and_end(buf)?;
+        limits.check_elements(len)?;
         let mut total = end + CRLF_LEN;
         for _ in 0..len {
-            let frame_len = RespFrame::expect_length(&buf[total..])?;
+            let frame_len = element_length(buf, total, nested)?;
             total += frame_len;
         }
         Ok(total)
@@ -1,10 +1,10 @@
 use std::ops::Deref;
 
-use bytes::BytesMut;
+use bytes::{BufMut as _, BytesMut};
 
 use crate::{RespDecoder, RespEncoder, RespError};
 
-use super::extract_data;
+use super::{encode_to_vec, extract_data, sanitize_line, CRLF};
 
 #[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
 pub struct SimpleString(pub(crate) String);
@@ -12,7 +12,13 @@ pub struct SimpleString(pub(crate) String);
 // - simple string: "+OK\r\n"
 impl RespEncoder for SimpleString {
     fn encode(&self) -> Vec<u8> {
-        format!("+{}\r\n", self.0).into_bytes()
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        buf.put_u8(b'+');
+        buf.extend_from_slice(sanitize_line(&self.0).as_bytes());
+        buf.extend_from_slice(CRLF.as_bytes());
     }
 }
 
@@ -29,6 +35,18 @@ impl SimpleString {
     pub fn new(s: impl Into<String>) -> Self {
         Self(s.into())
     }
+
+    // 内容中不允许出现 CR 或 LF，否则编码后的 frame 会被对端错误地切分
+    pub fn try_new(s: impl Into<String>) -> Result<Self, RespError> {
+        let s = s.into();
+        if s.contains(['\r', '\n']) {
+            return Err(RespError::Invalid(format!(
+                "SimpleString contains CR or LF: {:?}",
+                s
+            )));
+        }
+        Ok(Self(s))
+    }
 }
 
 impl Deref for SimpleString {
@@ -82,4 +100,22 @@ mod tests {
         );
         Ok(())
     }
+
+    #[test]
+    fn test_simple_string_rejects_cr_lf() {
+        assert!(SimpleString::try_new("hello").is_ok());
+        for s in ["hello\r\nworld", "hello\nworld", "hello\r"] {
+            assert!(
+                matches!(SimpleString::try_new(s), Err(RespError::Invalid(_))),
+                "{:?}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn test_simple_string_encode_sanitizes_cr_lf() {
+        let frame = SimpleString::new("hello\r\n+world");
+        assert_eq!(frame.encode(), b"+hello  +world\r\n");
+    }
 }
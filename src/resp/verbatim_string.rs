@@ -0,0 +1,153 @@
+use bytes::{Buf as _, BufMut as _, BytesMut};
+
+use crate::{RespDecoder, RespEncoder, RespError, RespLimits};
+
+use super::{encode_to_vec, extract_len_and_end, write_header, CRLF, CRLF_LEN};
+
+// 格式前缀固定为 3 个字符，与数据之间用 ':' 分隔
+const FORMAT_LEN: usize = 3;
+
+#[derive(Debug, Eq, PartialEq, Ord, PartialOrd, Clone, Hash)]
+pub struct VerbatimString {
+    format: [u8; FORMAT_LEN],
+    data: Vec<u8>,
+}
+
+// - verbatim string: "=<length>\r\n<format>:<data>\r\n"，length 包含格式前缀和冒号
+impl RespEncoder for VerbatimString {
+    fn encode(&self) -> Vec<u8> {
+        encode_to_vec(self)
+    }
+
+    fn encode_into(&self, buf: &mut BytesMut) {
+        write_header(buf, b'=', FORMAT_LEN + 1 + self.data.len());
+        buf.extend_from_slice(&self.format);
+        buf.put_u8(b':');
+        buf.extend_from_slice(&self.data);
+        buf.extend_from_slice(CRLF.as_bytes());
+    }
+}
+
+// 按长度读取数据，数据中可以包含 CRLF
+impl RespDecoder for VerbatimString {
+    const PREFIX: &'static str = "=";
+    fn decode(buf: &mut BytesMut) -> Result<Self, RespError> {
+        let total = Self::expect_length(buf)?;
+        let (len, end) = extract_len_and_end(buf)?;
+        let data_start = end + CRLF_LEN;
+        let payload = &buf[data_start..data_start + len];
+        if len < FORMAT_LEN + 1 || payload[FORMAT_LEN] != b':' {
+            return Err(RespError::Invalid(format!(
+                "Invalid verbatim string: {:?}",
+                String::from_utf8_lossy(payload)
+            )));
+        }
+        let mut format = [0; FORMAT_LEN];
+        format.copy_from_slice(&payload[..FORMAT_LEN]);
+        let data = payload[FORMAT_LEN + 1..].to_vec();
+        buf.advance(total);
+        Ok(Self { format, data })
+    }
+
+    fn expect_length(buf: &[u8]) -> Result<usize, RespError> {
+        if !buf.starts_with(Self::PREFIX.as_bytes()) {
+            return Err(RespError::InvalidFrameType(format!(
+                "Invalid frame: {:?}",
+                buf
+            )));
+        }
+        let (len, end) = extract_len_and_end(buf)?;
+        let data_end = end + CRLF_LEN + len;
+        if buf.len() < data_end + CRLF_LEN {
+            return Err(RespError::Incomplete);
+        }
+        if &buf[data_end..data_end + CRLF_LEN] != CRLF.as_bytes() {
+            return Err(RespError::InvalidFrameLength);
+        }
+        Ok(data_end + CRLF_LEN)
+    }
+
+    fn expect_length_with_limits(buf: &[u8], limits: RespLimits) -> Result<usize, RespError> {
+        let (len, _) = extract_len_and_end(buf)?;
+        limits.check_bulk_len(len)?;
+        Self::expect_length(buf)
+    }
+}
+
+impl VerbatimString {
+    pub fn new(format: [u8; FORMAT_LEN], data: impl Into<Vec<u8>>) -> Self {
+        Self {
+            format,
+            data: data.into(),
+        }
+    }
+
+    // 纯文本，客户端原样显示
+    pub fn text(data: impl Into<Vec<u8>>) -> Self {
+        Self::new(*b"txt", data)
+    }
+
+    pub fn markdown(data: impl Into<Vec<u8>>) -> Self {
+        Self::new(*b"mkd", data)
+    }
+
+    pub fn format(&self) -> &[u8] {
+        &self.format
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Result;
+
+    #[test]
+    fn test_verbatim_string_encode() {
+        let frame = VerbatimString::text("Some string");
+        assert_eq!(frame.encode(), b"=15\r\ntxt:Some string\r\n");
+
+        let frame = VerbatimString::markdown("");
+        assert_eq!(frame.encode(), b"=4\r\nmkd:\r\n");
+    }
+
+    #[test]
+    fn test_verbatim_string_decode() -> Result<()> {
+        let mut buf = BytesMut::from("=15\r\ntxt:Some string\r\n=11\r\nmkd:a\r\nb\r\nc\r\n");
+        let frame = VerbatimString::decode(&mut buf)?;
+        assert_eq!(frame, VerbatimString::text("Some string"));
+        assert_eq!(frame.format(), b"txt");
+        // 长度覆盖的数据中可以包含 CRLF
+        let frame = VerbatimString::decode(&mut buf)?;
+        assert_eq!(frame, VerbatimString::markdown("a\r\nb\r\nc"));
+        assert!(buf.is_empty());
+
+        let mut buf = BytesMut::from("=15\r\ntxt:Some");
+        assert_eq!(VerbatimString::decode(&mut buf), Err(RespError::Incomplete));
+        Ok(())
+    }
+
+    #[test]
+    fn test_verbatim_string_decode_invalid() {
+        let mut buf = BytesMut::from("=8\r\ntxt:hello\r\n");
+        assert_eq!(
+            VerbatimString::decode(&mut buf),
+            Err(RespError::InvalidFrameLength)
+        );
+
+        let mut buf = BytesMut::from("=5\r\ntxthi\r\n");
+        assert!(matches!(
+            VerbatimString::decode(&mut buf),
+            Err(RespError::Invalid(_))
+        ));
+
+        let mut buf = BytesMut::from("=2\r\ntx\r\n");
+        assert!(matches!(
+            VerbatimString::decode(&mut buf),
+            Err(RespError::Invalid(_))
+        ));
+    }
+}
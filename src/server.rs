@@ -0,0 +1,419 @@
+use std::{
+    net::SocketAddr,
+    ops::Deref,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicBool, AtomicU16, AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use tokio::sync::{broadcast, RwLock};
+use tokio_util::sync::CancellationToken;
+
+use crate::{
+    Backend, Databases, EventClass, NotifyFlags, PubSub, RespLimits, SlowLog, Tracking,
+    DEFAULT_MAX_BULK_LEN, DEFAULT_MAX_DEPTH, DEFAULT_MAX_ELEMENTS, DEFAULT_MAX_FRAME_SIZE,
+};
+
+// MONITOR 客户端消费过慢时丢弃最旧的消息
+const MONITOR_CAPACITY: usize = 1024;
+
+// 整个服务端共享的状态，每个连接持有一份 clone
+#[derive(Debug, Clone)]
+pub struct Server(Arc<ServerInner>);
+
+#[derive(Debug)]
+pub struct ServerInner {
+    config: ServerConfig,
+    databases: Databases,
+    pub(crate) stats: ServerStats,
+    // 所有客户端执行的命令，按 MONITOR 的输出格式广播给正在 MONITOR 的连接
+    monitor: broadcast::Sender<String>,
+    // 所有存活的连接，由 Session 创建时注册、drop 时移除
+    clients: DashMap<u64, ClientInfo>,
+    // SHUTDOWN 时取消，accept 循环和所有连接任务随之退出
+    shutdown: CancellationToken,
+    pub(crate) slowlog: SlowLog,
+    pubsub: PubSub,
+    // EXEC 持有写锁，其他连接的写命令执行时持有读锁，保证事务中的命令不会和其他写入交错
+    write_gate: RwLock<()>,
+    // notify-keyspace-events，可以通过 CONFIG SET 在运行时修改
+    notify_flags: AtomicU16,
+    // CLIENT TRACKING 记录的 key
+    tracking: Tracking,
+    // DEBUG SET-ACTIVE-EXPIRE 关闭后，过期的 key 只在被访问时删除
+    active_expire: AtomicBool,
+}
+
+// 启动时确定的配置，字段名对应 redis.conf 中的同名选项
+#[derive(Debug, Clone)]
+pub struct ServerConfig {
+    // enable-debug-command，生产环境可以关闭 DEBUG 命令
+    pub enable_debug_command: bool,
+    // slowlog-log-slower-than，单位微秒，负数表示关闭慢日志，0 表示记录所有命令
+    pub slowlog_log_slower_than: i64,
+    // slowlog-max-len
+    pub slowlog_max_len: usize,
+    // notify-keyspace-events，默认关闭
+    pub notify_keyspace_events: NotifyFlags,
+    // 每个开启 CLIENT TRACKING 的连接最多跟踪的 key 数量。redis 的 tracking-table-max-keys
+    // 是全局的，这里按连接限制，避免一个连接占满整个跟踪表
+    pub tracking_max_keys: usize,
+    // 客户端发送的帧允许的最大嵌套层数，超过时回复协议错误并关闭连接
+    pub max_nesting_depth: usize,
+    // proto-max-bulk-len，单个字符串参数的最大长度
+    pub max_bulk_len: usize,
+    // 一个数组最多的元素个数
+    pub max_multibulk_len: usize,
+    // client-query-buffer-limit，一个帧最多占用的字节数
+    pub max_frame_size: usize,
+    // port，为 0 时不监听 TCP
+    pub port: u16,
+    // unixsocket，设置后同时在这个路径上监听本地连接
+    pub unixsocket: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            enable_debug_command: true,
+            slowlog_log_slower_than: 10_000,
+            slowlog_max_len: 128,
+            notify_keyspace_events: NotifyFlags::default(),
+            tracking_max_keys: 10_000,
+            max_nesting_depth: DEFAULT_MAX_DEPTH,
+            max_bulk_len: DEFAULT_MAX_BULK_LEN,
+            max_multibulk_len: DEFAULT_MAX_ELEMENTS,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            port: 6379,
+            unixsocket: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    // 解析客户端发送的帧时使用的限制，超过任意一项都会回复协议错误并关闭连接
+    pub fn resp_limits(&self) -> RespLimits {
+        RespLimits {
+            max_depth: self.max_nesting_depth,
+            max_bulk_len: self.max_bulk_len,
+            max_elements: self.max_multibulk_len,
+            max_frame_size: self.max_frame_size,
+        }
+    }
+}
+
+// CLIENT LIST 展示的连接信息
+#[derive(Debug, Clone)]
+pub struct ClientInfo {
+    pub id: u64,
+    pub addr: Option<SocketAddr>,
+    pub name: Option<String>,
+    pub db: usize,
+    pub monitoring: bool,
+    // 订阅的频道数量
+    pub subscriptions: usize,
+    pub created_at: Instant,
+    pub last_interaction: Instant,
+    pub last_command: Option<&'static str>,
+    // CLIENT KILL 通过它通知连接任务退出
+    pub(crate) kill: CancellationToken,
+}
+
+// INFO 使用的全局计数器，keyspace 命中率由各个 Backend 分别统计
+#[derive(Debug)]
+pub struct ServerStats {
+    started_at: Instant,
+    connected_clients: AtomicUsize,
+    total_connections_received: AtomicU64,
+    total_commands_processed: AtomicU64,
+    // INFO commandstats，按命令名统计执行耗时
+    command_stats: DashMap<&'static str, CommandStat>,
+}
+
+// 单个命令的累计执行次数和耗时（微秒）
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CommandStat {
+    pub calls: u64,
+    pub usec: u64,
+    pub min_usec: u64,
+    pub max_usec: u64,
+}
+
+impl CommandStat {
+    pub fn usec_per_call(&self) -> f64 {
+        if self.calls == 0 {
+            return 0.0;
+        }
+        self.usec as f64 / self.calls as f64
+    }
+
+    fn record(&mut self, usec: u64) {
+        self.min_usec = if self.calls == 0 {
+            usec
+        } else {
+            self.min_usec.min(usec)
+        };
+        self.max_usec = self.max_usec.max(usec);
+        self.calls += 1;
+        self.usec += usec;
+    }
+}
+
+impl Server {
+    pub fn new(databases: Databases) -> Self {
+        Self::with_config(databases, ServerConfig::default())
+    }
+
+    pub fn with_config(databases: Databases, config: ServerConfig) -> Self {
+        let (monitor, _) = broadcast::channel(MONITOR_CAPACITY);
+        Self(Arc::new(ServerInner {
+            slowlog: SlowLog::new(config.slowlog_max_len),
+            pubsub: PubSub::default(),
+            write_gate: RwLock::new(()),
+            notify_flags: AtomicU16::new(config.notify_keyspace_events.bits()),
+            tracking: Tracking::new(config.tracking_max_keys),
+            active_expire: AtomicBool::new(true),
+            config,
+            databases,
+            stats: ServerStats::default(),
+            monitor,
+            clients: DashMap::new(),
+            shutdown: CancellationToken::new(),
+        }))
+    }
+
+    pub fn config(&self) -> &ServerConfig {
+        &self.config
+    }
+
+    pub fn databases(&self) -> &Databases {
+        &self.databases
+    }
+
+    pub fn pubsub(&self) -> &PubSub {
+        &self.pubsub
+    }
+
+    pub fn write_gate(&self) -> &RwLock<()> {
+        &self.write_gate
+    }
+
+    pub fn tracking(&self) -> &Tracking {
+        &self.tracking
+    }
+
+    pub fn active_expire(&self) -> bool {
+        self.active_expire.load(Ordering::Relaxed)
+    }
+
+    pub fn set_active_expire(&self, enabled: bool) {
+        self.active_expire.store(enabled, Ordering::Relaxed);
+    }
+
+    // 清理所有数据库中已过期的 key，返回删除的数量。由 serve 启动的后台任务定期调用
+    pub fn active_expire_cycle(&self) -> usize {
+        let mut removed = 0;
+        for (db, backend) in self.databases.iter().enumerate() {
+            for key in backend.remove_expired() {
+                self.key_expired(&backend, &key, db);
+                removed += 1;
+            }
+        }
+        removed
+    }
+
+    // key 因为过期被删除，无论是惰性删除还是后台清理都经过这里
+    pub(crate) fn key_expired(&self, backend: &Backend, key: &str, db: usize) {
+        let keys = [key.to_string()];
+        backend.bump_version(key);
+        // 连接 id 从 1 开始，0 表示不是由任何连接修改的，所有跟踪者都会收到通知
+        self.tracking.invalidate(0, &keys);
+        self.notify_keyspace_event(EventClass::Expired, "expired", key, db);
+    }
+
+    pub fn notify_flags(&self) -> NotifyFlags {
+        NotifyFlags::from_bits(self.notify_flags.load(Ordering::Relaxed))
+    }
+
+    pub fn set_notify_flags(&self, flags: NotifyFlags) {
+        self.notify_flags.store(flags.bits(), Ordering::Relaxed);
+    }
+
+    // 发送到 __keyspace@<db>__:<key> 和 __keyevent@<db>__:<event>，
+    // 所有 keyspace 通知都经过这里
+    pub fn notify_keyspace_event(&self, class: EventClass, event: &str, key: &str, db: usize) {
+        let flags = self.notify_flags();
+        if !flags.allows(class) {
+            return;
+        }
+        if flags.keyspace() {
+            let channel = format!("__keyspace@{}__:{}", db, key);
+            self.pubsub.publish(&channel, event.as_bytes().to_vec());
+        }
+        if flags.keyevent() {
+            let channel = format!("__keyevent@{}__:{}", db, event);
+            self.pubsub.publish(&channel, key.as_bytes().to_vec());
+        }
+    }
+
+    pub fn shutdown(&self) {
+        self.shutdown.cancel();
+    }
+
+    pub fn shutdown_token(&self) -> &CancellationToken {
+        &self.shutdown
+    }
+
+    pub fn has_monitors(&self) -> bool {
+        self.monitor.receiver_count() > 0
+    }
+
+    pub fn subscribe_monitor(&self) -> broadcast::Receiver<String> {
+        self.monitor.subscribe()
+    }
+
+    // 没有 MONITOR 连接时发送会失败，直接忽略
+    pub fn publish_monitor(&self, line: String) {
+        let _ = self.monitor.send(line);
+    }
+
+    // 按 id 排序的连接快照
+    pub fn clients(&self) -> Vec<ClientInfo> {
+        let mut clients = self
+            .clients
+            .iter()
+            .map(|v| v.value().clone())
+            .collect::<Vec<_>>();
+        clients.sort_by_key(|client| client.id);
+        clients
+    }
+
+    pub(crate) fn register_client(&self, id: u64, kill: CancellationToken) {
+        self.clients.insert(id, ClientInfo::new(id, kill));
+    }
+
+    // 关闭所有满足条件的连接，返回关闭的数量
+    pub fn kill_clients(&self, filter: impl Fn(&ClientInfo) -> bool) -> usize {
+        let mut killed = 0;
+        for client in self.clients.iter() {
+            if filter(client.value()) && !client.kill.is_cancelled() {
+                client.kill.cancel();
+                killed += 1;
+            }
+        }
+        killed
+    }
+
+    pub(crate) fn unregister_client(&self, id: u64) {
+        self.clients.remove(&id);
+    }
+
+    pub(crate) fn update_client(&self, id: u64, f: impl FnOnce(&mut ClientInfo)) {
+        if let Some(mut client) = self.clients.get_mut(&id) {
+            f(&mut client);
+        }
+    }
+}
+
+impl ClientInfo {
+    fn new(id: u64, kill: CancellationToken) -> Self {
+        let now = Instant::now();
+        Self {
+            id,
+            addr: None,
+            name: None,
+            db: 0,
+            monitoring: false,
+            subscriptions: 0,
+            created_at: now,
+            last_interaction: now,
+            last_command: None,
+            kill,
+        }
+    }
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Self::new(Databases::default())
+    }
+}
+
+impl Deref for Server {
+    type Target = ServerInner;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl ServerStats {
+    pub fn uptime_in_seconds(&self) -> u64 {
+        self.started_at.elapsed().as_secs()
+    }
+
+    pub fn connected_clients(&self) -> usize {
+        self.connected_clients.load(Ordering::Relaxed)
+    }
+
+    pub fn total_connections_received(&self) -> u64 {
+        self.total_connections_received.load(Ordering::Relaxed)
+    }
+
+    pub fn total_commands_processed(&self) -> u64 {
+        self.total_commands_processed.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn client_connected(&self) {
+        self.connected_clients.fetch_add(1, Ordering::Relaxed);
+        self.total_connections_received
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn client_disconnected(&self) {
+        self.connected_clients.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn command_processed(&self) {
+        self.total_commands_processed
+            .fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_command(&self, name: &'static str, duration: Duration) {
+        self.command_stats
+            .entry(name)
+            .or_default()
+            .record(duration.as_micros() as u64);
+    }
+
+    pub fn command_stat(&self, name: &str) -> Option<CommandStat> {
+        self.command_stats.get(name).map(|stat| *stat)
+    }
+
+    // 按命令名排序，输出顺序稳定
+    pub fn command_stats(&self) -> Vec<(&'static str, CommandStat)> {
+        let mut stats = self
+            .command_stats
+            .iter()
+            .map(|entry| (*entry.key(), *entry.value()))
+            .collect::<Vec<_>>();
+        stats.sort_by_key(|(name, _)| *name);
+        stats
+    }
+}
+
+impl Default for ServerStats {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            connected_clients: AtomicUsize::new(0),
+            total_connections_received: AtomicU64::new(0),
+            total_commands_processed: AtomicU64::new(0),
+            command_stats: DashMap::new(),
+        }
+    }
+}
@@ -0,0 +1,529 @@
+use std::{
+    net::SocketAddr,
+    sync::atomic::{AtomicU64, Ordering},
+};
+
+use tokio::sync::{
+    broadcast::{self, error::RecvError},
+    mpsc,
+};
+use tokio_stream::{
+    wrappers::{errors::BroadcastStreamRecvError, BroadcastStream},
+    StreamExt as _, StreamMap,
+};
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::{
+    cmd::Command, Backend, Databases, EventClass, PubSubMessage, RespFrame, RespVersion, Server,
+};
+
+// MONITOR 输出和订阅的消息，由 process_stream 在等待命令的同时推送给客户端
+#[derive(Debug)]
+pub enum SessionPush {
+    Monitor(String),
+    Message(PubSubMessage),
+    // CLIENT TRACKING 跟踪的 key 被修改
+    Invalidate(Vec<String>),
+}
+
+// 连接 id 在进程内单调递增，与 redis 一致从 1 开始
+static NEXT_CLIENT_ID: AtomicU64 = AtomicU64::new(1);
+
+// 每个连接独有的状态，由 process_stream 创建并贯穿整个连接的生命周期
+#[derive(Debug)]
+pub struct Session {
+    id: u64,
+    server: Server,
+    // 当前选择的数据库下标，总是小于 databases.len()
+    db: usize,
+    protocol: RespVersion,
+    // CLIENT SETNAME 设置的名称，同时输出到日志中方便定位连接
+    name: Option<String>,
+    // 远端地址，测试中构造的 session 没有地址
+    addr: Option<SocketAddr>,
+    // MONITOR 模式下接收其他客户端执行的命令
+    monitor: Option<broadcast::Receiver<String>>,
+    // SUBSCRIBE 订阅的频道，process_stream 在等待命令的同时接收这些频道的消息
+    subscriptions: StreamMap<String, BroadcastStream<PubSubMessage>>,
+    patterns: StreamMap<String, BroadcastStream<PubSubMessage>>,
+    // 命令执行过程中产生的额外回复，在命令本身的回复之前按顺序发送
+    replies: Vec<RespFrame>,
+    // 被 CLIENT KILL 时取消
+    kill: CancellationToken,
+    // QUIT 设置，回复发送完成后由 process_stream 关闭连接
+    closing: bool,
+    // MULTI 之后排队等待 EXEC 的命令
+    transaction: Option<Transaction>,
+    // WATCH 的 key，EXEC 或 UNWATCH 之后清空
+    watched: Vec<WatchedKey>,
+    // CLIENT TRACKING ON 之后接收失效的 key
+    tracking: Option<mpsc::UnboundedReceiver<Vec<String>>>,
+}
+
+#[derive(Debug)]
+struct WatchedKey {
+    db: usize,
+    // SWAPDB 之后同一个下标对应的 backend 会变化，这时也认为 key 被修改了
+    backend: Backend,
+    key: String,
+    version: u64,
+}
+
+// 命令在排队时就完成解析，任何一个命令解析失败都会让整个事务在 EXEC 时被放弃
+#[derive(Debug, Default)]
+pub struct Transaction {
+    pub commands: Vec<QueuedCommand>,
+    pub aborted: bool,
+}
+
+#[derive(Debug)]
+pub struct QueuedCommand {
+    pub cmd: Command,
+    // 参数中的 key，EXEC 执行后更新访问时间，写命令还会更新版本号
+    pub keys: Vec<String>,
+    pub name: Option<&'static str>,
+    pub write: bool,
+}
+
+impl Session {
+    pub fn new(server: Server) -> Self {
+        let id = NEXT_CLIENT_ID.fetch_add(1, Ordering::Relaxed);
+        server.stats.client_connected();
+        let kill = CancellationToken::new();
+        server.register_client(id, kill.clone());
+        Self {
+            id,
+            server,
+            db: 0,
+            protocol: RespVersion::default(),
+            name: None,
+            addr: None,
+            monitor: None,
+            subscriptions: StreamMap::new(),
+            patterns: StreamMap::new(),
+            replies: Vec::new(),
+            kill,
+            closing: false,
+            transaction: None,
+            watched: Vec::new(),
+            tracking: None,
+        }
+    }
+
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    // 当前选择的数据库，SWAPDB 之后会返回交换后的数据库
+    pub fn backend(&self) -> Backend {
+        self.server
+            .databases()
+            .get(self.db)
+            .expect("selected db is always in range")
+    }
+
+    pub fn server(&self) -> &Server {
+        &self.server
+    }
+
+    pub fn databases(&self) -> &Databases {
+        self.server.databases()
+    }
+
+    pub fn db(&self) -> usize {
+        self.db
+    }
+
+    // 命令访问 key 之前删除其中已经过期的 key
+    pub(crate) fn expire_keys(&self, keys: &[String]) {
+        let backend = self.backend();
+        for key in keys {
+            if backend.expire_if_needed(key) {
+                self.server.key_expired(&backend, key, self.db);
+            }
+        }
+    }
+
+    // 写命令执行前记录 key 是否存在，没有开启通知时返回 None，避免多余的查找
+    pub(crate) fn keys_existed(&self, keys: &[String]) -> Option<Vec<bool>> {
+        if self.server.notify_flags() == Default::default() {
+            return None;
+        }
+        let backend = self.backend();
+        Some(keys.iter().map(|key| backend.exists(key)).collect())
+    }
+
+    // 写命令执行后发送 keyspace 通知。事件名默认是命令名，类型由 key 当前的类型决定，
+    // 这样新增的写命令不需要单独处理；命令返回错误或者 key 前后都不存在时不通知
+    pub(crate) fn notify_write(
+        &self,
+        command: &'static str,
+        keys: &[String],
+        existed: &[bool],
+        reply: &RespFrame,
+    ) {
+        if matches!(reply, RespFrame::Error(_)) || command == "sort" {
+            return;
+        }
+        let backend = self.backend();
+        for (key, existed) in keys.iter().zip(existed) {
+            let key_type = backend.key_type(key);
+            if !existed && key_type.is_none() {
+                continue;
+            }
+            let (class, event) = match (command, key_type) {
+                ("del" | "unlink" | "copy" | "restore" | "expire" | "persist", _) => {
+                    (EventClass::Generic, command)
+                }
+                // 与 redis 一致，非正数的过期时间会直接删除 key
+                ("pexpire" | "expireat" | "pexpireat", None) => (EventClass::Generic, "del"),
+                ("pexpire" | "expireat" | "pexpireat", _) => (EventClass::Generic, "expire"),
+                ("zincrby", _) => (EventClass::ZSet, "zincr"),
+                // 与 redis 一致，阻塞弹出发送的是非阻塞版本的事件
+                ("blpop", Some(key_type)) => (key_type.into(), "lpop"),
+                ("brpop", Some(key_type)) => (key_type.into(), "rpop"),
+                (_, Some(key_type)) => (key_type.into(), command),
+                // 命令移除了最后一个元素，key 被删除
+                (_, None) => (EventClass::Generic, "del"),
+            };
+            self.server
+                .notify_keyspace_event(class, event, key, self.db);
+        }
+    }
+
+    // 写命令执行之后发送 keyspace 通知、更新 WATCH 的版本号并通知 CLIENT TRACKING 的客户端，
+    // 调用时需要持有 write gate 的读锁
+    pub(crate) fn after_write(
+        &self,
+        command: Option<&'static str>,
+        keys: &[String],
+        existed: Option<Vec<bool>>,
+        reply: &RespFrame,
+    ) {
+        if let (Some(command), Some(existed)) = (command, existed) {
+            self.notify_write(command, keys, &existed, reply);
+        }
+        let backend = self.backend();
+        for key in keys {
+            backend.bump_version(key);
+        }
+        self.server.tracking().invalidate(self.id, keys);
+    }
+
+    // 下标越界时返回 false 并保持当前选择不变
+    pub fn select(&mut self, db: usize) -> bool {
+        if db >= self.databases().len() {
+            return false;
+        }
+        self.db = db;
+        self.server.update_client(self.id, |client| client.db = db);
+        true
+    }
+
+    pub fn protocol(&self) -> RespVersion {
+        self.protocol
+    }
+
+    pub fn set_protocol(&mut self, protocol: RespVersion) {
+        self.protocol = protocol;
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn set_name(&mut self, name: Option<String>) {
+        self.server
+            .update_client(self.id, |client| client.name = name.clone());
+        self.name = name;
+    }
+
+    pub fn addr(&self) -> Option<SocketAddr> {
+        self.addr
+    }
+
+    pub fn set_addr(&mut self, addr: SocketAddr) {
+        self.addr = Some(addr);
+        self.server
+            .update_client(self.id, |client| client.addr = Some(addr));
+    }
+
+    // RESET 的实现：把连接状态恢复到刚建立连接时的样子，名称和 id 保持不变。
+    // 新增连接级别的状态时需要同时在这里处理
+    pub fn reset(&mut self) {
+        self.select(0);
+        self.protocol = RespVersion::default();
+        if self.monitor.take().is_some() {
+            self.server
+                .update_client(self.id, |client| client.monitoring = false);
+        }
+        self.unsubscribe_all();
+        self.punsubscribe_all();
+        self.transaction = None;
+        self.unwatch();
+        self.disable_tracking();
+    }
+
+    pub fn close_after_reply(&mut self) {
+        self.closing = true;
+    }
+
+    pub fn is_closing(&self) -> bool {
+        self.closing
+    }
+
+    // 已经处于 MULTI 状态时返回 false
+    pub fn multi(&mut self) -> bool {
+        if self.transaction.is_some() {
+            return false;
+        }
+        self.transaction = Some(Transaction::default());
+        true
+    }
+
+    pub fn in_multi(&self) -> bool {
+        self.transaction.is_some()
+    }
+
+    pub fn queue_command(&mut self, command: QueuedCommand) {
+        if let Some(transaction) = self.transaction.as_mut() {
+            transaction.commands.push(command);
+        }
+    }
+
+    // 排队时出错，EXEC 会返回 EXECABORT
+    pub fn abort_transaction(&mut self) {
+        if let Some(transaction) = self.transaction.as_mut() {
+            transaction.aborted = true;
+        }
+    }
+
+    // EXEC 和 DISCARD 结束事务，不处于 MULTI 状态时返回 None
+    pub fn take_transaction(&mut self) -> Option<Transaction> {
+        self.transaction.take()
+    }
+
+    // 记录当前数据库中 key 的版本号，重复 WATCH 同一个 key 时保留最早的版本号
+    pub fn watch(&mut self, key: String) {
+        let db = self.db;
+        if self.watched.iter().any(|w| w.db == db && w.key == key) {
+            return;
+        }
+        let backend = self.backend();
+        let version = backend.watch(&key);
+        self.watched.push(WatchedKey {
+            db,
+            backend,
+            key,
+            version,
+        });
+    }
+
+    pub fn unwatch(&mut self) {
+        for watched in self.watched.drain(..) {
+            watched.backend.unwatch(&watched.key);
+        }
+    }
+
+    // WATCH 之后是否有 key 被修改，EXEC 在持有写锁时检查
+    pub fn watched_keys_changed(&self) -> bool {
+        self.watched.iter().any(|w| {
+            let current = self.databases().get(w.db);
+            current.is_none_or(|backend| !backend.ptr_eq(&w.backend))
+                || w.backend.version(&w.key) != w.version
+        })
+    }
+
+    pub fn kill_token(&self) -> &CancellationToken {
+        &self.kill
+    }
+
+    pub fn start_monitor(&mut self) {
+        if self.monitor.is_none() {
+            self.monitor = Some(self.server.subscribe_monitor());
+            self.server
+                .update_client(self.id, |client| client.monitoring = true);
+        }
+    }
+
+    pub fn is_monitoring(&self) -> bool {
+        self.monitor.is_some()
+    }
+
+    // 等待下一条 MONITOR 输出，未处于 MONITOR 模式时永远不会返回，便于在 select! 中使用
+    pub async fn next_monitor_line(&mut self) -> String {
+        recv_monitor_line(&mut self.monitor).await
+    }
+
+    // 等待任意已订阅频道或模式的下一条消息，没有订阅时永远不会返回
+    pub async fn next_message(&mut self) -> PubSubMessage {
+        tokio::select! {
+            message = recv_message(&mut self.subscriptions) => message,
+            message = recv_message(&mut self.patterns) => message,
+        }
+    }
+
+    // 等待下一条需要主动推送给客户端的内容
+    pub async fn next_push(&mut self) -> SessionPush {
+        tokio::select! {
+            line = recv_monitor_line(&mut self.monitor) => SessionPush::Monitor(line),
+            message = recv_message(&mut self.subscriptions) => SessionPush::Message(message),
+            message = recv_message(&mut self.patterns) => SessionPush::Message(message),
+            keys = recv_invalidation(&mut self.tracking) => SessionPush::Invalidate(keys),
+        }
+    }
+
+    pub fn enable_tracking(&mut self) {
+        self.tracking = Some(self.server.tracking().enable(self.id));
+    }
+
+    pub fn disable_tracking(&mut self) {
+        if self.tracking.take().is_some() {
+            self.server.tracking().disable(self.id);
+        }
+    }
+
+    pub fn is_tracking(&self) -> bool {
+        self.tracking.is_some()
+    }
+
+    // 返回订阅后的频道和模式总数，重复订阅同一个频道不会产生影响
+    pub fn subscribe(&mut self, channel: String) -> usize {
+        if !self.subscriptions.contains_key(&channel) {
+            let receiver = self.server.pubsub().subscribe(&channel);
+            self.subscriptions
+                .insert(channel, BroadcastStream::new(receiver));
+            self.update_subscriptions();
+        }
+        self.subscription_count()
+    }
+
+    // 返回退订后的频道和模式总数
+    pub fn unsubscribe(&mut self, channel: &str) -> usize {
+        if self.subscriptions.remove(channel).is_some() {
+            self.server.pubsub().unsubscribe(channel);
+            self.update_subscriptions();
+        }
+        self.subscription_count()
+    }
+
+    pub fn psubscribe(&mut self, pattern: String) -> usize {
+        if !self.patterns.contains_key(&pattern) {
+            let receiver = self.server.pubsub().psubscribe(&pattern);
+            self.patterns
+                .insert(pattern, BroadcastStream::new(receiver));
+            self.update_subscriptions();
+        }
+        self.subscription_count()
+    }
+
+    pub fn punsubscribe(&mut self, pattern: &str) -> usize {
+        if self.patterns.remove(pattern).is_some() {
+            self.server.pubsub().punsubscribe(pattern);
+            self.update_subscriptions();
+        }
+        self.subscription_count()
+    }
+
+    // 退订所有频道，返回之前订阅的频道
+    pub fn unsubscribe_all(&mut self) -> Vec<String> {
+        let channels = self.channels();
+        for channel in &channels {
+            self.unsubscribe(channel);
+        }
+        channels
+    }
+
+    // 退订所有模式，返回之前订阅的模式
+    pub fn punsubscribe_all(&mut self) -> Vec<String> {
+        let patterns = self.patterns();
+        for pattern in &patterns {
+            self.punsubscribe(pattern);
+        }
+        patterns
+    }
+
+    pub fn channels(&self) -> Vec<String> {
+        self.subscriptions.keys().cloned().collect()
+    }
+
+    pub fn patterns(&self) -> Vec<String> {
+        self.patterns.keys().cloned().collect()
+    }
+
+    // 与 redis 一致，订阅确认中的数量包含频道和模式
+    pub fn subscription_count(&self) -> usize {
+        self.subscriptions.len() + self.patterns.len()
+    }
+
+    fn update_subscriptions(&self) {
+        let count = self.subscription_count();
+        self.server
+            .update_client(self.id, |client| client.subscriptions = count);
+    }
+
+    // 需要回复多个 frame 的命令（例如 SUBSCRIBE 多个频道）通过它追加回复
+    pub fn push_reply(&mut self, frame: RespFrame) {
+        self.replies.push(frame);
+    }
+
+    pub fn take_replies(&mut self) -> Vec<RespFrame> {
+        std::mem::take(&mut self.replies)
+    }
+}
+
+// 消费过慢时跳过被丢弃的消息
+async fn recv_monitor_line(monitor: &mut Option<broadcast::Receiver<String>>) -> String {
+    let Some(monitor) = monitor.as_mut() else {
+        return std::future::pending().await;
+    };
+    loop {
+        match monitor.recv().await {
+            Ok(line) => return line,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return std::future::pending().await,
+        }
+    }
+}
+
+async fn recv_message(
+    subscriptions: &mut StreamMap<String, BroadcastStream<PubSubMessage>>,
+) -> PubSubMessage {
+    loop {
+        if subscriptions.is_empty() {
+            return std::future::pending().await;
+        }
+        match subscriptions.next().await {
+            Some((_, Ok(message))) => return message,
+            Some((channel, Err(BroadcastStreamRecvError::Lagged(skipped)))) => {
+                warn!(channel, skipped, "Subscriber lagged, messages dropped");
+            }
+            None => return std::future::pending().await,
+        }
+    }
+}
+
+async fn recv_invalidation(
+    tracking: &mut Option<mpsc::UnboundedReceiver<Vec<String>>>,
+) -> Vec<String> {
+    let Some(tracking) = tracking.as_mut() else {
+        return std::future::pending().await;
+    };
+    match tracking.recv().await {
+        Some(keys) => keys,
+        None => std::future::pending().await,
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // 连接任务无论正常结束还是出错退出都会 drop session，在这里统一清理
+        self.unsubscribe_all();
+        self.punsubscribe_all();
+        self.disable_tracking();
+        self.unwatch();
+        self.server.stats.client_disconnected();
+        self.server.unregister_client(self.id);
+    }
+}
@@ -0,0 +1,155 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex, PoisonError,
+    },
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::RespFrame;
+
+// 与 redis 相同：每条记录最多保存 32 个参数，每个参数最多 128 字节
+const SLOWLOG_ENTRY_MAX_ARGC: usize = 32;
+const SLOWLOG_ENTRY_MAX_STRING: usize = 128;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlowLogEntry {
+    pub id: u64,
+    // unix 时间戳，单位秒
+    pub timestamp: u64,
+    pub duration: Duration,
+    pub args: Vec<Vec<u8>>,
+    pub client_addr: String,
+    pub client_name: String,
+}
+
+// 执行时间超过 slowlog-log-slower-than 的命令，最多保留 max_len 条，新记录在前
+#[derive(Debug)]
+pub struct SlowLog {
+    next_id: AtomicU64,
+    entries: Mutex<VecDeque<SlowLogEntry>>,
+    max_len: usize,
+}
+
+impl SlowLog {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            next_id: AtomicU64::new(0),
+            entries: Mutex::new(VecDeque::with_capacity(max_len)),
+            max_len,
+        }
+    }
+
+    pub fn push(
+        &self,
+        args: Vec<Vec<u8>>,
+        duration: Duration,
+        client_addr: String,
+        client_name: String,
+    ) {
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let entry = SlowLogEntry {
+            id: self.next_id.fetch_add(1, Ordering::Relaxed),
+            timestamp,
+            duration,
+            args,
+            client_addr,
+            client_name,
+        };
+        let mut entries = self.lock();
+        entries.push_front(entry);
+        entries.truncate(self.max_len);
+    }
+
+    // 最新的 count 条记录
+    pub fn get(&self, count: usize) -> Vec<SlowLogEntry> {
+        self.lock().iter().take(count).cloned().collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn reset(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<SlowLogEntry>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+// 命令执行前保存参数，超出数量或长度的部分按 redis 的格式省略，避免巨大的 MSET 占满内存
+pub fn slowlog_args(args: &[RespFrame]) -> Vec<Vec<u8>> {
+    let argc = args.len().min(SLOWLOG_ENTRY_MAX_ARGC);
+    let mut result = Vec::with_capacity(argc);
+    for (i, arg) in args.iter().take(argc).enumerate() {
+        if i == SLOWLOG_ENTRY_MAX_ARGC - 1 && args.len() > SLOWLOG_ENTRY_MAX_ARGC {
+            let more = args.len() - SLOWLOG_ENTRY_MAX_ARGC + 1;
+            result.push(format!("... ({} more arguments)", more).into_bytes());
+            break;
+        }
+        let mut arg = match arg {
+            RespFrame::BulkString(arg) => arg.to_vec(),
+            other => format!("{:?}", other).into_bytes(),
+        };
+        if arg.len() > SLOWLOG_ENTRY_MAX_STRING {
+            let more = arg.len() - SLOWLOG_ENTRY_MAX_STRING;
+            arg.truncate(SLOWLOG_ENTRY_MAX_STRING);
+            arg.extend_from_slice(format!("... ({} more bytes)", more).as_bytes());
+        }
+        result.push(arg);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slowlog_is_bounded() {
+        let slowlog = SlowLog::new(2);
+        for i in 0..3 {
+            slowlog.push(
+                vec![format!("cmd{}", i).into_bytes()],
+                Duration::from_millis(i),
+                String::new(),
+                String::new(),
+            );
+        }
+        assert_eq!(slowlog.len(), 2);
+
+        let entries = slowlog.get(10);
+        assert_eq!(entries[0].id, 2);
+        assert_eq!(entries[1].args, [b"cmd1".to_vec()]);
+        assert_eq!(slowlog.get(1).len(), 1);
+
+        slowlog.reset();
+        assert!(slowlog.is_empty());
+    }
+
+    #[test]
+    fn test_slowlog_args_are_truncated() {
+        let args = (0..40)
+            .map(|i| format!("arg{}", i).as_bytes().into())
+            .collect::<Vec<RespFrame>>();
+        let truncated = slowlog_args(&args);
+        assert_eq!(truncated.len(), SLOWLOG_ENTRY_MAX_ARGC);
+        assert_eq!(truncated[30], b"arg30");
+        assert_eq!(truncated[31], b"... (9 more arguments)");
+
+        let args: Vec<RespFrame> = vec![[b'a'; 130][..].into()];
+        let truncated = slowlog_args(&args);
+        assert_eq!(truncated[0].len(), 128 + "... (2 more bytes)".len());
+        assert!(truncated[0].ends_with(b"... (2 more bytes)"));
+    }
+}
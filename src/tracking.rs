@@ -0,0 +1,217 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex, PoisonError,
+    },
+};
+
+use tokio::sync::mpsc;
+
+// CLIENT TRACKING 的跟踪表：记录每个连接读过的 key，其他连接修改这些 key 时
+// 通过 channel 通知对应的连接发送 invalidate
+#[derive(Debug)]
+pub struct Tracking {
+    table: Mutex<TrackingTable>,
+    // 开启跟踪的连接数，在持有锁时更新。为 0 时写命令不需要获取锁
+    clients: AtomicUsize,
+    // 每个连接最多跟踪的 key 数量，超出后淘汰最早读取的 key
+    max_keys: usize,
+}
+
+#[derive(Debug, Default)]
+struct TrackingTable {
+    // key -> 跟踪它的连接 id
+    keys: HashMap<String, HashSet<u64>>,
+    clients: HashMap<u64, TrackingClient>,
+}
+
+#[derive(Debug)]
+struct TrackingClient {
+    sender: mpsc::UnboundedSender<Vec<String>>,
+    // 按读取顺序排列，不包含重复的 key
+    keys: VecDeque<String>,
+}
+
+impl Tracking {
+    pub fn new(max_keys: usize) -> Self {
+        Self {
+            table: Mutex::new(TrackingTable::default()),
+            clients: AtomicUsize::new(0),
+            max_keys: max_keys.max(1),
+        }
+    }
+
+    // 开启跟踪，返回接收失效 key 的 channel；重复开启会替换之前的 channel 并清空已跟踪的 key
+    pub fn enable(&self, client: u64) -> mpsc::UnboundedReceiver<Vec<String>> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let mut table = self.lock();
+        table.remove_client(client);
+        table.clients.insert(
+            client,
+            TrackingClient {
+                sender,
+                keys: VecDeque::new(),
+            },
+        );
+        self.clients.store(table.clients.len(), Ordering::Release);
+        receiver
+    }
+
+    pub fn disable(&self, client: u64) {
+        let mut table = self.lock();
+        table.remove_client(client);
+        self.clients.store(table.clients.len(), Ordering::Release);
+    }
+
+    // 记录连接读过的 key，没有开启跟踪的连接直接忽略
+    pub fn track(&self, client: u64, keys: &[String]) {
+        let mut table = self.lock();
+        let TrackingTable {
+            keys: tracked,
+            clients,
+        } = &mut *table;
+        let Some(state) = clients.get_mut(&client) else {
+            return;
+        };
+        for key in keys {
+            if !tracked.entry(key.clone()).or_default().insert(client) {
+                continue;
+            }
+            state.keys.push_back(key.clone());
+            if state.keys.len() > self.max_keys {
+                let Some(evicted) = state.keys.pop_front() else {
+                    continue;
+                };
+                remove_tracker(tracked, &evicted, client);
+                // 与 redis 一致，被淘汰的 key 也要通知客户端，否则它的缓存会一直保留旧值
+                let _ = state.sender.send(vec![evicted]);
+            }
+        }
+    }
+
+    // key 被 writer 修改，通知其他跟踪它的连接。通知之后不再跟踪，客户端再次读取时重新记录
+    pub fn invalidate(&self, writer: u64, keys: &[String]) {
+        // 没有连接开启跟踪时也就没有被跟踪的 key，所有写命令都经过这里，避免争用同一把锁
+        if self.clients.load(Ordering::Acquire) == 0 {
+            return;
+        }
+        let mut table = self.lock();
+        let TrackingTable {
+            keys: tracked,
+            clients,
+        } = &mut *table;
+        for key in keys {
+            let Some(trackers) = tracked.remove(key) else {
+                continue;
+            };
+            for id in trackers {
+                let Some(state) = clients.get_mut(&id) else {
+                    continue;
+                };
+                state.keys.retain(|k| k != key);
+                if id != writer {
+                    let _ = state.sender.send(vec![key.clone()]);
+                }
+            }
+        }
+    }
+
+    pub fn tracked_keys(&self, client: u64) -> usize {
+        self.lock()
+            .clients
+            .get(&client)
+            .map_or(0, |state| state.keys.len())
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TrackingTable> {
+        self.table.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl TrackingTable {
+    fn remove_client(&mut self, client: u64) {
+        if let Some(state) = self.clients.remove(&client) {
+            for key in state.keys {
+                remove_tracker(&mut self.keys, &key, client);
+            }
+        }
+    }
+}
+
+fn remove_tracker(keys: &mut HashMap<String, HashSet<u64>>, key: &str, client: u64) {
+    if let Some(trackers) = keys.get_mut(key) {
+        trackers.remove(&client);
+        if trackers.is_empty() {
+            keys.remove(key);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keys(keys: &[&str]) -> Vec<String> {
+        keys.iter().map(|key| key.to_string()).collect()
+    }
+
+    #[test]
+    fn test_invalidate_other_clients() {
+        let tracking = Tracking::new(16);
+        let mut reader = tracking.enable(1);
+        tracking.track(1, &keys(&["a", "b"]));
+        // 没有开启跟踪的连接不会被记录
+        tracking.track(2, &keys(&["a"]));
+
+        tracking.invalidate(2, &keys(&["a", "c"]));
+        assert_eq!(reader.try_recv().unwrap(), keys(&["a"]));
+        assert!(reader.try_recv().is_err());
+        assert_eq!(tracking.tracked_keys(1), 1);
+
+        // 自己写入不通知自己
+        tracking.invalidate(1, &keys(&["b"]));
+        assert!(reader.try_recv().is_err());
+        assert_eq!(tracking.tracked_keys(1), 0);
+    }
+
+    #[test]
+    fn test_evict_oldest_key() {
+        let tracking = Tracking::new(2);
+        let mut reader = tracking.enable(1);
+        tracking.track(1, &keys(&["a", "b", "a"]));
+        assert!(reader.try_recv().is_err());
+
+        tracking.track(1, &keys(&["c"]));
+        assert_eq!(reader.try_recv().unwrap(), keys(&["a"]));
+        assert_eq!(tracking.tracked_keys(1), 2);
+
+        tracking.invalidate(2, &keys(&["a"]));
+        assert!(reader.try_recv().is_err());
+
+        tracking.disable(1);
+        tracking.invalidate(2, &keys(&["b", "c"]));
+        assert!(reader.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_invalidate_skips_lock_without_clients() {
+        let tracking = Tracking::new(16);
+        {
+            // 持有锁时 invalidate 仍然立即返回
+            let _table = tracking.lock();
+            tracking.invalidate(1, &keys(&["a"]));
+        }
+
+        let mut reader = tracking.enable(1);
+        let _other = tracking.enable(2);
+        tracking.track(1, &keys(&["a"]));
+        tracking.disable(2);
+        assert_eq!(tracking.clients.load(Ordering::Acquire), 1);
+        tracking.invalidate(2, &keys(&["a"]));
+        assert_eq!(reader.try_recv().unwrap(), keys(&["a"]));
+
+        tracking.disable(1);
+        assert_eq!(tracking.clients.load(Ordering::Acquire), 0);
+    }
+}